@@ -5,11 +5,16 @@
 // ⛔ 禁止：写文件读写、数据库操作、复杂算法
 // ============================================================================
 
-use crate::database::Database;
-use crate::services::{analyzer, llm_client};
+use crate::database::{ClearReport, Database, EmbeddingModelInfo, ProjectReport};
+use crate::models::dtos::IndexProgress;
+use crate::services::metrics::CommandMetrics;
+use crate::services::secret_store::{self, OsKeychain};
+use crate::services::vector_index::VectorIndexCache;
+use crate::services::{analysis_pipeline, analyzer, llm_client, report};
+use futures::StreamExt;
 use serde::Serialize;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{Emitter, State};
 
 /// LLM 配置（从 settings 表读取，返回给前端）
 #[derive(Serialize)]
@@ -18,6 +23,9 @@ pub struct LlmConfig {
     pub api_key: String,
     pub model_name: String,
     pub embedding_model: String,
+    /// 附加请求头（`llm_extra_headers`），仅供后端发起请求，不返回给前端
+    #[serde(skip)]
+    pub extra_headers: Vec<(String, String)>,
 }
 
 /// LLM 模型信息（返回给前端）
@@ -29,6 +37,7 @@ pub struct LlmModel {
 /// 获取 LLM 配置
 ///
 /// 从 settings 表中读取 llm_base_url、llm_api_key、llm_model_name 三个键值
+/// （返回原始值，`${ENV:变量名}` 引用不在此解析，以便设置页面原样编辑）
 #[tauri::command]
 pub fn get_llm_config(db: State<'_, Mutex<Database>>) -> Result<LlmConfig, String> {
     let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
@@ -44,32 +53,160 @@ pub fn get_llm_config(db: State<'_, Mutex<Database>>) -> Result<LlmConfig, Strin
         .unwrap_or_default()
     };
 
+    // API Key 按来源设置从钥匙串或数据库读取，保证设置页面回显与保存一致
+    let (api_key, _) = secret_store::read_secret(
+        &OsKeychain,
+        &get_setting(secret_store::API_KEY_SOURCE_KEY),
+        "llm_api_key",
+        get_setting("llm_api_key"),
+    );
+
     Ok(LlmConfig {
         base_url: get_setting("llm_base_url"),
-        api_key: get_setting("llm_api_key"),
+        api_key,
         model_name: get_setting("llm_model_name"),
         embedding_model: get_setting("llm_embedding_model"),
+        extra_headers: llm_client::parse_extra_headers(&get_setting(llm_client::EXTRA_HEADERS_KEY)),
     })
 }
 
+/// 读取发起 LLM 请求所需的配置
+///
+/// API Key 按来源设置从钥匙串读取，`${ENV:变量名}` 引用在此解析（见 `resolve_llm_setting`）。
+fn load_llm_config(db: &Mutex<Database>) -> Result<LlmConfig, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let get = |key: &str| -> Result<String, String> {
+        let raw = db.get_setting(key)?.unwrap_or_default();
+        Ok(resolve_llm_setting(db.conn(), key, raw))
+    };
+    Ok(LlmConfig {
+        base_url: get("llm_base_url")?,
+        api_key: get("llm_api_key")?,
+        model_name: get("llm_model_name")?,
+        embedding_model: get("llm_embedding_model")?,
+        extra_headers: llm_client::parse_extra_headers(&get(llm_client::EXTRA_HEADERS_KEY)?),
+    })
+}
+
+/// 读取 Embedding 输入模式设置
+fn load_embedding_input_mode(
+    db: &Mutex<Database>,
+) -> Result<analysis_pipeline::EmbeddingInputMode, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let raw = db
+        .get_setting(analysis_pipeline::EMBEDDING_INPUT_MODE_KEY)?
+        .unwrap_or_default();
+    Ok(analysis_pipeline::EmbeddingInputMode::parse(&raw))
+}
+
+/// 解析从 settings 表读取的 LLM 设置值
+///
+/// `llm_api_key` 在 `llm_api_key_source = "keychain"` 时改从系统钥匙串读取（不可用时回退到数据库值），
+/// 随后统一解析 `${ENV:变量名}` 引用。
+fn resolve_llm_setting(conn: &rusqlite::Connection, key: &str, raw: String) -> String {
+    llm_client::resolve_setting_value(key, stored_llm_setting(conn, key, raw))
+}
+
+/// 已保存的 LLM 设置原始值：`llm_api_key` 按来源设置改从系统钥匙串读取，不解析环境变量引用
+fn stored_llm_setting(conn: &rusqlite::Connection, key: &str, raw: String) -> String {
+    if key == "llm_api_key" {
+        let source: String = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                rusqlite::params![secret_store::API_KEY_SOURCE_KEY],
+                |row| row.get(0),
+            )
+            .unwrap_or_default();
+        secret_store::read_secret(&OsKeychain, &source, key, raw).0
+    } else {
+        raw
+    }
+}
+
+/// 解析前端传入的 LLM 凭据（设置页尚未保存时的输入）
+///
+/// 与已保存的原始值（即 `get_llm_config` 的回显）相同时改用已保存的值解析 `${ENV:变量名}`，
+/// 其他值原样使用，前端无法借此读取任意环境变量。
+fn resolve_supplied_llm_setting(
+    db: &Mutex<Database>,
+    key: &str,
+    supplied: String,
+) -> Result<String, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let raw = db.get_setting(key)?.unwrap_or_default();
+    let stored = stored_llm_setting(db.conn(), key, raw);
+    Ok(llm_client::resolve_supplied_value(key, supplied, stored))
+}
+
 /// 从 OpenAI 兼容 API 获取可用模型列表
 ///
 /// # 参数
 /// - `base_url`: API 基础地址
 /// - `api_key`: API Key（可为空）
+///
+/// 附加请求头从已保存的 `llm_extra_headers` 设置读取。
 #[tauri::command]
-pub async fn list_llm_models(base_url: String, api_key: String) -> Result<Vec<LlmModel>, String> {
+pub async fn list_llm_models(
+    db: State<'_, Mutex<Database>>,
+    base_url: String,
+    api_key: String,
+) -> Result<Vec<LlmModel>, String> {
+    // 前端传入的可能是已保存的 ${ENV:变量名} 形式的原始设置值
+    let base_url = resolve_supplied_llm_setting(&db, "llm_base_url", base_url)?;
+    let api_key = resolve_supplied_llm_setting(&db, "llm_api_key", api_key)?;
+
     // 参数校验
     if base_url.trim().is_empty() {
         return Err("API 基础地址不能为空".to_string());
     }
 
+    let extra_headers = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let raw = db.get_setting(llm_client::EXTRA_HEADERS_KEY)?.unwrap_or_default();
+        llm_client::parse_extra_headers(&raw)
+    };
+
     // 委托给 services 层
-    let model_ids = llm_client::fetch_models(&base_url, &api_key).await?;
+    let model_ids = llm_client::fetch_models(&base_url, &api_key, &extra_headers).await?;
 
     Ok(model_ids.into_iter().map(|id| LlmModel { id }).collect())
 }
 
+/// 校验对话模型与 Embedding 模型是否填反
+///
+/// 对两个模型各发送一次最小探测请求，返回各自的 `{ok, detail}`，
+/// 便于在设置页保存前发现误配。附加请求头从已保存的 `llm_extra_headers` 设置读取。
+#[tauri::command]
+pub async fn validate_model_roles(
+    db: State<'_, Mutex<Database>>,
+    base_url: String,
+    api_key: String,
+    chat_model: String,
+    embedding_model: String,
+) -> Result<llm_client::ModelRoleCheck, String> {
+    let base_url = resolve_supplied_llm_setting(&db, "llm_base_url", base_url)?;
+    let api_key = resolve_supplied_llm_setting(&db, "llm_api_key", api_key)?;
+
+    if base_url.trim().is_empty() {
+        return Err("API 基础地址不能为空".to_string());
+    }
+
+    let extra_headers = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let raw = db.get_setting(llm_client::EXTRA_HEADERS_KEY)?.unwrap_or_default();
+        llm_client::parse_extra_headers(&raw)
+    };
+
+    Ok(llm_client::validate_model_roles(
+        &base_url,
+        &api_key,
+        &extra_headers,
+        chat_model.trim(),
+        embedding_model.trim(),
+    )
+    .await)
+}
+
 /// 文件索引条目（返回给前端）
 #[derive(Serialize)]
 pub struct FileIndexEntry {
@@ -83,57 +220,151 @@ pub struct FileIndexEntry {
     pub summary: Option<String>,
 }
 
+/// 文件索引扫描结果（返回给前端）
+#[derive(Serialize)]
+pub struct FileIndexScan {
+    /// 扫描到的文件及变更信息
+    pub entries: Vec<FileIndexEntry>,
+    /// 被跳过的文件或目录及原因（无读权限、扫描期间被删除等）
+    pub warnings: Vec<analyzer::ScanWarning>,
+}
+
 /// 扫描项目文件并与数据库中的索引对比，返回增量变更信息
 ///
 /// # 参数
 /// - `project_id`: 项目 ID（用于查询/更新 file_index 表）
 /// - `project_path`: 项目根目录路径
+/// - `sub_path`: 可选子目录（相对项目根目录，如 `backend`），指定后仅扫描该子树；
+///   索引路径仍相对项目根目录（如 `backend/app.py`），子树外的记录不受影响
+/// - `incremental`: 是否按目录修改时间跳过未变化的子树（默认开启，传 `false` 强制全量遍历）
+/// - `ignored_dirs`: 在默认忽略目录之外追加跳过的目录名（如 `coverage`）
+/// - `respect_gitignore`: 是否排除项目内 `.gitignore` 匹配的文件（默认关闭）
+///
+/// 哈希算法由 `hash_algo` 设置决定；已记录的哈希由其他算法计算时一律重新计算。
+/// 二进制文件不进入索引，避免为其生成摘要与向量。
+/// 无法读取的文件或目录跳过并在 `warnings` 中返回，不中断扫描。
+/// 文件遍历与哈希在后台线程执行，期间通过 `index-progress` 事件推送进度（`IndexProgress`）。
 #[tauri::command]
-pub fn scan_project_file_index(
+#[allow(clippy::too_many_arguments)]
+pub async fn scan_project_file_index(
+    app: tauri::AppHandle,
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
     project_path: String,
-) -> Result<Vec<FileIndexEntry>, String> {
-    // 调用 services 层扫描文件（含 file_size + mtime 元数据）
-    let entries =
-        analyzer::scan_project_files(std::path::Path::new(&project_path))?;
+    sub_path: Option<String>,
+    incremental: Option<bool>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<FileIndexScan, String> {
+    let _timer = metrics.start("scan_project_file_index");
+
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    // 扫描结果相对子目录，写入项目级索引前补上子目录前缀
+    let prefix = analyzer::scan_root_prefix(sub_path.as_deref());
+
+    let scan_config = analyzer::ScanConfig {
+        skip_binary: true,
+        ..load_scan_config(&db, ignored_dirs, respect_gitignore)?
+    };
+    let hash_algo = scan_config.hash_algo;
+
+    // 目录状态只对项目根目录、默认忽略规则记录：子目录扫描的相对路径基准不同，
+    // 追加忽略目录或遵循 .gitignore 时复用的子目录列表与忽略规则不一致，均始终全量遍历
+    let use_dir_state = incremental.unwrap_or(true)
+        && sub_path.is_none()
+        && scan_config.extra_ignored_dirs.is_empty()
+        && !scan_config.respect_gitignore;
+
+    // 从数据库加载扫描范围内已有的文件索引（含 file_size、mtime 用于增量快速判断，hash_algo 用于识别算法切换，
+    // binary_checked 标记记录是否已通过二进制检测）
+    let (existing, prior_dirs) = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let existing: std::collections::HashMap<_, _> = db
+            .load_file_scan_state(project_id, &prefix)?
+            .into_iter()
+            .map(|(path, (hash, summary, size, mtime, algo, binary_checked))| {
+                let algo = analyzer::HashAlgo::parse(&algo).unwrap_or_default();
+                (path, (hash, summary, size, mtime, algo, binary_checked))
+            })
+            .collect();
+        let prior_dirs = if use_dir_state {
+            db.get_dir_mtimes(project_id)?
+        } else {
+            std::collections::HashMap::new()
+        };
+        (existing, prior_dirs)
+    };
+
+    // 调用 services 层扫描文件（含 file_size + mtime 元数据），无目录状态时退化为全量扫描
+    let prior_files = existing
+        .iter()
+        .map(|(path, (hash, _, size, mtime, algo, binary_checked))| {
+            // 扫描使用相对子目录的路径
+            let path = path.strip_prefix(prefix.as_str()).unwrap_or(path);
+            let prior = analyzer::PriorFile {
+                file_hash: hash.clone(),
+                file_size: *size,
+                mtime: *mtime,
+                hash_algo: *algo,
+                binary_checked: *binary_checked,
+            };
+            (path.to_string(), prior)
+        })
+        .collect();
+    let scan = tauri::async_runtime::spawn_blocking(move || {
+        // 百分比变化时才推送进度，避免大项目事件过多
+        let on_progress = |done: usize, total: usize| {
+            if done * 100 / total != (done - 1) * 100 / total {
+                let _ = app.emit("index-progress", IndexProgress { done, total });
+            }
+        };
+        analyzer::scan_project_files_incremental_with_progress(
+            &root,
+            &prior_dirs,
+            &prior_files,
+            &scan_config,
+            &on_progress,
+        )
+    })
+    .await
+    .map_err(|e| format!("文件扫描任务异常终止：{}", e))??;
+    let mut entries = scan.files;
+    for entry in &mut entries {
+        entry.relative_path = format!("{}{}", prefix, entry.relative_path);
+    }
+    let mut warnings = scan.warnings;
+    for warning in &mut warnings {
+        warning.relative_path = format!("{}{}", prefix, warning.relative_path);
+    }
 
     let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
     let conn = db.conn();
-
-    // 从数据库加载已有的文件索引（含 file_size、mtime 用于增量快速判断）
-    let mut existing: std::collections::HashMap<String, (String, Option<String>, u64, u64)> =
-        std::collections::HashMap::new();
-    {
-        let mut stmt = conn
-            .prepare("SELECT file_path, file_hash, summary, file_size, mtime FROM file_index WHERE project_id = ?1")
-            .map_err(|e| format!("查询文件索引失败：{}", e))?;
-        let rows = stmt
-            .query_map(rusqlite::params![project_id], |row| {
-                Ok((
-                    row.get::<_, String>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, Option<String>>(2)?,
-                    row.get::<_, u64>(3).unwrap_or(0),
-                    row.get::<_, u64>(4).unwrap_or(0),
-                ))
-            })
-            .map_err(|e| format!("查询文件索引失败：{}", e))?;
-        for row in rows {
-            let (path, hash, summary, size, mtime) =
-                row.map_err(|e| format!("读取文件索引失败：{}", e))?;
-            existing.insert(path, (hash, summary, size, mtime));
-        }
-    }
+    let dir_mtimes = if use_dir_state {
+        scan.dir_mtimes
+    } else {
+        std::collections::HashMap::new()
+    };
+    db.replace_dir_mtimes(project_id, &dir_mtimes)?;
 
     // 增量对比：先用 file_size + mtime 快速判断，跳过未变化文件的哈希比较
     let mut result = Vec::with_capacity(entries.len());
     for entry in &entries {
         let (changed, old_summary, effective_hash) = match existing.get(&entry.relative_path) {
-            Some((old_hash, summary, old_size, old_mtime)) => {
+            Some((old_hash, summary, old_size, old_mtime, old_algo, _)) => {
                 // 快速路径：文件大小和修改时间都未变，直接复用缓存哈希
                 if *old_size == entry.file_size && *old_mtime == entry.mtime {
-                    (false, summary.clone(), old_hash.clone())
+                    // 算法切换时扫描已按新算法重算哈希，内容未变，保留摘要
+                    let hash = if *old_algo == hash_algo {
+                        old_hash
+                    } else {
+                        &entry.file_hash
+                    };
+                    (false, summary.clone(), hash.clone())
+                } else if *old_algo != hash_algo {
+                    // 旧哈希由其他算法计算，无法比较内容，视为变更
+                    (true, None, entry.file_hash.clone())
                 } else {
                     // 元数据变化，用新哈希对比
                     let hash_changed = old_hash != &entry.file_hash;
@@ -144,12 +375,15 @@ pub fn scan_project_file_index(
             None => (true, None, entry.file_hash.clone()), // 新文件视为变更
         };
 
-        // 使用 UPSERT 更新文件索引（含 file_size、mtime）
+        // 使用 UPSERT 更新文件索引（含 file_size、mtime、hash_algo；本次扫描已剔除二进制文件，标记 binary_checked）；
+        // 内容变化后旧的入度/出度已不可信，清空等待下次依赖分析
         conn.execute(
-            "INSERT INTO file_index (project_id, file_path, file_hash, summary, file_size, mtime, last_analyzed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))
+            "INSERT INTO file_index (project_id, file_path, file_hash, summary, file_size, mtime, hash_algo, binary_checked, last_analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, datetime('now'))
              ON CONFLICT(project_id, file_path)
-             DO UPDATE SET file_hash = ?3, summary = ?4, file_size = ?5, mtime = ?6, last_analyzed_at = datetime('now')",
+             DO UPDATE SET file_hash = ?3, summary = ?4, file_size = ?5, mtime = ?6, hash_algo = ?7, binary_checked = 1, last_analyzed_at = datetime('now'),
+                           in_degree = CASE WHEN ?8 THEN NULL ELSE in_degree END,
+                           out_degree = CASE WHEN ?8 THEN NULL ELSE out_degree END",
             rusqlite::params![
                 project_id,
                 entry.relative_path,
@@ -157,6 +391,8 @@ pub fn scan_project_file_index(
                 if changed { None::<String> } else { old_summary.clone() },
                 entry.file_size as i64,
                 entry.mtime as i64,
+                hash_algo.as_str(),
+                changed,
             ],
         )
         .map_err(|e| format!("更新文件索引失败：{}", e))?;
@@ -169,20 +405,18 @@ pub fn scan_project_file_index(
         });
     }
 
-    // 清理数据库中已不存在的文件记录
-    let current_paths: std::collections::HashSet<&str> =
-        entries.iter().map(|e| e.relative_path.as_str()).collect();
-    for old_path in existing.keys() {
-        if !current_paths.contains(old_path.as_str()) {
-            conn.execute(
-                "DELETE FROM file_index WHERE project_id = ?1 AND file_path = ?2",
-                rusqlite::params![project_id, old_path],
-            )
-            .map_err(|e| format!("清理文件索引失败：{}", e))?;
-        }
-    }
+    // 清理扫描范围内已不存在的文件记录（暂时无法读取的文件保留原记录）
+    let current_paths: std::collections::HashSet<String> = entries
+        .iter()
+        .map(|e| e.relative_path.clone())
+        .chain(warnings.iter().map(|w| w.relative_path.clone()))
+        .collect();
+    db.delete_stale_file_index(project_id, &prefix, &current_paths)?;
 
-    Ok(result)
+    Ok(FileIndexScan {
+        entries: result,
+        warnings,
+    })
 }
 
 
@@ -192,27 +426,27 @@ pub fn scan_project_file_index(
 /// - `project_id`: 项目 ID
 /// - `project_path`: 项目根目录路径
 /// - `file_path`: 文件相对路径
+///
+/// # 返回
+/// - 新摘要，以及旧 Embedding 是否因摘要变化被清空（需重新生成）
 #[tauri::command]
 pub async fn analyze_file_summary(
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
     project_path: String,
     file_path: String,
-) -> Result<String, String> {
+) -> Result<FileSummaryResult, String> {
+    let _timer = metrics.start("analyze_file_summary");
+
     // 1. 从 settings 表读取 LLM 配置
-    let (base_url, api_key, model_name) = {
-        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let get = |key: &str| -> String {
-            conn.query_row(
-                "SELECT value FROM settings WHERE key = ?1",
-                rusqlite::params![key],
-                |row| row.get::<_, String>(0),
-            )
-            .unwrap_or_default()
-        };
-        (get("llm_base_url"), get("llm_api_key"), get("llm_model_name"))
-    };
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        model_name,
+        ..
+    } = load_llm_config(&db)?;
 
     if base_url.is_empty() || model_name.is_empty() {
         return Err("请先在设置页面配置 LLM API 地址和模型".to_string());
@@ -230,22 +464,17 @@ pub async fn analyze_file_summary(
 
     // 3. 调用 LLM 生成摘要
     let summary = llm_client::generate_summary(
-        &base_url, &api_key, &model_name, &file_path, &content,
+        &base_url, &api_key, &extra_headers, &model_name, &file_path, &content,
     )
     .await?;
 
-    // 4. 将摘要写入数据库
-    {
+    // 4. 将摘要写入数据库（摘要变化时清空过期的 Embedding）
+    let needs_reembed = {
         let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        conn.execute(
-            "UPDATE file_index SET summary = ?1 WHERE project_id = ?2 AND file_path = ?3",
-            rusqlite::params![summary, project_id, file_path],
-        )
-        .map_err(|e| format!("保存摘要失败：{}", e))?;
-    }
+        db.save_file_summary(project_id, &file_path, &summary)?
+    };
 
-    Ok(summary)
+    Ok(FileSummaryResult { summary, needs_reembed })
 }
 
 // ============================================================================
@@ -262,30 +491,137 @@ pub struct DepEdge {
 /// 依赖图数据（返回给前端）
 #[derive(Serialize)]
 pub struct DependencyGraph {
-    /// 所有文件节点（相对路径）
-    pub nodes: Vec<String>,
+    /// 所有文件节点（相对路径、语言、入口标记、大小）
+    pub nodes: Vec<analyzer::DepNode>,
     /// 依赖边列表
     pub edges: Vec<DepEdge>,
+    /// 出入边总数最多的文件（高耦合热点）
+    pub hotspots: Vec<report::DependencyHotspot>,
+    /// 依赖解析中发现的源码问题（如相对导入超出项目根目录）
+    pub warnings: Vec<analyzer::DependencyWarning>,
+}
+
+/// 读取用户追加的代码文件扩展名（`code_extensions` 设置）
+fn load_code_extensions(db: &Mutex<Database>) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let raw = db.get_setting(analyzer::CODE_EXTENSIONS_KEY)?.unwrap_or_default();
+    Ok(analyzer::parse_code_extensions(&raw))
+}
+
+/// 读取文件索引哈希算法设置
+fn load_hash_algo(db: &Mutex<Database>) -> Result<analyzer::HashAlgo, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let raw = db.get_setting(analyzer::HASH_ALGO_KEY)?.unwrap_or_default();
+    Ok(analyzer::parse_hash_algo(&raw))
+}
+
+/// 由命令参数构建扫描配置：追加忽略目录与 `.gitignore` 开关，哈希算法取自 `hash_algo` 设置
+///
+/// 文件索引、依赖分析、概览、签名与报告共用，各视图统计的文件范围保持一致。
+fn load_scan_config(
+    db: &Mutex<Database>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<analyzer::ScanConfig, String> {
+    Ok(analyzer::ScanConfig {
+        respect_gitignore: respect_gitignore.unwrap_or(false),
+        hash_algo: load_hash_algo(db)?,
+        ..analyzer::ScanConfig::with_ignored_dirs(&ignored_dirs.unwrap_or_default())
+    })
+}
+
+/// 读取文件索引中扫描范围内的记录，作为只需文件列表的扫描可复用的哈希
+///
+/// `prefix` 为扫描子目录前缀（见 `analyzer::scan_root_prefix`），返回的路径相对扫描根目录；
+/// 未指定项目时返回空表，所有文件照常计算哈希。
+fn load_prior_files(
+    db: &Mutex<Database>,
+    project_id: Option<i64>,
+    prefix: &str,
+) -> Result<std::collections::HashMap<String, analyzer::PriorFile>, String> {
+    let Some(project_id) = project_id else {
+        return Ok(std::collections::HashMap::new());
+    };
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    Ok(db
+        .load_file_scan_state(project_id, prefix)?
+        .into_iter()
+        .map(|(path, (file_hash, _, file_size, mtime, algo, binary_checked))| {
+            let path = path.strip_prefix(prefix).unwrap_or(&path).to_string();
+            let prior = analyzer::PriorFile {
+                file_hash,
+                file_size,
+                mtime,
+                hash_algo: analyzer::HashAlgo::parse(&algo).unwrap_or_default(),
+                binary_checked,
+            };
+            (path, prior)
+        })
+        .collect())
+}
+
+/// 读取概览统计的单文件大小阈值设置
+fn load_max_overview_file_bytes(db: &Mutex<Database>) -> Result<u64, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let raw = db.get_setting(analyzer::MAX_OVERVIEW_FILE_BYTES_KEY)?.unwrap_or_default();
+    Ok(analyzer::parse_max_overview_file_bytes(&raw))
 }
 
 /// 分析项目文件间的 import 依赖关系
 ///
 /// # 参数
 /// - `project_path`: 项目根目录路径
+/// - `sub_path`: 可选子目录（相对项目根目录，如 `backend`），指定后仅分析该子树
+/// - `project_id`: 可选项目 ID，指定后复用文件索引中的哈希，
+///   并在分析整个项目（未指定 `sub_path`）时将每个文件的入度/出度写入文件索引
+/// - `ignored_dirs`: 在默认忽略目录之外追加跳过的目录名（如 `coverage`）
+/// - `respect_gitignore`: 是否排除项目内 `.gitignore` 匹配的文件（默认关闭）
 #[tauri::command]
-pub fn analyze_dependencies(project_path: String) -> Result<DependencyGraph, String> {
-    let path = std::path::Path::new(&project_path);
-
-    // 1. 扫描项目文件
-    let entries = analyzer::scan_project_files(path)?;
+pub fn analyze_dependencies(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    project_path: String,
+    sub_path: Option<String>,
+    project_id: Option<i64>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<DependencyGraph, String> {
+    let _timer = metrics.start("analyze_dependencies");
+    let whole_project = sub_path.is_none();
+
+    // 1-2. 扫描项目文件并提取依赖关系
+    let (entries, dep_edges, warnings) = scan_dependencies(
+        &db,
+        &project_path,
+        sub_path,
+        project_id,
+        ignored_dirs,
+        respect_gitignore,
+    )?;
     let file_paths: Vec<String> = entries.iter().map(|e| e.relative_path.clone()).collect();
 
-    // 2. 提取依赖关系
-    let dep_edges = analyzer::extract_dependencies(path, &file_paths)?;
+    // 空项目直接返回空图
+    if file_paths.is_empty() {
+        return Ok(DependencyGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            hotspots: Vec::new(),
+            warnings: Vec::new(),
+        });
+    }
 
-    // 3. 构建返回数据
+    // 3. 写入文件索引的入度/出度：子目录分析看不到子树外文件的 import，
+    //    得到的度数不完整，不覆盖已记录的项目级度数
+    if let Some(project_id) = project_id.filter(|_| whole_project) {
+        let degrees = report::file_degrees(&file_paths, &dep_edges);
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        db.update_dependency_degrees(project_id, &degrees)?;
+    }
+
+    // 4. 构建返回数据
+    let hotspots = report::dependency_hotspots(&dep_edges, report::DEPENDENCY_HOTSPOT_COUNT);
     Ok(DependencyGraph {
-        nodes: file_paths,
+        nodes: analyzer::dependency_nodes(&entries),
         edges: dep_edges
             .into_iter()
             .map(|e| DepEdge {
@@ -293,17 +629,88 @@ pub fn analyze_dependencies(project_path: String) -> Result<DependencyGraph, Str
                 target: e.target,
             })
             .collect(),
+        hotspots,
+        warnings,
     })
 }
 
+/// 将项目文件间的 import 依赖关系导出为 Graphviz DOT 文本
+///
+/// 参数含义与 `analyze_dependencies` 一致（`project_id` 仅用于复用文件索引中的哈希）；
+/// 节点按顶层目录分组为子图。
+#[tauri::command]
+pub fn export_dependency_graph_dot(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    project_path: String,
+    sub_path: Option<String>,
+    project_id: Option<i64>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<String, String> {
+    let _timer = metrics.start("export_dependency_graph_dot");
+
+    let (entries, dep_edges, _) = scan_dependencies(
+        &db,
+        &project_path,
+        sub_path,
+        project_id,
+        ignored_dirs,
+        respect_gitignore,
+    )?;
+    let file_paths: Vec<String> = entries.into_iter().map(|e| e.relative_path).collect();
+    Ok(report::to_dot(&file_paths, &dep_edges))
+}
+
+/// 依赖扫描结果：(文件条目, 依赖边, 依赖解析警告)
+type DependencyScan = (
+    Vec<analyzer::FileEntry>,
+    Vec<analyzer::DependencyEdge>,
+    Vec<analyzer::DependencyWarning>,
+);
+
+/// 扫描项目文件并提取依赖关系（含用户追加的代码扩展名），依赖解析警告写入日志
+///
+/// 指定 `project_id` 时复用文件索引中已记录的哈希，未变化的文件不再读取。
+fn scan_dependencies(
+    db: &Mutex<Database>,
+    project_path: &str,
+    sub_path: Option<String>,
+    project_id: Option<i64>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<DependencyScan, String> {
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(project_path), sub_path.as_deref())?;
+    let path = root.as_path();
+    let prefix = analyzer::scan_root_prefix(sub_path.as_deref());
+
+    let scan_config = load_scan_config(db, ignored_dirs, respect_gitignore)?;
+    let prior = load_prior_files(db, project_id, &prefix)?;
+    let entries = analyzer::scan_project_files_with_config(path, &scan_config, &prior)?;
+    // 空项目无需解析依赖
+    if entries.is_empty() {
+        return Ok((entries, Vec::new(), Vec::new()));
+    }
+
+    let file_paths: Vec<String> = entries.iter().map(|e| e.relative_path.clone()).collect();
+    let code_extensions = load_code_extensions(db)?;
+    let (dep_edges, warnings) =
+        analyzer::extract_dependencies_with_warnings(path, &file_paths, &code_extensions)?;
+    for warning in &warnings {
+        log::warn!("{}:{} {}", warning.file, warning.line, warning.message);
+    }
+    Ok((entries, dep_edges, warnings))
+}
+
 // ============================================================================
 // Embedding / 语义搜索
 // ============================================================================
 
 /// 为单个文件生成 Embedding 向量并存入数据库
 ///
-/// 使用文件摘要（summary）作为 embedding 输入文本。
-/// 如果文件没有摘要，则使用文件路径 + 文件内容前 2000 字符。
+/// 输入文本由 `embedding_input_mode` 设置决定（默认使用文件摘要；
+/// 没有摘要时使用文件路径 + 文件内容前 2000 字符），所用模式随向量一并记录。
 ///
 /// # 参数
 /// - `project_id`: 项目 ID
@@ -312,68 +719,61 @@ pub fn analyze_dependencies(project_path: String) -> Result<DependencyGraph, Str
 #[tauri::command]
 pub async fn embed_file(
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
     project_path: String,
     file_path: String,
 ) -> Result<(), String> {
+    let _timer = metrics.start("embed_file");
+
     // 1. 从 settings 表读取 Embedding 配置
-    let (base_url, api_key, embed_model) = {
-        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let get = |key: &str| -> String {
-            conn.query_row(
-                "SELECT value FROM settings WHERE key = ?1",
-                rusqlite::params![key],
-                |row| row.get::<_, String>(0),
-            )
-            .unwrap_or_default()
-        };
-        (get("llm_base_url"), get("llm_api_key"), get("llm_embedding_model"))
-    };
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        embedding_model: embed_model,
+        ..
+    } = load_llm_config(&db)?;
+    let input_mode = load_embedding_input_mode(&db)?;
 
     if base_url.is_empty() || embed_model.is_empty() {
         return Err("请先在设置页面配置 API 地址和 Embedding 模型".to_string());
     }
 
-    // 2. 获取文件摘要或读取文件内容作为 embedding 输入
-    let input_text = {
+    // 2. 按输入模式构造 embedding 输入（摘要、签名或文件内容）
+    let summary: Option<String> = {
         let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let summary: Option<String> = conn
+        db.conn()
             .query_row(
                 "SELECT summary FROM file_index WHERE project_id = ?1 AND file_path = ?2",
                 rusqlite::params![project_id, file_path],
                 |row| row.get(0),
             )
-            .unwrap_or(None);
-
-        match summary {
-            Some(s) if !s.is_empty() => format!("文件：{}\n摘要：{}", file_path, s),
-            _ => {
-                // 没有摘要时，使用文件路径 + 内容前 2000 字符
-                let abs_path = std::path::Path::new(&project_path).join(&file_path);
-                let content = std::fs::read_to_string(&abs_path)
-                    .map_err(|e| format!("读取文件失败 {}: {}", file_path, e))?;
-                let truncated = if content.len() > 2000 { &content[..2000] } else { &content };
-                format!("文件：{}\n内容：{}", file_path, truncated)
-            }
-        }
+            .unwrap_or(None)
     };
+    let (input_text, input_mode) =
+        analysis_pipeline::build_embedding_input(input_mode, &file_path, summary.as_deref(), || {
+            let abs_path = std::path::Path::new(&project_path).join(&file_path);
+            std::fs::read_to_string(&abs_path)
+                .map_err(|e| format!("读取文件失败 {}: {}", file_path, e))
+        })?;
 
     // 3. 调用 Embedding API
     let embedding = llm_client::generate_embedding(
-        &base_url, &api_key, &embed_model, &input_text,
+        &base_url, &api_key, &extra_headers, &embed_model, &input_text,
     )
     .await?;
 
-    // 4. 序列化并存入数据库
-    let bytes = analyzer::embedding_to_bytes(&embedding);
+    // 4. 校验向量维度后归一化、序列化并存入数据库
+    let bytes = analyzer::normalized_embedding_to_bytes(&embedding);
     {
         let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        db.ensure_embedding_dimension(embedding.len())?;
         let conn = db.conn();
         conn.execute(
-            "UPDATE file_index SET embedding = ?1 WHERE project_id = ?2 AND file_path = ?3",
-            rusqlite::params![bytes, project_id, file_path],
+            "UPDATE file_index SET embedding = ?1, embedding_mode = ?2
+             WHERE project_id = ?3 AND file_path = ?4",
+            rusqlite::params![bytes, input_mode.as_str(), project_id, file_path],
         )
         .map_err(|e| format!("保存 Embedding 失败：{}", e))?;
     }
@@ -392,43 +792,30 @@ pub async fn embed_file(
 #[tauri::command]
 pub async fn embed_all_files(
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
     project_path: String,
 ) -> Result<EmbedBatchResult, String> {
+    let _timer = metrics.start("embed_all_files");
+
     // 1. 读取配置
-    let (base_url, api_key, embed_model) = {
-        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let get = |key: &str| -> String {
-            conn.query_row(
-                "SELECT value FROM settings WHERE key = ?1",
-                rusqlite::params![key],
-                |row| row.get::<_, String>(0),
-            )
-            .unwrap_or_default()
-        };
-        (get("llm_base_url"), get("llm_api_key"), get("llm_embedding_model"))
-    };
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        embedding_model: embed_model,
+        ..
+    } = load_llm_config(&db)?;
+    let input_mode = load_embedding_input_mode(&db)?;
 
     if base_url.is_empty() || embed_model.is_empty() {
         return Err("请先在设置页面配置 API 地址和 Embedding 模型".to_string());
     }
 
     // 2. 获取所有缺少 embedding 的文件
-    let files_to_embed: Vec<(String, Option<String>)> = {
+    let files_to_embed = {
         let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let mut stmt = conn
-            .prepare(
-                "SELECT file_path, summary FROM file_index WHERE project_id = ?1 AND embedding IS NULL",
-            )
-            .map_err(|e| format!("查询文件索引失败：{}", e))?;
-        let rows = stmt
-            .query_map(rusqlite::params![project_id], |row| {
-                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
-            })
-            .map_err(|e| format!("查询文件索引失败：{}", e))?;
-        rows.filter_map(|r| r.ok()).collect()
+        db.list_files_without_embedding(project_id)?
     };
 
     let total = files_to_embed.len();
@@ -437,31 +824,37 @@ pub async fn embed_all_files(
 
     // 3. 逐个生成 embedding
     for (file_path, summary) in &files_to_embed {
-        let input_text = match summary {
-            Some(s) if !s.is_empty() => format!("文件：{}\n摘要：{}", file_path, s),
-            _ => {
-                let abs_path = std::path::Path::new(&project_path).join(file_path);
-                match std::fs::read_to_string(&abs_path) {
-                    Ok(content) => {
-                        let truncated = if content.len() > 2000 { &content[..2000] } else { &content };
-                        format!("文件：{}\n内容：{}", file_path, truncated)
-                    }
-                    Err(_) => {
-                        fail_count += 1;
-                        continue;
-                    }
-                }
+        let (input_text, effective_mode) = match analysis_pipeline::build_embedding_input(
+            input_mode,
+            file_path,
+            summary.as_deref(),
+            || {
+                std::fs::read_to_string(std::path::Path::new(&project_path).join(file_path))
+                    .map_err(|e| e.to_string())
+            },
+        ) {
+            Ok(input) => input,
+            Err(_) => {
+                fail_count += 1;
+                continue;
             }
         };
 
-        match llm_client::generate_embedding(&base_url, &api_key, &embed_model, &input_text).await {
+        let embedding = llm_client::generate_embedding(
+            &base_url, &api_key, &extra_headers, &embed_model, &input_text,
+        )
+        .await;
+        match embedding {
             Ok(embedding) => {
-                let bytes = analyzer::embedding_to_bytes(&embedding);
+                let bytes = analyzer::normalized_embedding_to_bytes(&embedding);
                 let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+                // 维度不一致时中止整批：后续向量来自同一模型，同样无法存储
+                db.ensure_embedding_dimension(embedding.len())?;
                 let conn = db.conn();
                 conn.execute(
-                    "UPDATE file_index SET embedding = ?1 WHERE project_id = ?2 AND file_path = ?3",
-                    rusqlite::params![bytes, project_id, file_path],
+                    "UPDATE file_index SET embedding = ?1, embedding_mode = ?2
+                     WHERE project_id = ?3 AND file_path = ?4",
+                    rusqlite::params![bytes, effective_mode.as_str(), project_id, file_path],
                 )
                 .map_err(|e| format!("保存 Embedding 失败：{}", e))?;
                 success_count += 1;
@@ -481,6 +874,64 @@ pub async fn embed_all_files(
     })
 }
 
+/// 获取当前 Embedding 模型及已记录的向量维度
+#[tauri::command]
+pub fn get_embedding_model_info(
+    db: State<'_, Mutex<Database>>,
+) -> Result<EmbeddingModelInfo, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    db.get_embedding_model_info()
+}
+
+/// 清空项目的所有 Embedding 向量（切换 Embedding 模型后需清空再重新索引）
+///
+/// # 返回
+/// - 被清空向量的文件数
+#[tauri::command]
+pub fn clear_embeddings(db: State<'_, Mutex<Database>>, project_id: i64) -> Result<usize, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    db.clear_embeddings(project_id)
+}
+
+/// 清空项目的全部分析数据（文件索引、摘要、向量、目录索引与分析报告），保留项目与构建记录
+///
+/// - `vacuum`: 为 true 时随后执行 `VACUUM` 回收磁盘空间（大型数据库可能耗时较长）
+///
+/// # 返回
+/// - 各表删除的行数
+#[tauri::command]
+pub fn clear_project_analysis(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    vacuum: Option<bool>,
+) -> Result<ClearReport, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let report = db.clear_project_analysis(project_id)?;
+    if vacuum.unwrap_or(false) {
+        db.vacuum()?;
+    }
+    Ok(report)
+}
+
+/// 列出项目中已有向量所使用的输入模式；多于一种时说明向量混用，需清空后重新索引
+#[tauri::command]
+pub fn list_embedding_modes(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+) -> Result<Vec<String>, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    db.list_embedding_modes(project_id)
+}
+
+/// 单文件摘要结果
+#[derive(Serialize)]
+pub struct FileSummaryResult {
+    /// 新生成的摘要
+    pub summary: String,
+    /// 旧 Embedding 已因摘要变化被清空，需重新生成
+    pub needs_reembed: bool,
+}
+
 /// 批量 Embedding 结果
 #[derive(Serialize)]
 pub struct EmbedBatchResult {
@@ -489,90 +940,259 @@ pub struct EmbedBatchResult {
     pub failed: u32,
 }
 
-/// 语义搜索：根据查询文本找到最相似的文件
+/// 批量"摘要 + Embedding"结果
+#[derive(Serialize)]
+pub struct AnalyzeEmbedBatchResult {
+    /// 文件索引中的文件总数
+    pub total: u32,
+    /// 已有摘要和向量且内容未变、被跳过的文件数
+    pub skipped: u32,
+    /// 成功生成摘要的文件数
+    pub summarized: u32,
+    /// 成功生成向量的文件数
+    pub embedded: u32,
+    /// 处理失败的文件数
+    pub failed: u32,
+    /// 每个失败文件的具体原因
+    pub failures: Vec<FileFailure>,
+}
+
+/// 单个文件的失败信息
+#[derive(Serialize)]
+pub struct FileFailure {
+    pub file_path: String,
+    pub error: String,
+}
+
+/// 一次完成批量摘要与 Embedding：逐文件先生成摘要，再以新摘要作为 Embedding 输入
+///
+/// 有限并发执行，结果按顺序逐条落库；已有摘要和向量且文件哈希未变的文件会被跳过，
+/// 中途中断后重新调用即可从断点继续。
 ///
 /// # 参数
 /// - `project_id`: 项目 ID
-/// - `query`: 搜索查询文本
-/// - `top_k`: 返回前 K 个最相似的结果
+/// - `project_path`: 项目根目录路径
 #[tauri::command]
-pub async fn search_similar_files(
+pub async fn analyze_and_embed_all(
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
-    query: String,
-    top_k: usize,
-) -> Result<Vec<SimilarFileEntry>, String> {
-    // 1. 读取配置
-    let (base_url, api_key, embed_model) = {
-        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let get = |key: &str| -> String {
-            conn.query_row(
-                "SELECT value FROM settings WHERE key = ?1",
-                rusqlite::params![key],
-                |row| row.get::<_, String>(0),
-            )
-            .unwrap_or_default()
-        };
-        (get("llm_base_url"), get("llm_api_key"), get("llm_embedding_model"))
-    };
-
-    if base_url.is_empty() || embed_model.is_empty() {
-        return Err("请先在设置页面配置 API 地址和 Embedding 模型".to_string());
+    project_path: String,
+) -> Result<AnalyzeEmbedBatchResult, String> {
+    let _timer = metrics.start("analyze_and_embed_all");
+
+    // 1. 读取配置（摘要模型 + Embedding 模型）
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        model_name,
+        embedding_model: embed_model,
+        ..
+    } = load_llm_config(&db)?;
+    let input_mode = load_embedding_input_mode(&db)?;
+
+    if base_url.is_empty() || model_name.is_empty() || embed_model.is_empty() {
+        return Err("请先在设置页面配置 API 地址、LLM 模型和 Embedding 模型".to_string());
     }
 
-    // 2. 生成查询文本的 embedding
-    let query_embedding = llm_client::generate_embedding(
-        &base_url, &api_key, &embed_model, &query,
-    )
-    .await?;
-
-    // 3. 从数据库加载所有有 embedding 的文件
-    let file_embeddings: Vec<(String, Option<String>, Vec<u8>)> = {
+    // 2. 读取文件索引（路径、哈希及其算法、是否已有摘要/向量）
+    let indexed: Vec<(String, String, String, bool)> = {
         let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
         let conn = db.conn();
         let mut stmt = conn
             .prepare(
-                "SELECT file_path, summary, embedding FROM file_index WHERE project_id = ?1 AND embedding IS NOT NULL",
+                "SELECT file_path, file_hash, hash_algo,
+                        summary IS NOT NULL AND summary != '' AND embedding IS NOT NULL
+                 FROM file_index WHERE project_id = ?1 ORDER BY file_path",
             )
             .map_err(|e| format!("查询文件索引失败：{}", e))?;
         let rows = stmt
             .query_map(rusqlite::params![project_id], |row| {
                 Ok((
                     row.get::<_, String>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                    row.get::<_, Vec<u8>>(2)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, bool>(3)?,
                 ))
             })
             .map_err(|e| format!("查询文件索引失败：{}", e))?;
         rows.filter_map(|r| r.ok()).collect()
     };
 
-    if file_embeddings.is_empty() {
-        return Ok(vec![]);
+    // 3. 断点续跑：已完成且哈希未变的文件直接跳过
+    let root = std::path::Path::new(&project_path);
+    let total = indexed.len() as u32;
+    let mut skipped = 0u32;
+    let mut current_hashes: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+    let mut pending: Vec<String> = Vec::new();
+    for (file_path, stored_hash, hash_algo, done) in indexed {
+        // 按记录的算法重算，写回的哈希与 hash_algo 列保持一致
+        let hash_algo = analyzer::HashAlgo::parse(&hash_algo).unwrap_or_default();
+        let current_hash =
+            analyzer::compute_file_hash_with(&root.join(&file_path), hash_algo).unwrap_or_default();
+        if done && current_hash == stored_hash {
+            skipped += 1;
+        } else {
+            current_hashes.insert(file_path.clone(), current_hash);
+            pending.push(file_path);
+        }
     }
 
-    // 4. 计算余弦相似度并排序
-    let mut results: Vec<SimilarFileEntry> = file_embeddings
-        .iter()
-        .map(|(path, summary, bytes)| {
-            let emb = analyzer::bytes_to_embedding(bytes);
-            let score = analyzer::cosine_similarity(&query_embedding, &emb);
-            SimilarFileEntry {
-                relative_path: path.clone(),
-                summary: summary.clone(),
-                score,
-            }
+    // 4. 有限并发执行"摘要 → Embedding"，按顺序逐条落库
+    let summarize = |file_path: String| {
+        let (base_url, api_key, model_name) = (&base_url, &api_key, &model_name);
+        let extra_headers = &extra_headers;
+        async move {
+            let content = std::fs::read_to_string(root.join(&file_path))
+                .map_err(|e| format!("读取文件失败 {}: {}", file_path, e))?;
+            llm_client::generate_summary(
+                base_url, api_key, extra_headers, model_name, &file_path, &content,
+            )
+            .await
+        }
+    };
+    let embed = |input: String| {
+        let (base_url, api_key, embed_model) = (&base_url, &api_key, &embed_model);
+        let extra_headers = &extra_headers;
+        async move {
+            llm_client::generate_embedding(base_url, api_key, extra_headers, embed_model, &input)
+                .await
+        }
+    };
+    // 摘要始终生成；向量输入按 embedding_input_mode 决定
+    let input = |file_path: &str, summary: &str| {
+        analysis_pipeline::build_embedding_input(input_mode, file_path, Some(summary), || {
+            std::fs::read_to_string(root.join(file_path))
+                .map_err(|e| format!("读取文件失败 {}: {}", file_path, e))
         })
-        .collect();
+    };
+    let mut results = Box::pin(analysis_pipeline::summarize_then_embed_with_input(
+        pending,
+        analysis_pipeline::DEFAULT_PIPELINE_CONCURRENCY,
+        summarize,
+        input,
+        embed,
+    ));
+
+    let mut summarized = 0u32;
+    let mut embedded = 0u32;
+    let mut failures: Vec<FileFailure> = Vec::new();
+    while let Some(mut item) = results.next().await {
+        if let Some(ref summary) = item.summary {
+            let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+            // 维度与已记录维度不一致的向量不落库，按失败记录
+            if let Some(embedding) = &item.embedding {
+                if let Err(e) = db.ensure_embedding_dimension(embedding.len()) {
+                    item.embedding = None;
+                    item.error = Some(e);
+                }
+            }
+            // 摘要与向量一并写入；向量失败时清空旧向量，避免与新摘要不一致
+            let bytes = item.embedding.as_deref().map(analyzer::normalized_embedding_to_bytes);
+            let mode = item.embedding_mode.filter(|_| item.embedding.is_some()).map(|m| m.as_str());
+            let file_hash = current_hashes.get(&item.file_path).cloned().unwrap_or_default();
+            db.conn()
+                .execute(
+                    "UPDATE file_index SET summary = ?1, embedding = ?2, embedding_mode = ?3, file_hash = ?4
+                     WHERE project_id = ?5 AND file_path = ?6",
+                    rusqlite::params![summary, bytes, mode, file_hash, project_id, item.file_path],
+                )
+                .map_err(|e| format!("保存分析结果失败：{}", e))?;
+            summarized += 1;
+            if item.embedding.is_some() {
+                embedded += 1;
+            }
+        }
+
+        if let Some(error) = item.error {
+            log::warn!("摘要+Embedding 处理失败 [{}]: {}", item.file_path, error);
+            failures.push(FileFailure {
+                file_path: item.file_path,
+                error,
+            });
+        }
+    }
+
+    Ok(AnalyzeEmbedBatchResult {
+        total,
+        skipped,
+        summarized,
+        embedded,
+        failed: failures.len() as u32,
+        failures,
+    })
+}
+
+/// 语义搜索：根据查询文本找到最相似的文件
+///
+/// # 参数
+/// - `project_id`: 项目 ID
+/// - `query`: 搜索查询文本
+/// - `top_k`: 返回前 K 个最相似的结果
+/// - `path_prefix`: 可选路径前缀（如 `modules/auth`），仅在该范围内搜索；为空时搜索整个项目
+/// - `min_score`: 可选最低相似度，低于该值的文件不返回；为空时始终返回 Top-K
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_similar_files(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    index_cache: State<'_, VectorIndexCache>,
+    project_id: i64,
+    query: String,
+    top_k: usize,
+    path_prefix: Option<String>,
+    min_score: Option<f32>,
+) -> Result<Vec<SimilarFileEntry>, String> {
+    let _timer = metrics.start("search_similar_files");
+
+    // 1. 读取配置
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        embedding_model: embed_model,
+        ..
+    } = load_llm_config(&db)?;
+
+    if base_url.is_empty() || embed_model.is_empty() {
+        return Err("请先在设置页面配置 API 地址和 Embedding 模型".to_string());
+    }
 
-    // 按相似度降序排序
-    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    // 2. 生成查询文本的 embedding
+    let query_embedding = llm_client::generate_embedding(
+        &base_url, &api_key, &extra_headers, &embed_model, &query,
+    )
+    .await?;
 
-    // 取 Top-K
-    results.truncate(top_k);
+    // 3. 取出项目的向量索引（向量版本号未变时复用缓存，否则从数据库重建）
+    let index = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let modes = db.list_embedding_modes(project_id)?;
+        if modes.len() > 1 {
+            log::warn!("项目 {} 的向量混用了多种输入模式 {:?}，建议清空后重新索引", project_id, modes);
+        }
+        let version = db.embedding_version(project_id)?;
+        index_cache.get_or_build(project_id, version, || {
+            Ok(db
+                .list_file_embeddings(project_id, None)?
+                .into_iter()
+                .map(|(path, summary, bytes)| (path, summary, analyzer::bytes_to_embedding(&bytes)))
+                .collect())
+        })?
+    };
 
-    Ok(results)
+    // 4. 按路径前缀限定范围，剔除低于阈值的文件后取相似度最高的 Top-K
+    Ok(index
+        .search(&query_embedding, top_k, path_prefix.as_deref(), min_score)
+        .into_iter()
+        .map(|r| SimilarFileEntry {
+            relative_path: r.relative_path,
+            summary: r.summary,
+            score: r.score,
+        })
+        .collect())
 }
 
 /// 语义搜索结果条目（返回给前端）
@@ -596,6 +1216,8 @@ pub struct LanguageStatEntry {
     pub language: String,
     pub file_count: u32,
     pub line_count: u32,
+    pub blank_lines: u32,
+    pub comment_lines: u32,
 }
 
 /// 项目概览数据（返回给前端）
@@ -607,16 +1229,34 @@ pub struct ProjectOverviewEntry {
     pub tech_stack: Vec<String>,
     pub languages: Vec<LanguageStatEntry>,
     pub entry_files: Vec<String>,
+    pub skipped_large_files: Vec<String>,
+    pub duplicate_groups: Vec<Vec<String>>,
 }
 
 /// 获取项目概览信息（技术栈检测、文件统计、语言分布）
 ///
 /// # 参数
 /// - `project_path`: 项目根目录路径
+/// - `sub_path`: 可选子目录（相对项目根目录，如 `backend`），指定后仅分析该子树
+/// - `ignored_dirs`: 在默认忽略目录之外追加跳过的目录名（如 `coverage`）
+/// - `respect_gitignore`: 是否排除项目内 `.gitignore` 匹配的文件（默认关闭）
 #[tauri::command]
-pub fn get_project_overview(project_path: String) -> Result<ProjectOverviewEntry, String> {
-    let path = std::path::Path::new(&project_path);
-    let overview = analyzer::analyze_project_overview(path)?;
+pub fn get_project_overview(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    project_path: String,
+    sub_path: Option<String>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
+) -> Result<ProjectOverviewEntry, String> {
+    let _timer = metrics.start("get_project_overview");
+
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    let max_file_bytes = load_max_overview_file_bytes(&db)?;
+    let scan_config = load_scan_config(&db, ignored_dirs, respect_gitignore)?;
+    let overview =
+        analyzer::analyze_project_overview_with_config(&root, max_file_bytes, &scan_config)?;
 
     Ok(ProjectOverviewEntry {
         total_files: overview.total_files,
@@ -627,8 +1267,12 @@ pub fn get_project_overview(project_path: String) -> Result<ProjectOverviewEntry
             language: l.language,
             file_count: l.file_count,
             line_count: l.line_count,
+            blank_lines: l.blank_lines,
+            comment_lines: l.comment_lines,
         }).collect(),
         entry_files: overview.entry_files,
+        skipped_large_files: overview.skipped_large_files,
+        duplicate_groups: overview.duplicate_groups,
     })
 }
 
@@ -650,16 +1294,39 @@ pub struct IndexSignaturesResult {
 /// # 参数
 /// - `project_id`: 项目 ID
 /// - `project_path`: 项目根目录路径
+/// - `sub_path`: 可选子目录（相对项目根目录，如 `backend`），指定后仅分析该子树
+/// - `ignored_dirs`: 在默认忽略目录之外追加跳过的目录名（如 `coverage`）
+/// - `respect_gitignore`: 是否排除项目内 `.gitignore` 匹配的文件（默认关闭）
 #[tauri::command]
 pub fn index_project_signatures(
     db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_id: i64,
     project_path: String,
+    sub_path: Option<String>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
 ) -> Result<IndexSignaturesResult, String> {
-    let path = std::path::Path::new(&project_path);
-
-    // 1. 提取所有文件签名
-    let signatures = analyzer::extract_project_signatures(path)?;
+    let _timer = metrics.start("index_project_signatures");
+
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    let path = root.as_path();
+
+    // 1. 提取所有文件签名（含用户追加的代码扩展名），文件列表扫描复用文件索引中的哈希
+    let code_extensions = load_code_extensions(&db)?;
+    let scan_config = load_scan_config(&db, ignored_dirs, respect_gitignore)?;
+    let prior = load_prior_files(
+        &db,
+        Some(project_id),
+        &analyzer::scan_root_prefix(sub_path.as_deref()),
+    )?;
+    let signatures = analyzer::extract_project_signatures_with_config(
+        path,
+        &code_extensions,
+        &scan_config,
+        &prior,
+    )?;
     let total = signatures.len() as u32;
 
     // 2. 将签名序列化后存入 file_index.signatures 列
@@ -687,50 +1354,79 @@ pub fn index_project_signatures(
 /// # 参数
 /// - `project_id`: 项目 ID
 /// - `project_path`: 项目根目录路径
-/// - `mode`: 报告模式 "fast"（1次LLM调用）或 "deep"（分层压缩）
+/// - `mode`: 报告模式 "fast"（1次LLM调用）、"deep"（分层压缩）或 "offline"（不调用 LLM 的静态报告）
+/// - `sub_path`: 可选子目录（相对项目根目录，如 `backend`），指定后仅分析该子树
+/// - `resume`: deep 模式汇总失败后重试时传 true，复用上次已完成的签名压缩结果
+/// - `ignored_dirs`: 在默认忽略目录之外追加跳过的目录名（如 `coverage`）
+/// - `respect_gitignore`: 是否排除项目内 `.gitignore` 匹配的文件（默认关闭）
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn generate_project_report(
     db: State<'_, Mutex<Database>>,
-    _project_id: i64,
+    metrics: State<'_, CommandMetrics>,
+    compression_cache: State<'_, report::CompressionCache>,
+    project_id: i64,
     project_path: String,
     mode: String,
+    sub_path: Option<String>,
+    resume: Option<bool>,
+    ignored_dirs: Option<Vec<String>>,
+    respect_gitignore: Option<bool>,
 ) -> Result<String, String> {
-    let path = std::path::Path::new(&project_path);
+    let _timer = metrics.start("generate_project_report");
+
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    let path = root.as_path();
+
+    // 1. 收集项目数据（无代码文件时直接返回提示，避免向 LLM 发送空 prompt）
+    //    概览、签名与依赖使用同一扫描配置，忽略目录在各部分中一致生效
+    let max_file_bytes = load_max_overview_file_bytes(&db)?;
+    let scan_config = load_scan_config(&db, ignored_dirs, respect_gitignore)?;
+    let overview =
+        analyzer::analyze_project_overview_with_config(path, max_file_bytes, &scan_config)?;
+    report::ensure_has_code_files(&overview)?;
+    let code_extensions = load_code_extensions(&db)?;
+    // 签名与依赖扫描复用文件索引中的哈希，未变化的文件不再重新计算
+    let prior = load_prior_files(
+        &db,
+        Some(project_id),
+        &analyzer::scan_root_prefix(sub_path.as_deref()),
+    )?;
+    let signatures = analyzer::extract_project_signatures_with_config(
+        path,
+        &code_extensions,
+        &scan_config,
+        &prior,
+    )?;
+    let sig_text = analyzer::format_signatures_for_llm(&signatures);
 
-    // 1. 读取 LLM 配置
-    let (base_url, api_key, model_name) = {
-        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
-        let conn = db.conn();
-        let get = |key: &str| -> String {
-            conn.query_row(
-                "SELECT value FROM settings WHERE key = ?1",
-                rusqlite::params![key],
-                |row| row.get::<_, String>(0),
-            )
-            .unwrap_or_default()
-        };
-        (get("llm_base_url"), get("llm_api_key"), get("llm_model_name"))
-    };
+    // 2. 收集依赖关系
+    let entries = analyzer::scan_project_files_with_config(path, &scan_config, &prior)?;
+    let file_paths: Vec<String> = entries.iter().map(|e| e.relative_path.clone()).collect();
+    let dep_edges =
+        analyzer::extract_dependencies_with_extensions(path, &file_paths, &code_extensions)?;
+
+    // 离线模式：仅基于静态分析拼装报告，无需 LLM 配置
+    if mode == "offline" {
+        return Ok(report::build_offline_report(&overview, &signatures, &dep_edges));
+    }
+
+    // 3. 读取 LLM 配置
+    let LlmConfig {
+        base_url,
+        api_key,
+        extra_headers,
+        model_name,
+        ..
+    } = load_llm_config(&db)?;
 
     if base_url.is_empty() || model_name.is_empty() {
         return Err("请先在设置页面配置 LLM API 地址和模型".to_string());
     }
 
-    // 2. 收集项目数据
-    let overview = analyzer::analyze_project_overview(path)?;
-    let signatures = analyzer::extract_project_signatures(path)?;
-    let sig_text = analyzer::format_signatures_for_llm(&signatures);
-
-    // 3. 收集依赖关系
-    let entries = analyzer::scan_project_files(path)?;
-    let file_paths: Vec<String> = entries.iter().map(|e| e.relative_path.clone()).collect();
-    let dep_edges = analyzer::extract_dependencies(path, &file_paths)?;
-    let dep_text = dep_edges
-        .iter()
-        .take(200) // 限制依赖边数量，避免 prompt 过长
-        .map(|e| format!("  {} -> {}", e.source, e.target))
-        .collect::<Vec<_>>()
-        .join("\n");
+    // 限制依赖边数量，避免 prompt 过长；超出部分以高耦合文件汇总代替
+    let dep_text = report::format_dependency_text(&dep_edges, report::MAX_PROMPT_DEP_EDGES);
 
     // 4. 构建 system prompt
     let system_prompt = "你是一个资深软件架构师。请根据提供的项目数据，生成一份全面的项目分析报告。\n\
@@ -769,45 +1465,64 @@ pub async fn generate_project_report(
         "fast" => {
             // Fast 模式：直接一次调用
             llm_client::generate_report(
-                &base_url, &api_key, &model_name,
+                &base_url, &api_key, &extra_headers, &model_name,
                 system_prompt, &user_prompt,
             ).await
         }
         "deep" => {
-            // Deep 模式：签名过长时先压缩再汇总
-            if sig_text.len() > 30000 {
+            // Deep 模式：签名过长时先压缩再汇总（压缩结果缓存，汇总失败可 resume 重试）
+            if sig_text.len() > report::DEEP_COMPRESS_THRESHOLD {
+                let (base_url, api_key, extra_headers, model_name) =
+                    (&base_url, &api_key, &extra_headers, &model_name);
                 // 第一步：压缩签名摘要
-                let compress_prompt = format!(
-                    "以下是一个大型项目的代码签名列表，请将其压缩为一份结构化摘要，\
-                    保留关键的类、函数和模块信息，去除重复和不重要的细节：\n\n{}",
-                    sig_text
-                );
-                let compressed = llm_client::generate_report(
-                    &base_url, &api_key, &model_name,
-                    "你是一个代码分析助手，请压缩以下代码签名信息。",
-                    &compress_prompt,
-                ).await?;
+                let compress = |sig_text: &str| {
+                    let compress_prompt = format!(
+                        "以下是一个大型项目的代码签名列表，请将其压缩为一份结构化摘要，\
+                        保留关键的类、函数和模块信息，去除重复和不重要的细节：\n\n{}",
+                        sig_text
+                    );
+                    async move {
+                        llm_client::generate_report(
+                            base_url, api_key, extra_headers, model_name,
+                            "你是一个代码分析助手，请压缩以下代码签名信息。",
+                            &compress_prompt,
+                        ).await
+                    }
+                };
 
                 // 第二步：用压缩后的签名生成报告
-                let final_prompt = format!(
-                    "## 项目统计\n- 文件数：{}\n- 代码行数：{}\n- 目录数：{}\n- 技术栈：{}\n\n\
-                     ## 代码结构摘要\n{}\n\n\
-                     ## 依赖关系\n{}",
-                    overview.total_files,
-                    overview.total_lines,
-                    overview.total_dirs,
-                    overview.tech_stack.join(", "),
-                    compressed,
-                    dep_text,
-                );
-                llm_client::generate_report(
-                    &base_url, &api_key, &model_name,
-                    system_prompt, &final_prompt,
-                ).await
+                let synthesize = |compressed: String| {
+                    let final_prompt = format!(
+                        "## 项目统计\n- 文件数：{}\n- 代码行数：{}\n- 目录数：{}\n- 技术栈：{}\n\n\
+                         ## 代码结构摘要\n{}\n\n\
+                         ## 依赖关系\n{}",
+                        overview.total_files,
+                        overview.total_lines,
+                        overview.total_dirs,
+                        overview.tech_stack.join(", "),
+                        compressed,
+                        dep_text,
+                    );
+                    async move {
+                        llm_client::generate_report(
+                            base_url, api_key, extra_headers, model_name,
+                            system_prompt, &final_prompt,
+                        ).await
+                    }
+                };
+
+                report::deep_report_with_cache(
+                    &sig_text,
+                    &compression_cache,
+                    resume.unwrap_or(false),
+                    compress,
+                    synthesize,
+                )
+                .await
             } else {
                 // 签名不多，等同于 fast 模式
                 llm_client::generate_report(
-                    &base_url, &api_key, &model_name,
+                    &base_url, &api_key, &extra_headers, &model_name,
                     system_prompt, &user_prompt,
                 ).await
             }
@@ -816,3 +1531,157 @@ pub async fn generate_project_report(
     }
 }
 
+/// 按当前代码重新生成已保存的报告，保存为新版本
+///
+/// 沿用原报告的项目、模式与子目录调用 `generate_project_report`，
+/// 新报告的 `supersedes` 指向原报告，原报告保留以便回溯历史。
+#[tauri::command]
+pub async fn refresh_report(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    compression_cache: State<'_, report::CompressionCache>,
+    report_id: i64,
+) -> Result<ProjectReport, String> {
+    let (previous, project) = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let previous = db.get_report(report_id)?;
+        let project = db.get_project(previous.project_id)?;
+        (previous, project)
+    };
+
+    let content = generate_project_report(
+        db.clone(),
+        metrics,
+        compression_cache,
+        project.id,
+        project.repo_path,
+        previous.mode,
+        previous.sub_path,
+        None,
+        None,
+        None,
+    )
+    .await?;
+
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    db.supersede_report(report_id, &content)
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scan_dependencies_reuses_indexed_hashes() {
+        let data_dir = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        let backend = repo.path().join("backend");
+        fs::create_dir(&backend).unwrap();
+        fs::write(backend.join("a.py"), "import b\n").unwrap();
+        fs::write(backend.join("b.py"), "x = 1\n").unwrap();
+
+        let db = Database::init(data_dir.path()).unwrap();
+        let category = db.create_category("测试分类", None).unwrap();
+        let repo_path = repo.path().to_str().unwrap();
+        let project = db.create_project("测试项目", category.id, repo_path, "fastapi", "").unwrap();
+
+        // 索引中记录与磁盘一致的大小和修改时间，哈希用占位值：复用时结果中出现占位值
+        for entry in analyzer::scan_project_files(repo.path()).unwrap() {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, file_size, mtime)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![
+                        project.id,
+                        entry.relative_path,
+                        format!("cached:{}", entry.relative_path),
+                        entry.file_size as i64,
+                        entry.mtime as i64
+                    ],
+                )
+                .unwrap();
+        }
+        let db = Mutex::new(db);
+
+        // 子目录扫描：按前缀读取记录，未变化的文件不再读取
+        let (entries, edges, _) =
+            scan_dependencies(&db, repo_path, Some("backend".into()), Some(project.id), None, None)
+                .unwrap();
+        let hashes: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e.relative_path.as_str(), e.file_hash.as_str()))
+            .collect();
+        assert_eq!(hashes, vec![("a.py", "cached:backend/a.py"), ("b.py", "cached:backend/b.py")]);
+        assert_eq!(edges.len(), 1);
+
+        // 内容变化的文件重新计算哈希
+        fs::write(backend.join("b.py"), "x = 1  # changed\n").unwrap();
+        let (entries, _, _) =
+            scan_dependencies(&db, repo_path, Some("backend".into()), Some(project.id), None, None)
+                .unwrap();
+        assert_eq!(entries[0].file_hash, "cached:backend/a.py");
+        assert_ne!(entries[1].file_hash, "cached:backend/b.py");
+
+        // 未指定项目时不复用任何记录
+        let (entries, _, _) = scan_dependencies(&db, repo_path, None, None, None, None).unwrap();
+        assert!(entries.iter().all(|e| !e.file_hash.starts_with("cached:")));
+    }
+
+    #[test]
+    fn test_sub_path_analysis_keeps_project_degrees() {
+        let data_dir = TempDir::new().unwrap();
+        let repo = TempDir::new().unwrap();
+        let backend = repo.path().join("backend");
+        fs::create_dir(&backend).unwrap();
+        fs::write(backend.join("hub.py"), "VALUE = 1\n").unwrap();
+        fs::write(backend.join("a.py"), "from .hub import VALUE\n").unwrap();
+        fs::write(repo.path().join("main.py"), "from backend.hub import VALUE\n").unwrap();
+
+        let db = Database::init(data_dir.path()).unwrap();
+        let category = db.create_category("测试分类", None).unwrap();
+        let repo_path = repo.path().to_str().unwrap().to_string();
+        let project = db.create_project("测试项目", category.id, &repo_path, "fastapi", "").unwrap();
+        for entry in analyzer::scan_project_files(repo.path()).unwrap() {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash) VALUES (?1, ?2, 'h')",
+                    rusqlite::params![project.id, entry.relative_path],
+                )
+                .unwrap();
+        }
+        let db = Mutex::new(db);
+        let metrics = CommandMetrics::default();
+        let hub_in_degree = || {
+            let db = db.lock().unwrap();
+            let rows = db.list_file_index(project.id).unwrap();
+            rows.iter().find(|r| r.file_path == "backend/hub.py").unwrap().in_degree
+        };
+        let analyze = |sub_path: Option<&str>| {
+            analyze_dependencies(
+                State(&db),
+                State(&metrics),
+                repo_path.clone(),
+                sub_path.map(str::to_string),
+                Some(project.id),
+                None,
+                None,
+            )
+            .unwrap()
+        };
+
+        analyze(None);
+        assert_eq!(hub_in_degree(), Some(2));
+
+        // 子目录分析看不到 main.py 的引用，不覆盖项目级度数
+        let graph = analyze(Some("backend"));
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(hub_in_degree(), Some(2));
+    }
+}
+
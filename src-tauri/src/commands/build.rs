@@ -3,36 +3,76 @@
 // 负责：构建交付包（含多技术栈）、打开文件夹
 // ============================================================================
 
-use crate::models::dtos::BuildResult;
-use crate::services::build_strategy::{self, BuildStrategy};
+use std::sync::Mutex;
+
+use crate::commands::project::load_module_depth;
+use crate::database::Database;
+use crate::models::dtos::{
+    ArchiveInfo, BfsScope, BuildOptions, BuildResult, CheckItem, ClientBuildResult,
+    ExpansionResult, PackageVerification, TechStackInfo, TimestampStyle,
+};
+use crate::services::analyzer;
+use crate::services::build_strategy;
+use crate::services::metrics::CommandMetrics;
+use crate::services::module_rewriter::{self, MissingImport};
+use crate::services::packer;
 use crate::services::scanner;
-use tauri::Emitter;
+use tauri::{Emitter, State};
+
+/// 从设置项读取交付包命名的时间戳风格（`build_timestamp_tz` / `build_timestamp_format`）
+fn load_timestamp_style(db: &Mutex<Database>) -> Result<TimestampStyle, String> {
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    let tz = db.get_setting(build_strategy::BUILD_TIMESTAMP_TZ_KEY)?;
+    let format = db.get_setting(build_strategy::BUILD_TIMESTAMP_FORMAT_KEY)?;
+    Ok(build_strategy::timestamp_style_from_settings(
+        tz.as_deref(),
+        format.as_deref(),
+    ))
+}
 
 /// 构建交付包（V1 兼容接口）：委托给 FastAPI 构建策略
 ///
 /// 此命令保留为 QuickBuildPage 的后端接口，内部直接复用
 /// FastApiBuildStrategy 的构建逻辑，消除重复代码。
+/// `options` 为可选构建选项（如 `dry_run: true` 仅返回构建计划），未传入时使用默认值。
+/// `sub_path` 为可选子目录（如 monorepo 中的 `backend`），指定后以该子目录作为构建根目录。
 #[tauri::command]
 pub async fn build_package(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_path: String,
     selected_modules: Vec<String>,
     client_name: String,
+    options: Option<BuildOptions>,
+    sub_path: Option<String>,
 ) -> Result<BuildResult, String> {
-    let path = std::path::Path::new(&project_path);
+    let _timer = metrics.start("build_package");
+
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    let path = root.as_path();
     // 扫描所有模块名用于依赖分析
-    let all_module_names: Vec<String> = scanner::scan_modules_dir(&path.join("modules"))
-        .unwrap_or_default()
-        .into_iter()
-        .map(|m| m.name)
-        .collect();
+    let module_depth = load_module_depth(&db)?;
+    let all_module_names: Vec<String> =
+        scanner::scan_modules_dir_with_depth(&path.join("modules"), module_depth)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+
+    let mut options = options.unwrap_or_default();
+    options.timestamp = load_timestamp_style(&db)?;
 
     let builder = build_strategy::FastApiBuildStrategy;
-    builder.build(
+    build_strategy::build_common_with_options(
+        &builder,
         path,
         &selected_modules,
         &client_name,
         "",
         &all_module_names,
+        &options,
+        &|_| {},
     )
     .map_err(|e| e.to_string())
 }
@@ -41,17 +81,30 @@ pub async fn build_package(
 ///
 /// 根据技术栈类型调用对应的构建策略，通过 Tauri Event 向前端推送构建日志。
 /// 构建前自动扫描所有模块名，用于 BFS 传递依赖分析。
+/// `options` 为可选构建选项（如 `rewrite: false` 仅复制不重写入口文件、
+/// `dry_run: true` 仅返回构建计划），未传入时使用默认值。
+/// `sub_path` 为可选子目录（如 monorepo 中的 `backend`），指定后以该子目录作为构建根目录，
+/// 模块发现、依赖分析和打包均在该子树内进行。
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn build_project_package(
     app: tauri::AppHandle,
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
     project_path: String,
     selected_modules: Vec<String>,
     client_name: String,
     tech_stack: String,
     modules_dir: String,
+    options: Option<BuildOptions>,
+    sub_path: Option<String>,
 ) -> Result<BuildResult, String> {
+    let _timer = metrics.start("build_project_package");
+
     let builder = build_strategy::get_builder(&tech_stack).map_err(|e| e.to_string())?;
-    let path = std::path::Path::new(&project_path);
+    let root =
+        analyzer::resolve_scan_root(std::path::Path::new(&project_path), sub_path.as_deref())?;
+    let path = root.as_path();
 
     // 确定模块目录（用户自定义优先，否则使用策略默认值）
     let modules_dir_name = if modules_dir.is_empty() {
@@ -61,38 +114,356 @@ pub async fn build_project_package(
     };
 
     // 扫描所有模块名用于依赖分析
-    let all_module_names: Vec<String> = scanner::scan_modules_dir(&path.join(modules_dir_name))
-        .unwrap_or_default()
-        .into_iter()
-        .map(|m| m.name)
-        .collect();
+    let module_depth = load_module_depth(&db)?;
+    let all_module_names: Vec<String> =
+        scanner::scan_modules_dir_with_depth(&path.join(modules_dir_name), module_depth)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
 
     // 构建日志回调：通过 Tauri Event 推送到前端
     let log_fn = |msg: &str| {
         let _ = app.emit("build-log", msg.to_string());
     };
 
-    builder.build_with_log(
+    let mut options = options.unwrap_or_default();
+    options.timestamp = load_timestamp_style(&db)?;
+
+    build_strategy::build_common_with_options(
+        builder.as_ref(),
         path,
         &selected_modules,
         &client_name,
         &modules_dir,
         &all_module_names,
+        &options,
         &log_fn,
     )
     .map_err(|e| e.to_string())
 }
 
+/// 一次构建、为多个客户分别打包（带实时日志推送）
+///
+/// 公共构建步骤只执行一次，随后为每个客户生成以客户名命名的 ZIP 并写入构建记录。
+/// 单个客户打包失败不影响其他客户，失败原因在对应结果的 `error` 中返回。
+/// 各客户的构建记录在同一事务中写入；试运行（`options.dry_run`）时不写入构建记录。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn build_for_clients(
+    app: tauri::AppHandle,
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    project_id: i64,
+    project_path: String,
+    selected_modules: Vec<String>,
+    client_ids: Vec<i64>,
+    modules_dir: String,
+    options: Option<BuildOptions>,
+) -> Result<Vec<ClientBuildResult>, String> {
+    let _timer = metrics.start("build_for_clients");
+
+    // 1. 读取项目技术栈与客户信息（客户须关联到该项目）
+    let (tech_stack, clients) = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        let project = db.get_project(project_id)?;
+        let project_clients = db.list_clients_by_project(project_id)?;
+        let clients = client_ids
+            .iter()
+            .map(|id| {
+                project_clients
+                    .iter()
+                    .find(|c| c.id == *id)
+                    .cloned()
+                    .ok_or_else(|| format!("客户 ID {} 不属于该项目", id))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        (project.tech_stack_type, clients)
+    };
+
+    let builder = build_strategy::get_builder(&tech_stack).map_err(|e| e.to_string())?;
+    let path = std::path::Path::new(&project_path);
+    let modules_dir_name = if modules_dir.is_empty() {
+        builder.default_modules_dir()
+    } else {
+        &modules_dir
+    };
+
+    // 扫描所有模块名用于依赖分析
+    let module_depth = load_module_depth(&db)?;
+    let all_module_names: Vec<String> =
+        scanner::scan_modules_dir_with_depth(&path.join(modules_dir_name), module_depth)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+
+    // 构建日志回调：通过 Tauri Event 推送到前端
+    let log_fn = |msg: &str| {
+        let _ = app.emit("build-log", msg.to_string());
+    };
+
+    // 2. 公共构建 + 逐客户打包
+    let client_names: Vec<String> = clients.iter().map(|c| c.name.clone()).collect();
+    let mut options = options.unwrap_or_default();
+    options.timestamp = load_timestamp_style(&db)?;
+    let results = build_strategy::build_for_clients_with_options(
+        builder.as_ref(),
+        path,
+        &selected_modules,
+        &client_names,
+        &modules_dir,
+        &all_module_names,
+        &options,
+        &log_fn,
+    )
+    .map_err(|e| e.to_string())?;
+
+    // 3. 为打包成功的客户写入构建记录（版本号与变更日志按客户分别计算）
+    let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+    db.with_transaction(|db| {
+        let mut outcomes = Vec::with_capacity(clients.len());
+        for (client, result) in clients.into_iter().zip(results) {
+            match result {
+                Ok(result) if options.dry_run => outcomes.push(ClientBuildResult {
+                    client_id: client.id,
+                    client_name: client.name,
+                    result: Some(result),
+                    build_record_id: None,
+                    error: None,
+                }),
+                Ok(result) => {
+                    let version = db.get_next_version(client.id, project_id)?;
+                    let last_modules = db.get_last_build_modules(client.id, project_id)?;
+                    let changelog =
+                        modules_changelog(last_modules.as_deref(), &result.expanded_modules);
+                    let modules_json = serde_json::to_string(&result.expanded_modules)
+                        .map_err(|e| format!("序列化模块列表失败：{}", e))?;
+                    let record = db.create_build_record_with_config(
+                        project_id,
+                        client.id,
+                        &modules_json,
+                        &result.zip_path,
+                        &version,
+                        changelog.as_deref(),
+                        &tech_stack,
+                        modules_dir_name,
+                        None,
+                    )?;
+                    outcomes.push(ClientBuildResult {
+                        client_id: client.id,
+                        client_name: client.name,
+                        result: Some(result),
+                        build_record_id: Some(record.id),
+                        error: None,
+                    });
+                }
+                Err(error) => outcomes.push(ClientBuildResult {
+                    client_id: client.id,
+                    client_name: client.name,
+                    result: None,
+                    build_record_id: None,
+                    error: Some(error),
+                }),
+            }
+        }
+
+        Ok(outcomes)
+    })
+}
+
+/// 按构建记录重建交付包（带实时日志推送）
+///
+/// 使用记录中保存的技术栈、模块目录与模块列表重新构建，项目与客户按 ID 解析，
+/// 不受项目当前模块目录配置影响。仅生成交付包，不写入新的构建记录。
+#[tauri::command]
+pub async fn rebuild_from_record(
+    app: tauri::AppHandle,
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    build_record_id: i64,
+) -> Result<BuildResult, String> {
+    let _timer = metrics.start("rebuild_from_record");
+
+    let params = {
+        let db = db.lock().map_err(|e| format!("数据库锁获取失败：{}", e))?;
+        db.get_rebuild_params(build_record_id)?
+    };
+
+    let builder = build_strategy::get_builder(&params.tech_stack).map_err(|e| e.to_string())?;
+    let path = std::path::Path::new(&params.project_path);
+    let modules_dir_name = if params.modules_dir.is_empty() {
+        builder.default_modules_dir()
+    } else {
+        &params.modules_dir
+    };
+
+    // 扫描所有模块名用于依赖分析
+    let module_depth = load_module_depth(&db)?;
+    let all_module_names: Vec<String> =
+        scanner::scan_modules_dir_with_depth(&path.join(modules_dir_name), module_depth)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+
+    // 构建日志回调：通过 Tauri Event 推送到前端
+    let log_fn = |msg: &str| {
+        let _ = app.emit("build-log", msg.to_string());
+    };
+
+    build_strategy::build_common_with_options(
+        builder.as_ref(),
+        path,
+        &params.selected_modules,
+        &params.client_name,
+        &params.modules_dir,
+        &all_module_names,
+        &BuildOptions::default(),
+        &log_fn,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 生成与上次构建的模块差异说明（格式与构建页面的变更日志一致）
+///
+/// 无历史构建时返回 None。
+fn modules_changelog(last_modules_json: Option<&str>, current: &[String]) -> Option<String> {
+    let last: Vec<String> = serde_json::from_str(last_modules_json?).ok()?;
+    let added: Vec<&str> = current
+        .iter()
+        .filter(|m| !last.contains(m))
+        .map(String::as_str)
+        .collect();
+    let removed: Vec<&str> = last
+        .iter()
+        .filter(|m| !current.contains(m))
+        .map(String::as_str)
+        .collect();
+
+    let mut parts = Vec::new();
+    if !added.is_empty() {
+        parts.push(format!("新增: {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        parts.push(format!("移除: {}", removed.join(", ")));
+    }
+    Some(if parts.is_empty() {
+        "无变更".to_string()
+    } else {
+        parts.join("; ")
+    })
+}
+
+/// 检查输出目录是否可写（创建并删除一个临时文件），用于长时间构建前的预检
+#[tauri::command]
+pub async fn check_output_writable(dir: String) -> Result<(), String> {
+    packer::check_output_writable(std::path::Path::new(&dir))
+}
+
+/// 校验源项目入口文件的模块导入（直接读取项目目录，不经过构建暂存目录）
+///
+/// 返回引用了不存在模块的导入列表，用于交付前发现源码本身的问题。
+/// - `modules_dir`: 模块目录（相对路径），为空则使用技术栈默认值
+#[tauri::command]
+pub async fn validate_project_imports(
+    project_path: String,
+    tech_stack: String,
+    modules_dir: String,
+) -> Result<Vec<MissingImport>, String> {
+    let builder = build_strategy::get_builder(&tech_stack).map_err(|e| e.to_string())?;
+    let Some(rewriter) = module_rewriter::get_rewriter(&tech_stack) else {
+        // 该技术栈不做导入重写，也就没有可校验的模块导入
+        return Ok(Vec::new());
+    };
+    let modules_dir_name = if modules_dir.is_empty() {
+        builder.default_modules_dir()
+    } else {
+        &modules_dir
+    };
+    module_rewriter::validate_project_imports(
+        rewriter.as_ref(),
+        std::path::Path::new(&project_path),
+        modules_dir_name,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 校验已交付的 ZIP 包（内嵌清单逐文件哈希比对 + 可选的 `.sha256` 整包校验）
+///
+/// 用于确认交付包在传输过程中未被损坏或篡改。
+#[tauri::command]
+pub async fn verify_package(archive_path: String) -> Result<PackageVerification, String> {
+    packer::verify_package(std::path::Path::new(&archive_path)).map_err(|e| e.to_string())
+}
+
+/// 交付前检查清单：对已构建的交付包逐项给出通过 / 警告 / 失败结论
+///
+/// 汇总清单校验、`.sha256` 校验、敏感文件、核心文件、入口文件与 README/LICENSE 检查。
+#[tauri::command]
+pub async fn delivery_checklist(archive_path: String) -> Result<Vec<CheckItem>, String> {
+    packer::delivery_checklist(std::path::Path::new(&archive_path)).map_err(|e| e.to_string())
+}
+
+/// 读取交付包概要（大小、文件数、压缩前后字节数、顶层条目）
+///
+/// 只读取 ZIP 中央目录，不解压，供构建完成后即时展示。
+#[tauri::command]
+pub async fn describe_archive(archive_path: String) -> Result<ArchiveInfo, String> {
+    packer::describe_archive(std::path::Path::new(&archive_path)).map_err(|e| e.to_string())
+}
+
+/// 预览依赖自动补充：返回选中模块、将被自动补充的模块及每个补充模块的原因
+///
+/// `bfs_scope` 与构建选项一致，未传入时只跟随模块之间的直接 import。
+#[tauri::command]
+pub async fn preview_module_expansion(
+    db: State<'_, Mutex<Database>>,
+    metrics: State<'_, CommandMetrics>,
+    project_path: String,
+    selected_modules: Vec<String>,
+    modules_dir: String,
+    bfs_scope: Option<BfsScope>,
+) -> Result<ExpansionResult, String> {
+    let _timer = metrics.start("preview_module_expansion");
+
+    let path = std::path::Path::new(&project_path);
+    let module_depth = load_module_depth(&db)?;
+    let all_module_names: Vec<String> =
+        scanner::scan_modules_dir_with_depth(&path.join(&modules_dir), module_depth)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+
+    analyzer::preview_module_expansion(
+        path,
+        &modules_dir,
+        &selected_modules,
+        &all_module_names,
+        bfs_scope.unwrap_or(BfsScope::ModulesOnly),
+    )
+}
+
+/// 列出构建流水线支持的内置技术栈（标识、显示名称、默认模块目录、是否支持重写）
+#[tauri::command]
+pub async fn list_supported_tech_stacks() -> Result<Vec<TechStackInfo>, String> {
+    Ok(build_strategy::list_supported_tech_stacks())
+}
+
 /// 扫描项目骨架文件树（排除模块目录和默认排除项）
 ///
 /// 返回项目中除模块目录外的骨架文件相对路径列表，
 /// 用于前端展示交付包中包含的核心文件结构。
 #[tauri::command]
 pub async fn scan_project_skeleton(
+    metrics: State<'_, CommandMetrics>,
     project_path: String,
     tech_stack: String,
     modules_dir: String,
 ) -> Result<Vec<String>, String> {
+    let _timer = metrics.start("scan_project_skeleton");
+
     let builder = build_strategy::get_builder(&tech_stack).map_err(|e| e.to_string())?;
     let path = std::path::Path::new(&project_path);
 
@@ -8,7 +8,14 @@
 // ⛔ 禁止：包含业务逻辑
 // ============================================================================
 
-use crate::database::{BuildRecord, Category, Client, Database, Project, TechStackTemplate};
+use crate::database::{
+    ArtifactDeletion, BuildRecord, BundleImportResult, Category, Client, Database, DbHealth,
+    DeleteImpact, FileIndexRow, Project, ProjectReport, ReadPool, RelocateReport, RepairReport,
+    TechStackTemplate, DURABLE_SETTING_KEYS,
+};
+use crate::services::llm_client;
+use crate::services::secret_store::{self, OsKeychain, SecretSource};
+use crate::services::settings_schema;
 use std::sync::Mutex;
 use tauri::State;
 
@@ -49,11 +56,16 @@ pub async fn db_create_category(
 
 /// 查询所有分类
 #[tauri::command]
-pub async fn db_list_categories(db: State<'_, Mutex<Database>>) -> Result<Vec<Category>, String> {
-    let db = db
-        .lock()
-        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.list_categories()
+pub async fn db_list_categories(pool: State<'_, ReadPool>) -> Result<Vec<Category>, String> {
+    pool.read(|db| db.list_categories())
+}
+
+/// 查询所有分类及其项目数量（无项目的分类数量为 0）
+#[tauri::command]
+pub async fn db_list_categories_with_counts(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<(Category, i64)>, String> {
+    pool.read(|db| db.list_categories_with_counts())
 }
 
 /// 更新分类
@@ -79,6 +91,19 @@ pub async fn db_delete_category(db: State<'_, Mutex<Database>>, id: i64) -> Resu
     db.delete_category(id)
 }
 
+/// 将分类下的项目迁移到目标分类后删除该分类，返回迁移的项目数
+#[tauri::command]
+pub async fn db_reassign_and_delete_category(
+    db: State<'_, Mutex<Database>>,
+    category_id: i64,
+    target_category_id: i64,
+) -> Result<usize, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.reassign_and_delete_category(category_id, target_category_id)
+}
+
 // ============================================================================
 // 项目 CRUD Commands
 // ============================================================================
@@ -99,13 +124,56 @@ pub async fn db_create_project(
     db.create_project(&name, category_id, &repo_path, &tech_stack, &modules_dir)
 }
 
-/// 查询所有项目
+/// 复制项目配置（分类、技术栈、模块目录）到新的仓库路径，不复制构建记录与客户
+///
+/// `copy_module_presets` 为 true 时同时复制各客户记忆的模块选择。
 #[tauri::command]
-pub async fn db_list_projects(db: State<'_, Mutex<Database>>) -> Result<Vec<Project>, String> {
+pub async fn db_clone_project(
+    db: State<'_, Mutex<Database>>,
+    source_id: i64,
+    new_name: String,
+    new_repo_path: String,
+    copy_module_presets: Option<bool>,
+) -> Result<Project, String> {
     let db = db
         .lock()
         .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.list_projects()
+    db.clone_project_with_presets(
+        source_id,
+        &new_name,
+        &new_repo_path,
+        copy_module_presets.unwrap_or(false),
+    )
+}
+
+/// 将单个项目导出为 JSON 项目包（项目配置、客户关联与模块预设），便于分享给同事
+///
+/// `include_summaries` 为 true 时附带已生成的文件摘要。
+#[tauri::command]
+pub async fn db_export_project_bundle(
+    pool: State<'_, ReadPool>,
+    project_id: i64,
+    include_summaries: Option<bool>,
+) -> Result<String, String> {
+    pool.read(|db| db.export_project_bundle(project_id, include_summaries.unwrap_or(false)))
+}
+
+/// 从 JSON 项目包重建项目，分类与客户按名称匹配，仓库路径不存在时给出提示
+#[tauri::command]
+pub async fn db_import_project_bundle(
+    db: State<'_, Mutex<Database>>,
+    json: String,
+) -> Result<BundleImportResult, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.import_project_bundle(&json)
+}
+
+/// 查询所有项目
+#[tauri::command]
+pub async fn db_list_projects(pool: State<'_, ReadPool>) -> Result<Vec<Project>, String> {
+    pool.read(|db| db.list_projects())
 }
 
 /// 更新项目
@@ -125,6 +193,18 @@ pub async fn db_update_project(
     db.update_project(id, &name, category_id, &repo_path, &tech_stack, &modules_dir)
 }
 
+/// 预估删除项目的级联影响（构建记录、客户关联等数量），不执行删除
+#[tauri::command]
+pub async fn db_project_delete_impact(
+    db: State<'_, Mutex<Database>>,
+    id: i64,
+) -> Result<DeleteImpact, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.project_delete_impact(id)
+}
+
 /// 删除项目
 #[tauri::command]
 pub async fn db_delete_project(db: State<'_, Mutex<Database>>, id: i64) -> Result<(), String> {
@@ -154,26 +234,51 @@ pub async fn db_create_client(
 /// 查询指定项目关联的所有客户
 #[tauri::command]
 pub async fn db_list_clients_by_project(
-    db: State<'_, Mutex<Database>>,
+    pool: State<'_, ReadPool>,
     project_id: i64,
 ) -> Result<Vec<Client>, String> {
-    let db = db
-        .lock()
-        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.list_clients_by_project(project_id)
+    pool.read(|db| db.list_clients_by_project(project_id))
+}
+
+/// 查询所有客户及其构建记录数量（无构建记录的客户数量为 0）
+#[tauri::command]
+pub async fn db_list_all_clients_with_counts(
+    pool: State<'_, ReadPool>,
+) -> Result<Vec<(Client, i64)>, String> {
+    pool.read(|db| db.list_all_clients_with_counts())
 }
 
 /// 更新客户名称
+///
+/// `with_build_count = true` 时额外返回引用该客户的构建记录数，供 UI 提示影响范围。
 #[tauri::command]
 pub async fn db_update_client(
     db: State<'_, Mutex<Database>>,
     id: i64,
     name: String,
-) -> Result<(), String> {
+    with_build_count: Option<bool>,
+) -> Result<Option<i64>, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.update_client(id, &name)?;
+    if with_build_count.unwrap_or(false) {
+        db.client_build_count(id).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// 统计引用该客户的构建记录数（重命名/删除前确认影响范围）
+#[tauri::command]
+pub async fn db_client_build_count(
+    db: State<'_, Mutex<Database>>,
+    client_id: i64,
+) -> Result<i64, String> {
     let db = db
         .lock()
         .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.update_client(id, &name)
+    db.client_build_count(client_id)
 }
 
 /// 删除客户
@@ -185,12 +290,37 @@ pub async fn db_delete_client(db: State<'_, Mutex<Database>>, id: i64) -> Result
     db.delete_client(id)
 }
 
+/// 清理引用已删除项目或客户的孤立关联（project_clients），返回删除数量
+#[tauri::command]
+pub async fn db_repair_orphaned_associations(
+    db: State<'_, Mutex<Database>>,
+) -> Result<RepairReport, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.repair_orphaned_associations()
+}
+
+/// 数据库健康快照（各表行数、文件大小、SQLite 版本、日志模式、外键状态、完整性检查），供问题诊断
+#[tauri::command]
+pub async fn db_health(db: State<'_, Mutex<Database>>) -> Result<DbHealth, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.health()
+}
+
 // ============================================================================
 // 构建记录 Commands
 // ============================================================================
 
 /// 创建构建记录
+///
+/// `tech_stack` / `modules_dir` 为构建时使用的技术栈与模块目录，供之后按记录重建；
+/// 未传入时记录为空（重建时回退到项目当前配置）。
+/// `idempotency_key` 为前端生成的 UUID，超时重试时传入同一键不会产生重复记录。
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn db_create_build_record(
     db: State<'_, Mutex<Database>>,
     project_id: i64,
@@ -199,45 +329,77 @@ pub async fn db_create_build_record(
     output_path: String,
     version: String,
     changelog: Option<String>,
+    tech_stack: Option<String>,
+    modules_dir: Option<String>,
+    idempotency_key: Option<String>,
 ) -> Result<BuildRecord, String> {
     let db = db
         .lock()
         .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.create_build_record(project_id, client_id, &modules_json, &output_path, &version, changelog.as_deref())
+    db.create_build_record_with_config(
+        project_id,
+        client_id,
+        &modules_json,
+        &output_path,
+        &version,
+        changelog.as_deref(),
+        tech_stack.as_deref().unwrap_or_default(),
+        modules_dir.as_deref().unwrap_or_default(),
+        idempotency_key.as_deref(),
+    )
 }
 
 /// 查询指定项目的构建记录列表
 #[tauri::command]
 pub async fn db_list_build_records(
-    db: State<'_, Mutex<Database>>,
+    pool: State<'_, ReadPool>,
     project_id: i64,
 ) -> Result<Vec<BuildRecord>, String> {
-    let db = db
-        .lock()
-        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.list_build_records_by_project(project_id)
+    pool.read(|db| db.list_build_records_by_project(project_id))
+}
+
+/// 查询指定项目中包含某模块的构建记录（如"哪些交付包含 payments 模块"）
+#[tauri::command]
+pub async fn db_find_builds_with_module(
+    pool: State<'_, ReadPool>,
+    project_id: i64,
+    module_name: String,
+) -> Result<Vec<BuildRecord>, String> {
+    pool.read(|db| db.find_builds_with_module(project_id, &module_name))
 }
 
 /// 删除单条构建记录
-/// - `delete_files`: 是否同时删除对应的 ZIP 文件
+/// - `delete_files`: 是否同时删除对应的 ZIP 文件（文件已不存在时不报错）
+///
+/// 返回文件是否存在、是否已删除
 #[tauri::command]
 pub async fn db_delete_build_record(
     db: State<'_, Mutex<Database>>,
     id: i64,
     delete_files: bool,
-) -> Result<(), String> {
+) -> Result<ArtifactDeletion, String> {
     let db = db
         .lock()
         .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.delete_build_record(id, delete_files)
+}
 
-    // 如果需要删除文件，先查出记录的 output_path
-    if delete_files {
-        if let Ok(records) = db.list_build_records_by_ids(&[id]) {
-            delete_output_files(&records);
-        }
+/// 将项目的构建产物迁移到新目录，并同步更新构建记录中的输出路径
+///
+/// 返回已迁移与文件缺失的记录数
+#[tauri::command]
+pub async fn db_relocate_build_artifacts(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    new_dir: String,
+) -> Result<RelocateReport, String> {
+    if new_dir.trim().is_empty() {
+        return Err("目标目录不能为空".to_string());
     }
-
-    db.delete_build_record(id)
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.relocate_build_artifacts(project_id, std::path::Path::new(&new_dir))
 }
 
 /// 清空指定项目的所有构建记录
@@ -315,16 +477,46 @@ pub async fn get_app_setting(
 }
 
 /// 保存单个设置项
+///
+/// 保存前按 `settings_schema` 校验键名与取值，`force = true` 时跳过校验（用于实验性设置）。
+/// 关键设置（API Key、输出目录等）走持久化路径，写入后立即落盘；
+/// 其余设置保持快速路径。
 #[tauri::command]
 pub async fn save_app_setting(
     db: State<'_, Mutex<Database>>,
     key: String,
     value: String,
+    force: Option<bool>,
 ) -> Result<(), String> {
+    if !force.unwrap_or(false) {
+        settings_schema::validate_setting(&key, &value)?;
+    }
     let db = db
         .lock()
         .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.save_setting(&key, &value)
+
+    // API Key 来源为钥匙串时写入系统钥匙串，数据库中不再保留明文（钥匙串不可用或重启后不保留时仍存数据库）
+    let mut value = value;
+    if key == "llm_api_key" {
+        let source = db
+            .get_setting(secret_store::API_KEY_SOURCE_KEY)?
+            .unwrap_or_default();
+        if secret_store::write_secret(&OsKeychain, &source, &key, &value) == SecretSource::Keychain {
+            value = String::new();
+        }
+    }
+
+    if DURABLE_SETTING_KEYS.contains(&key.as_str()) {
+        db.save_setting_durable(&key, &value)?;
+    } else {
+        db.save_setting(&key, &value)?;
+    }
+
+    // 全局 LLM 并发上限即时生效，无需重启
+    if key == llm_client::GLOBAL_CONCURRENCY_KEY {
+        llm_client::set_global_concurrency(llm_client::parse_global_concurrency(&value));
+    }
+    Ok(())
 }
 
 // ============================================================================
@@ -408,12 +600,9 @@ pub async fn db_create_template(
 /// 查询所有技术栈模板
 #[tauri::command]
 pub async fn db_list_templates(
-    db: State<'_, Mutex<Database>>,
+    pool: State<'_, ReadPool>,
 ) -> Result<Vec<TechStackTemplate>, String> {
-    let db = db
-        .lock()
-        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
-    db.list_templates()
+    pool.read(|db| db.list_templates())
 }
 
 /// 更新自定义技术栈模板（内置模板不可修改）
@@ -485,3 +674,72 @@ pub async fn import_template_json(
         &imported.router_pattern,
     )
 }
+
+// ============================================================================
+// 文件索引 Commands
+// ============================================================================
+
+/// 查询项目的文件索引（只读，供索引查看界面使用，不触发重新扫描）
+#[tauri::command]
+pub async fn db_list_file_index(
+    pool: State<'_, ReadPool>,
+    project_id: i64,
+) -> Result<Vec<FileIndexRow>, String> {
+    pool.read(|db| db.list_file_index(project_id))
+}
+
+/// 将项目的文件索引导出为 JSON Lines 文件，返回导出的条目数
+#[tauri::command]
+pub async fn export_file_index_jsonl(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    out_path: String,
+) -> Result<usize, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.export_file_index_jsonl(project_id, std::path::Path::new(&out_path))
+}
+
+/// 将项目的构建历史导出为 CSV 文件，返回导出的记录数
+#[tauri::command]
+pub async fn export_build_history_csv(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    out_path: String,
+) -> Result<usize, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.export_build_history_csv(project_id, std::path::Path::new(&out_path))
+}
+
+/// 保存项目分析报告（`generate_project_report` 的结果），返回完整的报告记录
+#[tauri::command]
+pub async fn db_save_report(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    mode: String,
+    sub_path: Option<String>,
+    content: String,
+) -> Result<ProjectReport, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    db.save_report(project_id, &mode, sub_path.as_deref(), &content, None)
+}
+
+/// 根据 ID 查询分析报告
+#[tauri::command]
+pub async fn db_get_report(pool: State<'_, ReadPool>, id: i64) -> Result<ProjectReport, String> {
+    pool.read(|db| db.get_report(id))
+}
+
+/// 查询项目的全部分析报告（含历史版本，最新在前）
+#[tauri::command]
+pub async fn db_list_reports(
+    pool: State<'_, ReadPool>,
+    project_id: i64,
+) -> Result<Vec<ProjectReport>, String> {
+    pool.read(|db| db.list_reports(project_id))
+}
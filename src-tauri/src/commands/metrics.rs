@@ -0,0 +1,16 @@
+// ============================================================================
+// 诊断相关 Commands
+// 负责：查询命令耗时统计
+// ============================================================================
+
+use crate::models::dtos::CommandMetric;
+use crate::services::metrics::CommandMetrics;
+use tauri::State;
+
+/// 获取各命令的耗时统计（平均/最近/最大），用于诊断本机上哪些操作较慢
+///
+/// 仅统计已接入计时的构建与分析类命令，数据保存在内存中，应用重启后清空。
+#[tauri::command]
+pub fn get_command_metrics(metrics: State<'_, CommandMetrics>) -> Vec<CommandMetric> {
+    metrics.snapshot()
+}
@@ -7,4 +7,5 @@
 pub mod analysis;
 pub mod build;
 pub mod db_crud;
+pub mod metrics;
 pub mod project;
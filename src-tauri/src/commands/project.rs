@@ -3,9 +3,16 @@
 // 负责：项目打开、模块扫描（含多技术栈）
 // ============================================================================
 
-use crate::models::dtos::{ModuleInfo, ProjectInfo};
+use crate::database::{Database, ProjectImportSummary};
+use crate::models::dtos::{
+    DirSize, ModuleDiff, ModuleInfo, ModuleMetadata, ModuleSelectionState, ProjectInfo,
+};
+use crate::services::analyzer;
+use crate::services::build_strategy;
 use crate::services::scan_strategy;
 use crate::services::scanner;
+use std::sync::Mutex;
+use tauri::State;
 
 /// 打开项目：弹出原生文件夹选择对话框，返回项目路径
 ///
@@ -35,13 +42,34 @@ pub async fn open_project(app: tauri::AppHandle) -> Result<ProjectInfo, String>
     })
 }
 
-/// 扫描模块：读取 modules/ 下的一级子目录，过滤忽略项
+/// 从设置项读取模块发现深度（`module_depth`，未设置时为 1）
+///
+/// 模块列表与构建时的依赖分析使用同一深度，保证限定模块名（`admin/users`）前后一致。
+pub(crate) fn load_module_depth(db: &Mutex<Database>) -> Result<usize, String> {
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    let raw = db.get_setting(scanner::MODULE_DEPTH_KEY)?;
+    Ok(scanner::module_depth_from_setting(raw.as_deref()))
+}
+
+/// 扫描模块：读取 modules/ 下的子目录，过滤忽略项
 ///
 /// 接收项目路径，拼接 modules/ 子目录后调用 services 层执行扫描。
+/// - `module_depth`: 模块发现深度，未传入时使用 `module_depth` 设置项；
+///   为 2 时分组目录下的模块以 `admin/users` 形式返回，最大为 2
 #[tauri::command]
-pub async fn scan_modules(project_path: String) -> Result<Vec<ModuleInfo>, String> {
+pub async fn scan_modules(
+    db: State<'_, Mutex<Database>>,
+    project_path: String,
+    module_depth: Option<usize>,
+) -> Result<Vec<ModuleInfo>, String> {
+    let module_depth = match module_depth {
+        Some(depth) => depth,
+        None => load_module_depth(&db)?,
+    };
     let modules_path = std::path::Path::new(&project_path).join("modules");
-    scanner::scan_modules_dir(&modules_path).map_err(|e| e.to_string())
+    scanner::scan_modules_dir_with_depth(&modules_path, module_depth).map_err(|e| e.to_string())
 }
 
 /// 扫描项目模块（多技术栈支持）
@@ -52,12 +80,161 @@ pub async fn scan_modules(project_path: String) -> Result<Vec<ModuleInfo>, Strin
 /// - `project_path`: 项目根目录路径
 /// - `tech_stack`: 技术栈类型标识（如 "fastapi"、"vue3"）
 /// - `modules_dir`: 用户自定义的模块目录（相对路径），为空则使用技术栈默认值
+///
+/// 分组目录按 `module_depth` 设置项展开。
 #[tauri::command]
 pub async fn scan_project_modules(
+    db: State<'_, Mutex<Database>>,
     project_path: String,
     tech_stack: String,
     modules_dir: String,
 ) -> Result<Vec<ModuleInfo>, String> {
+    let module_depth = load_module_depth(&db)?;
     let scanner = scan_strategy::get_scanner(&tech_stack).map_err(|e| e.to_string())?;
-    scanner.scan(std::path::Path::new(&project_path), &modules_dir).map_err(|e| e.to_string())
+    scanner
+        .scan(std::path::Path::new(&project_path), &modules_dir, module_depth)
+        .map_err(|e| e.to_string())
+}
+
+/// 按目录布局推测项目的模块目录，用于新建项目时预填
+///
+/// 返回相对路径（如 `modules`、`src/views`）；无法判断或技术栈没有内置推测规则时返回 None。
+#[tauri::command]
+pub async fn detect_modules_dir(
+    project_path: String,
+    tech_stack: String,
+) -> Result<Option<String>, String> {
+    let path = std::path::Path::new(&project_path);
+    if !path.is_dir() {
+        return Err(format!("项目路径不存在：{}", project_path));
+    }
+    Ok(scan_strategy::get_scanner(&tech_stack)
+        .ok()
+        .and_then(|scanner| scanner.detect_modules_dir(path)))
+}
+
+/// 检测嵌套模块目录中的同名模块
+///
+/// 返回歧义警告列表（如 `modules/admin/users` 与 `modules/users`），
+/// 前端据此提示用户使用限定名（`admin/users`）选择模块。
+#[tauri::command]
+pub async fn detect_ambiguous_modules(
+    project_path: String,
+    modules_dir: String,
+) -> Result<Vec<String>, String> {
+    let modules_path = std::path::Path::new(&project_path).join(&modules_dir);
+    if !modules_path.is_dir() {
+        return Err(format!("模块目录不存在：{}", modules_dir));
+    }
+    Ok(scanner::find_ambiguous_module_names(&modules_path))
+}
+
+/// 比较两个项目的模块集合：仅 A 有、仅 B 有、共有
+///
+/// 两个项目使用同一模块目录（相对路径，如 `modules`），用于对比模板项目与派生项目的模块漂移。
+#[tauri::command]
+pub async fn compare_project_modules(
+    project_path_a: String,
+    project_path_b: String,
+    modules_dir: String,
+) -> Result<ModuleDiff, String> {
+    scanner::compare_modules_dirs(
+        &std::path::Path::new(&project_path_a).join(&modules_dir),
+        &std::path::Path::new(&project_path_b).join(&modules_dir),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 统计项目各顶层目录的体积分布（按字节数降序）
+///
+/// 遵循与文件索引相同的忽略规则，用于交付前定位臃肿模块或残留产物。
+#[tauri::command]
+pub async fn project_size_breakdown(project_path: String) -> Result<Vec<DirSize>, String> {
+    analyzer::project_size_breakdown(std::path::Path::new(&project_path))
+}
+
+/// 提取各模块概况（文件数、体积、路由数、是否含测试），供构建页面以卡片展示
+///
+/// - `modules_dir`: 模块目录（相对路径），为空则使用技术栈默认值
+#[tauri::command]
+pub async fn module_metadata(
+    project_path: String,
+    tech_stack: String,
+    modules_dir: String,
+) -> Result<Vec<ModuleMetadata>, String> {
+    let modules_dir = if modules_dir.is_empty() {
+        build_strategy::get_builder(&tech_stack)
+            .map_err(|e| e.to_string())?
+            .default_modules_dir()
+            .to_string()
+    } else {
+        modules_dir
+    };
+    analyzer::extract_module_metadata(std::path::Path::new(&project_path), &modules_dir)
+}
+
+/// 批量导入项目：将 `parent_dir` 下的每个代码仓库注册为一个项目
+///
+/// 技术栈自动识别，识别不出内置技术栈时使用 `tech_stack_hint`；
+/// 非仓库目录与已注册的仓库路径跳过，返回创建/跳过汇总。
+#[tauri::command]
+pub async fn scan_and_import_projects(
+    db: State<'_, Mutex<Database>>,
+    parent_dir: String,
+    category_id: i64,
+    tech_stack_hint: Option<String>,
+) -> Result<ProjectImportSummary, String> {
+    let (candidates, mut skipped) = analyzer::discover_project_repos(
+        std::path::Path::new(&parent_dir),
+        tech_stack_hint.as_deref(),
+    )?;
+
+    let db = db
+        .lock()
+        .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+    let mut summary = db.import_projects(category_id, &candidates)?;
+    skipped.append(&mut summary.skipped);
+    summary.skipped = skipped;
+    Ok(summary)
+}
+
+/// 扫描模块并附带该客户上次构建的选中状态
+///
+/// 合并 `scan_modules` 与 `db_get_last_build_modules` 两次调用，供构建页面一次性获取。
+///
+/// # 参数
+/// - `project_id`: 项目 ID
+/// - `project_path`: 项目根目录路径
+/// - `client_id`: 客户 ID
+/// - `modules_dir`: 模块目录（相对路径），为空则使用项目配置的模块目录
+#[tauri::command]
+pub async fn list_modules_with_state(
+    db: State<'_, Mutex<Database>>,
+    project_id: i64,
+    project_path: String,
+    client_id: i64,
+    modules_dir: String,
+) -> Result<Vec<ModuleSelectionState>, String> {
+    let (last_json, modules_dir) = {
+        let db = db
+            .lock()
+            .map_err(|_| "数据库访问失败：无法获取锁".to_string())?;
+        let last_json = db.get_last_build_modules(client_id, project_id)?;
+        let modules_dir = if modules_dir.is_empty() {
+            db.get_project(project_id)?.modules_dir
+        } else {
+            modules_dir
+        };
+        (last_json, modules_dir)
+    };
+
+    // 上次构建的模块列表（无记录或解析失败时视为全部未选中）
+    let last_selected: Vec<String> = last_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let module_depth = load_module_depth(&db)?;
+    let modules_path = std::path::Path::new(&project_path).join(&modules_dir);
+    scanner::scan_modules_with_state(&modules_path, &last_selected, module_depth)
+        .map_err(|e| e.to_string())
 }
@@ -3,9 +3,14 @@
 // 使用 rusqlite 直接操作 SQLite，遵循 KISS 原则，不引入 ORM
 // ============================================================================
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use crate::models::dtos::{RepoCandidate, SkippedRepo};
 
 // ============================================================================
 // 数据结构定义
@@ -56,6 +61,23 @@ pub struct BuildRecord {
     /// 变更日志（与上次构建的模块差异）
     pub changelog: Option<String>,
     pub created_at: String,
+    /// 构建时使用的技术栈（旧记录为空字符串）
+    pub tech_stack: String,
+    /// 构建时使用的模块目录（为空表示该技术栈的默认目录）
+    pub modules_dir: String,
+}
+
+/// 按构建记录重建所需的参数（项目与客户按 ID 解析）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RebuildParams {
+    pub project_id: i64,
+    pub project_path: String,
+    pub client_id: i64,
+    pub client_name: String,
+    pub tech_stack: String,
+    pub modules_dir: String,
+    /// 记录中的模块列表（构建时实际打包的完整列表）
+    pub selected_modules: Vec<String>,
 }
 
 /// 应用设置
@@ -86,6 +108,234 @@ pub struct TechStackTemplate {
     pub created_at: String,
 }
 
+/// 已保存的项目分析报告
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectReport {
+    pub id: i64,
+    pub project_id: i64,
+    /// 生成模式（"fast"、"deep"、"offline"）
+    pub mode: String,
+    /// 分析的子目录（为空表示整个项目）
+    pub sub_path: Option<String>,
+    /// 报告正文（Markdown）
+    pub content: String,
+    pub created_at: String,
+    /// 被本报告取代的旧版本报告 ID（首个版本为 None）
+    pub supersedes: Option<i64>,
+}
+
+/// 文件索引记录（只读视图，不含 embedding 原始 BLOB）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileIndexRow {
+    /// 文件相对路径
+    pub file_path: String,
+    pub file_hash: String,
+    /// 计算 `file_hash` 所用的算法（`sha256` / `blake3`）
+    pub hash_algo: String,
+    /// 文件大小（字节）
+    pub file_size: i64,
+    /// 文件修改时间（Unix 时间戳秒数）
+    pub mtime: i64,
+    pub summary: Option<String>,
+    /// 是否已生成摘要
+    pub has_summary: bool,
+    /// 是否已生成 Embedding 向量
+    pub has_embedding: bool,
+    /// 生成向量时的输入模式（无向量时为 None）
+    pub embedding_mode: Option<String>,
+    /// 被其他文件引用的次数（尚未做过依赖分析时为 None）
+    pub in_degree: Option<i64>,
+    /// 引用其他文件的次数（尚未做过依赖分析时为 None）
+    pub out_degree: Option<i64>,
+    pub last_analyzed_at: String,
+}
+
+/// Embedding 模型信息：当前配置的模型及已记录的向量维度
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EmbeddingModelInfo {
+    /// 当前配置的 Embedding 模型（llm_embedding_model）
+    pub model: String,
+    /// 首次成功生成向量时记录的维度；尚未生成过向量时为 None
+    pub dimension: Option<usize>,
+}
+
+/// 文件索引导出行（JSON Lines 每行一个对象，不含向量原始字节）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FileIndexExportRow {
+    pub path: String,
+    pub hash: String,
+    pub size: i64,
+    pub mtime: i64,
+    pub has_summary: bool,
+    pub has_embedding: bool,
+    /// 向量维度（无向量时为 None）
+    pub embedding_dim: Option<usize>,
+    /// 签名列表（未建立签名索引时为 None）
+    pub signatures: Option<serde_json::Value>,
+}
+
+/// 删除项目时将被级联删除的关联数据数量（只读预估）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DeleteImpact {
+    /// 构建记录数
+    pub build_records: i64,
+    /// 客户关联数（project_clients，客户本身不会被删除）
+    pub client_associations: i64,
+    /// 客户模块配置数
+    pub client_module_configs: i64,
+    /// 文件索引条目数
+    pub file_index_entries: i64,
+}
+
+/// 删除构建记录时输出文件的处理结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactDeletion {
+    /// 请求删除文件且文件仍存在
+    pub file_found: bool,
+    /// 文件已成功删除
+    pub file_deleted: bool,
+}
+
+/// 清空项目分析数据的结果（各表删除的行数）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ClearReport {
+    /// 文件索引条目（含哈希、摘要、签名与向量）
+    pub file_index_entries: usize,
+    /// 目录索引条目（增量扫描缓存）
+    pub dir_index_entries: usize,
+    /// 已保存的分析报告
+    pub reports: usize,
+}
+
+/// 迁移构建产物到新目录的结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelocateReport {
+    /// 已移动文件并更新 `output_path` 的记录数
+    pub moved: usize,
+    /// `output_path` 指向的文件已不存在的记录数（记录保持不变）
+    pub missing: usize,
+}
+
+/// 清理孤立客户关联的结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RepairReport {
+    /// 因项目已不存在而删除的关联数
+    pub missing_project: usize,
+    /// 因客户已不存在而删除的关联数（项目同样缺失的行只计入 `missing_project`）
+    pub missing_client: usize,
+}
+
+/// 数据库健康快照（供用户反馈问题时一次性收集诊断信息）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DbHealth {
+    /// 各表行数（表名 → 行数）
+    pub table_counts: BTreeMap<String, i64>,
+    /// 数据库主文件大小（字节，内存数据库为 0）
+    pub file_size_bytes: u64,
+    /// WAL 文件大小（字节，不存在时为 0）
+    pub wal_size_bytes: u64,
+    /// SQLite 版本
+    pub sqlite_version: String,
+    /// 日志模式（如 wal、delete）
+    pub journal_mode: String,
+    /// 外键约束是否启用
+    pub foreign_keys: bool,
+    /// `PRAGMA quick_check` 是否通过
+    pub integrity_ok: bool,
+    /// `PRAGMA quick_check` 报告的问题（通过时为空）
+    pub integrity_errors: Vec<String>,
+}
+
+/// 批量导入项目的结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectImportSummary {
+    /// 新创建的项目
+    pub created: Vec<Project>,
+    /// 被跳过的目录及原因
+    pub skipped: Vec<SkippedRepo>,
+}
+
+/// 项目导出包的格式版本（结构不兼容变更时递增）
+pub const PROJECT_BUNDLE_VERSION: u32 = 1;
+
+/// 单个项目的导出包：项目配置、客户关联与模块预设，可选附带文件摘要
+///
+/// 不含任何数据库 ID，导入时按名称匹配或新建分类与客户。
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProjectBundle {
+    /// 格式版本（见 `PROJECT_BUNDLE_VERSION`）
+    pub version: u32,
+    pub name: String,
+    /// 分类名称
+    pub category: String,
+    pub repo_path: String,
+    pub tech_stack_type: String,
+    pub modules_dir: String,
+    /// 关联客户或有模块预设的客户
+    pub clients: Vec<BundleClient>,
+    /// 文件摘要（导出时未选择附带则为空）
+    #[serde(default)]
+    pub file_summaries: Vec<BundleFileSummary>,
+}
+
+/// 导出包中的客户
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BundleClient {
+    pub name: String,
+    /// 是否关联到该项目
+    pub associated: bool,
+    /// 该客户在该项目下记忆的模块选择（JSON 数组字符串）
+    pub modules_json: Option<String>,
+}
+
+/// 导出包中的文件摘要
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BundleFileSummary {
+    pub file_path: String,
+    pub file_hash: String,
+    /// 计算 `file_hash` 所用的算法（旧版导出包没有该字段，均为 SHA256）
+    #[serde(default = "default_bundle_hash_algo")]
+    pub hash_algo: String,
+    pub summary: String,
+}
+
+fn default_bundle_hash_algo() -> String {
+    "sha256".to_string()
+}
+
+/// 导入项目包的结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleImportResult {
+    /// 新建的项目
+    pub project: Project,
+    /// 新建的客户数（同名客户已存在时直接复用，不计入）
+    pub clients_created: usize,
+    /// 写入的文件摘要数
+    pub summaries_imported: usize,
+    /// 导入时的提示（如仓库路径在本机不存在）
+    pub warnings: Vec<String>,
+}
+
+/// 语义搜索候选行：(file_path, summary, embedding 字节)
+pub type FileEmbeddingRow = (String, Option<String>, Vec<u8>);
+
+/// 文件索引中已记录的扫描状态：(file_hash, summary, file_size, mtime, hash_algo, binary_checked)
+pub type IndexedFileState = (String, Option<String>, u64, u64, String, bool);
+
+/// 记录 Embedding 向量维度的设置键
+pub const EMBEDDING_DIM_KEY: &str = "llm_embedding_dim";
+
+/// 向量维度与已记录维度不一致时的提示信息
+pub const EMBEDDING_DIM_MISMATCH: &str = "embedding 维度不一致，请清空后重新索引";
+
+/// 关键设置键：保存时走持久化路径（`save_setting_durable`）
+/// 这些设置丢失后用户会静默回退到空配置，需在写入后立即落盘
+pub const DURABLE_SETTING_KEYS: &[&str] = &[
+    "llm_api_key",
+    "llm_base_url",
+    "default_output_dir",
+];
+
 // ============================================================================
 // 数据库管理器
 // ============================================================================
@@ -129,6 +379,10 @@ impl Database {
         conn.execute_batch("PRAGMA foreign_keys = ON;")
             .map_err(|e| format!("数据库初始化失败：无法启用外键约束: {}", e))?;
 
+        // 启用 WAL 日志模式：读连接（ReadPool）不会被写事务阻塞
+        conn.execute_batch("PRAGMA journal_mode = WAL;")
+            .map_err(|e| format!("数据库初始化失败：无法启用 WAL 模式: {}", e))?;
+
         // 创建所有必要的表
         Self::create_tables(&conn)?;
 
@@ -192,6 +446,9 @@ impl Database {
                 version TEXT NOT NULL DEFAULT 'v1.0.0',
                 changelog TEXT,
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                tech_stack TEXT NOT NULL DEFAULT '',
+                modules_dir TEXT NOT NULL DEFAULT '',
+                idempotency_key TEXT,
                 FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
                 FOREIGN KEY (client_id) REFERENCES clients(id)
             );
@@ -225,11 +482,25 @@ impl Database {
                 summary TEXT,
                 signatures TEXT,
                 embedding BLOB,
+                embedding_mode TEXT,
+                in_degree INTEGER,
+                out_degree INTEGER,
+                hash_algo TEXT NOT NULL DEFAULT 'sha256',
+                binary_checked INTEGER NOT NULL DEFAULT 0,
                 last_analyzed_at TEXT NOT NULL DEFAULT (datetime('now')),
                 UNIQUE(project_id, file_path),
                 FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
             );
 
+            -- 目录索引表（记录目录修改时间，增量扫描时跳过未变化的目录）
+            CREATE TABLE IF NOT EXISTS dir_index (
+                project_id INTEGER NOT NULL,
+                dir_path TEXT NOT NULL,
+                mtime INTEGER NOT NULL,
+                PRIMARY KEY (project_id, dir_path),
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+            );
+
             -- 技术栈模板表（可配置的构建策略模板）
             CREATE TABLE IF NOT EXISTS tech_stack_templates (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -242,6 +513,19 @@ impl Database {
                 is_builtin INTEGER NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL DEFAULT (datetime('now'))
             );
+
+            -- 项目分析报告表（刷新报告时生成新版本，supersedes 指向被取代的旧版本）
+            CREATE TABLE IF NOT EXISTS project_reports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                project_id INTEGER NOT NULL,
+                mode TEXT NOT NULL,
+                sub_path TEXT,
+                content TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                supersedes INTEGER,
+                FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE,
+                FOREIGN KEY (supersedes) REFERENCES project_reports(id) ON DELETE SET NULL
+            );
             ",
         )
         .map_err(|e| format!("数据库初始化失败：创建表结构时出错: {}", e))?;
@@ -366,6 +650,51 @@ impl Database {
             .map_err(|e| format!("数据库迁移失败：添加 version/changelog 列时出错: {}", e))?;
         }
 
+        // 检查 build_records 表是否缺少 tech_stack 列（按记录重建用）
+        let has_record_tech_stack: bool = conn
+            .prepare("PRAGMA table_info(build_records)")
+            .map(|mut stmt| {
+                let cols: Vec<String> = stmt
+                    .query_map([], |row| row.get::<_, String>(1))
+                    .unwrap()
+                    .filter_map(|r| r.ok())
+                    .collect();
+                cols.contains(&"tech_stack".to_string())
+            })
+            .unwrap_or(false);
+
+        if !has_record_tech_stack {
+            conn.execute_batch(
+                "ALTER TABLE build_records ADD COLUMN tech_stack TEXT NOT NULL DEFAULT '';
+                 ALTER TABLE build_records ADD COLUMN modules_dir TEXT NOT NULL DEFAULT '';",
+            )
+            .map_err(|e| format!("数据库迁移失败：添加 tech_stack/modules_dir 列时出错: {}", e))?;
+        }
+
+        // 检查 build_records 表是否缺少 idempotency_key 列（重试去重用）
+        let has_idempotency_key: bool = conn
+            .prepare("PRAGMA table_info(build_records)")
+            .map(|mut stmt| {
+                let cols: Vec<String> = stmt
+                    .query_map([], |row| row.get::<_, String>(1))
+                    .unwrap()
+                    .filter_map(|r| r.ok())
+                    .collect();
+                cols.contains(&"idempotency_key".to_string())
+            })
+            .unwrap_or(false);
+
+        if !has_idempotency_key {
+            conn.execute_batch("ALTER TABLE build_records ADD COLUMN idempotency_key TEXT;")
+                .map_err(|e| format!("数据库迁移失败：添加 idempotency_key 列时出错: {}", e))?;
+        }
+        // ALTER TABLE 无法添加 UNIQUE 约束，以唯一索引代替（NULL 不参与唯一性比较）
+        conn.execute_batch(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_build_records_idempotency_key
+             ON build_records(idempotency_key);",
+        )
+        .map_err(|e| format!("数据库迁移失败：创建 idempotency_key 索引时出错: {}", e))?;
+
         // 检查 file_index 表是否存在，不存在则创建（项目分析功能）
         let has_file_index: bool = conn
             .prepare("SELECT count(*) FROM sqlite_master WHERE type='table' AND name='file_index'")
@@ -471,6 +800,87 @@ impl Database {
                 )
                 .map_err(|e| format!("数据库迁移失败：添加 file_size/mtime 列时出错: {}", e))?;
             }
+
+            // 检查 file_index 表是否缺少 embedding_mode 列（记录向量的输入模式）
+            let has_embedding_mode: bool = conn
+                .prepare("PRAGMA table_info(file_index)")
+                .map(|mut stmt| {
+                    let cols: Vec<String> = stmt
+                        .query_map([], |row| row.get::<_, String>(1))
+                        .unwrap()
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    cols.contains(&"embedding_mode".to_string())
+                })
+                .unwrap_or(false);
+
+            if !has_embedding_mode {
+                conn.execute_batch(
+                    "ALTER TABLE file_index ADD COLUMN embedding_mode TEXT;",
+                )
+                .map_err(|e| format!("数据库迁移失败：添加 embedding_mode 列时出错: {}", e))?;
+            }
+
+            // 检查 file_index 表是否缺少 in_degree/out_degree 列（依赖分析写入的入度/出度）
+            let has_degrees: bool = conn
+                .prepare("PRAGMA table_info(file_index)")
+                .map(|mut stmt| {
+                    let cols: Vec<String> = stmt
+                        .query_map([], |row| row.get::<_, String>(1))
+                        .unwrap()
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    cols.contains(&"in_degree".to_string())
+                })
+                .unwrap_or(false);
+
+            if !has_degrees {
+                conn.execute_batch(
+                    "ALTER TABLE file_index ADD COLUMN in_degree INTEGER;
+                     ALTER TABLE file_index ADD COLUMN out_degree INTEGER;",
+                )
+                .map_err(|e| format!("数据库迁移失败：添加 in_degree/out_degree 列时出错: {}", e))?;
+            }
+
+            // 检查 file_index 表是否缺少 hash_algo 列（file_hash 所用算法，旧记录均为 SHA256）
+            let has_hash_algo: bool = conn
+                .prepare("PRAGMA table_info(file_index)")
+                .map(|mut stmt| {
+                    let cols: Vec<String> = stmt
+                        .query_map([], |row| row.get::<_, String>(1))
+                        .unwrap()
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    cols.contains(&"hash_algo".to_string())
+                })
+                .unwrap_or(false);
+
+            if !has_hash_algo {
+                conn.execute_batch(
+                    "ALTER TABLE file_index ADD COLUMN hash_algo TEXT NOT NULL DEFAULT 'sha256';",
+                )
+                .map_err(|e| format!("数据库迁移失败：添加 hash_algo 列时出错: {}", e))?;
+            }
+
+            // 检查 file_index 表是否缺少 binary_checked 列（记录是否由跳过二进制文件的扫描写入）
+            let has_binary_checked: bool = conn
+                .prepare("PRAGMA table_info(file_index)")
+                .map(|mut stmt| {
+                    let cols: Vec<String> = stmt
+                        .query_map([], |row| row.get::<_, String>(1))
+                        .unwrap()
+                        .filter_map(|r| r.ok())
+                        .collect();
+                    cols.contains(&"binary_checked".to_string())
+                })
+                .unwrap_or(false);
+
+            if !has_binary_checked {
+                conn.execute_batch(
+                    "ALTER TABLE file_index ADD COLUMN binary_checked INTEGER NOT NULL DEFAULT 0;",
+                )
+                .map_err(|e| format!("数据库迁移失败：添加 binary_checked 列时出错: {}", e))?;
+            }
         }
 
         // 检查 tech_stack_templates 表是否存在，不存在则创建并插入内置模板
@@ -502,14 +912,91 @@ impl Database {
             Self::seed_builtin_templates(conn)?;
         }
 
+        // 向量版本号：文件索引的向量、摘要或路径变化时由触发器递增，
+        // 供内存中的向量检索索引判断是否需要重建（触发器依赖上面补齐的列，须在迁移后创建）。
+        // 更新触发器只在值实际变化时递增（扫描会原值写回摘要），旧版本创建的无条件触发器先删除重建
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS embedding_versions (
+                project_id INTEGER PRIMARY KEY,
+                version INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TRIGGER IF NOT EXISTS file_index_embedding_insert
+            AFTER INSERT ON file_index WHEN NEW.embedding IS NOT NULL
+            BEGIN
+                INSERT INTO embedding_versions (project_id, version) VALUES (NEW.project_id, 1)
+                ON CONFLICT(project_id) DO UPDATE SET version = version + 1;
+            END;
+            DROP TRIGGER IF EXISTS file_index_embedding_update;
+            CREATE TRIGGER file_index_embedding_update
+            AFTER UPDATE OF embedding, summary, file_path ON file_index
+            WHEN OLD.embedding IS NOT NEW.embedding
+                OR OLD.summary IS NOT NEW.summary
+                OR OLD.file_path IS NOT NEW.file_path
+            BEGIN
+                INSERT INTO embedding_versions (project_id, version) VALUES (NEW.project_id, 1)
+                ON CONFLICT(project_id) DO UPDATE SET version = version + 1;
+            END;
+            CREATE TRIGGER IF NOT EXISTS file_index_embedding_delete
+            AFTER DELETE ON file_index WHEN OLD.embedding IS NOT NULL
+            BEGIN
+                INSERT INTO embedding_versions (project_id, version) VALUES (OLD.project_id, 1)
+                ON CONFLICT(project_id) DO UPDATE SET version = version + 1;
+            END;",
+        )
+        .map_err(|e| format!("数据库迁移失败：创建向量版本触发器时出错: {}", e))?;
+
         Ok(())
     }
 
+    /// 以只读方式打开已初始化的数据库文件（供 ReadPool 使用，不建表、不迁移）
+    pub fn open_read_only(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| format!("打开只读数据库连接失败 {}: {}", db_path.display(), e))?;
+        conn.execute_batch("PRAGMA query_only = ON;")
+            .map_err(|e| format!("打开只读数据库连接失败：无法启用 query_only: {}", e))?;
+        Ok(Database { conn })
+    }
+
+    /// 数据库文件路径（内存数据库返回 None）
+    pub fn path(&self) -> Option<PathBuf> {
+        self.conn
+            .path()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+    }
+
     /// 获取数据库连接的引用（供 CRUD 方法使用）
     pub fn conn(&self) -> &Connection {
         &self.conn
     }
 
+    /// 在事务中执行多步写操作：`f` 返回 `Ok` 时提交，返回 `Err` 时全部回滚
+    ///
+    /// `f` 通过传入的 `&Database` 照常调用其他方法，所有语句共用同一连接，均处于事务内。
+    /// 已处于事务中时（嵌套调用）直接执行 `f`，由最外层事务统一提交或回滚。
+    pub fn with_transaction<T, F>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(&Database) -> Result<T, String>,
+    {
+        if !self.conn.is_autocommit() {
+            return f(self);
+        }
+
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| format!("开启数据库事务失败：{}", e))?;
+        // 出错时 tx 被丢弃，自动回滚
+        let value = f(self)?;
+        tx.commit().map_err(|e| format!("提交数据库事务失败：{}", e))?;
+        Ok(value)
+    }
+
     // ========================================================================
     // 分类 CRUD 方法
     // ========================================================================
@@ -588,6 +1075,40 @@ impl Database {
         Ok(categories)
     }
 
+    /// 查询所有分类及每个分类下的项目数量
+    ///
+    /// 使用 LEFT JOIN 分组统计，没有项目的分类同样返回（数量为 0）。
+    pub fn list_categories_with_counts(&self) -> Result<Vec<(Category, i64)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.id, c.name, c.description, c.created_at, COUNT(p.id)
+                 FROM categories c
+                 LEFT JOIN projects p ON p.category_id = c.id
+                 GROUP BY c.id
+                 ORDER BY c.id",
+            )
+            .map_err(|e| format!("查询分类失败：{}", e))?;
+
+        let categories = stmt
+            .query_map([], |row| {
+                Ok((
+                    Category {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        description: row.get(2)?,
+                        created_at: row.get(3)?,
+                    },
+                    row.get(4)?,
+                ))
+            })
+            .map_err(|e| format!("查询分类失败：{}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("查询分类失败：读取记录时出错: {}", e))?;
+
+        Ok(categories)
+    }
+
     /// 更新分类
     ///
     /// # 参数
@@ -666,6 +1187,61 @@ impl Database {
         Ok(())
     }
 
+    /// 将分类下的所有项目迁移到目标分类，然后删除该分类
+    ///
+    /// 对应"删除并迁移项目"操作：非空分类无法直接删除时，先迁移再删除。
+    /// 迁移与删除在同一事务中完成，任一步失败均不产生部分修改。
+    ///
+    /// # 参数
+    /// - `category_id`: 待删除的分类 ID
+    /// - `target_category_id`: 接收项目的目标分类 ID（须存在且不同于待删除分类）
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 被迁移的项目数
+    /// - `Err(String)`: 目标分类无效或分类不存在，返回中文错误描述
+    pub fn reassign_and_delete_category(
+        &self,
+        category_id: i64,
+        target_category_id: i64,
+    ) -> Result<usize, String> {
+        if category_id == target_category_id {
+            return Err("目标分类不能与待删除分类相同".to_string());
+        }
+
+        let target_exists: bool = self
+            .conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM categories WHERE id = ?1)",
+                params![target_category_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("删除分类失败：查询目标分类时出错: {}", e))?;
+        if !target_exists {
+            return Err(format!("删除分类失败：目标分类 ID {} 不存在", target_category_id));
+        }
+
+        self.with_transaction(|db| {
+            let reassigned = db
+                .conn
+                .execute(
+                    "UPDATE projects SET category_id = ?1, updated_at = datetime('now')
+                     WHERE category_id = ?2",
+                    params![target_category_id, category_id],
+                )
+                .map_err(|e| format!("删除分类失败：迁移项目时出错: {}", e))?;
+
+            let rows_affected = db
+                .conn
+                .execute("DELETE FROM categories WHERE id = ?1", params![category_id])
+                .map_err(|e| format!("删除分类失败：{}", e))?;
+            if rows_affected == 0 {
+                return Err(format!("删除分类失败：ID {} 不存在", category_id));
+            }
+
+            Ok(reassigned)
+        })
+    }
+
     // ========================================================================
     // 项目 CRUD 方法
     // ========================================================================
@@ -696,6 +1272,18 @@ impl Database {
             return Err(format!("项目路径不存在：{}", repo_path));
         }
 
+        self.insert_project(name, category_id, repo_path, tech_stack, modules_dir)
+    }
+
+    /// 插入项目记录（不校验仓库路径，供导入他人导出的项目包使用）
+    fn insert_project(
+        &self,
+        name: &str,
+        category_id: i64,
+        repo_path: &str,
+        tech_stack: &str,
+        modules_dir: &str,
+    ) -> Result<Project, String> {
         // 插入项目记录，空字符串时使用数据库默认值
         let effective_modules_dir = if modules_dir.is_empty() {
             "modules"
@@ -731,41 +1319,308 @@ impl Database {
             .map_err(|e| format!("创建项目失败：无法读取新记录: {}", e))
     }
 
-    /// 查询所有项目
+    /// 复制项目配置：以新名称和新仓库路径创建项目，沿用源项目的分类、技术栈与模块目录
+    ///
+    /// 不复制构建记录、客户关联和客户模块配置。
     ///
     /// # 返回
-    /// - `Ok(Vec<Project>)`: 所有项目列表（按 id 升序）
-    /// - `Err(String)`: 查询失败，返回中文错误描述
-    pub fn list_projects(&self) -> Result<Vec<Project>, String> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT id, name, category_id, repo_path, tech_stack_type, modules_dir, created_at, updated_at FROM projects ORDER BY id")
-            .map_err(|e| format!("查询项目失败：{}", e))?;
+    /// - `Ok(Project)`: 新建的项目记录
+    /// - `Err(String)`: 源项目不存在或新路径不存在，返回中文错误描述
+    pub fn clone_project(
+        &self,
+        source_id: i64,
+        new_name: &str,
+        new_repo_path: &str,
+    ) -> Result<Project, String> {
+        self.clone_project_with_presets(source_id, new_name, new_repo_path, false)
+    }
 
-        let projects = stmt
-            .query_map([], |row| {
-                Ok(Project {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    category_id: row.get(2)?,
-                    repo_path: row.get(3)?,
-                    tech_stack_type: row.get(4)?,
-                    modules_dir: row.get(5)?,
-                    created_at: row.get(6)?,
-                    updated_at: row.get(7)?,
-                })
-            })
-            .map_err(|e| format!("查询项目失败：{}", e))?
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|e| format!("查询项目失败：读取记录时出错: {}", e))?;
+    /// 复制项目配置（同 `clone_project`），可选复制各客户的模块预设
+    ///
+    /// `copy_module_presets` 为 true 时，源项目下每个客户记忆的模块选择（client_module_configs）
+    /// 一并复制到新项目，客户之后关联到新项目时即可沿用；客户关联本身仍不复制。
+    pub fn clone_project_with_presets(
+        &self,
+        source_id: i64,
+        new_name: &str,
+        new_repo_path: &str,
+        copy_module_presets: bool,
+    ) -> Result<Project, String> {
+        let source = self.get_project(source_id)?;
+        // 复制预设失败时连同新项目一起回滚，不留下半成品
+        self.with_transaction(|db| {
+            let project = db.create_project(
+                new_name,
+                source.category_id,
+                new_repo_path,
+                &source.tech_stack_type,
+                &source.modules_dir,
+            )?;
+
+            if copy_module_presets {
+                db.conn
+                    .execute(
+                        "INSERT INTO client_module_configs (client_id, project_id, modules_json)
+                         SELECT client_id, ?1, modules_json FROM client_module_configs WHERE project_id = ?2",
+                        params![project.id, source_id],
+                    )
+                    .map_err(|e| format!("复制模块预设失败：{}", e))?;
+            }
 
-        Ok(projects)
+            Ok(project)
+        })
     }
 
-    /// 根据 ID 查询单个项目
-    ///
-    /// # 参数
-    /// - `id`: 项目 ID
+    /// 批量创建项目，仓库路径已注册的候选跳过
+    ///
+    /// 单个候选创建失败时记入 `skipped`，不中断其余候选的导入。
+    pub fn import_projects(
+        &self,
+        category_id: i64,
+        candidates: &[RepoCandidate],
+    ) -> Result<ProjectImportSummary, String> {
+        let mut registered: Vec<PathBuf> = self
+            .list_projects()?
+            .into_iter()
+            .map(|p| PathBuf::from(p.repo_path))
+            .collect();
+        let mut summary = ProjectImportSummary {
+            created: Vec::new(),
+            skipped: Vec::new(),
+        };
+        for candidate in candidates {
+            if registered.iter().any(|p| p == Path::new(&candidate.path)) {
+                summary.skipped.push(SkippedRepo {
+                    path: candidate.path.clone(),
+                    reason: "已注册".to_string(),
+                });
+                continue;
+            }
+            match self.create_project(
+                &candidate.name,
+                category_id,
+                &candidate.path,
+                &candidate.tech_stack,
+                &candidate.modules_dir,
+            ) {
+                Ok(project) => {
+                    registered.push(PathBuf::from(&project.repo_path));
+                    summary.created.push(project);
+                }
+                Err(reason) => summary.skipped.push(SkippedRepo {
+                    path: candidate.path.clone(),
+                    reason,
+                }),
+            }
+        }
+        Ok(summary)
+    }
+
+    /// 将单个项目导出为 JSON 项目包（项目配置、客户关联与模块预设）
+    ///
+    /// 比整库备份更轻量，便于把项目配置分享给同事。
+    /// `include_summaries` 为 true 时附带已生成的文件摘要（不含向量）。
+    pub fn export_project_bundle(
+        &self,
+        project_id: i64,
+        include_summaries: bool,
+    ) -> Result<String, String> {
+        let project = self.get_project(project_id)?;
+        let category: String = self
+            .conn
+            .query_row(
+                "SELECT name FROM categories WHERE id = ?1",
+                params![project.category_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("导出项目失败：读取分类时出错: {}", e))?;
+
+        // 关联客户与有模块预设的客户取并集，按客户 ID 排序
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.name,
+                        EXISTS(SELECT 1 FROM project_clients pc
+                               WHERE pc.client_id = c.id AND pc.project_id = ?1),
+                        (SELECT m.modules_json FROM client_module_configs m
+                         WHERE m.client_id = c.id AND m.project_id = ?1)
+                 FROM clients c
+                 WHERE c.id IN (SELECT client_id FROM project_clients WHERE project_id = ?1
+                                UNION SELECT client_id FROM client_module_configs WHERE project_id = ?1)
+                 ORDER BY c.id",
+            )
+            .map_err(|e| format!("导出项目失败：{}", e))?;
+        let clients = stmt
+            .query_map(params![project_id], |row| {
+                Ok(BundleClient {
+                    name: row.get(0)?,
+                    associated: row.get(1)?,
+                    modules_json: row.get(2)?,
+                })
+            })
+            .map_err(|e| format!("导出项目失败：{}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("导出项目失败：读取客户时出错: {}", e))?;
+
+        let file_summaries = if include_summaries {
+            self.list_file_index(project_id)?
+                .into_iter()
+                .filter_map(|row| {
+                    let summary = row.summary.filter(|s| !s.is_empty())?;
+                    Some(BundleFileSummary {
+                        file_path: row.file_path,
+                        file_hash: row.file_hash,
+                        hash_algo: row.hash_algo,
+                        summary,
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let bundle = ProjectBundle {
+            version: PROJECT_BUNDLE_VERSION,
+            name: project.name,
+            category,
+            repo_path: project.repo_path,
+            tech_stack_type: project.tech_stack_type,
+            modules_dir: project.modules_dir,
+            clients,
+            file_summaries,
+        };
+        serde_json::to_string_pretty(&bundle).map_err(|e| format!("导出项目失败：{}", e))
+    }
+
+    /// 从 JSON 项目包重建项目（`export_project_bundle` 的逆操作）
+    ///
+    /// 分类与客户按名称匹配，不存在时新建；所有 ID 重新分配。仓库路径在本机不存在时
+    /// 仍然导入，并在结果中给出提示。任一步骤失败时整体回滚。
+    pub fn import_project_bundle(&self, json: &str) -> Result<BundleImportResult, String> {
+        let bundle: ProjectBundle =
+            serde_json::from_str(json).map_err(|e| format!("项目包格式无效：{}", e))?;
+        if bundle.version > PROJECT_BUNDLE_VERSION {
+            return Err(format!(
+                "项目包版本 {} 高于当前支持的版本 {}，请升级应用后再导入",
+                bundle.version, PROJECT_BUNDLE_VERSION
+            ));
+        }
+
+        let mut warnings = Vec::new();
+        if !Path::new(&bundle.repo_path).exists() {
+            warnings.push(format!(
+                "仓库路径在本机不存在：{}，请导入后修改项目路径",
+                bundle.repo_path
+            ));
+        }
+
+        self.with_transaction(|db| {
+            let category_id = match db
+                .list_categories()?
+                .into_iter()
+                .find(|c| c.name == bundle.category)
+            {
+                Some(category) => category.id,
+                None => db.create_category(&bundle.category, None)?.id,
+            };
+            let project = db.insert_project(
+                &bundle.name,
+                category_id,
+                &bundle.repo_path,
+                &bundle.tech_stack_type,
+                &bundle.modules_dir,
+            )?;
+
+            let mut clients_created = 0;
+            for client in &bundle.clients {
+                let existing: Option<i64> = db
+                    .conn
+                    .query_row(
+                        "SELECT id FROM clients WHERE name = ?1 ORDER BY id LIMIT 1",
+                        params![client.name],
+                        |row| row.get(0),
+                    )
+                    .ok();
+                let client_id = match existing {
+                    Some(id) => id,
+                    None => {
+                        clients_created += 1;
+                        db.create_client(&client.name, &[])?.id
+                    }
+                };
+                if client.associated {
+                    db.conn
+                        .execute(
+                            "INSERT INTO project_clients (project_id, client_id) VALUES (?1, ?2)",
+                            params![project.id, client_id],
+                        )
+                        .map_err(|e| format!("创建客户关联失败：{}", e))?;
+                }
+                if let Some(modules_json) = &client.modules_json {
+                    db.save_client_module_config(client_id, project.id, modules_json)?;
+                }
+            }
+
+            for file in &bundle.file_summaries {
+                db.conn
+                    .execute(
+                        "INSERT INTO file_index (project_id, file_path, file_hash, hash_algo, summary)
+                         VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![
+                            project.id,
+                            file.file_path,
+                            file.file_hash,
+                            file.hash_algo,
+                            file.summary
+                        ],
+                    )
+                    .map_err(|e| format!("导入文件摘要失败：{}", e))?;
+            }
+
+            Ok(BundleImportResult {
+                project,
+                clients_created,
+                summaries_imported: bundle.file_summaries.len(),
+                warnings,
+            })
+        })
+    }
+
+    /// 查询所有项目
+    ///
+    /// # 返回
+    /// - `Ok(Vec<Project>)`: 所有项目列表（按 id 升序）
+    /// - `Err(String)`: 查询失败，返回中文错误描述
+    pub fn list_projects(&self) -> Result<Vec<Project>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, category_id, repo_path, tech_stack_type, modules_dir, created_at, updated_at FROM projects ORDER BY id")
+            .map_err(|e| format!("查询项目失败：{}", e))?;
+
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    category_id: row.get(2)?,
+                    repo_path: row.get(3)?,
+                    tech_stack_type: row.get(4)?,
+                    modules_dir: row.get(5)?,
+                    created_at: row.get(6)?,
+                    updated_at: row.get(7)?,
+                })
+            })
+            .map_err(|e| format!("查询项目失败：{}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("查询项目失败：读取记录时出错: {}", e))?;
+
+        Ok(projects)
+    }
+
+    /// 根据 ID 查询单个项目
+    ///
+    /// # 参数
+    /// - `id`: 项目 ID
     ///
     /// # 返回
     /// - `Ok(Project)`: 查询到的项目记录
@@ -869,6 +1724,36 @@ impl Database {
         Ok(())
     }
 
+    /// 预估删除项目的级联影响（只读，不执行删除）
+    ///
+    /// 统计删除项目时 ON DELETE CASCADE 会一并清理的构建记录、客户关联、
+    /// 客户模块配置和文件索引条目数量，供前端在确认删除前展示。
+    ///
+    /// # 返回
+    /// - `Ok(DeleteImpact)`: 各关联表中将被删除的行数
+    /// - `Err(String)`: 项目不存在或查询失败，返回中文错误描述
+    pub fn project_delete_impact(&self, id: i64) -> Result<DeleteImpact, String> {
+        // 先确认项目存在，避免对不存在的项目返回全 0 的误导结果
+        self.get_project(id)?;
+
+        let count = |table: &str| -> Result<i64, String> {
+            self.conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM {} WHERE project_id = ?1", table),
+                    params![id],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("查询删除影响失败：{}", e))
+        };
+
+        Ok(DeleteImpact {
+            build_records: count("build_records")?,
+            client_associations: count("project_clients")?,
+            client_module_configs: count("client_module_configs")?,
+            file_index_entries: count("file_index")?,
+        })
+    }
+
     // ========================================================================
     // 客户 CRUD 方法
     // ========================================================================
@@ -886,37 +1771,40 @@ impl Database {
     /// - `Ok(Client)`: 创建成功，返回完整的客户记录
     /// - `Err(String)`: 创建失败，返回中文错误描述
     pub fn create_client(&self, name: &str, project_ids: &[i64]) -> Result<Client, String> {
-        // 插入客户记录
-        self.conn
-            .execute("INSERT INTO clients (name) VALUES (?1)", params![name])
-            .map_err(|e| format!("创建客户失败：{}", e))?;
-
-        let client_id = self.conn.last_insert_rowid();
+        // 客户与关联记录在同一事务中写入，任一关联失败则整体回滚
+        self.with_transaction(|db| {
+            // 插入客户记录
+            db.conn
+                .execute("INSERT INTO clients (name) VALUES (?1)", params![name])
+                .map_err(|e| format!("创建客户失败：{}", e))?;
+
+            let client_id = db.conn.last_insert_rowid();
+
+            // 为每个项目创建关联记录
+            for &project_id in project_ids {
+                db.conn
+                    .execute(
+                        "INSERT INTO project_clients (project_id, client_id) VALUES (?1, ?2)",
+                        params![project_id, client_id],
+                    )
+                    .map_err(|e| format!("创建客户关联失败：{}", e))?;
+            }
 
-        // 为每个项目创建关联记录
-        for &project_id in project_ids {
-            self.conn
-                .execute(
-                    "INSERT INTO project_clients (project_id, client_id) VALUES (?1, ?2)",
-                    params![project_id, client_id],
+            // 查询刚插入的客户记录并返回
+            db.conn
+                .query_row(
+                    "SELECT id, name, created_at FROM clients WHERE id = ?1",
+                    params![client_id],
+                    |row| {
+                        Ok(Client {
+                            id: row.get(0)?,
+                            name: row.get(1)?,
+                            created_at: row.get(2)?,
+                        })
+                    },
                 )
-                .map_err(|e| format!("创建客户关联失败：{}", e))?;
-        }
-
-        // 查询刚插入的客户记录并返回
-        self.conn
-            .query_row(
-                "SELECT id, name, created_at FROM clients WHERE id = ?1",
-                params![client_id],
-                |row| {
-                    Ok(Client {
-                        id: row.get(0)?,
-                        name: row.get(1)?,
-                        created_at: row.get(2)?,
-                    })
-                },
-            )
-            .map_err(|e| format!("创建客户失败：无法读取新记录: {}", e))
+                .map_err(|e| format!("创建客户失败：无法读取新记录: {}", e))
+        })
     }
 
     /// 查询指定项目关联的所有客户
@@ -956,6 +1844,39 @@ impl Database {
         Ok(clients)
     }
 
+    /// 查询所有客户及每个客户的构建记录数量
+    ///
+    /// 使用 LEFT JOIN 分组统计，没有构建记录的客户同样返回（数量为 0）。
+    pub fn list_all_clients_with_counts(&self) -> Result<Vec<(Client, i64)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT c.id, c.name, c.created_at, COUNT(b.id)
+                 FROM clients c
+                 LEFT JOIN build_records b ON b.client_id = c.id
+                 GROUP BY c.id
+                 ORDER BY c.id",
+            )
+            .map_err(|e| format!("查询客户失败：{}", e))?;
+
+        let clients = stmt
+            .query_map([], |row| {
+                Ok((
+                    Client {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        created_at: row.get(2)?,
+                    },
+                    row.get(3)?,
+                ))
+            })
+            .map_err(|e| format!("查询客户失败：{}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("查询客户失败：读取记录时出错: {}", e))?;
+
+        Ok(clients)
+    }
+
     /// 更新客户名称
     ///
     /// # 参数
@@ -981,6 +1902,19 @@ impl Database {
         Ok(())
     }
 
+    /// 统计引用该客户的构建记录数
+    ///
+    /// 供重命名/删除前向用户确认影响范围（"该客户已有 N 次交付"）。
+    pub fn client_build_count(&self, client_id: i64) -> Result<i64, String> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM build_records WHERE client_id = ?1",
+                params![client_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("统计客户构建记录失败：{}", e))
+    }
+
     /// 删除客户
     ///
     /// 依赖 ON DELETE CASCADE 自动清理 project_clients 中的关联记录。
@@ -1004,6 +1938,40 @@ impl Database {
         Ok(())
     }
 
+    /// 清理孤立的项目-客户关联
+    ///
+    /// 外键约束启用之前写入或外部导入/编辑的数据中，project_clients 可能引用已删除的项目或客户。
+    /// 删除这些悬空行并按原因分别计数。
+    ///
+    /// # 返回
+    /// - `Ok(RepairReport)`: 删除的关联数
+    /// - `Err(String)`: 清理失败，返回中文错误描述
+    pub fn repair_orphaned_associations(&self) -> Result<RepairReport, String> {
+        self.with_transaction(|db| {
+            let missing_project = db
+                .conn
+                .execute(
+                    "DELETE FROM project_clients
+                     WHERE project_id NOT IN (SELECT id FROM projects)",
+                    [],
+                )
+                .map_err(|e| format!("清理孤立关联失败：{}", e))?;
+            let missing_client = db
+                .conn
+                .execute(
+                    "DELETE FROM project_clients
+                     WHERE client_id NOT IN (SELECT id FROM clients)",
+                    [],
+                )
+                .map_err(|e| format!("清理孤立关联失败：{}", e))?;
+
+            Ok(RepairReport {
+                missing_project,
+                missing_client,
+            })
+        })
+    }
+
     // ========================================================================
     // 构建记录方法
     // ========================================================================
@@ -1017,7 +1985,7 @@ impl Database {
     /// - `project_id`: 关联的项目 ID
     /// - `client_id`: 关联的客户 ID
     /// - `modules_json`: 选中模块的 JSON 数组字符串
-    /// - `output_path`: 构建输出文件路径
+    /// - `output_path`: 构建输出文件的绝对路径（按原生格式存储，与 `relocate_build_artifacts` 一致）
     ///
     /// # 返回
     /// - `Ok(BuildRecord)`: 创建成功，返回完整的构建记录
@@ -1031,19 +1999,70 @@ impl Database {
         version: &str,
         changelog: Option<&str>,
     ) -> Result<BuildRecord, String> {
-        self.conn
-            .execute(
-                "INSERT INTO build_records (project_id, client_id, selected_modules, output_path, version, changelog) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                params![project_id, client_id, modules_json, output_path, version, changelog],
-            )
-            .map_err(|e| format!("创建构建记录失败：{}", e))?;
+        self.create_build_record_with_config(
+            project_id,
+            client_id,
+            modules_json,
+            output_path,
+            version,
+            changelog,
+            "",
+            "",
+            None,
+        )
+    }
 
-        let id = self.conn.last_insert_rowid();
+    /// 创建构建记录，并记录构建时使用的技术栈与模块目录（用于按记录重建）
+    ///
+    /// 其余参数同 `create_build_record`；`modules_dir` 为空表示使用技术栈默认目录。
+    /// `idempotency_key` 为客户端生成的 UUID：已存在相同键的记录时直接返回该记录，
+    /// 避免界面超时重试导致重复记录。
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_build_record_with_config(
+        &self,
+        project_id: i64,
+        client_id: i64,
+        modules_json: &str,
+        output_path: &str,
+        version: &str,
+        changelog: Option<&str>,
+        tech_stack: &str,
+        modules_dir: &str,
+        idempotency_key: Option<&str>,
+    ) -> Result<BuildRecord, String> {
+        let existing: Option<i64> = match idempotency_key {
+            Some(key) => self
+                .conn
+                .query_row(
+                    "SELECT id FROM build_records WHERE idempotency_key = ?1",
+                    params![key],
+                    |row| row.get(0),
+                )
+                .map(Some)
+                .or_else(|e| match e {
+                    rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                    e => Err(format!("查询构建记录失败：{}", e)),
+                })?,
+            None => None,
+        };
+
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                self.conn
+                    .execute(
+                        "INSERT INTO build_records (project_id, client_id, selected_modules, output_path, version, changelog, tech_stack, modules_dir, idempotency_key) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                        params![project_id, client_id, modules_json, output_path, version, changelog, tech_stack, modules_dir, idempotency_key],
+                    )
+                    .map_err(|e| format!("创建构建记录失败：{}", e))?;
+                self.conn.last_insert_rowid()
+            }
+        };
 
         // 查询刚插入的记录以获取完整字段（包括 created_at 默认值）
         self.conn
             .query_row(
-                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at FROM build_records WHERE id = ?1",
+                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at, tech_stack, modules_dir FROM build_records WHERE id = ?1",
                 params![id],
                 |row| {
                     Ok(BuildRecord {
@@ -1055,12 +2074,56 @@ impl Database {
                         version: row.get(5)?,
                         changelog: row.get(6)?,
                         created_at: row.get(7)?,
+                        tech_stack: row.get(8)?,
+                        modules_dir: row.get(9)?,
                     })
                 },
             )
             .map_err(|e| format!("查询构建记录失败：{}", e))
     }
 
+    /// 解析按构建记录重建所需的参数
+    ///
+    /// 技术栈与模块目录取自记录本身，不受项目当前配置影响；
+    /// 未记录技术栈的旧记录回退到项目当前的技术栈与模块目录。
+    ///
+    /// # 返回
+    /// - `Ok(RebuildParams)`: 项目路径、客户名称与记录中的构建参数
+    /// - `Err(String)`: 记录、项目或客户不存在，或模块列表无法解析
+    pub fn get_rebuild_params(&self, build_record_id: i64) -> Result<RebuildParams, String> {
+        let record = self
+            .list_build_records_by_ids(&[build_record_id])?
+            .pop()
+            .ok_or_else(|| format!("构建记录不存在：ID {}", build_record_id))?;
+        let project = self.get_project(record.project_id)?;
+        let client_name: String = self
+            .conn
+            .query_row(
+                "SELECT name FROM clients WHERE id = ?1",
+                params![record.client_id],
+                |row| row.get(0),
+            )
+            .map_err(|_| format!("客户不存在：ID {}", record.client_id))?;
+        let selected_modules: Vec<String> = serde_json::from_str(&record.selected_modules)
+            .map_err(|e| format!("构建记录的模块列表格式无效：{}", e))?;
+
+        let (tech_stack, modules_dir) = if record.tech_stack.is_empty() {
+            (project.tech_stack_type, project.modules_dir)
+        } else {
+            (record.tech_stack, record.modules_dir)
+        };
+
+        Ok(RebuildParams {
+            project_id: project.id,
+            project_path: project.repo_path,
+            client_id: record.client_id,
+            client_name,
+            tech_stack,
+            modules_dir,
+            selected_modules,
+        })
+    }
+
     /// 按项目 ID 查询构建记录列表
     ///
     /// 返回指定项目的所有构建记录，按创建时间倒序排列（最新的在前）。
@@ -1078,7 +2141,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at FROM build_records WHERE project_id = ?1 ORDER BY created_at DESC, id DESC",
+                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at, tech_stack, modules_dir FROM build_records WHERE project_id = ?1 ORDER BY created_at DESC, id DESC",
             )
             .map_err(|e| format!("查询构建记录失败：{}", e))?;
 
@@ -1093,6 +2156,8 @@ impl Database {
                     version: row.get(5)?,
                     changelog: row.get(6)?,
                     created_at: row.get(7)?,
+                    tech_stack: row.get(8)?,
+                    modules_dir: row.get(9)?,
                 })
             })
             .map_err(|e| format!("查询构建记录失败：{}", e))?;
@@ -1110,7 +2175,7 @@ impl Database {
         // 动态构建 IN 子句的占位符
         let placeholders: Vec<String> = ids.iter().enumerate().map(|(i, _)| format!("?{}", i + 1)).collect();
         let sql = format!(
-            "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at FROM build_records WHERE id IN ({})",
+            "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at, tech_stack, modules_dir FROM build_records WHERE id IN ({})",
             placeholders.join(", ")
         );
         let mut stmt = self.conn.prepare(&sql).map_err(|e| format!("查询构建记录失败：{}", e))?;
@@ -1126,6 +2191,8 @@ impl Database {
                     version: row.get(5)?,
                     changelog: row.get(6)?,
                     created_at: row.get(7)?,
+                    tech_stack: row.get(8)?,
+                    modules_dir: row.get(9)?,
                 })
             })
             .map_err(|e| format!("查询构建记录失败：{}", e))?;
@@ -1137,7 +2204,7 @@ impl Database {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at FROM build_records WHERE project_id = ?1 AND created_at < datetime('now', ?2) ORDER BY created_at DESC",
+                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at, tech_stack, modules_dir FROM build_records WHERE project_id = ?1 AND created_at < datetime('now', ?2) ORDER BY created_at DESC",
             )
             .map_err(|e| format!("查询构建记录失败：{}", e))?;
         let records = stmt
@@ -1151,45 +2218,214 @@ impl Database {
                     version: row.get(5)?,
                     changelog: row.get(6)?,
                     created_at: row.get(7)?,
+                    tech_stack: row.get(8)?,
+                    modules_dir: row.get(9)?,
                 })
             })
             .map_err(|e| format!("查询构建记录失败：{}", e))?;
         records.collect::<Result<Vec<_>, _>>().map_err(|e| format!("读取构建记录失败：{}", e))
     }
 
-    /// 删除单条构建记录
+    /// 查询指定项目中包含某模块的构建记录（按创建时间倒序）
     ///
-    /// # 参数
-    /// - `id`: 构建记录 ID
-    pub fn delete_build_record(&self, id: i64) -> Result<(), String> {
-        let affected = self
-            .conn
-            .execute("DELETE FROM build_records WHERE id = ?1", params![id])
-            .map_err(|e| format!("删除构建记录失败：{}", e))?;
-
-        if affected == 0 {
-            return Err(format!("构建记录不存在：id={}", id));
+    /// SQLite 支持 JSON1 时在 SQL 中用 `json_each` 判断 `selected_modules` 是否包含该模块，
+    /// 否则回退到读取全部记录后在 Rust 中解析过滤。`selected_modules` 不是合法 JSON 的记录视为不包含。
+    pub fn find_builds_with_module(
+        &self,
+        project_id: i64,
+        module_name: &str,
+    ) -> Result<Vec<BuildRecord>, String> {
+        if !self.supports_json1() {
+            let records = self.list_build_records_by_project(project_id)?;
+            return Ok(filter_records_with_module(records, module_name));
         }
-        Ok(())
-    }
 
-    /// 删除指定项目的所有构建记录
-    ///
-    /// # 返回
-    /// - `Ok(u64)`: 删除的记录数
-    pub fn delete_all_build_records(&self, project_id: i64) -> Result<u64, String> {
-        let affected = self
+        let mut stmt = self
             .conn
-            .execute(
-                "DELETE FROM build_records WHERE project_id = ?1",
-                params![project_id],
+            .prepare(
+                "SELECT id, project_id, client_id, selected_modules, output_path, version, changelog, created_at, tech_stack, modules_dir FROM build_records
+                 WHERE project_id = ?1
+                   AND EXISTS (
+                       SELECT 1 FROM json_each(CASE WHEN json_valid(selected_modules) THEN selected_modules ELSE '[]' END)
+                       WHERE json_each.value = ?2
+                   )
+                 ORDER BY created_at DESC, id DESC",
             )
-            .map_err(|e| format!("清空构建记录失败：{}", e))?;
+            .map_err(|e| format!("查询构建记录失败：{}", e))?;
+        let records = stmt
+            .query_map(params![project_id, module_name], |row| {
+                Ok(BuildRecord {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    client_id: row.get(2)?,
+                    selected_modules: row.get(3)?,
+                    output_path: row.get(4)?,
+                    version: row.get(5)?,
+                    changelog: row.get(6)?,
+                    created_at: row.get(7)?,
+                    tech_stack: row.get(8)?,
+                    modules_dir: row.get(9)?,
+                })
+            })
+            .map_err(|e| format!("查询构建记录失败：{}", e))?;
+        records.collect::<Result<Vec<_>, _>>().map_err(|e| format!("读取构建记录失败：{}", e))
+    }
 
-        Ok(affected as u64)
+    /// 当前 SQLite 是否支持 JSON1 函数
+    fn supports_json1(&self) -> bool {
+        self.conn
+            .query_row("SELECT json_valid('[]')", [], |row| row.get::<_, bool>(0))
+            .unwrap_or(false)
     }
 
-    /// 删除指定项目中 N 天前的构建记录
+    /// 删除单条构建记录，可选同时删除其输出文件
+    ///
+    /// 先删除记录再删除文件：文件已不存在时视为正常，删除失败仅记录日志，
+    /// 结果中如实反映文件是否存在、是否已删除。
+    ///
+    /// # 参数
+    /// - `id`: 构建记录 ID
+    /// - `delete_file`: 是否同时删除 `output_path` 指向的文件
+    pub fn delete_build_record(&self, id: i64, delete_file: bool) -> Result<ArtifactDeletion, String> {
+        let output_path: String = self
+            .conn
+            .query_row(
+                "SELECT output_path FROM build_records WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .map_err(|e| {
+                if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
+                    format!("构建记录不存在：id={}", id)
+                } else {
+                    format!("删除构建记录失败：{}", e)
+                }
+            })?;
+
+        self.conn
+            .execute("DELETE FROM build_records WHERE id = ?1", params![id])
+            .map_err(|e| format!("删除构建记录失败：{}", e))?;
+
+        let path = Path::new(&output_path);
+        let file_found = delete_file && !output_path.is_empty() && path.is_file();
+        let file_deleted = file_found
+            && match std::fs::remove_file(path) {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("删除构建文件失败（已忽略）：{} - {}", output_path, e);
+                    false
+                }
+            };
+        Ok(ArtifactDeletion {
+            file_found,
+            file_deleted,
+        })
+    }
+
+    /// 将项目的构建产物迁移到新目录，并同步更新构建记录的 `output_path`
+    ///
+    /// 记录更新在同一事务内完成；任一文件移动或更新失败时事务回滚，
+    /// 已移动的文件按相反顺序移回原处，保证数据库与文件系统一致。
+    /// 同名 `.sha256` 校验文件随产物一起移动。跨设备时退回复制后删除。
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `new_dir`: 目标目录（不存在时自动创建）
+    pub fn relocate_build_artifacts(
+        &self,
+        project_id: i64,
+        new_dir: &Path,
+    ) -> Result<RelocateReport, String> {
+        std::fs::create_dir_all(new_dir)
+            .map_err(|e| format!("创建目标目录失败：{} - {}", new_dir.display(), e))?;
+        let records = self.list_build_records_by_project(project_id)?;
+
+        // (原路径, 新路径)，用于失败时把文件移回原处
+        let mut moved_files: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut moved = 0;
+        let mut missing = 0;
+        let result = self.with_transaction(|db| {
+            for record in &records {
+                if record.output_path.is_empty() {
+                    continue;
+                }
+                let source = Path::new(&record.output_path);
+                if !source.is_file() {
+                    missing += 1;
+                    continue;
+                }
+                let file_name = source
+                    .file_name()
+                    .ok_or_else(|| format!("无效的产物路径：{}", record.output_path))?;
+                let target = new_dir.join(file_name);
+                if same_file(source, &target) {
+                    continue;
+                }
+                if target.exists() {
+                    return Err(format!("目标目录已存在同名文件：{}", target.display()));
+                }
+
+                move_file(source, &target).map_err(|e| {
+                    format!(
+                        "移动构建产物失败：{} → {} - {}",
+                        source.display(),
+                        target.display(),
+                        e
+                    )
+                })?;
+                moved_files.push((source.to_path_buf(), target.clone()));
+
+                let mut sidecar = source.as_os_str().to_owned();
+                sidecar.push(".sha256");
+                let sidecar = PathBuf::from(sidecar);
+                if sidecar.is_file() {
+                    let mut sidecar_target = target.as_os_str().to_owned();
+                    sidecar_target.push(".sha256");
+                    let sidecar_target = PathBuf::from(sidecar_target);
+                    move_file(&sidecar, &sidecar_target)
+                        .map_err(|e| format!("移动校验文件失败：{} - {}", sidecar.display(), e))?;
+                    moved_files.push((sidecar, sidecar_target));
+                }
+
+                db.conn
+                    .execute(
+                        "UPDATE build_records SET output_path = ?1 WHERE id = ?2",
+                        params![target.to_string_lossy().to_string(), record.id],
+                    )
+                    .map_err(|e| format!("更新构建记录失败：{}", e))?;
+                moved += 1;
+            }
+            Ok(())
+        });
+
+        if let Err(e) = result {
+            for (source, target) in moved_files.iter().rev() {
+                if let Err(err) = move_file(target, source) {
+                    log::warn!("回滚构建产物迁移失败：{} - {}", target.display(), err);
+                }
+            }
+            return Err(e);
+        }
+        Ok(RelocateReport { moved, missing })
+    }
+
+    /// 删除指定项目的所有构建记录
+    ///
+    /// # 返回
+    /// - `Ok(u64)`: 删除的记录数
+    pub fn delete_all_build_records(&self, project_id: i64) -> Result<u64, String> {
+        let affected = self
+            .conn
+            .execute(
+                "DELETE FROM build_records WHERE project_id = ?1",
+                params![project_id],
+            )
+            .map_err(|e| format!("清空构建记录失败：{}", e))?;
+
+        Ok(affected as u64)
+    }
+
+    /// 删除指定项目中 N 天前的构建记录
     ///
     /// # 参数
     /// - `project_id`: 项目 ID
@@ -1287,6 +2523,102 @@ impl Database {
         Ok(())
     }
 
+    /// 持久化保存单个设置项：写入后立即执行 WAL checkpoint
+    ///
+    /// 普通 `save_setting` 依赖 SQLite 自动 checkpoint，进程崩溃时
+    /// 尚未落盘的 WAL 内容可能丢失。API Key、输出目录等关键设置
+    /// 通过此方法保存，写入后执行 `PRAGMA wal_checkpoint(TRUNCATE)`
+    /// 将 WAL 内容合并回主数据库文件。
+    ///
+    /// # 参数
+    /// - `key`: 设置键名
+    /// - `value`: 设置值
+    ///
+    /// # 返回
+    /// - `Ok(())`: 保存并落盘成功
+    /// - `Err(String)`: 保存或 checkpoint 失败，返回中文错误描述
+    pub fn save_setting_durable(&self, key: &str, value: &str) -> Result<(), String> {
+        self.save_setting(key, value)?;
+        self.checkpoint()
+            .map_err(|e| format!("保存设置失败：{}", e))
+    }
+
+    /// 执行 WAL checkpoint，将 WAL 内容合并回主数据库文件
+    ///
+    /// 除关键设置外，应用退出前也会调用，避免批量 Embedding 等
+    /// 最近写入在进程结束时仍停留在 WAL 中。
+    pub fn checkpoint(&self) -> Result<(), String> {
+        // 返回 (busy, log, checkpointed)，非 WAL 模式下为无操作
+        self.conn
+            .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+            .map_err(|e| format!("数据落盘时出错: {}", e))
+    }
+
+    /// 收集数据库健康快照：各表行数、文件大小、SQLite 版本、日志模式、外键状态与快速完整性检查
+    pub fn health(&self) -> Result<DbHealth, String> {
+        let err = |e: rusqlite::Error| format!("数据库健康检查失败：{}", e);
+
+        let tables: Vec<String> = {
+            let mut stmt = self
+                .conn
+                .prepare(
+                    "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+                )
+                .map_err(err)?;
+            let rows = stmt.query_map([], |row| row.get(0)).map_err(err)?;
+            rows.collect::<Result<_, _>>().map_err(err)?
+        };
+        let mut table_counts = BTreeMap::new();
+        for table in tables {
+            let count: i64 = self
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table), [], |row| row.get(0))
+                .map_err(err)?;
+            table_counts.insert(table, count);
+        }
+
+        let file_size = |path: PathBuf| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let (file_size_bytes, wal_size_bytes) = match self.path() {
+            Some(path) => {
+                let mut wal = path.clone().into_os_string();
+                wal.push("-wal");
+                (file_size(path), file_size(PathBuf::from(wal)))
+            }
+            None => (0, 0),
+        };
+
+        let sqlite_version: String = self
+            .conn
+            .query_row("SELECT sqlite_version()", [], |row| row.get(0))
+            .map_err(err)?;
+        let journal_mode: String = self
+            .conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .map_err(err)?;
+        let foreign_keys: bool = self
+            .conn
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .map_err(err)?;
+
+        let check: Vec<String> = {
+            let mut stmt = self.conn.prepare("PRAGMA quick_check").map_err(err)?;
+            let rows = stmt.query_map([], |row| row.get(0)).map_err(err)?;
+            rows.collect::<Result<_, _>>().map_err(err)?
+        };
+        let integrity_ok = check.len() == 1 && check[0] == "ok";
+
+        Ok(DbHealth {
+            table_counts,
+            file_size_bytes,
+            wal_size_bytes,
+            sqlite_version,
+            journal_mode,
+            foreign_keys,
+            integrity_ok,
+            integrity_errors: if integrity_ok { Vec::new() } else { check },
+        })
+    }
+
     // ========================================================================
     // 构建版本号与变更日志
     // ========================================================================
@@ -1559,39 +2891,771 @@ impl Database {
                 format!("更新模板失败：{}", e)
             })?;
 
-        Ok(())
+        Ok(())
+    }
+
+    /// 删除自定义模板（内置模板不可删除）
+    pub fn delete_template(&self, id: i64) -> Result<(), String> {
+        // 检查是否为内置模板
+        let is_builtin: bool = self
+            .conn
+            .query_row(
+                "SELECT is_builtin FROM tech_stack_templates WHERE id = ?1",
+                params![id],
+                |row| row.get::<_, i32>(0).map(|v| v != 0),
+            )
+            .map_err(|e| {
+                if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
+                    format!("模板不存在：ID {}", id)
+                } else {
+                    format!("查询模板失败：{}", e)
+                }
+            })?;
+
+        if is_builtin {
+            return Err("内置模板不可删除".to_string());
+        }
+
+        self.conn
+            .execute(
+                "DELETE FROM tech_stack_templates WHERE id = ?1",
+                params![id],
+            )
+            .map_err(|e| format!("删除模板失败：{}", e))?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Embedding 维度管理
+    // ========================================================================
+
+    /// 获取当前 Embedding 模型及已记录的向量维度
+    pub fn get_embedding_model_info(&self) -> Result<EmbeddingModelInfo, String> {
+        let model = self.get_setting("llm_embedding_model")?.unwrap_or_default();
+        let dimension = self
+            .get_setting(EMBEDDING_DIM_KEY)?
+            .and_then(|v| v.parse::<usize>().ok());
+        Ok(EmbeddingModelInfo { model, dimension })
+    }
+
+    /// 校验即将存储的向量维度
+    ///
+    /// 尚未记录维度时以本次维度为准写入 `llm_embedding_dim`；
+    /// 已记录且不一致时拒绝存储，避免混用不同模型的向量导致搜索失效。
+    pub fn ensure_embedding_dimension(&self, dimension: usize) -> Result<(), String> {
+        match self.get_embedding_model_info()?.dimension {
+            None => self.save_setting(EMBEDDING_DIM_KEY, &dimension.to_string()),
+            Some(recorded) if recorded == dimension => Ok(()),
+            Some(recorded) => Err(format!(
+                "{}（已记录 {} 维，当前 {} 维）",
+                EMBEDDING_DIM_MISMATCH, recorded, dimension
+            )),
+        }
+    }
+
+    /// 清空指定项目的所有 Embedding 向量
+    ///
+    /// 清空后若已无任何项目保留向量，同时重置已记录的维度，以便切换 Embedding 模型。
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 被清空向量的文件数
+    pub fn clear_embeddings(&self, project_id: i64) -> Result<usize, String> {
+        let cleared = self
+            .conn
+            .execute(
+                "UPDATE file_index SET embedding = NULL, embedding_mode = NULL
+                 WHERE project_id = ?1 AND embedding IS NOT NULL",
+                params![project_id],
+            )
+            .map_err(|e| format!("清空 Embedding 失败：{}", e))?;
+
+        self.reset_embedding_dimension_if_unused()?;
+        Ok(cleared)
+    }
+
+    /// 已无任何项目保留向量时重置已记录的 Embedding 维度
+    fn reset_embedding_dimension_if_unused(&self) -> Result<(), String> {
+        let remaining: i64 = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) FROM file_index WHERE embedding IS NOT NULL",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("清空 Embedding 失败：{}", e))?;
+        if remaining == 0 {
+            self.conn
+                .execute("DELETE FROM settings WHERE key = ?1", params![EMBEDDING_DIM_KEY])
+                .map_err(|e| format!("重置 Embedding 维度失败：{}", e))?;
+        }
+        Ok(())
+    }
+
+    /// 清空项目的全部分析数据，保留项目、客户与构建记录
+    ///
+    /// 在一个事务内删除文件索引（哈希、摘要、签名、向量）、目录索引与已保存的分析报告，
+    /// 下次分析将从头全量扫描。需要回收磁盘空间时随后调用 `vacuum`。
+    ///
+    /// # 返回
+    /// - `Ok(ClearReport)`: 各表删除的行数
+    /// - `Err(String)`: 项目不存在或删除失败（已回滚）
+    pub fn clear_project_analysis(&self, project_id: i64) -> Result<ClearReport, String> {
+        self.get_project(project_id)?;
+
+        self.with_transaction(|db| {
+            let delete = |table: &str| -> Result<usize, String> {
+                db.conn
+                    .execute(
+                        &format!("DELETE FROM {} WHERE project_id = ?1", table),
+                        params![project_id],
+                    )
+                    .map_err(|e| format!("清空分析数据失败：{}", e))
+            };
+            let report = ClearReport {
+                file_index_entries: delete("file_index")?,
+                dir_index_entries: delete("dir_index")?,
+                reports: delete("project_reports")?,
+            };
+            db.reset_embedding_dimension_if_unused()?;
+            Ok(report)
+        })
+    }
+
+    /// 执行 `VACUUM` 整理数据库文件，回收已删除数据占用的磁盘空间
+    pub fn vacuum(&self) -> Result<(), String> {
+        self.conn
+            .execute_batch("VACUUM")
+            .map_err(|e| format!("整理数据库失败：{}", e))
+    }
+
+    /// 保存文件摘要，摘要变化时清空该文件已有的 Embedding
+    ///
+    /// Embedding 优先以摘要为输入生成，摘要更新后旧向量即已过期；
+    /// 清空后下一次批量 Embedding 会基于新摘要重新生成。
+    ///
+    /// # 返回
+    /// - `Ok(true)`: 已清空过期向量，需要重新生成 Embedding
+    /// - `Ok(false)`: 摘要未变化或文件尚无向量
+    pub fn save_file_summary(
+        &self,
+        project_id: i64,
+        file_path: &str,
+        summary: &str,
+    ) -> Result<bool, String> {
+        let cleared = self
+            .conn
+            .execute(
+                "UPDATE file_index SET embedding = NULL, embedding_mode = NULL
+                 WHERE project_id = ?1 AND file_path = ?2 AND embedding IS NOT NULL
+                   AND summary IS NOT ?3",
+                params![project_id, file_path, summary],
+            )
+            .map_err(|e| format!("保存摘要失败：{}", e))?;
+        self.conn
+            .execute(
+                "UPDATE file_index SET summary = ?1 WHERE project_id = ?2 AND file_path = ?3",
+                params![summary, project_id, file_path],
+            )
+            .map_err(|e| format!("保存摘要失败：{}", e))?;
+        Ok(cleared > 0)
+    }
+
+    /// 列出项目中已有向量所使用的输入模式（去重、排序）
+    ///
+    /// 返回多于一种模式说明向量混用了不同输入，彼此不可比，应清空后重新索引。
+    /// 新增该列之前生成的向量均以摘要为输入，按 `summary` 计。
+    pub fn list_embedding_modes(&self, project_id: i64) -> Result<Vec<String>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT COALESCE(embedding_mode, 'summary') FROM file_index
+                 WHERE project_id = ?1 AND embedding IS NOT NULL ORDER BY 1",
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        let rows = stmt
+            .query_map(params![project_id], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("读取文件索引失败：{}", e))
+    }
+
+    /// 列出项目中缺少 Embedding 的文件（批量 Embedding 的待处理列表）
+    ///
+    /// # 返回
+    /// - `Ok(Vec<(String, Option<String>)>)`: 每项为 (file_path, summary)
+    pub fn list_files_without_embedding(
+        &self,
+        project_id: i64,
+    ) -> Result<Vec<(String, Option<String>)>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_path, summary FROM file_index WHERE project_id = ?1 AND embedding IS NULL",
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+            })
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        Ok(rows.filter_map(|r| r.ok()).collect())
+    }
+
+    // ========================================================================
+    // 目录索引方法（增量扫描）
+    // ========================================================================
+
+    /// 获取项目上次扫描记录的目录修改时间（相对路径 → 纳秒时间戳）
+    pub fn get_dir_mtimes(&self, project_id: i64) -> Result<HashMap<String, u64>, String> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT dir_path, mtime FROM dir_index WHERE project_id = ?1")
+            .map_err(|e| format!("查询目录索引失败：{}", e))?;
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })
+            .map_err(|e| format!("查询目录索引失败：{}", e))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| format!("读取目录索引失败：{}", e))
+    }
+
+    /// 用本次扫描结果整体替换项目的目录修改时间记录
+    pub fn replace_dir_mtimes(
+        &self,
+        project_id: i64,
+        dir_mtimes: &HashMap<String, u64>,
+    ) -> Result<(), String> {
+        self.with_transaction(|db| {
+            db.conn
+                .execute("DELETE FROM dir_index WHERE project_id = ?1", params![project_id])
+                .map_err(|e| format!("更新目录索引失败：{}", e))?;
+            let mut stmt = db
+                .conn
+                .prepare("INSERT INTO dir_index (project_id, dir_path, mtime) VALUES (?1, ?2, ?3)")
+                .map_err(|e| format!("更新目录索引失败：{}", e))?;
+            for (dir_path, mtime) in dir_mtimes {
+                stmt.execute(params![project_id, dir_path, *mtime as i64])
+                    .map_err(|e| format!("更新目录索引失败：{}", e))?;
+            }
+            Ok(())
+        })
+    }
+
+    // ========================================================================
+    // 文件索引查询方法
+    // ========================================================================
+
+    /// 项目向量数据的版本号
+    ///
+    /// 文件索引中该项目的向量、摘要或路径每次变化后由触发器递增，从未变化时为 0。
+    /// 内存中的向量检索索引以此判断缓存是否过期。
+    pub fn embedding_version(&self, project_id: i64) -> Result<i64, String> {
+        self.conn
+            .query_row(
+                "SELECT version FROM embedding_versions WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(0),
+                e => Err(format!("查询向量版本失败：{}", e)),
+            })
+    }
+
+    /// 查询项目中已生成向量的文件（语义搜索候选集）
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    /// - `path_prefix`: 可选路径前缀（如 `modules/auth`），仅返回该路径本身或其下的文件
+    ///   （`modules/auth` 不匹配 `modules/auth_v2/`）；为空或 None 时返回整个项目
+    ///
+    /// # 返回
+    /// - `Ok(Vec<FileEmbeddingRow>)`: 每项为 (file_path, summary, embedding)
+    pub fn list_file_embeddings(
+        &self,
+        project_id: i64,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<FileEmbeddingRow>, String> {
+        let prefix = path_prefix.unwrap_or("").replace('\\', "/");
+        let prefix = prefix.trim_end_matches('/');
+        // 路径本身（LIKE 不含通配符即不区分 ASCII 大小写的相等）或以 `{prefix}/` 开头
+        let (exact, nested) = if prefix.is_empty() {
+            ("%".to_string(), "%".to_string())
+        } else {
+            (like_escape(prefix), like_prefix_pattern(&format!("{}/", prefix)))
+        };
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_path, summary, embedding FROM file_index
+                 WHERE project_id = ?1 AND embedding IS NOT NULL
+                   AND (file_path LIKE ?2 ESCAPE '!' OR file_path LIKE ?3 ESCAPE '!')",
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        let rows = stmt
+            .query_map(params![project_id, exact, nested], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Vec<u8>>(2)?,
+                ))
+            })
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("读取文件索引失败：{}", e))
+    }
+
+    /// 查询指定项目的文件索引（只读，不触发扫描或写入）
+    ///
+    /// # 参数
+    /// - `project_id`: 项目 ID
+    ///
+    /// # 返回
+    /// - `Ok(Vec<FileIndexRow>)`: 按文件路径排序的索引记录
+    /// - `Err(String)`: 查询失败，返回中文错误描述
+    pub fn list_file_index(&self, project_id: i64) -> Result<Vec<FileIndexRow>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_path, file_hash, file_size, mtime, summary, embedding IS NOT NULL, last_analyzed_at,
+                        CASE WHEN embedding IS NOT NULL THEN COALESCE(embedding_mode, 'summary') END,
+                        in_degree, out_degree, hash_algo
+                 FROM file_index WHERE project_id = ?1 ORDER BY file_path",
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                let summary: Option<String> = row.get(4)?;
+                Ok(FileIndexRow {
+                    file_path: row.get(0)?,
+                    file_hash: row.get(1)?,
+                    hash_algo: row.get(10)?,
+                    file_size: row.get(2)?,
+                    mtime: row.get(3)?,
+                    has_summary: summary.as_deref().is_some_and(|s| !s.is_empty()),
+                    summary,
+                    has_embedding: row.get(5)?,
+                    embedding_mode: row.get(7)?,
+                    in_degree: row.get(8)?,
+                    out_degree: row.get(9)?,
+                    last_analyzed_at: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("读取文件索引失败：{}", e))
+    }
+
+    /// 读取路径以 `path_prefix` 开头的文件的扫描状态（相对项目根目录的路径 → 状态）
+    ///
+    /// `path_prefix` 为空时返回整个项目；子目录扫描传入 `backend/` 形式的前缀，只对比该子树。
+    pub fn load_file_scan_state(
+        &self,
+        project_id: i64,
+        path_prefix: &str,
+    ) -> Result<HashMap<String, IndexedFileState>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_path, file_hash, summary, file_size, mtime, hash_algo, binary_checked
+                 FROM file_index WHERE project_id = ?1 AND file_path LIKE ?2 ESCAPE '!'",
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        let rows = stmt
+            .query_map(
+                params![project_id, like_prefix_pattern(path_prefix)],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        (
+                            row.get::<_, String>(1)?,
+                            row.get::<_, Option<String>>(2)?,
+                            row.get::<_, u64>(3).unwrap_or(0),
+                            row.get::<_, u64>(4).unwrap_or(0),
+                            row.get::<_, String>(5)?,
+                            row.get::<_, bool>(6)?,
+                        ),
+                    ))
+                },
+            )
+            .map_err(|e| format!("查询文件索引失败：{}", e))?;
+        rows.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| format!("读取文件索引失败：{}", e))
+    }
+
+    /// 删除路径以 `path_prefix` 开头、但本次扫描未出现的文件索引记录
+    ///
+    /// 子目录扫描只清理该子树，子树外文件的摘要与向量保持不变。
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 删除的记录数
+    pub fn delete_stale_file_index(
+        &self,
+        project_id: i64,
+        path_prefix: &str,
+        current_paths: &HashSet<String>,
+    ) -> Result<usize, String> {
+        let stale: Vec<String> = self
+            .load_file_scan_state(project_id, path_prefix)?
+            .into_keys()
+            .filter(|path| !current_paths.contains(path))
+            .collect();
+        self.with_transaction(|db| {
+            for path in &stale {
+                db.conn
+                    .execute(
+                        "DELETE FROM file_index WHERE project_id = ?1 AND file_path = ?2",
+                        params![project_id, path],
+                    )
+                    .map_err(|e| format!("清理文件索引失败：{}", e))?;
+            }
+            Ok(stale.len())
+        })
+    }
+
+    /// 写入整个项目的依赖分析得到的文件入度/出度
+    ///
+    /// 先清空项目中所有文件已记录的度数，再写入本次结果：不在本次分析中的文件
+    /// （已删除或被忽略）不保留过期的度数。只更新文件索引中已存在的文件，尚未扫描入库的文件忽略。
+    ///
+    /// # 参数
+    /// - `degrees`: (相对路径, 入度, 出度)
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 实际更新的文件数
+    pub fn update_dependency_degrees(
+        &self,
+        project_id: i64,
+        degrees: &[(String, usize, usize)],
+    ) -> Result<usize, String> {
+        self.with_transaction(|db| {
+            db.conn
+                .execute(
+                    "UPDATE file_index SET in_degree = NULL, out_degree = NULL
+                     WHERE project_id = ?1",
+                    params![project_id],
+                )
+                .map_err(|e| format!("更新依赖度数失败：{}", e))?;
+            let mut stmt = db
+                .conn
+                .prepare(
+                    "UPDATE file_index SET in_degree = ?1, out_degree = ?2
+                     WHERE project_id = ?3 AND file_path = ?4",
+                )
+                .map_err(|e| format!("更新依赖度数失败：{}", e))?;
+            let mut updated = 0;
+            for (path, in_degree, out_degree) in degrees {
+                updated += stmt
+                    .execute(params![
+                        *in_degree as i64,
+                        *out_degree as i64,
+                        project_id,
+                        path
+                    ])
+                    .map_err(|e| format!("更新依赖度数失败：{}", e))?;
+            }
+            Ok(updated)
+        })
+    }
+
+    /// 将项目的文件索引导出为 JSON Lines 文件（每行一个文件）
+    ///
+    /// 供 jq/grep 等外部工具处理；向量原始字节不导出，仅导出其维度。
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 导出的文件条目数
+    pub fn export_file_index_jsonl(&self, project_id: i64, out_path: &Path) -> Result<usize, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_path, file_hash, file_size, mtime, summary, length(embedding), signatures
+                 FROM file_index WHERE project_id = ?1 ORDER BY file_path",
+            )
+            .map_err(|e| format!("导出文件索引失败：{}", e))?;
+
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                let summary: Option<String> = row.get(4)?;
+                let embedding_len: Option<i64> = row.get(5)?;
+                let signatures: Option<String> = row.get(6)?;
+                Ok(FileIndexExportRow {
+                    path: row.get(0)?,
+                    hash: row.get(1)?,
+                    size: row.get(2)?,
+                    mtime: row.get(3)?,
+                    has_summary: summary.as_deref().is_some_and(|s| !s.is_empty()),
+                    has_embedding: embedding_len.is_some(),
+                    // 向量按 f32 小端序存储，每维 4 字节
+                    embedding_dim: embedding_len.map(|len| len as usize / 4),
+                    signatures: signatures.map(|raw| {
+                        serde_json::from_str(&raw).unwrap_or(serde_json::Value::String(raw))
+                    }),
+                })
+            })
+            .map_err(|e| format!("导出文件索引失败：{}", e))?;
+
+        let mut out = String::new();
+        let mut count = 0usize;
+        for row in rows {
+            let row = row.map_err(|e| format!("读取文件索引失败：{}", e))?;
+            let line = serde_json::to_string(&row)
+                .map_err(|e| format!("导出文件索引失败：序列化出错: {}", e))?;
+            out.push_str(&line);
+            out.push('\n');
+            count += 1;
+        }
+
+        std::fs::write(out_path, out)
+            .map_err(|e| format!("导出文件索引失败：无法写入 {}: {}", out_path.display(), e))?;
+        Ok(count)
+    }
+
+    /// 将项目的构建历史导出为 CSV 文件（供表格软件打开）
+    ///
+    /// 列依次为：构建 ID、客户名称、版本号、模块数、输出路径、构建时间；
+    /// 客户名称通过关联查询得到，客户已删除时为空。按构建时间倒序排列。
+    /// 文件以 UTF-8 BOM 开头，Excel 直接打开时中文不乱码。
+    ///
+    /// # 返回
+    /// - `Ok(usize)`: 导出的构建记录数（不含表头）
+    pub fn export_build_history_csv(&self, project_id: i64, out_path: &Path) -> Result<usize, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT b.id, COALESCE(c.name, ''), b.version, b.selected_modules, b.output_path, b.created_at
+                 FROM build_records b LEFT JOIN clients c ON c.id = b.client_id
+                 WHERE b.project_id = ?1 ORDER BY b.created_at DESC, b.id DESC",
+            )
+            .map_err(|e| format!("导出构建历史失败：{}", e))?;
+
+        let rows = stmt
+            .query_map(params![project_id], |row| {
+                let selected_modules: String = row.get(3)?;
+                let module_count = serde_json::from_str::<Vec<String>>(&selected_modules)
+                    .map(|modules| modules.len())
+                    .unwrap_or(0);
+                Ok([
+                    row.get::<_, i64>(0)?.to_string(),
+                    row.get(1)?,
+                    row.get(2)?,
+                    module_count.to_string(),
+                    row.get(4)?,
+                    row.get(5)?,
+                ])
+            })
+            .map_err(|e| format!("导出构建历史失败：{}", e))?;
+
+        let mut out = String::from(
+            "\u{FEFF}build_id,client_name,version,module_count,output_path,created_at\r\n",
+        );
+        let mut count = 0usize;
+        for row in rows {
+            let fields = row.map_err(|e| format!("读取构建记录失败：{}", e))?;
+            let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            out.push_str(&line.join(","));
+            out.push_str("\r\n");
+            count += 1;
+        }
+
+        std::fs::write(out_path, out)
+            .map_err(|e| format!("导出构建历史失败：无法写入 {}: {}", out_path.display(), e))?;
+        Ok(count)
+    }
+
+    // ========================================================================
+    // 项目分析报告方法
+    // ========================================================================
+
+    /// 保存项目分析报告
+    ///
+    /// # 参数
+    /// - `supersedes`: 被取代的旧版本报告 ID（首次保存传 None）
+    pub fn save_report(
+        &self,
+        project_id: i64,
+        mode: &str,
+        sub_path: Option<&str>,
+        content: &str,
+        supersedes: Option<i64>,
+    ) -> Result<ProjectReport, String> {
+        self.conn
+            .execute(
+                "INSERT INTO project_reports (project_id, mode, sub_path, content, supersedes) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![project_id, mode, sub_path, content, supersedes],
+            )
+            .map_err(|e| format!("保存分析报告失败：{}", e))?;
+        self.get_report(self.conn.last_insert_rowid())
+    }
+
+    /// 保存报告的新版本：沿用旧报告的项目、模式与子目录，并以 supersedes 链接旧报告
+    ///
+    /// 旧报告保持不变，可通过 `supersedes` 回溯完整历史。
+    pub fn supersede_report(&self, report_id: i64, content: &str) -> Result<ProjectReport, String> {
+        let previous = self.get_report(report_id)?;
+        self.save_report(
+            previous.project_id,
+            &previous.mode,
+            previous.sub_path.as_deref(),
+            content,
+            Some(previous.id),
+        )
     }
 
-    /// 删除自定义模板（内置模板不可删除）
-    pub fn delete_template(&self, id: i64) -> Result<(), String> {
-        // 检查是否为内置模板
-        let is_builtin: bool = self
-            .conn
+    /// 根据 ID 查询分析报告
+    pub fn get_report(&self, id: i64) -> Result<ProjectReport, String> {
+        self.conn
             .query_row(
-                "SELECT is_builtin FROM tech_stack_templates WHERE id = ?1",
+                "SELECT id, project_id, mode, sub_path, content, created_at, supersedes FROM project_reports WHERE id = ?1",
                 params![id],
-                |row| row.get::<_, i32>(0).map(|v| v != 0),
+                |row| {
+                    Ok(ProjectReport {
+                        id: row.get(0)?,
+                        project_id: row.get(1)?,
+                        mode: row.get(2)?,
+                        sub_path: row.get(3)?,
+                        content: row.get(4)?,
+                        created_at: row.get(5)?,
+                        supersedes: row.get(6)?,
+                    })
+                },
             )
             .map_err(|e| {
                 if matches!(e, rusqlite::Error::QueryReturnedNoRows) {
-                    format!("模板不存在：ID {}", id)
+                    format!("查询分析报告失败：ID {} 不存在", id)
                 } else {
-                    format!("查询模板失败：{}", e)
+                    format!("查询分析报告失败：{}", e)
                 }
-            })?;
+            })
+    }
 
-        if is_builtin {
-            return Err("内置模板不可删除".to_string());
+    /// 查询项目的全部分析报告（含历史版本，按 id 倒序）
+    pub fn list_reports(&self, project_id: i64) -> Result<Vec<ProjectReport>, String> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, project_id, mode, sub_path, content, created_at, supersedes FROM project_reports
+                 WHERE project_id = ?1 ORDER BY id DESC",
+            )
+            .map_err(|e| format!("查询分析报告失败：{}", e))?;
+        let reports = stmt
+            .query_map(params![project_id], |row| {
+                Ok(ProjectReport {
+                    id: row.get(0)?,
+                    project_id: row.get(1)?,
+                    mode: row.get(2)?,
+                    sub_path: row.get(3)?,
+                    content: row.get(4)?,
+                    created_at: row.get(5)?,
+                    supersedes: row.get(6)?,
+                })
+            })
+            .map_err(|e| format!("查询分析报告失败：{}", e))?;
+        reports
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("读取分析报告失败：{}", e))
+    }
+}
+
+// ============================================================================
+// 只读连接池
+// ============================================================================
+
+/// 默认只读连接数
+pub const READ_POOL_SIZE: usize = 4;
+
+/// 只读连接池：多个只读连接指向同一数据库文件
+///
+/// 写操作仍通过 `Mutex<Database>` 单连接串行执行；纯查询命令走 `ReadPool::read`，
+/// 在 WAL 模式下读取最近一次已提交的数据，不会被进行中的写事务阻塞。
+pub struct ReadPool {
+    conns: Vec<Mutex<Database>>,
+    next: AtomicUsize,
+}
+
+impl ReadPool {
+    /// 为 `db_path` 打开 `size` 个只读连接（至少 1 个）
+    ///
+    /// 数据库须已由 `Database::init` 初始化（建表并启用 WAL）。
+    pub fn open(db_path: &Path, size: usize) -> Result<Self, String> {
+        let conns = (0..size.max(1))
+            .map(|_| Database::open_read_only(db_path).map(Mutex::new))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(ReadPool {
+            conns,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// 借用一个只读连接执行查询：优先取空闲连接，全部繁忙时等待轮询到的那个
+    pub fn read<T>(&self, f: impl FnOnce(&Database) -> Result<T, String>) -> Result<T, String> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let n = self.conns.len();
+        for i in 0..n {
+            if let Ok(db) = self.conns[(start + i) % n].try_lock() {
+                return f(&db);
+            }
         }
+        let db = self.conns[start % n]
+            .lock()
+            .map_err(|_| "数据库访问失败：无法获取只读连接".to_string())?;
+        f(&db)
+    }
+}
 
-        self.conn
-            .execute(
-                "DELETE FROM tech_stack_templates WHERE id = ?1",
-                params![id],
-            )
-            .map_err(|e| format!("删除模板失败：{}", e))?;
+/// 保留 `selected_modules`（JSON 数组）中包含 `module_name` 的构建记录（JSON1 不可用时的回退实现）
+fn filter_records_with_module(records: Vec<BuildRecord>, module_name: &str) -> Vec<BuildRecord> {
+    records
+        .into_iter()
+        .filter(|record| {
+            serde_json::from_str::<Vec<String>>(&record.selected_modules)
+                .map(|modules| modules.iter().any(|m| m == module_name))
+                .unwrap_or(false)
+        })
+        .collect()
+}
 
-        Ok(())
+/// 移动文件：优先 rename，跨设备等 rename 失败的情况退回复制后删除源文件
+fn move_file(source: &Path, target: &Path) -> std::io::Result<()> {
+    if std::fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(source, target)?;
+    if let Err(e) = std::fs::remove_file(source) {
+        // 源文件删不掉时撤销复制，避免产物出现两份
+        let _ = std::fs::remove_file(target);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 两个路径是否指向同一个已存在的文件
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// 生成按字面前缀匹配的 LIKE 模式（以 `!` 转义通配符，模块名中常见的 `_` 需按字面匹配）
+fn like_prefix_pattern(prefix: &str) -> String {
+    format!("{}%", like_escape(prefix))
+}
+
+/// 以 `!` 转义 LIKE 通配符，使文本按字面匹配
+fn like_escape(text: &str) -> String {
+    text.replace('!', "!!").replace('%', "!%").replace('_', "!_")
+}
+
+/// 按 RFC 4180 转义 CSV 字段：含逗号、引号或换行时整体加引号，内部引号写两次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
 }
 
@@ -1625,7 +3689,7 @@ mod tests {
             .filter_map(|r| r.ok())
             .collect();
 
-        assert_eq!(table_names.len(), 9);
+        assert_eq!(table_names.len(), 12);
         assert!(table_names.contains(&"categories".to_string()));
         assert!(table_names.contains(&"projects".to_string()));
         assert!(table_names.contains(&"clients".to_string()));
@@ -1635,6 +3699,9 @@ mod tests {
         assert!(table_names.contains(&"client_module_configs".to_string()));
         assert!(table_names.contains(&"file_index".to_string()));
         assert!(table_names.contains(&"tech_stack_templates".to_string()));
+        assert!(table_names.contains(&"dir_index".to_string()));
+        assert!(table_names.contains(&"project_reports".to_string()));
+        assert!(table_names.contains(&"embedding_versions".to_string()));
     }
 
     /// 测试数据库初始化：外键约束已启用
@@ -1670,7 +3737,7 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 9);
+        assert_eq!(count, 12);
     }
 
     /// 测试数据库初始化：自动创建不存在的目录
@@ -1693,7 +3760,7 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(count, 9);
+        assert_eq!(count, 12);
     }
 
     /// 测试 categories 表结构：验证列定义
@@ -2043,6 +4110,53 @@ mod tests {
         assert_eq!(cats.len(), 1);
     }
 
+    /// 测试 reassign_and_delete_category：项目迁移到目标分类后删除原分类
+    #[test]
+    fn test_reassign_and_delete_category_success() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+
+        let source = db.create_category("旧分类", None).unwrap();
+        let target = db.create_category("新分类", None).unwrap();
+        let repo = dir.path().to_str().unwrap();
+        for name in ["项目A", "项目B"] {
+            db.create_project(name, source.id, repo, "fastapi", "modules")
+                .unwrap();
+        }
+
+        let moved = db.reassign_and_delete_category(source.id, target.id).unwrap();
+        assert_eq!(moved, 2);
+
+        let cats = db.list_categories().unwrap();
+        assert_eq!(cats.len(), 1);
+        assert_eq!(cats[0].id, target.id);
+        let projects = db.list_projects().unwrap();
+        assert!(projects.iter().all(|p| p.category_id == target.id));
+    }
+
+    /// 测试 reassign_and_delete_category：目标分类不存在或与原分类相同时拒绝
+    #[test]
+    fn test_reassign_and_delete_category_rejects_invalid_target() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+
+        let source = db.create_category("旧分类", None).unwrap();
+        let project = db
+            .create_project("项目A", source.id, dir.path().to_str().unwrap(), "fastapi", "modules")
+            .unwrap();
+
+        let err = db.reassign_and_delete_category(source.id, 999).unwrap_err();
+        assert!(err.contains("不存在"));
+        let err = db.reassign_and_delete_category(source.id, source.id).unwrap_err();
+        assert!(err.contains("相同"));
+
+        // 原分类与项目均保持不变
+        assert_eq!(db.list_categories().unwrap().len(), 1);
+        let projects = db.list_projects().unwrap();
+        assert_eq!(projects[0].id, project.id);
+        assert_eq!(projects[0].category_id, source.id);
+    }
+
     /// 测试 delete_category：不存在的 ID
     #[test]
     fn test_delete_category_not_found() {
@@ -2124,6 +4238,412 @@ mod tests {
         (db, dir, project.id, client.id)
     }
 
+    /// 测试 export_build_history_csv：客户名称含逗号/引号时正确加引号，可按原值解析回来
+    #[test]
+    fn test_export_build_history_csv_quotes_fields() {
+        let (db, dir, project_id, client_id) = setup_project_and_client();
+        let quoted = db.create_client("Acme, \"Inc\"", &[project_id]).unwrap();
+        db.create_build_record(project_id, client_id, r#"["auth","billing"]"#, "/out/a.zip", "v1.0.0", None)
+            .unwrap();
+        db.create_build_record(project_id, quoted.id, r#"["auth"]"#, "/out/b.zip", "v1.0.1", None)
+            .unwrap();
+
+        let out = dir.path().join("history.csv");
+        assert_eq!(db.export_build_history_csv(project_id, &out).unwrap(), 2);
+        let text = std::fs::read_to_string(&out).unwrap();
+        let text = text.strip_prefix('\u{FEFF}').expect("CSV 应以 UTF-8 BOM 开头");
+        assert!(text.contains("\"Acme, \"\"Inc\"\"\""));
+
+        // 最小 CSV 解析：引号内的逗号不分列，连续两个引号还原为一个
+        let parse = |line: &str| -> Vec<String> {
+            let mut fields = vec![String::new()];
+            let mut in_quotes = false;
+            let mut chars = line.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if in_quotes && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        fields.last_mut().unwrap().push('"');
+                    }
+                    '"' => in_quotes = !in_quotes,
+                    ',' if !in_quotes => fields.push(String::new()),
+                    _ => fields.last_mut().unwrap().push(c),
+                }
+            }
+            fields
+        };
+        let rows: Vec<Vec<String>> = text.lines().map(parse).collect();
+        assert_eq!(rows[0], ["build_id", "client_name", "version", "module_count", "output_path", "created_at"]);
+        let mut data = rows[1..].to_vec();
+        data.sort_by(|a, b| a[2].cmp(&b[2]));
+        assert_eq!(data.len(), 2);
+        assert_eq!(&data[0][1..5], ["测试客户", "v1.0.0", "2", "/out/a.zip"]);
+        assert_eq!(&data[1][1..5], ["Acme, \"Inc\"", "v1.0.1", "1", "/out/b.zip"]);
+    }
+
+    /// 测试 supersede_report：刷新后生成引用旧版本的新报告，旧报告仍可查询
+    #[test]
+    fn test_supersede_report_keeps_history() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        let original = db
+            .save_report(project_id, "deep", Some("backend"), "# 旧报告", None)
+            .unwrap();
+        assert_eq!(original.supersedes, None);
+
+        let refreshed = db.supersede_report(original.id, "# 新报告").unwrap();
+        assert_ne!(refreshed.id, original.id);
+        assert_eq!(refreshed.supersedes, Some(original.id));
+        assert_eq!(refreshed.project_id, project_id);
+        assert_eq!(refreshed.mode, "deep");
+        assert_eq!(refreshed.sub_path.as_deref(), Some("backend"));
+        assert_eq!(refreshed.content, "# 新报告");
+
+        let old = db.get_report(original.id).unwrap();
+        assert_eq!(old.content, "# 旧报告");
+        assert_eq!(old.supersedes, None);
+
+        let history = db.list_reports(project_id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].id, history[1].id), (refreshed.id, original.id));
+        let err = db.supersede_report(refreshed.id + 100, "x").unwrap_err();
+        assert!(err.contains("不存在"));
+    }
+
+    /// 测试 delete_build_record：按需删除输出文件，文件已不存在时正常删除记录
+    #[test]
+    fn test_delete_build_record_with_artifact() {
+        let (db, dir, project_id, client_id) = setup_project_and_client();
+        let zip = dir.path().join("dist_a.zip");
+        std::fs::write(&zip, "zip").unwrap();
+        let zip_str = zip.to_str().unwrap();
+
+        let kept = db.create_build_record(project_id, client_id, "[]", zip_str, "v1.0.0", None).unwrap();
+        let result = db.delete_build_record(kept.id, false).unwrap();
+        assert_eq!(result, ArtifactDeletion { file_found: false, file_deleted: false });
+        assert!(zip.exists());
+
+        let removed = db.create_build_record(project_id, client_id, "[]", zip_str, "v1.0.1", None).unwrap();
+        let result = db.delete_build_record(removed.id, true).unwrap();
+        assert_eq!(result, ArtifactDeletion { file_found: true, file_deleted: true });
+        assert!(!zip.exists());
+        assert!(db.list_build_records_by_project(project_id).unwrap().is_empty());
+
+        // 文件已被手动删除：记录照常删除，结果标明未找到文件
+        let stale = db.create_build_record(project_id, client_id, "[]", zip_str, "v1.0.2", None).unwrap();
+        let result = db.delete_build_record(stale.id, true).unwrap();
+        assert_eq!(result, ArtifactDeletion { file_found: false, file_deleted: false });
+        assert!(db.delete_build_record(stale.id, true).unwrap_err().contains("构建记录不存在"));
+    }
+
+    /// 测试 relocate_build_artifacts：产物与校验文件移入新目录，记录指向新路径，缺失文件单独计数
+    #[test]
+    fn test_relocate_build_artifacts_moves_files_and_updates_records() {
+        let (db, dir, project_id, client_id) = setup_project_and_client();
+        let old_dir = dir.path().join("old");
+        std::fs::create_dir(&old_dir).unwrap();
+        let zip_a = old_dir.join("dist_a.zip");
+        let zip_b = old_dir.join("dist_b.zip");
+        std::fs::write(&zip_a, "a").unwrap();
+        std::fs::write(&zip_b, "b").unwrap();
+        std::fs::write(old_dir.join("dist_a.zip.sha256"), "hash").unwrap();
+        let gone = old_dir.join("dist_gone.zip");
+
+        for (path, version) in [(&zip_a, "v1.0.0"), (&zip_b, "v1.0.1"), (&gone, "v1.0.2")] {
+            db.create_build_record(project_id, client_id, "[]", path.to_str().unwrap(), version, None)
+                .unwrap();
+        }
+
+        let new_dir = dir.path().join("archive");
+        let report = db.relocate_build_artifacts(project_id, &new_dir).unwrap();
+        assert_eq!(report, RelocateReport { moved: 2, missing: 1 });
+
+        let records = db.list_build_records_by_project(project_id).unwrap();
+        let path_of = |version: &str| {
+            records.iter().find(|r| r.version == version).unwrap().output_path.clone()
+        };
+        assert_eq!(path_of("v1.0.0"), new_dir.join("dist_a.zip").to_string_lossy());
+        assert_eq!(path_of("v1.0.1"), new_dir.join("dist_b.zip").to_string_lossy());
+        assert_eq!(path_of("v1.0.2"), gone.to_string_lossy());
+        assert_eq!(std::fs::read_to_string(new_dir.join("dist_a.zip")).unwrap(), "a");
+        assert!(new_dir.join("dist_a.zip.sha256").is_file());
+        assert!(!zip_a.exists() && !zip_b.exists());
+    }
+
+    /// 测试 relocate_build_artifacts：目标目录有同名文件时回滚，已移动的文件回到原处
+    #[test]
+    fn test_relocate_build_artifacts_rolls_back_on_conflict() {
+        let (db, dir, project_id, client_id) = setup_project_and_client();
+        let zip_a = dir.path().join("dist_a.zip");
+        let zip_b = dir.path().join("dist_b.zip");
+        std::fs::write(&zip_a, "a").unwrap();
+        std::fs::write(&zip_b, "b").unwrap();
+        db.create_build_record(project_id, client_id, "[]", zip_a.to_str().unwrap(), "v1", None)
+            .unwrap();
+        db.create_build_record(project_id, client_id, "[]", zip_b.to_str().unwrap(), "v2", None)
+            .unwrap();
+
+        // 较新的 v2 先处理，v1 与目标目录中的文件重名
+        let new_dir = dir.path().join("archive");
+        std::fs::create_dir(&new_dir).unwrap();
+        std::fs::write(new_dir.join("dist_a.zip"), "other").unwrap();
+
+        let err = db.relocate_build_artifacts(project_id, &new_dir).unwrap_err();
+        assert!(err.contains("同名文件"));
+        assert!(zip_a.is_file() && zip_b.is_file());
+        assert!(!new_dir.join("dist_b.zip").exists());
+        let records = db.list_build_records_by_project(project_id).unwrap();
+        assert!(records.iter().all(|r| Path::new(&r.output_path).parent() == Some(dir.path())));
+    }
+
+    /// 测试 create_client：关联写入中途失败时回滚，客户记录也不保留
+    #[test]
+    fn test_create_client_rolls_back_on_association_failure() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        let before = db.list_all_clients_with_counts().unwrap().len();
+
+        // 第二个项目 ID 不存在，外键约束使关联插入失败
+        let err = db.create_client("半成品客户", &[project_id, 999_999]).unwrap_err();
+        assert!(err.contains("创建客户关联失败"));
+
+        let clients = db.list_all_clients_with_counts().unwrap();
+        assert_eq!(clients.len(), before);
+        assert!(clients.iter().all(|(c, _)| c.name != "半成品客户"));
+        let associations: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM project_clients", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(associations, 1);
+
+        // 回滚后连接回到自动提交状态，后续写入不受影响
+        assert!(db.conn().is_autocommit());
+        assert!(db.create_client("正常客户", &[project_id]).is_ok());
+    }
+
+    /// 测试 repair_orphaned_associations：清理外键关闭期间写入的悬空关联
+    #[test]
+    fn test_repair_orphaned_associations() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+
+        // 模拟外键约束启用前的历史数据
+        db.conn().execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+        db.conn()
+            .execute(
+                "INSERT INTO project_clients (project_id, client_id) VALUES (?1, ?2), (?3, ?4), (?5, ?6)",
+                params![9999, client_id, project_id, 8888, 9999, 8888],
+            )
+            .unwrap();
+        db.conn().execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+
+        let report = db.repair_orphaned_associations().unwrap();
+        assert_eq!(
+            report,
+            RepairReport {
+                missing_project: 2,
+                missing_client: 1,
+            }
+        );
+
+        // 有效关联保留，再次清理无事可做
+        let remaining: Vec<(i64, i64)> = db
+            .conn()
+            .prepare("SELECT project_id, client_id FROM project_clients")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, vec![(project_id, client_id)]);
+        assert_eq!(
+            db.repair_orphaned_associations().unwrap(),
+            RepairReport {
+                missing_project: 0,
+                missing_client: 0,
+            }
+        );
+    }
+
+    /// 测试 clone_project：沿用分类与技术栈，不复制构建记录和客户
+    #[test]
+    fn test_clone_project_copies_config_only() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        db.conn()
+            .execute(
+                "UPDATE projects SET tech_stack_type = 'vue3', modules_dir = 'src/views' WHERE id = ?1",
+                params![project_id],
+            )
+            .unwrap();
+        db.create_build_record(project_id, client_id, r#"["home"]"#, "/out/a.zip", "v1.0.0", None)
+            .unwrap();
+        db.save_client_module_config(client_id, project_id, r#"["home"]"#).unwrap();
+
+        let new_repo = TempDir::new().unwrap();
+        let new_path = new_repo.path().to_str().unwrap();
+        let source = db.get_project(project_id).unwrap();
+        let clone = db.clone_project(project_id, "复制项目", new_path).unwrap();
+
+        assert_ne!(clone.id, project_id);
+        assert_eq!(clone.name, "复制项目");
+        assert_eq!(clone.repo_path, new_path);
+        assert_eq!(clone.category_id, source.category_id);
+        assert_eq!(clone.tech_stack_type, "vue3");
+        assert_eq!(clone.modules_dir, "src/views");
+        assert!(db.list_build_records_by_project(clone.id).unwrap().is_empty());
+        assert!(db.list_clients_by_project(clone.id).unwrap().is_empty());
+        assert!(db.load_client_module_config(client_id, clone.id).unwrap().is_none());
+
+        // 可选复制模块预设
+        let with_presets = db
+            .clone_project_with_presets(project_id, "带预设", new_path, true)
+            .unwrap();
+        assert_eq!(
+            db.load_client_module_config(client_id, with_presets.id).unwrap().as_deref(),
+            Some(r#"["home"]"#)
+        );
+
+        // 新路径必须存在
+        assert!(db.clone_project(project_id, "无效路径", "/nonexistent/path/xyz").is_err());
+        assert!(db.clone_project(9999, "源不存在", new_path).is_err());
+    }
+
+    /// 测试项目包：导出后导入全新数据库，项目配置、客户关联、模块预设与摘要均被重建
+    #[test]
+    fn test_project_bundle_round_trip_into_fresh_database() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        db.save_client_module_config(client_id, project_id, r#"["auth","billing"]"#).unwrap();
+        // 未关联但有预设的客户同样导出
+        let preset_only = db.create_client("仅预设客户", &[]).unwrap();
+        db.save_client_module_config(preset_only.id, project_id, r#"["auth"]"#).unwrap();
+        db.conn()
+            .execute(
+                "INSERT INTO file_index (project_id, file_path, file_hash, summary)
+                 VALUES (?1, 'main.py', 'h1', '入口文件'), (?1, 'empty.py', 'h2', NULL)",
+                params![project_id],
+            )
+            .unwrap();
+        let json = db.export_project_bundle(project_id, true).unwrap();
+
+        let fresh_dir = TempDir::new().unwrap();
+        let fresh = Database::init(fresh_dir.path()).unwrap();
+        let result = fresh.import_project_bundle(&json).unwrap();
+
+        let source = db.get_project(project_id).unwrap();
+        let project = result.project;
+        assert_eq!(project.name, source.name);
+        assert_eq!(project.repo_path, source.repo_path);
+        assert_eq!(project.tech_stack_type, source.tech_stack_type);
+        assert_eq!(project.modules_dir, source.modules_dir);
+        assert_eq!(fresh.list_categories().unwrap()[0].name, "测试分类");
+        assert_eq!(result.clients_created, 2);
+        assert_eq!(result.summaries_imported, 1);
+        // 测试仓库目录已被清理，导入仍成功但给出提示
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("仓库路径在本机不存在"));
+
+        let clients = fresh.list_clients_by_project(project.id).unwrap();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].name, "测试客户");
+        assert_eq!(
+            fresh.load_client_module_config(clients[0].id, project.id).unwrap().as_deref(),
+            Some(r#"["auth","billing"]"#)
+        );
+        let (preset_client, _) = fresh
+            .list_all_clients_with_counts()
+            .unwrap()
+            .into_iter()
+            .find(|(c, _)| c.name == "仅预设客户")
+            .unwrap();
+        assert_eq!(
+            fresh.load_client_module_config(preset_client.id, project.id).unwrap().as_deref(),
+            Some(r#"["auth"]"#)
+        );
+        let files = fresh.list_file_index(project.id).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].summary.as_deref(), Some("入口文件"));
+
+        // 再次导入同一数据库：同名客户复用，不重复创建
+        let again = db.import_project_bundle(&json).unwrap();
+        assert_eq!(again.clients_created, 0);
+        assert!(db.import_project_bundle("{}").unwrap_err().contains("项目包格式无效"));
+    }
+
+    /// 测试 project_delete_impact：统计级联删除数量且不执行删除
+    #[test]
+    fn test_project_delete_impact_counts_without_deleting() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        let other_client = db.create_client("另一个客户", &[project_id]).unwrap();
+        for version in ["v1.0.0", "v1.0.1", "v1.0.2"] {
+            db.create_build_record(project_id, client_id, "[]", "/out.zip", version, None)
+                .unwrap();
+        }
+        db.save_client_module_config(other_client.id, project_id, "[\"auth\"]")
+            .unwrap();
+        db.conn()
+            .execute(
+                "INSERT INTO file_index (project_id, file_path, file_hash) VALUES (?1, 'a.py', 'h')",
+                params![project_id],
+            )
+            .unwrap();
+
+        let impact = db.project_delete_impact(project_id).unwrap();
+        assert_eq!(
+            impact,
+            DeleteImpact {
+                build_records: 3,
+                client_associations: 2,
+                client_module_configs: 1,
+                file_index_entries: 1,
+            }
+        );
+
+        // 只读查询：再次查询结果不变，项目仍存在
+        assert_eq!(db.project_delete_impact(project_id).unwrap(), impact);
+        assert!(db.get_project(project_id).is_ok());
+        assert!(db.project_delete_impact(project_id + 100).is_err());
+    }
+
+    /// 测试 get_rebuild_params：使用记录中的模块目录，不受项目当前配置影响
+    #[test]
+    fn test_rebuild_params_use_recorded_modules_dir() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        let project = db.get_project(project_id).unwrap();
+        let recorded = db
+            .create_build_record_with_config(
+                project_id,
+                client_id,
+                r#"["auth"]"#,
+                "/out/v1.zip",
+                "v1.0.0",
+                None,
+                "fastapi",
+                "backend/modules",
+                None,
+            )
+            .unwrap();
+        assert_eq!(recorded.modules_dir, "backend/modules");
+        let legacy = db
+            .create_build_record(project_id, client_id, r#"["auth"]"#, "/out/v0.zip", "v0.9.0", None)
+            .unwrap();
+
+        // 构建后项目改用其他模块目录
+        db.conn()
+            .execute(
+                "UPDATE projects SET modules_dir = 'apps' WHERE id = ?1",
+                params![project_id],
+            )
+            .unwrap();
+
+        let params = db.get_rebuild_params(recorded.id).unwrap();
+        assert_eq!(params.modules_dir, "backend/modules");
+        assert_eq!(params.tech_stack, "fastapi");
+        assert_eq!(params.selected_modules, vec!["auth".to_string()]);
+        assert_eq!(params.client_name, "测试客户");
+        assert_eq!(params.project_path, project.repo_path);
+
+        // 旧记录未保存构建配置 → 回退到项目当前配置
+        assert_eq!(db.get_rebuild_params(legacy.id).unwrap().modules_dir, "apps");
+        assert!(db.get_rebuild_params(recorded.id + 100).is_err());
+    }
+
     /// 测试 create_build_record：正常创建构建记录
     #[test]
     fn test_create_build_record_success() {
@@ -2145,6 +4665,18 @@ mod tests {
         assert!(!record.created_at.is_empty());
     }
 
+    /// 测试 create_build_record：绝对输出路径按原生格式存储，不做分隔符转换
+    #[test]
+    fn test_create_build_record_keeps_native_output_path() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+
+        let record = db
+            .create_build_record(project_id, client_id, "[]", r"C:\out\dist_客户A.zip", "v1.0.0", None)
+            .unwrap();
+
+        assert_eq!(record.output_path, r"C:\out\dist_客户A.zip");
+    }
+
     /// 测试 create_build_record：selected_modules 以 JSON 字符串存储
     #[test]
     fn test_create_build_record_json_modules() {
@@ -2186,8 +4718,84 @@ mod tests {
     fn test_list_build_records_by_project_empty() {
         let (db, _dir, project_id, _client_id) = setup_project_and_client();
 
-        let records = db.list_build_records_by_project(project_id).unwrap();
-        assert!(records.is_empty());
+        let records = db.list_build_records_by_project(project_id).unwrap();
+        assert!(records.is_empty());
+    }
+
+    /// 测试 create_build_record_with_config：相同幂等键重复创建只插入一行并返回同一记录
+    #[test]
+    fn test_create_build_record_idempotency_key() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        let key = Some("6f1c2d3e-0000-4000-8000-000000000001");
+        let create = |key: Option<&str>| {
+            db.create_build_record_with_config(
+                project_id,
+                client_id,
+                r#"["auth"]"#,
+                "/out/a.zip",
+                "v1.0.0",
+                None,
+                "fastapi",
+                "",
+                key,
+            )
+            .unwrap()
+        };
+
+        let first = create(key);
+        let retried = create(key);
+        assert_eq!(retried.id, first.id);
+        assert_eq!(db.list_build_records_by_project(project_id).unwrap().len(), 1);
+
+        // 不同键或未提供键时照常新建
+        let other = create(Some("6f1c2d3e-0000-4000-8000-000000000002"));
+        assert_ne!(other.id, first.id);
+        create(None);
+        create(None);
+        assert_eq!(db.list_build_records_by_project(project_id).unwrap().len(), 4);
+    }
+
+    /// 测试 find_builds_with_module：仅返回包含该模块的记录，SQL 与回退实现结果一致
+    #[test]
+    fn test_find_builds_with_module() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        // 模块名作为子串出现（payments_v2）、JSON 非法的记录均不应命中
+        let modules = [
+            r#"["auth","payments"]"#,
+            r#"["auth"]"#,
+            r#"["payments"]"#,
+            r#"["payments_v2"]"#,
+            "payments",
+        ];
+        let records: Vec<BuildRecord> = modules
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let version = format!("v1.0.{}", i);
+                db.create_build_record(project_id, client_id, m, "/tmp/out.zip", &version, None)
+                    .unwrap()
+            })
+            .collect();
+        let (both, only) = (&records[0], &records[2]);
+
+        let ids = |records: Vec<BuildRecord>| {
+            let mut ids: Vec<i64> = records.iter().map(|r| r.id).collect();
+            ids.sort();
+            ids
+        };
+        assert!(db.supports_json1());
+        assert_eq!(
+            ids(db.find_builds_with_module(project_id, "payments").unwrap()),
+            vec![both.id, only.id]
+        );
+        assert!(db.find_builds_with_module(project_id, "billing").unwrap().is_empty());
+        assert!(db.find_builds_with_module(project_id + 1, "payments").unwrap().is_empty());
+
+        let all = db.list_build_records_by_project(project_id).unwrap();
+        assert_eq!(
+            ids(filter_records_with_module(all, "payments")),
+            vec![both.id, only.id]
+        );
     }
 
     /// 测试 list_build_records_by_project：不同项目的记录互不干扰
@@ -2316,6 +4924,100 @@ mod tests {
         assert_eq!(theme, "dark");
     }
 
+    /// 测试 checkpoint：WAL 模式下合并并清空 WAL 文件，非 WAL 模式下为无操作
+    #[test]
+    fn test_checkpoint_flushes_wal() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+        db.checkpoint().unwrap();
+
+        db.conn().execute_batch("PRAGMA journal_mode = WAL;").unwrap();
+        db.save_setting("llm_embedding_model", "nomic-embed-text").unwrap();
+        db.checkpoint().unwrap();
+
+        let wal = dir.path().join("prism_console.db-wal");
+        assert_eq!(std::fs::metadata(wal).unwrap().len(), 0);
+    }
+
+    /// 测试 health：行数与插入一致，外键已启用，完整性检查通过
+    #[test]
+    fn test_health_reports_counts_and_pragmas() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        db.create_build_record(project_id, client_id, "[\"auth\"]", "/tmp/a.zip", "v1.0.0", None)
+            .unwrap();
+        db.create_build_record(project_id, client_id, "[\"auth\"]", "/tmp/b.zip", "v1.0.1", None)
+            .unwrap();
+
+        let health = db.health().unwrap();
+        assert_eq!(health.table_counts.get("categories"), Some(&1));
+        assert_eq!(health.table_counts.get("projects"), Some(&1));
+        assert_eq!(health.table_counts.get("clients"), Some(&1));
+        assert_eq!(health.table_counts.get("project_clients"), Some(&1));
+        assert_eq!(health.table_counts.get("build_records"), Some(&2));
+        assert!(health.foreign_keys);
+        assert!(health.integrity_ok);
+        assert!(health.integrity_errors.is_empty());
+        assert_eq!(health.journal_mode, "wal");
+        assert!(health.file_size_bytes > 0);
+        assert!(!health.sqlite_version.is_empty());
+    }
+
+    /// 测试 ReadPool：写连接持有未提交的写事务时，只读连接仍可并发读取已提交数据
+    #[test]
+    fn test_read_pool_not_blocked_by_long_write() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+        db.create_category("已提交", None).unwrap();
+        let pool = std::sync::Arc::new(ReadPool::open(&db.path().unwrap(), 2).unwrap());
+
+        db.conn().execute_batch("BEGIN IMMEDIATE;").unwrap();
+        db.create_category("未提交", None).unwrap();
+
+        let reader = std::sync::Arc::clone(&pool);
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            tx.send(reader.read(|r| r.list_categories())).unwrap();
+        });
+        let names: Vec<String> = rx
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("读取被写事务阻塞")
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["已提交".to_string()]);
+
+        db.conn().execute_batch("COMMIT;").unwrap();
+        let count = pool.read(|r| r.list_categories()).unwrap().len();
+        assert_eq!(count, 2);
+    }
+
+    /// 测试 ReadPool：只读连接拒绝写入
+    #[test]
+    fn test_read_pool_rejects_writes() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+        let pool = ReadPool::open(&db.path().unwrap(), 1).unwrap();
+        assert!(pool.read(|r| r.create_category("x", None)).is_err());
+    }
+
+    /// 测试 save_setting_durable：持久化保存后重新打开数据库仍可读取
+    #[test]
+    fn test_save_setting_durable_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        {
+            let db = Database::init(dir.path()).unwrap();
+            db.save_setting_durable("llm_api_key", "sk-test-123").unwrap();
+        }
+
+        // 重新打开同一数据库文件
+        let db = Database::init(dir.path()).unwrap();
+        assert_eq!(
+            db.get_setting("llm_api_key").unwrap(),
+            Some("sk-test-123".to_string())
+        );
+    }
+
     // ========================================================================
     // Project CRUD 方法单元测试
     // ========================================================================
@@ -2388,6 +5090,49 @@ mod tests {
         assert_eq!(projects[1].tech_stack_type, "vue3");
     }
 
+    /// 测试 import_projects：批量导入一级子目录中的仓库，空目录与已注册仓库跳过
+    #[test]
+    fn test_import_projects_from_parent_dir() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+        let cat = db.create_category("批量导入", None).unwrap();
+
+        let parent = TempDir::new().unwrap();
+        let api = parent.path().join("api");
+        std::fs::create_dir_all(api.join("modules/users")).unwrap();
+        std::fs::write(api.join("requirements.txt"), "fastapi==0.110\n").unwrap();
+        std::fs::write(api.join("main.py"), "app = FastAPI()\n").unwrap();
+        let web = parent.path().join("web");
+        std::fs::create_dir_all(web.join("src/views")).unwrap();
+        std::fs::write(
+            web.join("package.json"),
+            r#"{"dependencies":{"vue":"^3.4.0"}}"#,
+        )
+        .unwrap();
+        std::fs::write(web.join("src/main.ts"), "createApp(App)\n").unwrap();
+        std::fs::create_dir(parent.path().join("empty")).unwrap();
+
+        let (candidates, skipped) =
+            crate::services::analyzer::discover_project_repos(parent.path(), None).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].path.ends_with("empty"));
+
+        let summary = db.import_projects(cat.id, &candidates).unwrap();
+        assert_eq!(summary.created.len(), 2);
+        assert!(summary.skipped.is_empty());
+        assert_eq!(summary.created[0].name, "api");
+        assert_eq!(summary.created[0].tech_stack_type, "fastapi");
+        assert_eq!(summary.created[1].name, "web");
+        assert_eq!(summary.created[1].tech_stack_type, "vue3");
+        assert_eq!(db.list_projects().unwrap().len(), 2);
+
+        // 再次导入：已注册的仓库全部跳过
+        let again = db.import_projects(cat.id, &candidates).unwrap();
+        assert!(again.created.is_empty());
+        assert_eq!(again.skipped.len(), 2);
+        assert!(again.skipped.iter().all(|s| s.reason == "已注册"));
+    }
+
     /// 测试 get_project：根据 ID 查询项目
     #[test]
     fn test_get_project_success() {
@@ -2712,6 +5457,28 @@ mod tests {
         assert_eq!(name, "新名称");
     }
 
+    /// 测试 client_build_count：重命名后构建记录仍指向原客户
+    #[test]
+    fn test_client_build_count_survives_rename() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        for i in 1..=3 {
+            let output_path = format!("/out/{}.zip", i);
+            db.create_build_record(project_id, client_id, "[]", &output_path, "v1.0.0", None)
+                .unwrap();
+        }
+        assert_eq!(db.client_build_count(client_id).unwrap(), 3);
+
+        db.update_client(client_id, "新名称").unwrap();
+        assert_eq!(db.client_build_count(client_id).unwrap(), 3);
+        let ids: Vec<i64> = db
+            .list_build_records_by_project(project_id)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.client_id)
+            .collect();
+        assert_eq!(ids, vec![client_id; 3]);
+    }
+
     /// 测试 update_client：不存在的 ID
     #[test]
     fn test_update_client_not_found() {
@@ -2794,6 +5561,494 @@ mod tests {
         assert_eq!(pc_after, 0);
     }
 
+    /// 测试 list_categories_with_counts / list_all_clients_with_counts：计数正确，零计数项也返回
+    #[test]
+    fn test_list_with_counts() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        let repo_dir = TempDir::new().unwrap();
+        let repo_path = repo_dir.path().to_str().unwrap();
+
+        // 第二个项目归入同一分类；另建一个空分类
+        let cat_id = db.get_project(project_id).unwrap().category_id;
+        db.create_project("测试项目2", cat_id, repo_path, "fastapi", "").unwrap();
+        let empty_cat = db.create_category("空分类", None).unwrap();
+
+        // 客户 A 两次构建，客户 B 无构建
+        db.create_build_record(project_id, client_id, "[]", "/tmp/a1.zip", "v1.0.0", None).unwrap();
+        db.create_build_record(project_id, client_id, "[]", "/tmp/a2.zip", "v1.0.1", None).unwrap();
+        let idle_client = db.create_client("空闲客户", &[project_id]).unwrap();
+
+        let categories = db.list_categories_with_counts().unwrap();
+        let counts: Vec<(i64, i64)> = categories.iter().map(|(c, n)| (c.id, *n)).collect();
+        assert_eq!(counts, vec![(cat_id, 2), (empty_cat.id, 0)]);
+
+        let clients = db.list_all_clients_with_counts().unwrap();
+        let counts: Vec<(i64, i64)> = clients.iter().map(|(c, n)| (c.id, *n)).collect();
+        assert_eq!(counts, vec![(client_id, 2), (idle_client.id, 0)]);
+    }
+
+    /// 测试 replace_dir_mtimes / get_dir_mtimes：整体替换，旧记录不残留
+    #[test]
+    fn test_dir_mtimes_roundtrip() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        assert!(db.get_dir_mtimes(project_id).unwrap().is_empty());
+
+        let first: HashMap<String, u64> =
+            [("".to_string(), 1), ("a".to_string(), 2)].into_iter().collect();
+        db.replace_dir_mtimes(project_id, &first).unwrap();
+        assert_eq!(db.get_dir_mtimes(project_id).unwrap(), first);
+
+        let second: HashMap<String, u64> = [("".to_string(), 3)].into_iter().collect();
+        db.replace_dir_mtimes(project_id, &second).unwrap();
+        assert_eq!(db.get_dir_mtimes(project_id).unwrap(), second);
+    }
+
+    /// 测试客户模块配置：未构建也可保存，读取返回原样 JSON，再次保存覆盖旧值
+    #[test]
+    fn test_client_module_config_roundtrip_and_upsert() {
+        let (db, _dir, project_id, client_id) = setup_project_and_client();
+        assert!(db.load_client_module_config(client_id, project_id).unwrap().is_none());
+
+        db.save_client_module_config(client_id, project_id, r#"["auth","billing"]"#)
+            .unwrap();
+        assert_eq!(
+            db.load_client_module_config(client_id, project_id).unwrap().as_deref(),
+            Some(r#"["auth","billing"]"#)
+        );
+        assert!(db.list_build_records_by_project(project_id).unwrap().is_empty());
+
+        db.save_client_module_config(client_id, project_id, r#"["auth"]"#).unwrap();
+        assert_eq!(
+            db.load_client_module_config(client_id, project_id).unwrap().as_deref(),
+            Some(r#"["auth"]"#)
+        );
+        let rows: i64 = db
+            .conn()
+            .query_row("SELECT COUNT(*) FROM client_module_configs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    // ========================================================================
+    // Embedding 维度管理单元测试
+    // ========================================================================
+
+    /// 测试 ensure_embedding_dimension：首次记录维度，之后拒绝不一致的维度
+    #[test]
+    fn test_embedding_dimension_mismatch_rejected() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::init(dir.path()).unwrap();
+        assert_eq!(db.get_embedding_model_info().unwrap().dimension, None);
+
+        db.ensure_embedding_dimension(1536).unwrap();
+        assert_eq!(db.get_embedding_model_info().unwrap().dimension, Some(1536));
+        db.ensure_embedding_dimension(1536).unwrap();
+
+        let err = db.ensure_embedding_dimension(768).unwrap_err();
+        assert!(err.contains(EMBEDDING_DIM_MISMATCH));
+        assert_eq!(db.get_embedding_model_info().unwrap().dimension, Some(1536));
+    }
+
+    /// 测试 clear_embeddings：清空项目向量，全部清空后重置已记录维度
+    #[test]
+    fn test_clear_embeddings_resets_dimension() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        for path in ["a.py", "b.py"] {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, embedding)
+                     VALUES (?1, ?2, 'hash', ?3)",
+                    params![project_id, path, vec![0u8; 8]],
+                )
+                .unwrap();
+        }
+        db.ensure_embedding_dimension(2).unwrap();
+
+        assert_eq!(db.clear_embeddings(project_id).unwrap(), 2);
+        let rows = db.list_file_index(project_id).unwrap();
+        assert!(rows.iter().all(|r| !r.has_embedding));
+        assert_eq!(db.get_embedding_model_info().unwrap().dimension, None);
+
+        // 维度重置后可切换到其他维度的模型
+        db.ensure_embedding_dimension(768).unwrap();
+    }
+
+    #[test]
+    fn test_dependency_degrees_written_to_file_index() {
+        use crate::services::{analyzer, report};
+
+        let (db, dir, project_id, _client_id) = setup_project_and_client();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        std::fs::write(repo.join("hub.py"), "VALUE = 1\n").unwrap();
+        for name in ["a.py", "b.py", "c.py"] {
+            std::fs::write(repo.join(name), "from hub import VALUE\n").unwrap();
+        }
+        std::fs::write(repo.join("lonely.py"), "print(1)\n").unwrap();
+
+        let files: Vec<String> = analyzer::scan_project_files(&repo)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.relative_path)
+            .collect();
+        for path in &files {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash) VALUES (?1, ?2, 'h')",
+                    params![project_id, path],
+                )
+                .unwrap();
+        }
+        // 依赖分析之前度数为空
+        let rows = db.list_file_index(project_id).unwrap();
+        assert!(rows.iter().all(|r| r.in_degree.is_none() && r.out_degree.is_none()));
+
+        let (edges, _) = analyzer::extract_dependencies_with_warnings(&repo, &files, &[]).unwrap();
+        let degrees = report::file_degrees(&files, &edges);
+        let updated = db.update_dependency_degrees(project_id, &degrees).unwrap();
+        assert_eq!(updated, 5);
+
+        let rows = db.list_file_index(project_id).unwrap();
+        let degree = |path: &str| {
+            let row = rows.iter().find(|r| r.file_path == path).unwrap();
+            (row.in_degree, row.out_degree)
+        };
+        assert_eq!(degree("hub.py"), (Some(3), Some(0)));
+        assert_eq!(degree("a.py"), (Some(0), Some(1)));
+        assert_eq!(degree("lonely.py"), (Some(0), Some(0)));
+
+        // 再次分析时不在结果中的文件清空旧度数
+        let degrees: Vec<_> = degrees.into_iter().filter(|(p, _, _)| p != "lonely.py").collect();
+        db.update_dependency_degrees(project_id, &degrees).unwrap();
+        let rows = db.list_file_index(project_id).unwrap();
+        let lonely = rows.iter().find(|r| r.file_path == "lonely.py").unwrap();
+        assert_eq!((lonely.in_degree, lonely.out_degree), (None, None));
+    }
+
+    #[test]
+    fn test_clear_project_analysis_keeps_project_and_builds() {
+        let (db, dir, project_id, client_id) = setup_project_and_client();
+        for path in ["a.py", "b.py"] {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, summary, signatures, embedding)
+                     VALUES (?1, ?2, 'hash', '摘要', 'def f()', ?3)",
+                    params![project_id, path, vec![0u8; 8]],
+                )
+                .unwrap();
+        }
+        db.ensure_embedding_dimension(2).unwrap();
+        db.replace_dir_mtimes(project_id, &HashMap::from([(".".to_string(), 1)])).unwrap();
+        db.save_report(project_id, "deep", None, "# 报告", None).unwrap();
+        let zip = dir.path().join("dist_a.zip");
+        db.create_build_record(project_id, client_id, "[]", zip.to_str().unwrap(), "v1.0.0", None)
+            .unwrap();
+
+        let report = db.clear_project_analysis(project_id).unwrap();
+        assert_eq!(
+            report,
+            ClearReport {
+                file_index_entries: 2,
+                dir_index_entries: 1,
+                reports: 1,
+            }
+        );
+        assert!(db.list_file_index(project_id).unwrap().is_empty());
+        assert!(db.get_dir_mtimes(project_id).unwrap().is_empty());
+        assert!(db.list_reports(project_id).unwrap().is_empty());
+        assert_eq!(db.get_embedding_model_info().unwrap().dimension, None);
+
+        // 项目、客户关联与构建记录保持不变
+        assert!(db.get_project(project_id).is_ok());
+        assert_eq!(db.list_build_records_by_project(project_id).unwrap().len(), 1);
+        assert_eq!(db.client_build_count(client_id).unwrap(), 1);
+        db.vacuum().unwrap();
+
+        assert!(db.clear_project_analysis(999_999).is_err());
+    }
+
+    #[test]
+    fn test_save_file_summary_clears_stale_embedding() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        db.conn()
+            .execute(
+                "INSERT INTO file_index (project_id, file_path, file_hash, summary, embedding)
+                 VALUES (?1, 'a.py', 'hash', '旧摘要', ?2)",
+                params![project_id, vec![0u8; 8]],
+            )
+            .unwrap();
+        assert!(db.list_files_without_embedding(project_id).unwrap().is_empty());
+
+        // 摘要未变化 → 保留向量
+        assert!(!db.save_file_summary(project_id, "a.py", "旧摘要").unwrap());
+        assert!(db.list_files_without_embedding(project_id).unwrap().is_empty());
+
+        // 重新生成摘要 → 清空向量，下一次批量 Embedding 以新摘要为输入
+        assert!(db.save_file_summary(project_id, "a.py", "新摘要").unwrap());
+        let pending = db.list_files_without_embedding(project_id).unwrap();
+        assert_eq!(pending, vec![("a.py".to_string(), Some("新摘要".to_string()))]);
+        assert!(!db.list_file_index(project_id).unwrap()[0].has_embedding);
+    }
+
+    // ========================================================================
+    // File Index 查询方法单元测试
+    // ========================================================================
+
+    /// 测试 export_file_index_jsonl：每行一个合法 JSON 对象，与存储的记录一致
+    #[test]
+    fn test_export_file_index_jsonl() {
+        let (db, dir, project_id, _client_id) = setup_project_and_client();
+        db.conn()
+            .execute(
+                "INSERT INTO file_index (project_id, file_path, file_hash, file_size, mtime, summary, embedding, signatures)
+                 VALUES (?1, 'a.py', 'h1', 10, 100, '摘要', ?2, '[\"def f()\"]')",
+                params![project_id, vec![0u8; 12]],
+            )
+            .unwrap();
+        db.conn()
+            .execute(
+                "INSERT INTO file_index (project_id, file_path, file_hash, file_size, mtime)
+                 VALUES (?1, 'b/c.ts', 'h2', 20, 200)",
+                params![project_id],
+            )
+            .unwrap();
+
+        let out = dir.path().join("index.jsonl");
+        assert_eq!(db.export_file_index_jsonl(project_id, &out).unwrap(), 2);
+
+        let content = std::fs::read_to_string(&out).unwrap();
+        let rows: Vec<FileIndexExportRow> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert!(!content.contains("\"embedding\""));
+
+        assert_eq!(rows[0].path, "a.py");
+        assert_eq!(rows[0].hash, "h1");
+        assert_eq!((rows[0].size, rows[0].mtime), (10, 100));
+        assert!(rows[0].has_summary && rows[0].has_embedding);
+        assert_eq!(rows[0].embedding_dim, Some(3));
+        assert_eq!(rows[0].signatures, Some(serde_json::json!(["def f()"])));
+
+        assert_eq!(rows[1].path, "b/c.ts");
+        assert!(!rows[1].has_summary && !rows[1].has_embedding);
+        assert_eq!(rows[1].embedding_dim, None);
+        assert_eq!(rows[1].signatures, None);
+    }
+
+    /// 测试 list_file_embeddings：路径前缀将候选集限制在对应目录
+    #[test]
+    fn test_list_file_embeddings_path_prefix() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        for path in [
+            "modules/auth/login.py",
+            "modules/auth/token.py",
+            "modules/auth_v2/login.py",
+            "modules/billing/pay.py",
+        ] {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, embedding)
+                     VALUES (?1, ?2, 'hash', ?3)",
+                    params![project_id, path, vec![0u8; 8]],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(db.list_file_embeddings(project_id, None).unwrap().len(), 4);
+        assert_eq!(db.list_file_embeddings(project_id, Some("")).unwrap().len(), 4);
+
+        // 前缀须按整段目录匹配：`modules/auth` 不包含同名前缀的 `modules/auth_v2`
+        for prefix in ["modules/auth", "modules/auth/", "MODULES/AUTH"] {
+            let scoped = db.list_file_embeddings(project_id, Some(prefix)).unwrap();
+            let paths: Vec<&str> = scoped.iter().map(|(p, _, _)| p.as_str()).collect();
+            assert_eq!(paths.len(), 2, "{}", prefix);
+            assert!(paths.iter().all(|p| p.starts_with("modules/auth/")));
+        }
+        let single = db.list_file_embeddings(project_id, Some("modules/auth/login.py")).unwrap();
+        assert_eq!(single.len(), 1);
+
+        // `_` 按字面匹配，不作为单字符通配符
+        assert!(db.list_file_embeddings(project_id, Some("modules_auth")).unwrap().is_empty());
+    }
+
+    /// 测试子目录扫描：只对比、清理该子树，子树外文件的摘要与向量保持不变
+    #[test]
+    fn test_sub_path_scan_keeps_rows_outside_subtree() {
+        use crate::services::analyzer;
+
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        for path in [
+            "api.py",
+            "backend/app.py",
+            "backend/old.py",
+            "backend_tools/x.py",
+        ] {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, summary, embedding)
+                     VALUES (?1, ?2, 'hash', '摘要', ?3)",
+                    params![project_id, path, vec![0u8; 8]],
+                )
+                .unwrap();
+        }
+
+        let repo = TempDir::new().unwrap();
+        std::fs::create_dir_all(repo.path().join("backend")).unwrap();
+        std::fs::write(repo.path().join("backend/app.py"), "x = 1").unwrap();
+        std::fs::write(repo.path().join("api.py"), "y = 2").unwrap();
+        let root = analyzer::resolve_scan_root(repo.path(), Some("backend")).unwrap();
+        let prefix = analyzer::scan_root_prefix(Some("backend"));
+        let scanned: HashSet<String> = analyzer::scan_project_files(&root)
+            .unwrap()
+            .into_iter()
+            .map(|e| format!("{}{}", prefix, e.relative_path))
+            .collect();
+        assert_eq!(scanned, HashSet::from(["backend/app.py".to_string()]));
+
+        let mut in_scope: Vec<String> = db
+            .load_file_scan_state(project_id, &prefix)
+            .unwrap()
+            .into_keys()
+            .collect();
+        in_scope.sort();
+        assert_eq!(in_scope, vec!["backend/app.py", "backend/old.py"]);
+
+        assert_eq!(
+            db.delete_stale_file_index(project_id, &prefix, &scanned)
+                .unwrap(),
+            1
+        );
+        let rows = db.list_file_index(project_id).unwrap();
+        let paths: Vec<&str> = rows.iter().map(|r| r.file_path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["api.py", "backend/app.py", "backend_tools/x.py"]
+        );
+        assert!(rows.iter().all(|r| r.has_summary && r.has_embedding));
+    }
+
+    /// 测试 list_embedding_modes：旧向量按 summary 计，多种模式同时存在时全部列出
+    #[test]
+    fn test_list_embedding_modes_detects_mixed_inputs() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        let seed = [
+            ("a.py", None, Some(vec![0u8; 8])),
+            ("b.py", Some("signatures"), Some(vec![0u8; 8])),
+            ("c.py", Some("content"), None),
+        ];
+        for (path, mode, embedding) in &seed {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, embedding, embedding_mode)
+                     VALUES (?1, ?2, 'hash', ?3, ?4)",
+                    params![project_id, path, embedding, mode],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(db.list_embedding_modes(project_id).unwrap(), vec!["signatures", "summary"]);
+        let rows = db.list_file_index(project_id).unwrap();
+        assert_eq!(rows[0].embedding_mode.as_deref(), Some("summary"));
+        assert_eq!(rows[2].embedding_mode, None);
+
+        db.clear_embeddings(project_id).unwrap();
+        assert!(db.list_embedding_modes(project_id).unwrap().is_empty());
+    }
+
+    /// 测试 embedding_version：向量写入、更新、清空与删除时递增，无关字段变化时不变
+    #[test]
+    fn test_embedding_version_tracks_vector_changes() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        assert_eq!(db.embedding_version(project_id).unwrap(), 0);
+
+        let insert = |path: &str, embedding: Option<Vec<u8>>| {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, embedding)
+                     VALUES (?1, ?2, 'hash', ?3)",
+                    params![project_id, path, embedding],
+                )
+                .unwrap();
+        };
+        insert("a.py", None);
+        assert_eq!(db.embedding_version(project_id).unwrap(), 0);
+        insert("b.py", Some(vec![0u8; 8]));
+        assert_eq!(db.embedding_version(project_id).unwrap(), 1);
+
+        // 仅哈希变化不影响检索结果，版本号不变
+        db.conn()
+            .execute(
+                "UPDATE file_index SET file_hash = 'other' WHERE file_path = 'b.py'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(db.embedding_version(project_id).unwrap(), 1);
+
+        db.conn()
+            .execute(
+                "UPDATE file_index SET summary = '摘要' WHERE file_path = 'b.py'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(db.embedding_version(project_id).unwrap(), 2);
+
+        // 原值写回（如扫描时保留摘要）不视为变化
+        db.conn()
+            .execute(
+                "UPDATE file_index SET summary = '摘要', embedding = embedding, file_path = file_path
+                 WHERE file_path = 'b.py'",
+                [],
+            )
+            .unwrap();
+        assert_eq!(db.embedding_version(project_id).unwrap(), 2);
+
+        db.clear_embeddings(project_id).unwrap();
+        let cleared = db.embedding_version(project_id).unwrap();
+        assert!(cleared > 2);
+
+        insert("c.py", Some(vec![0u8; 8]));
+        db.conn()
+            .execute("DELETE FROM file_index WHERE file_path = 'c.py'", [])
+            .unwrap();
+        assert_eq!(db.embedding_version(project_id).unwrap(), cleared + 2);
+    }
+
+    /// 测试 list_file_index：has_summary / has_embedding 反映摘要与向量是否存在
+    #[test]
+    fn test_list_file_index_reports_presence_flags() {
+        let (db, _dir, project_id, _client_id) = setup_project_and_client();
+        let seed = [
+            ("b.py", Some("摘要 B"), Some(vec![0u8; 8])),
+            ("a.py", None, None),
+            ("c.py", Some("摘要 C"), None),
+        ];
+        for (path, summary, embedding) in &seed {
+            db.conn()
+                .execute(
+                    "INSERT INTO file_index (project_id, file_path, file_hash, summary, embedding)
+                     VALUES (?1, ?2, 'hash', ?3, ?4)",
+                    params![project_id, path, summary, embedding],
+                )
+                .unwrap();
+        }
+
+        let rows = db.list_file_index(project_id).unwrap();
+        let flags: Vec<(&str, bool, bool)> = rows
+            .iter()
+            .map(|r| (r.file_path.as_str(), r.has_summary, r.has_embedding))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![("a.py", false, false), ("b.py", true, true), ("c.py", true, false)]
+        );
+        assert_eq!(rows[1].summary.as_deref(), Some("摘要 B"));
+
+        // 其他项目无索引记录
+        assert!(db.list_file_index(project_id + 1).unwrap().is_empty());
+    }
+
     // ========================================================================
     // Category CRUD 属性测试 (Property-Based Tests)
     // ========================================================================
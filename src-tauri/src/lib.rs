@@ -18,6 +18,8 @@ use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 本地时区偏移只能在单线程时可靠获取，须在启动 Tauri 运行时之前记录
+    services::build_strategy::capture_local_offset();
     tauri::Builder::default()
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
@@ -31,8 +33,30 @@ pub fn run() {
                 .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
             let db = database::Database::init(&app_data_dir)
                 .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
-            // 注册数据库为 Tauri managed state（使用 Mutex 保证线程安全）
+            // 只读连接池：纯查询命令走独立只读连接，不与写操作争用同一把锁
+            let read_pool = db
+                .path()
+                .ok_or_else(|| "数据库路径未知，无法创建只读连接池".to_string())
+                .and_then(|path| database::ReadPool::open(&path, database::READ_POOL_SIZE))
+                .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))?;
+            // 按设置初始化全局 LLM 并发上限（跨批量操作限制在途请求总数）
+            let llm_concurrency = db
+                .get_setting(services::llm_client::GLOBAL_CONCURRENCY_KEY)
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+            services::llm_client::set_global_concurrency(
+                services::llm_client::parse_global_concurrency(&llm_concurrency),
+            );
+            // 注册数据库为 Tauri managed state（使用 Mutex 保证线程安全，所有写操作经此串行执行）
             app.manage(std::sync::Mutex::new(db));
+            app.manage(read_pool);
+            // 注册命令耗时统计（内存中，重启清空）
+            app.manage(services::metrics::CommandMetrics::default());
+            // 注册深度报告压缩结果缓存（内存中，供汇总失败后 resume 重试）
+            app.manage(services::report::CompressionCache::default());
+            // 注册语义搜索向量索引缓存（内存中，按向量版本号失效）
+            app.manage(services::vector_index::VectorIndexCache::default());
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -40,29 +64,57 @@ pub fn run() {
             commands::project::open_project,
             commands::project::scan_modules,
             commands::project::scan_project_modules,
+            commands::project::list_modules_with_state,
+            commands::project::detect_modules_dir,
+            commands::project::detect_ambiguous_modules,
+            commands::project::compare_project_modules,
+            commands::project::project_size_breakdown,
+            commands::project::module_metadata,
+            commands::project::scan_and_import_projects,
             // 构建 commands
             commands::build::build_package,
             commands::build::build_project_package,
+            commands::build::build_for_clients,
             commands::build::scan_project_skeleton,
+            commands::build::check_output_writable,
+            commands::build::verify_package,
+            commands::build::delivery_checklist,
+            commands::build::validate_project_imports,
+            commands::build::describe_archive,
+            commands::build::preview_module_expansion,
+            commands::build::list_supported_tech_stacks,
+            commands::build::rebuild_from_record,
             commands::build::open_folder,
             // 数据库 CRUD commands
             commands::db_crud::db_create_category,
             commands::db_crud::db_list_categories,
+            commands::db_crud::db_list_categories_with_counts,
             commands::db_crud::db_update_category,
             commands::db_crud::db_delete_category,
+            commands::db_crud::db_reassign_and_delete_category,
             commands::db_crud::db_create_project,
+            commands::db_crud::db_clone_project,
+            commands::db_crud::db_export_project_bundle,
+            commands::db_crud::db_import_project_bundle,
             commands::db_crud::db_list_projects,
             commands::db_crud::db_update_project,
             commands::db_crud::db_delete_project,
+            commands::db_crud::db_project_delete_impact,
             commands::db_crud::db_create_client,
             commands::db_crud::db_list_clients_by_project,
+            commands::db_crud::db_list_all_clients_with_counts,
             commands::db_crud::db_update_client,
+            commands::db_crud::db_client_build_count,
             commands::db_crud::db_delete_client,
+            commands::db_crud::db_repair_orphaned_associations,
+            commands::db_crud::db_health,
             commands::db_crud::db_create_build_record,
             commands::db_crud::db_list_build_records,
+            commands::db_crud::db_find_builds_with_module,
             commands::db_crud::db_delete_build_record,
             commands::db_crud::db_delete_all_build_records,
             commands::db_crud::db_delete_build_records_before_days,
+            commands::db_crud::db_relocate_build_artifacts,
             // 设置 commands
             commands::db_crud::get_app_settings,
             commands::db_crud::get_app_setting,
@@ -80,20 +132,48 @@ pub fn run() {
             commands::db_crud::db_delete_template,
             commands::db_crud::export_template_json,
             commands::db_crud::import_template_json,
+            // 文件索引查询 commands
+            commands::db_crud::db_list_file_index,
+            commands::db_crud::export_file_index_jsonl,
+            commands::db_crud::export_build_history_csv,
+            commands::db_crud::db_save_report,
+            commands::db_crud::db_get_report,
+            commands::db_crud::db_list_reports,
+            // 诊断 commands
+            commands::metrics::get_command_metrics,
             // 项目分析 commands
             commands::analysis::get_llm_config,
             commands::analysis::list_llm_models,
+            commands::analysis::validate_model_roles,
             commands::analysis::scan_project_file_index,
             commands::analysis::analyze_file_summary,
             commands::analysis::analyze_dependencies,
+            commands::analysis::export_dependency_graph_dot,
             commands::analysis::embed_file,
             commands::analysis::embed_all_files,
+            commands::analysis::analyze_and_embed_all,
+            commands::analysis::get_embedding_model_info,
+            commands::analysis::clear_embeddings,
+            commands::analysis::clear_project_analysis,
+            commands::analysis::list_embedding_modes,
             commands::analysis::search_similar_files,
             commands::analysis::get_project_overview,
             // 签名索引 + AI 报告 commands
             commands::analysis::index_project_signatures,
             commands::analysis::generate_project_report,
+            commands::analysis::refresh_report,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // 退出前合并 WAL，避免批量 Embedding 中途退出时丢失最近写入的向量
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                if let Some(db) = app_handle.try_state::<std::sync::Mutex<database::Database>>() {
+                    match db.lock().map_err(|e| e.to_string()).and_then(|db| db.checkpoint()) {
+                        Ok(()) => log::info!("退出前数据库 WAL checkpoint 完成"),
+                        Err(e) => log::warn!("退出前数据库 WAL checkpoint 失败：{}", e),
+                    }
+                }
+            }
+        });
 }
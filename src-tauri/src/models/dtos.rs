@@ -26,6 +26,53 @@ pub struct ModuleInfo {
     pub path: String,
 }
 
+/// 带选中状态的模块信息，由 `list_modules_with_state` command 返回
+/// 在模块列表基础上标记该客户上次构建时是否选中了此模块
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModuleSelectionState {
+    /// 模块名称（即子目录名）
+    pub name: String,
+    /// 模块的完整路径
+    pub path: String,
+    /// 该客户最近一次构建是否包含此模块（新增模块默认为 false）
+    pub previously_selected: bool,
+}
+
+/// 两个项目模块集合的差异，由 `compare_project_modules` command 返回（各列表按名称排序）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModuleDiff {
+    /// 仅项目 A 拥有的模块
+    pub only_in_a: Vec<String>,
+    /// 仅项目 B 拥有的模块
+    pub only_in_b: Vec<String>,
+    /// 两个项目共有的模块
+    pub common: Vec<String>,
+}
+
+/// 文件索引进度，随 `index-progress` 事件推送到前端
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexProgress {
+    /// 已完成哈希的文件数
+    pub done: usize,
+    /// 待处理的文件总数
+    pub total: usize,
+}
+
+/// 多客户构建中单个客户的结果，由 `build_for_clients` command 返回
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientBuildResult {
+    /// 客户 ID
+    pub client_id: i64,
+    /// 客户名称
+    pub client_name: String,
+    /// 构建结果（打包失败时为 None）
+    pub result: Option<BuildResult>,
+    /// 写入的构建记录 ID（打包失败时为 None）
+    pub build_record_id: Option<i64>,
+    /// 打包失败原因
+    pub error: Option<String>,
+}
+
 /// 构建结果，由 `build_package` / `build_project_package` command 返回
 /// 包含生成的 ZIP 交付包信息
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -39,4 +86,279 @@ pub struct BuildResult {
     /// 实际打包的完整模块列表（用户选中 + 依赖分析自动补充）
     /// 前端应使用此字段保存构建记录，而非原始 selectedModules
     pub expanded_modules: Vec<String>,
+    /// 完整性校验发现的不一致文件（构建目录与源文件哈希不符）
+    /// 正常构建存在不一致时直接失败；试运行不失败，在此列出供预览时检查
+    pub integrity_errors: Vec<String>,
+    /// 试运行（dry-run）时返回的构建计划；正常构建时为 None（此时 zip_path 有效）
+    pub plan: Option<BuildPlan>,
+    /// 构建过程中产生的警告（如入口文件不在约定位置、跳过了不存在的模块）
+    pub warnings: Vec<String>,
+}
+
+/// 试运行构建计划：执行除写入 ZIP 以外的全部构建步骤后得到的交付包预览
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BuildPlan {
+    /// 交付包中将包含的文件（相对路径，正斜杠分隔，已排序）
+    pub files: Vec<String>,
+    /// 依赖分析自动补充的模块
+    pub auto_added_modules: Vec<String>,
+    /// 入口文件重写差异（`- ` 为删除行，`+ ` 为新增行）
+    pub entry_file_diff: Vec<String>,
+    /// 预估交付包大小（未压缩字节数）
+    pub estimated_size: u64,
+    /// 构建过程中产生的警告
+    pub warnings: Vec<String>,
+}
+
+/// 构建选项，由 `build_project_package` command 接收（可选参数）
+/// 未传入的字段使用默认值，即完整构建流程
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BuildOptions {
+    /// 是否重写入口文件中的模块导入（默认 true）
+    /// 设为 false 时为"仅复制"模式：跳过入口文件重写与导入校验，
+    /// 适用于没有功能模块结构、只需排除式打包的项目
+    pub rewrite: bool,
+    /// 试运行（默认 false）：执行除写入 ZIP 以外的全部步骤，在系统临时目录的沙箱中构建，
+    /// 结束后清理沙箱并通过 `BuildResult.plan` 返回构建计划，适用于 CI 校验交付配置
+    pub dry_run: bool,
+    /// 依赖分析的遍历范围（默认 `ModulesOnly`，即只跟随模块之间的直接 import）
+    pub bfs_scope: BfsScope,
+    /// 骨架复制时对匹配文件执行的内容替换（如将开发环境 API 地址替换为客户地址）
+    pub content_transforms: Vec<ContentTransform>,
+    /// 排除测试文件（默认 false）：在常规排除项之外排除 `tests/`、`__tests__/`、
+    /// `test_*.py`、`*_test.py`、`*.test.ts`、`*.spec.ts`，骨架与模块均生效
+    pub exclude_tests: bool,
+    /// 入口文件必须位于技术栈约定位置（默认 false）：设为 true 时入口文件缺失直接构建失败，
+    /// 否则仅在 `BuildResult.warnings` 中提示并跳过入口文件重写
+    pub require_entry_file: bool,
+    /// 生成可复现的 ZIP（默认 false）：条目按路径排序并使用固定时间戳，
+    /// 相同输入得到逐字节相同的交付包（见 `packer::create_zip_from_dir_with`）
+    pub deterministic_zip: bool,
+    /// 交付包命名的时间戳风格，由 commands 层从设置项读取，不接受前端传入
+    #[serde(skip)]
+    pub timestamp: TimestampStyle,
+}
+
+/// 构建命名时间戳风格（默认 UTC + `yyyyMMdd_HHmmss`）
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimestampStyle {
+    /// 是否使用本地时区（`build_timestamp_tz = local`）
+    pub local: bool,
+    /// `time` 格式描述（`build_timestamp_format`），None 时使用默认格式
+    pub format: Option<String>,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            rewrite: true,
+            dry_run: false,
+            bfs_scope: BfsScope::ModulesOnly,
+            content_transforms: Vec::new(),
+            exclude_tests: false,
+            require_entry_file: false,
+            deterministic_zip: false,
+            timestamp: TimestampStyle::default(),
+        }
+    }
+}
+
+/// 复制时的文件内容替换规则
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContentTransform {
+    /// 文件匹配模式（`*`、`?` 通配）：含 `/` 时匹配相对路径，否则只匹配文件名
+    pub glob: String,
+    /// 待替换的原文
+    pub find: String,
+    /// 替换后的内容
+    pub replace: String,
+}
+
+/// 构建依赖分析（BFS）的遍历范围
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BfsScope {
+    /// 跟随全部 import：经由 core/utils 等共享文件间接引用的模块也会被自动补充
+    AllImports,
+    /// 只跟随目标位于模块目录内的 import：共享文件照常随骨架整体打包，
+    /// 但不会经由共享文件把其他功能模块带入交付包
+    ModulesOnly,
+}
+
+/// 模块被自动补充的原因：一条跨模块的文件级依赖
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModuleAddition {
+    /// 被自动补充的模块
+    pub module: String,
+    /// 引入该模块的已包含模块
+    pub required_by: String,
+    /// 发起 import 的文件（可能是该模块经由的共享文件，如 `utils/shared.py`）
+    pub source_file: String,
+    /// 被 import 的文件（位于被补充模块内）
+    pub target_file: String,
+}
+
+/// 依赖自动补充预览（构建前展示 BFS 将带入的模块）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExpansionResult {
+    /// 用户选中的模块
+    pub selected: Vec<String>,
+    /// 自动补充的模块（排序）
+    pub added: Vec<String>,
+    /// 每个自动补充模块的原因（按补充顺序）；仅由 import 文本识别、无法对应到文件的模块不在其中
+    pub reasons: Vec<ModuleAddition>,
+}
+
+/// 交付包清单（以 `prism-manifest.json` 写入 ZIP 根目录）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PackageManifest {
+    /// 客户名称
+    pub client_name: String,
+    /// 实际打包的模块列表
+    pub modules: Vec<String>,
+    /// 包内文件清单（不含清单文件自身，按路径排序）
+    pub files: Vec<ManifestFile>,
+}
+
+/// 交付包清单中的单个文件
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ManifestFile {
+    /// 包内相对路径（正斜杠分隔）
+    pub path: String,
+    /// 文件内容的 SHA256 哈希（十六进制）
+    pub sha256: String,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 交付包校验结果
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PackageVerification {
+    /// 是否全部校验通过
+    pub passed: bool,
+    /// 内容与清单哈希不一致的文件
+    pub mismatched: Vec<String>,
+    /// 清单中列出但包内缺失的文件
+    pub missing: Vec<String>,
+    /// 包内存在但清单中未列出的文件
+    pub extra: Vec<String>,
+    /// 整包哈希与 `.sha256` 校验文件是否一致；无校验文件时为 None
+    pub archive_hash_ok: Option<bool>,
+}
+
+/// 交付检查项的结论
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// 通过
+    Pass,
+    /// 不阻断交付，但建议人工确认
+    Warn,
+    /// 不应交付
+    Fail,
+}
+
+/// 交付前检查清单中的单项结果
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CheckItem {
+    /// 检查项标识（如 `manifest`、`checksum`），供前端稳定引用
+    pub key: String,
+    /// 检查项名称
+    pub label: String,
+    /// 检查结论
+    pub status: CheckStatus,
+    /// 结论说明
+    pub detail: String,
+}
+
+/// 交付包概要（读取 ZIP 中央目录得到，无需解压）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ArchiveInfo {
+    /// ZIP 文件自身大小（字节）
+    pub archive_size: u64,
+    /// 包内文件数（不含目录条目）
+    pub file_count: usize,
+    /// 压缩后总字节数
+    pub compressed_bytes: u64,
+    /// 解压后总字节数
+    pub uncompressed_bytes: u64,
+    /// 顶层条目（目录以 `/` 结尾，按名称排序）
+    pub top_level: Vec<String>,
+}
+
+/// 项目顶层目录的体积统计（用于查看体积分布）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DirSize {
+    /// 顶层目录名（根目录下直接存放的文件为 "."）
+    pub name: String,
+    /// 目录内文件累计字节数
+    pub total_bytes: u64,
+    /// 目录内文件数
+    pub file_count: usize,
+}
+
+/// 模块概况（模块卡片展示用，由 `module_metadata` command 返回）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ModuleMetadata {
+    /// 模块名称（即子目录名）
+    pub name: String,
+    /// 模块内文件数（不含忽略目录）
+    pub file_count: usize,
+    /// 模块内文件累计字节数
+    pub total_bytes: u64,
+    /// 路由数（代码文件中 `include_router` 与 `path:` 的出现次数）
+    pub route_count: usize,
+    /// 是否包含测试（`tests/`、`__tests__/` 目录或 `test_*.py`、`*.spec.ts` 等测试文件）
+    pub has_tests: bool,
+}
+
+/// 批量导入时发现的候选仓库（`analyzer::discover_project_repos` 返回）
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RepoCandidate {
+    /// 项目名称（即子目录名）
+    pub name: String,
+    /// 仓库绝对路径
+    pub path: String,
+    /// 技术栈标识（如 "fastapi"、"vue3"）
+    pub tech_stack: String,
+    /// 推测的模块目录（无法推测时为空，使用技术栈默认值）
+    pub modules_dir: String,
+}
+
+/// 批量导入时被跳过的目录及原因
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SkippedRepo {
+    /// 目录路径
+    pub path: String,
+    /// 跳过原因（如"不是代码仓库"、"已注册"）
+    pub reason: String,
+}
+
+/// 构建流水线支持的技术栈（返回给前端，用于技术栈下拉选项）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TechStackInfo {
+    /// 技术栈标识（如 "fastapi"）
+    pub id: String,
+    /// 显示名称（如 "FastAPI"）
+    pub display_name: String,
+    /// 默认模块目录
+    pub default_modules_dir: String,
+    /// 是否支持入口文件导入重写
+    pub supports_rewrite: bool,
+}
+
+/// 单个命令的耗时统计（返回给前端）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommandMetric {
+    /// 命令名称
+    pub command: String,
+    /// 调用次数（自应用启动以来）
+    pub count: u64,
+    /// 耗时指数移动平均（毫秒）
+    pub avg_ms: f64,
+    /// 最近一次耗时（毫秒）
+    pub last_ms: f64,
+    /// 最大耗时（毫秒）
+    pub max_ms: f64,
 }
@@ -0,0 +1,368 @@
+// ============================================================================
+// 批量分析流水线：摘要 → Embedding 一次完成
+// ✅ 只能做：编排摘要/向量生成的并发与顺序，不关心具体 LLM 调用
+// ⛔ 禁止：依赖 tauri::*，直接操作数据库
+// ============================================================================
+
+use futures::stream::{self, Stream, StreamExt};
+use std::future::Future;
+
+use crate::services::analyzer;
+
+/// 流水线默认并发数（同时处理的文件数上限）
+pub const DEFAULT_PIPELINE_CONCURRENCY: usize = 4;
+
+/// Embedding 输入模式的设置键
+pub const EMBEDDING_INPUT_MODE_KEY: &str = "embedding_input_mode";
+
+/// 无摘要或无签名时，作为 Embedding 输入的文件内容最大字节数
+const CONTENT_INPUT_MAX_BYTES: usize = 2000;
+
+/// Embedding 输入模式：决定以什么文本生成向量
+///
+/// 不同模式生成的向量彼此不可比，每条向量会记录生成它的模式，便于发现混用。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmbeddingInputMode {
+    /// 文件摘要（无摘要时回退到文件内容）
+    #[default]
+    Summary,
+    /// 静态提取的代码签名
+    Signatures,
+    /// 文件内容（截断）
+    Content,
+    /// 文件路径 + 代码签名
+    PathPlusSignatures,
+}
+
+impl EmbeddingInputMode {
+    /// 所有模式的设置取值
+    pub const ALL: &'static [&'static str] =
+        &["summary", "signatures", "content", "path_plus_signatures"];
+
+    /// 解析设置值，空值或未知取值按 `summary` 处理
+    pub fn parse(value: &str) -> Self {
+        match value.trim() {
+            "signatures" => Self::Signatures,
+            "content" => Self::Content,
+            "path_plus_signatures" => Self::PathPlusSignatures,
+            _ => Self::Summary,
+        }
+    }
+
+    /// 设置取值（同时作为 `file_index.embedding_mode` 的存储值）
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Summary => "summary",
+            Self::Signatures => "signatures",
+            Self::Content => "content",
+            Self::PathPlusSignatures => "path_plus_signatures",
+        }
+    }
+}
+
+/// 单个文件的流水线处理结果
+#[derive(Debug, Clone)]
+pub struct PipelineFileResult {
+    /// 文件相对路径
+    pub file_path: String,
+    /// 新生成的摘要（摘要失败时为 None）
+    pub summary: Option<String>,
+    /// 基于新摘要生成的向量（摘要或向量失败时为 None）
+    pub embedding: Option<Vec<f32>>,
+    /// 生成向量实际使用的输入模式（回退到文件内容时为 `Content`；向量失败时为 None）
+    pub embedding_mode: Option<EmbeddingInputMode>,
+    /// 失败原因（摘要或向量任一步骤失败时记录）
+    pub error: Option<String>,
+}
+
+/// 根据摘要构造 Embedding 输入文本
+///
+/// 与 `embed_file` 中有摘要时的输入格式保持一致，保证两条路径生成的向量可比。
+pub fn embedding_input_from_summary(file_path: &str, summary: &str) -> String {
+    format!("文件：{}\n摘要：{}", file_path, summary)
+}
+
+/// 以文件路径 + 截断内容构造 Embedding 输入文本
+fn embedding_input_from_content(file_path: &str, content: &str) -> String {
+    let mut end = content.len().min(CONTENT_INPUT_MAX_BYTES);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("文件：{}\n内容：{}", file_path, &content[..end])
+}
+
+/// 按输入模式构造 Embedding 输入文本
+///
+/// 签名复用 `extract_signatures_from_content` 提取；提取不到签名（如配置文件）
+/// 或 `summary` 模式下没有摘要时，回退到文件内容。
+/// 仅在需要时调用 `read_content` 读取文件。
+///
+/// # 参数
+/// - `mode`: 输入模式
+/// - `file_path`: 文件相对路径（用于检测语言和拼接输入）
+/// - `summary`: 文件摘要（可能不存在）
+/// - `read_content`: 读取文件内容
+///
+/// # 返回
+/// (输入文本, 实际使用的模式)：发生回退时模式为 `Content`，应以此记录 `embedding_mode`
+pub fn build_embedding_input<R>(
+    mode: EmbeddingInputMode,
+    file_path: &str,
+    summary: Option<&str>,
+    read_content: R,
+) -> Result<(String, EmbeddingInputMode), String>
+where
+    R: FnOnce() -> Result<String, String>,
+{
+    if mode == EmbeddingInputMode::Summary {
+        if let Some(s) = summary.filter(|s| !s.is_empty()) {
+            return Ok((embedding_input_from_summary(file_path, s), mode));
+        }
+    }
+
+    let content = read_content()?;
+    let signatures = match mode {
+        EmbeddingInputMode::Signatures | EmbeddingInputMode::PathPlusSignatures => {
+            let language = analyzer::detect_language(file_path);
+            analyzer::extract_signatures_from_content(&content, &language)
+        }
+        EmbeddingInputMode::Summary | EmbeddingInputMode::Content => Vec::new(),
+    };
+    if signatures.is_empty() {
+        return Ok((
+            embedding_input_from_content(file_path, &content),
+            EmbeddingInputMode::Content,
+        ));
+    }
+
+    let input = match mode {
+        EmbeddingInputMode::PathPlusSignatures => {
+            format!("文件：{}\n签名：\n{}", file_path, signatures.join("\n"))
+        }
+        _ => signatures.join("\n"),
+    };
+    Ok((input, mode))
+}
+
+/// 对一批文件依次执行"生成摘要 → 以新摘要生成向量"
+///
+/// 最多同时处理 `concurrency` 个文件；结果按输入顺序产出
+/// （`buffered` 而非 `buffer_unordered`），调用方可逐条落库以支持断点续跑。
+///
+/// # 参数
+/// - `files`: 待处理的文件相对路径列表
+/// - `concurrency`: 并发上限（为 0 时按 1 处理）
+/// - `summarize`: 摘要生成函数，输入文件路径，返回摘要文本
+/// - `embed`: 向量生成函数，输入 Embedding 文本，返回向量
+pub fn summarize_then_embed<'a, S, SFut, E, EFut>(
+    files: Vec<String>,
+    concurrency: usize,
+    summarize: S,
+    embed: E,
+) -> impl Stream<Item = PipelineFileResult> + 'a
+where
+    S: Fn(String) -> SFut + 'a,
+    SFut: Future<Output = Result<String, String>> + 'a,
+    E: Fn(String) -> EFut + Clone + 'a,
+    EFut: Future<Output = Result<Vec<f32>, String>> + 'a,
+{
+    summarize_then_embed_with_input(
+        files,
+        concurrency,
+        summarize,
+        |file_path: &str, summary: &str| {
+            Ok((
+                embedding_input_from_summary(file_path, summary),
+                EmbeddingInputMode::Summary,
+            ))
+        },
+        embed,
+    )
+}
+
+/// 同 `summarize_then_embed`，由 `input` 根据（文件路径, 新摘要）构造 Embedding 输入
+///
+/// 用于非 `summary` 输入模式：摘要照常生成并落库，向量输入改用签名或文件内容。
+/// `input` 同时返回实际使用的输入模式，记录在结果的 `embedding_mode` 中。
+pub fn summarize_then_embed_with_input<'a, S, SFut, I, E, EFut>(
+    files: Vec<String>,
+    concurrency: usize,
+    summarize: S,
+    input: I,
+    embed: E,
+) -> impl Stream<Item = PipelineFileResult> + 'a
+where
+    S: Fn(String) -> SFut + 'a,
+    SFut: Future<Output = Result<String, String>> + 'a,
+    I: Fn(&str, &str) -> Result<(String, EmbeddingInputMode), String> + Clone + 'a,
+    E: Fn(String) -> EFut + Clone + 'a,
+    EFut: Future<Output = Result<Vec<f32>, String>> + 'a,
+{
+    stream::iter(files)
+        .map(move |file_path| {
+            let summary_fut = summarize(file_path.clone());
+            let input = input.clone();
+            let embed = embed.clone();
+            async move {
+                let summary = match summary_fut.await {
+                    Ok(s) => s,
+                    Err(e) => {
+                        return PipelineFileResult {
+                            file_path,
+                            summary: None,
+                            embedding: None,
+                            embedding_mode: None,
+                            error: Some(format!("摘要生成失败：{}", e)),
+                        };
+                    }
+                };
+
+                let (input, mode) = match input(&file_path, &summary) {
+                    Ok(input) => input,
+                    Err(e) => {
+                        return PipelineFileResult {
+                            file_path,
+                            summary: Some(summary),
+                            embedding: None,
+                            embedding_mode: None,
+                            error: Some(format!("Embedding 输入构造失败：{}", e)),
+                        };
+                    }
+                };
+                match embed(input).await {
+                    Ok(embedding) => PipelineFileResult {
+                        file_path,
+                        summary: Some(summary),
+                        embedding: Some(embedding),
+                        embedding_mode: Some(mode),
+                        error: None,
+                    },
+                    Err(e) => PipelineFileResult {
+                        file_path,
+                        summary: Some(summary),
+                        embedding: None,
+                        embedding_mode: None,
+                        error: Some(format!("Embedding 生成失败：{}", e)),
+                    },
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_embeddings_use_fresh_summaries() {
+        let embed_inputs: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        let files = vec!["a.py".to_string(), "b.py".to_string(), "c.py".to_string()];
+
+        let results: Vec<PipelineFileResult> = futures::executor::block_on(
+            summarize_then_embed(
+                files,
+                2,
+                |path| async move { Ok(format!("{} 的新摘要", path)) },
+                |input| {
+                    embed_inputs.lock().unwrap().push(input.clone());
+                    async move { Ok(vec![input.len() as f32]) }
+                },
+            )
+            .collect(),
+        );
+
+        // 结果按输入顺序产出，且全部成功
+        let paths: Vec<&str> = results.iter().map(|r| r.file_path.as_str()).collect();
+        assert_eq!(paths, vec!["a.py", "b.py", "c.py"]);
+        assert!(results.iter().all(|r| r.error.is_none() && r.embedding.is_some()));
+
+        // 每个 Embedding 输入都来自刚生成的摘要
+        let inputs = embed_inputs.lock().unwrap();
+        assert_eq!(inputs.len(), 3);
+        for path in ["a.py", "b.py", "c.py"] {
+            let expected = embedding_input_from_summary(path, &format!("{} 的新摘要", path));
+            assert!(inputs.contains(&expected));
+        }
+    }
+
+    #[test]
+    fn test_summary_failure_skips_embedding() {
+        let results: Vec<PipelineFileResult> = futures::executor::block_on(
+            summarize_then_embed(
+                vec!["ok.py".to_string(), "bad.py".to_string()],
+                DEFAULT_PIPELINE_CONCURRENCY,
+                |path| async move {
+                    if path == "bad.py" {
+                        Err("HTTP 500".to_string())
+                    } else {
+                        Ok("摘要".to_string())
+                    }
+                },
+                |_| async { Ok(vec![1.0]) },
+            )
+            .collect(),
+        );
+
+        assert!(results[0].error.is_none());
+        assert!(results[1].summary.is_none());
+        assert!(results[1].embedding.is_none());
+        assert!(results[1].error.as_deref().unwrap().contains("摘要生成失败"));
+    }
+
+    #[test]
+    fn test_signatures_mode_embeds_extracted_signatures() {
+        let embed_inputs: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        let content = "import os\n\nclass UserService:\n    def get_user(self, uid):\n        return uid\n";
+        let mode = EmbeddingInputMode::parse("signatures");
+        assert_eq!(mode, EmbeddingInputMode::Signatures);
+
+        let results: Vec<PipelineFileResult> = futures::executor::block_on(
+            summarize_then_embed_with_input(
+                vec!["services/user.py".to_string()],
+                1,
+                |_| async { Ok("用户服务".to_string()) },
+                |path: &str, summary: &str| {
+                    build_embedding_input(mode, path, Some(summary), || Ok(content.to_string()))
+                },
+                |input| {
+                    embed_inputs.lock().unwrap().push(input);
+                    async { Ok(vec![1.0]) }
+                },
+            )
+            .collect(),
+        );
+
+        // 摘要照常保留，向量输入为提取出的签名而非摘要
+        assert_eq!(results[0].summary.as_deref(), Some("用户服务"));
+        assert_eq!(results[0].embedding_mode, Some(EmbeddingInputMode::Signatures));
+        let expected = analyzer::extract_signatures_from_content(content, "Python").join("\n");
+        assert!(expected.contains("UserService"));
+        assert_eq!(*embed_inputs.lock().unwrap(), vec![expected]);
+    }
+
+    #[test]
+    fn test_embedding_input_reports_content_fallback() {
+        let read = || Ok("key: value\n".to_string());
+
+        // 提取不到签名、summary 模式下没有摘要时回退到文件内容，返回的模式随之变为 content
+        let (input, mode) =
+            build_embedding_input(EmbeddingInputMode::Signatures, "config.yaml", None, read)
+                .unwrap();
+        assert_eq!(mode, EmbeddingInputMode::Content);
+        assert!(input.contains("key: value"));
+        let (_, mode) =
+            build_embedding_input(EmbeddingInputMode::Summary, "config.yaml", Some(""), read)
+                .unwrap();
+        assert_eq!(mode, EmbeddingInputMode::Content);
+
+        let (_, mode) =
+            build_embedding_input(EmbeddingInputMode::Summary, "a.py", Some("摘要"), read).unwrap();
+        assert_eq!(mode, EmbeddingInputMode::Summary);
+    }
+}
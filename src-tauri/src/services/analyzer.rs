@@ -4,12 +4,19 @@
 // ⛔ 禁止：依赖 tauri::*，直接操作数据库
 // ============================================================================
 
+use once_cell::sync::Lazy;
 use regex::Regex;
 use rayon::prelude::*;
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
-use std::path::Path;
-use walkdir::WalkDir;
+use std::path::{Component, Path, PathBuf};
+
+use crate::models::dtos::{
+    BfsScope, DirSize, ExpansionResult, ModuleAddition, ModuleMetadata, RepoCandidate, SkippedRepo,
+};
+use crate::services::{module_rewriter, scan_strategy, scanner, TEST_EXCLUDES};
+use crate::utils::path::normalize_rel_path;
+use crate::utils::pattern::wildcard_match;
 
 /// 文件索引条目（单个文件的元信息）
 #[derive(Debug, Clone)]
@@ -40,91 +47,935 @@ const IGNORED_DIRS: &[&str] = &[
     ".nuxt",
 ];
 
+/// 文件扫描配置
+///
+/// 默认值与不带配置的扫描函数行为一致：只跳过 `IGNORED_DIRS`。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanConfig {
+    /// 在 `IGNORED_DIRS` 之外追加忽略的目录名（按目录名精确匹配，如 `coverage`、`.cache`）
+    pub extra_ignored_dirs: Vec<String>,
+    /// 是否遵循项目根目录及各子目录中的 `.gitignore`（支持 `!` 反选与嵌套规则）
+    ///
+    /// 不要求项目是 git 仓库；项目根目录之外的 `.gitignore` 与全局忽略规则不生效。
+    pub respect_gitignore: bool,
+    /// 文件哈希算法
+    pub hash_algo: HashAlgo,
+    /// 是否跳过二进制文件（见 `is_probably_binary`）：不计算哈希，也不出现在扫描结果中
+    pub skip_binary: bool,
+    /// 是否跟随符号链接：指向文件的链接按目标内容计算哈希，指向目录的链接继续遍历
+    ///
+    /// 链接成环时记为扫描警告。构建前的源文件哈希扫描开启此项，与复制构建目录的行为一致。
+    pub follow_links: bool,
+}
+
+impl ScanConfig {
+    /// 由用户配置的目录名构建，去除首尾空白并丢弃空项
+    pub fn with_ignored_dirs(names: &[String]) -> Self {
+        Self {
+            extra_ignored_dirs: names
+                .iter()
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// 目录名是否需要在扫描时跳过
+    pub fn is_ignored_dir(&self, name: &str) -> bool {
+        IGNORED_DIRS.contains(&name) || self.extra_ignored_dirs.iter().any(|d| d == name)
+    }
+}
+
+/// 文件哈希算法（结果均为小写十六进制字符串）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum HashAlgo {
+    /// SHA256（默认，构建完整性校验与 `.sha256` 校验文件均使用该算法）
+    #[default]
+    Sha256,
+    /// BLAKE3，大型项目重建索引时明显快于 SHA256
+    Blake3,
+}
+
+impl HashAlgo {
+    /// 记录在文件索引中的算法标识
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Blake3 => "blake3",
+        }
+    }
+
+    /// 解析算法标识（不区分大小写），无法识别时返回 None
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "sha256" => Some(HashAlgo::Sha256),
+            "blake3" => Some(HashAlgo::Blake3),
+            _ => None,
+        }
+    }
+}
+
+/// 文件索引哈希算法的设置键（`sha256` 或 `blake3`）
+pub const HASH_ALGO_KEY: &str = "hash_algo";
+
+/// `hash_algo` 的可选值
+pub const HASH_ALGO_VALUES: &[&str] = &["sha256", "blake3"];
+
+/// 解析 `hash_algo` 设置，未设置或无法识别时使用 SHA256
+pub fn parse_hash_algo(raw: &str) -> HashAlgo {
+    if raw.trim().is_empty() {
+        return HashAlgo::default();
+    }
+    HashAlgo::parse(raw).unwrap_or_else(|| {
+        log::warn!(
+            "设置项 {} 不是支持的哈希算法，使用 sha256：{}",
+            HASH_ALGO_KEY,
+            raw
+        );
+        HashAlgo::default()
+    })
+}
+
+/// 扫描中被跳过的文件（无读权限、扫描期间被删除等）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanWarning {
+    /// 相对于项目根目录的路径
+    pub relative_path: String,
+    /// 跳过原因
+    pub error: String,
+}
+
 /// 递归遍历项目目录，计算每个文件的 SHA256 哈希
 ///
 /// 使用 rayon 并行计算文件哈希，大幅提升大型项目的扫描速度。
+/// 目录树按文件名顺序遍历，返回顺序与并行度无关，同一目录树多次扫描结果一致。
+/// 单个文件无法读取时跳过并记录警告日志，不中断整个扫描；
+/// 需要获取被跳过文件列表时使用 `scan_project_files_with_prior`，
+/// 需要复用文件索引中已记录的哈希时使用 `scan_project_files_with_config`。
 ///
 /// # 参数
 /// - `project_path`: 项目根目录路径
 ///
 /// # 返回
-/// - `Ok(Vec<FileEntry>)`: 所有文件的索引条目
-/// - `Err(String)`: 遍历失败的错误描述
+/// - `Ok(Vec<FileEntry>)`: 所有可读文件的索引条目
+/// - `Err(String)`: 项目路径不存在
 pub fn scan_project_files(project_path: &Path) -> Result<Vec<FileEntry>, String> {
+    scan_project_files_with_config(project_path, &ScanConfig::default(), &HashMap::new())
+}
+
+/// 同 `scan_project_files`，额外跳过 `config` 中追加的忽略目录
+///
+/// 大小与修改时间均与 `prior` 记录一致的文件直接复用记录的哈希，不读取文件内容；
+/// 依赖分析、报告等只需文件列表的扫描传入文件索引中的记录，未变化的项目无需重新哈希。
+pub fn scan_project_files_with_config(
+    project_path: &Path,
+    config: &ScanConfig,
+    prior: &HashMap<String, PriorFile>,
+) -> Result<Vec<FileEntry>, String> {
+    let (entries, warnings) = scan_with_prior(project_path, prior, config)?;
+    log_scan_warnings(&warnings);
+    Ok(entries)
+}
+
+/// 严格扫描：任一文件无法读取即返回错误
+///
+/// 用于构建前记录源文件哈希，跳过文件会让后续完整性校验失去意义。
+/// 跟随符号链接，与构建时复制项目的方式一致。
+pub fn scan_project_files_strict(project_path: &Path) -> Result<Vec<FileEntry>, String> {
+    let config = ScanConfig {
+        follow_links: true,
+        ..ScanConfig::default()
+    };
+    let (entries, warnings) = scan_with_prior(project_path, &HashMap::new(), &config)?;
+    match warnings.into_iter().next() {
+        Some(w) => Err(format!("读取文件失败 {}: {}", w.relative_path, w.error)),
+        None => Ok(entries),
+    }
+}
+
+/// 同 `scan_project_files`，大小与修改时间均与 `prior` 记录一致的文件直接复用记录的哈希
+///
+/// 复用时不读取文件内容；`prior` 中没有记录或元数据已变化的文件照常计算哈希。
+/// 与 `scan_project_files_incremental` 不同，目录树始终完整遍历，无需目录状态。
+///
+/// # 返回
+/// - `Ok((entries, warnings))`: 可读文件的索引条目，以及被跳过文件的原因
+/// - `Err(String)`: 项目路径不存在
+pub fn scan_project_files_with_prior(
+    project_path: &Path,
+    prior: &HashMap<String, PriorFile>,
+) -> Result<(Vec<FileEntry>, Vec<ScanWarning>), String> {
+    scan_with_prior(project_path, prior, &ScanConfig::default())
+}
+
+/// 带复用记录与扫描配置的扫描实现
+fn scan_with_prior(
+    project_path: &Path,
+    prior: &HashMap<String, PriorFile>,
+    config: &ScanConfig,
+) -> Result<(Vec<FileEntry>, Vec<ScanWarning>), String> {
     if !project_path.exists() {
         return Err(format!("项目路径不存在：{}", project_path.display()));
     }
 
     // 第一步：收集所有文件路径及元数据（单线程遍历目录树）
-    let mut file_paths: Vec<(String, std::path::PathBuf, u64, u64)> = Vec::new();
+    let mut warnings = Vec::new();
+    let file_paths = collect_project_files_lenient(project_path, config, &mut warnings);
+    let file_paths = drop_binary_files(file_paths, prior, config);
 
-    for entry in WalkDir::new(project_path)
-        .into_iter()
-        .filter_entry(|e| {
+    // 第二步：使用 rayon 并行计算哈希，元数据未变且算法相同的文件复用记录的哈希
+    let results: Vec<Result<FileEntry, ScanWarning>> = file_paths
+        .par_iter()
+        .map(|(relative, abs_path, file_size, mtime)| {
+            let hash = match prior.get(relative) {
+                Some(p) if p.reusable(*file_size, *mtime, config.hash_algo) => p.file_hash.clone(),
+                _ => compute_file_hash_with(abs_path, config.hash_algo).map_err(|error| {
+                    ScanWarning {
+                        relative_path: relative.clone(),
+                        error,
+                    }
+                })?,
+            };
+            Ok(FileEntry {
+                relative_path: relative.clone(),
+                file_hash: hash,
+                file_size: *file_size,
+                mtime: *mtime,
+            })
+        })
+        .collect();
+
+    let mut entries = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok(entry) => entries.push(entry),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    Ok((entries, warnings))
+}
+
+/// 启用 `skip_binary` 时并行剔除二进制文件
+///
+/// 记录可复用且由启用 `skip_binary` 的扫描写入（`binary_checked`）的文件已通过检测，不再读取；
+/// 其余文件（包括未做检测的旧记录）读取头部重新判断。
+fn drop_binary_files(
+    file_paths: Vec<(String, PathBuf, u64, u64)>,
+    prior: &HashMap<String, PriorFile>,
+    config: &ScanConfig,
+) -> Vec<(String, PathBuf, u64, u64)> {
+    if !config.skip_binary {
+        return file_paths;
+    }
+    let total = file_paths.len();
+    let kept: Vec<_> = file_paths
+        .into_par_iter()
+        .filter(|(relative, abs_path, file_size, mtime)| {
+            let known_text = prior.get(relative).is_some_and(|p| {
+                p.binary_checked && p.reusable(*file_size, *mtime, config.hash_algo)
+            });
+            known_text || !is_probably_binary(abs_path)
+        })
+        .collect();
+    if kept.len() < total {
+        log::info!("扫描时跳过 {} 个二进制文件", total - kept.len());
+    }
+    kept
+}
+
+/// 将被跳过的文件逐条写入警告日志
+fn log_scan_warnings(warnings: &[ScanWarning]) {
+    for w in warnings {
+        log::warn!("扫描时跳过文件 [{}]: {}", w.relative_path, w.error);
+    }
+}
+
+/// 遍历项目目录（跳过忽略目录），收集每个文件的相对路径、绝对路径、大小与修改时间
+///
+/// 无法访问的文件或目录跳过并记录警告日志。
+fn collect_project_files(
+    project_path: &Path,
+) -> Result<Vec<(String, PathBuf, u64, u64)>, String> {
+    collect_project_files_with_config(project_path, &ScanConfig::default())
+}
+
+/// 同 `collect_project_files`，额外跳过 `config` 中追加的忽略目录
+fn collect_project_files_with_config(
+    project_path: &Path,
+    config: &ScanConfig,
+) -> Result<Vec<(String, PathBuf, u64, u64)>, String> {
+    let mut warnings = Vec::new();
+    let file_paths = collect_project_files_lenient(project_path, config, &mut warnings);
+    log_scan_warnings(&warnings);
+    Ok(file_paths)
+}
+
+/// 同 `collect_project_files_with_config`，无法访问的文件或目录记入 `warnings`
+fn collect_project_files_lenient(
+    project_path: &Path,
+    config: &ScanConfig,
+    warnings: &mut Vec<ScanWarning>,
+) -> Vec<(String, PathBuf, u64, u64)> {
+    let mut file_paths: Vec<(String, PathBuf, u64, u64)> = Vec::new();
+    let relative_of = |path: &Path| {
+        path.strip_prefix(project_path)
+            .map(normalize_rel_path)
+            .unwrap_or_else(|_| path.to_string_lossy().to_string())
+    };
+
+    // 关闭 ignore 的默认过滤（隐藏文件、.ignore 等），只按配置启用 .gitignore
+    let filter_config = config.clone();
+    let walker = ignore::WalkBuilder::new(project_path)
+        .standard_filters(false)
+        .git_ignore(config.respect_gitignore)
+        .require_git(false)
+        .parents(false)
+        .follow_links(config.follow_links)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |e| {
             // 过滤掉忽略目录
-            if e.file_type().is_dir() {
+            if e.file_type().is_some_and(|t| t.is_dir()) {
                 if let Some(name) = e.file_name().to_str() {
-                    return !IGNORED_DIRS.contains(&name);
+                    return !filter_config.is_ignored_dir(name);
                 }
             }
             true
         })
-    {
-        let entry = entry.map_err(|e| format!("遍历文件失败：{}", e))?;
+        .build();
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                warnings.push(ScanWarning {
+                    relative_path: walk_error_path(&e).map(relative_of).unwrap_or_default(),
+                    error: format!("遍历文件失败：{}", e),
+                });
+                continue;
+            }
+        };
+
+        // .gitignore 中无法解析的规则被跳过，其余规则照常生效
+        if let Some(e) = entry.error() {
+            warnings.push(ScanWarning {
+                relative_path: relative_of(entry.path()),
+                error: format!("解析 .gitignore 失败：{}", e),
+            });
+        }
 
         // 只处理文件，跳过目录
-        if !entry.file_type().is_file() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
             continue;
         }
 
         let abs_path = entry.path().to_path_buf();
 
-        // 计算相对路径
-        let relative = abs_path
-            .strip_prefix(project_path)
-            .map_err(|e| format!("计算相对路径失败：{}", e))?
-            .to_string_lossy()
-            .replace('\\', "/"); // 统一使用正斜杠
+        // 计算相对路径（统一使用正斜杠）
+        let relative = relative_of(&abs_path);
 
         // 读取文件元数据（大小 + 修改时间），用于增量哈希缓存
-        let metadata = std::fs::metadata(&abs_path)
-            .map_err(|e| format!("读取文件元数据失败 {}: {}", abs_path.display(), e))?;
-        let file_size = metadata.len();
-        let mtime = metadata
-            .modified()
+        match read_file_meta(&abs_path) {
+            Ok((file_size, mtime)) => file_paths.push((relative, abs_path, file_size, mtime)),
+            Err(error) => warnings.push(ScanWarning {
+                relative_path: relative,
+                error,
+            }),
+        }
+    }
+
+    file_paths
+}
+
+/// 取出遍历错误关联的路径（无路径信息时返回 None）
+fn walk_error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            walk_error_path(err)
+        }
+        _ => None,
+    }
+}
+
+/// 根目录下直接存放的文件在体积分布中的归属名称
+pub const ROOT_FILES_GROUP: &str = ".";
+
+/// 按顶层目录统计项目体积分布
+///
+/// 与 `scan_project_files` 使用相同的遍历与忽略规则，但只读取文件元数据、不计算哈希。
+/// 根目录下直接存放的文件归入 `ROOT_FILES_GROUP`。
+///
+/// # 返回
+/// - 各顶层目录的累计字节数与文件数，按字节数降序（相同时按名称升序）
+pub fn project_size_breakdown(project_path: &Path) -> Result<Vec<DirSize>, String> {
+    if !project_path.is_dir() {
+        return Err(format!("项目路径不存在：{}", project_path.display()));
+    }
+
+    let mut groups: HashMap<String, (u64, usize)> = HashMap::new();
+    for (relative, _, file_size, _) in collect_project_files(project_path)? {
+        let name = match relative.split_once('/') {
+            Some((top, _)) => top.to_string(),
+            None => ROOT_FILES_GROUP.to_string(),
+        };
+        let group = groups.entry(name).or_insert((0, 0));
+        group.0 += file_size;
+        group.1 += 1;
+    }
+
+    let mut result: Vec<DirSize> = groups
+        .into_iter()
+        .map(|(name, (total_bytes, file_count))| DirSize {
+            name,
+            total_bytes,
+            file_count,
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.total_bytes
+            .cmp(&a.total_bytes)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(result)
+}
+
+/// 路由注册标记：FastAPI `include_router`、Vue Router 路由表的 `path:`
+const ROUTE_MARKERS: &[&str] = &["include_router", "path:"];
+
+/// 提取模块目录下每个模块的概况：文件数、体积、路由数、是否含测试
+///
+/// 模块列表来自 `scanner::scan_modules_dir`，文件遍历沿用 `scan_project_files` 的忽略规则。
+///
+/// # 参数
+/// - `project_path`: 项目根目录
+/// - `modules_dir`: 模块目录（相对路径，如 `modules`、`src/views`）
+pub fn extract_module_metadata(
+    project_path: &Path,
+    modules_dir: &str,
+) -> Result<Vec<ModuleMetadata>, String> {
+    let modules = scanner::scan_modules_dir(&project_path.join(modules_dir))
+        .map_err(|e| e.to_string())?;
+
+    let mut result = Vec::with_capacity(modules.len());
+    for module in modules {
+        let mut metadata = ModuleMetadata {
+            name: module.name,
+            file_count: 0,
+            total_bytes: 0,
+            route_count: 0,
+            has_tests: false,
+        };
+        for (relative, abs_path, file_size, _) in collect_project_files(Path::new(&module.path))? {
+            metadata.file_count += 1;
+            metadata.total_bytes += file_size;
+            metadata.has_tests |= is_test_path(&relative);
+            if is_code_file(&relative, &[]) {
+                if let Ok(content) = std::fs::read_to_string(&abs_path) {
+                    metadata.route_count += ROUTE_MARKERS
+                        .iter()
+                        .map(|marker| content.matches(marker).count())
+                        .sum::<usize>();
+                }
+            }
+        }
+        result.push(metadata);
+    }
+    Ok(result)
+}
+
+/// 判断相对路径是否为测试文件（规则与构建时的 `TEST_EXCLUDES` 一致）
+fn is_test_path(relative: &str) -> bool {
+    let mut segments = relative.split('/').peekable();
+    while let Some(segment) = segments.next() {
+        let is_file = segments.peek().is_none();
+        let matched = TEST_EXCLUDES.iter().any(|pattern| {
+            if pattern.contains('*') {
+                is_file && wildcard_match(pattern, segment)
+            } else {
+                !is_file && segment == *pattern
+            }
+        });
+        if matched {
+            return true;
+        }
+    }
+    false
+}
+
+/// `detect_tech_stack` 识别出的框架 → 内置技术栈标识（按优先级排列）
+const BUILTIN_STACK_LABELS: &[(&str, &str)] = &[("FastAPI", "fastapi"), ("Vue", "vue3")];
+
+/// 枚举 `parent_dir` 的一级子目录，识别可批量导入为项目的代码仓库
+///
+/// 技术栈由 `detect_tech_stack` 推断并映射到内置技术栈；无法映射时使用 `tech_stack_hint`。
+/// 既没有 `.git` 也识别不出任何技术栈的目录视为非仓库跳过，隐藏目录直接忽略。
+///
+/// # 返回
+/// - `Ok((candidates, skipped))`: 候选仓库（按名称排序）与被跳过的目录
+/// - `Err(String)`: `parent_dir` 不存在或无法读取
+pub fn discover_project_repos(
+    parent_dir: &Path,
+    tech_stack_hint: Option<&str>,
+) -> Result<(Vec<RepoCandidate>, Vec<SkippedRepo>), String> {
+    let read_dir = std::fs::read_dir(parent_dir)
+        .map_err(|e| format!("读取目录失败 {}: {}", parent_dir.display(), e))?;
+    let mut dirs: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            !path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    dirs.sort();
+
+    let hint = tech_stack_hint.map(str::trim).filter(|h| !h.is_empty());
+    let mut candidates = Vec::new();
+    let mut skipped = Vec::new();
+    for dir in dirs {
+        let path = dir.to_string_lossy().to_string();
+        let entries: Vec<FileEntry> = collect_project_files(&dir)?
+            .into_iter()
+            .map(|(relative_path, _, file_size, mtime)| FileEntry {
+                relative_path,
+                file_hash: String::new(),
+                file_size,
+                mtime,
+            })
+            .collect();
+        let labels = detect_tech_stack(&dir, &entries);
+        if labels.is_empty() && !dir.join(".git").exists() {
+            skipped.push(SkippedRepo {
+                path,
+                reason: "不是代码仓库".to_string(),
+            });
+            continue;
+        }
+        let detected = BUILTIN_STACK_LABELS
+            .iter()
+            .find(|(label, _)| labels.iter().any(|l| l == label))
+            .map(|(_, id)| *id);
+        let Some(tech_stack) = detected.or(hint) else {
+            skipped.push(SkippedRepo {
+                path,
+                reason: format!("无法识别技术栈（检测到：{}）", labels.join(", ")),
+            });
+            continue;
+        };
+        let modules_dir = scan_strategy::get_scanner(tech_stack)
             .ok()
-            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+            .and_then(|scanner| scanner.detect_modules_dir(&dir))
+            .unwrap_or_default();
+        candidates.push(RepoCandidate {
+            name: dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path,
+            tech_stack: tech_stack.to_string(),
+            modules_dir,
+        });
+    }
+    Ok((candidates, skipped))
+}
+
+/// 读取文件大小与修改时间（Unix 时间戳秒数）
+fn read_file_meta(abs_path: &Path) -> Result<(u64, u64), String> {
+    let metadata = std::fs::metadata(abs_path)
+        .map_err(|e| format!("读取文件元数据失败 {}: {}", abs_path.display(), e))?;
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((metadata.len(), mtime))
+}
+
+/// 读取目录修改时间（Unix 时间戳纳秒数），目录不存在时返回 None
+///
+/// 使用纳秒精度：同一秒内的新增/删除也能被识别。
+fn read_dir_mtime(abs_dir: &Path) -> Option<u64> {
+    std::fs::metadata(abs_dir)
+        .ok()
+        .filter(|m| m.is_dir())
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+}
+
+/// 相对路径的父目录（根目录用空字符串表示）
+fn parent_rel(path: &str) -> &str {
+    path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
+
+/// 拼接目录相对路径与子项名称
+fn join_rel(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// 上次扫描记录的文件状态（用于增量扫描复用哈希）
+#[derive(Debug, Clone)]
+pub struct PriorFile {
+    /// 上次记录的哈希
+    pub file_hash: String,
+    /// 上次记录的文件大小
+    pub file_size: u64,
+    /// 上次记录的修改时间（秒）
+    pub mtime: u64,
+    /// 计算 `file_hash` 所用的算法
+    pub hash_algo: HashAlgo,
+    /// 记录是否由启用 `skip_binary` 的扫描写入（即已确认不是二进制文件）
+    pub binary_checked: bool,
+}
+
+impl PriorFile {
+    /// 大小、修改时间与哈希算法均一致时，记录的哈希可直接复用
+    fn reusable(&self, file_size: u64, mtime: u64, hash_algo: HashAlgo) -> bool {
+        self.file_size == file_size && self.mtime == mtime && self.hash_algo == hash_algo
+    }
+}
+
+/// 增量扫描结果
+#[derive(Debug)]
+pub struct IncrementalScan {
+    /// 所有文件的索引条目
+    pub files: Vec<FileEntry>,
+    /// 本次扫描后各目录的修改时间（相对路径 → 纳秒时间戳，根目录为空字符串）
+    pub dir_mtimes: HashMap<String, u64>,
+    /// 实际重新读取目录内容的目录数（遵循 `.gitignore` 全量遍历时为含文件的目录数）
+    pub walked_dirs: usize,
+    /// 被跳过的文件或目录及原因（无读权限、扫描期间被删除等）
+    pub warnings: Vec<ScanWarning>,
+}
+
+/// 基于目录修改时间的增量扫描
+///
+/// 目录的 mtime 仅在其直接子项增删/重命名时变化：mtime 未变的目录不再读取目录内容，
+/// 直接复用上次记录的文件与子目录列表，只对这些文件做一次 stat 以发现原地修改；
+/// mtime 变化的目录重新读取。文件大小与修改时间均未变时复用上次的哈希，
+/// 因此只有变化目录中的新文件和被修改的文件需要重新计算哈希。
+///
+/// `prior_dirs` 为空（首次扫描或尚无目录状态）时退化为全量扫描。
+///
+/// # 参数
+/// - `project_path`: 扫描根目录
+/// - `prior_dirs`: 上次扫描记录的目录修改时间
+/// - `prior_files`: 上次扫描记录的文件状态（键为相对路径）
+pub fn scan_project_files_incremental(
+    project_path: &Path,
+    prior_dirs: &HashMap<String, u64>,
+    prior_files: &HashMap<String, PriorFile>,
+) -> Result<IncrementalScan, String> {
+    scan_project_files_incremental_with_progress(
+        project_path,
+        prior_dirs,
+        prior_files,
+        &ScanConfig::default(),
+        &|_, _| {},
+    )
+}
 
-        file_paths.push((relative, abs_path, file_size, mtime));
+/// 增量扫描，哈希阶段每处理完一个文件调用一次 `on_progress(已完成数, 文件总数)`
+///
+/// 哈希并行计算，但回调串行执行且已完成数严格递增，最后一次回调的已完成数等于文件总数。
+/// 与 `scan_project_files_with_prior` 一致，无法读取的文件或目录跳过并记入 `warnings`，不中断扫描。
+/// `prior_dirs` 须由相同的 `config` 扫描得到：未变化目录直接复用上次的子目录列表，
+/// 不会发现此前被忽略、现已取消忽略的子目录。
+/// 启用 `respect_gitignore` 时忽略 `prior_dirs` 并全量遍历，返回的 `dir_mtimes` 为空：
+/// 原地修改 `.gitignore` 不改变目录 mtime，复用目录状态会沿用过期的忽略规则。
+pub fn scan_project_files_incremental_with_progress(
+    project_path: &Path,
+    prior_dirs: &HashMap<String, u64>,
+    prior_files: &HashMap<String, PriorFile>,
+    config: &ScanConfig,
+    on_progress: &(dyn Fn(usize, usize) + Sync),
+) -> Result<IncrementalScan, String> {
+    if !project_path.exists() {
+        return Err(format!("项目路径不存在：{}", project_path.display()));
     }
 
-    // 第二步：使用 rayon 并行计算所有文件的 SHA256 哈希
-    let entries: Result<Vec<FileEntry>, String> = file_paths
+    // 第一步：收集文件路径及元数据（遵循 .gitignore 时全量遍历，不记录目录状态）
+    let mut warnings = Vec::new();
+    let (file_paths, dir_mtimes, walked_dirs) = if config.respect_gitignore {
+        let file_paths = collect_project_files_lenient(project_path, config, &mut warnings);
+        let walked_dirs = file_paths
+            .iter()
+            .map(|(relative, _, _, _)| parent_rel(relative))
+            .collect::<HashSet<_>>()
+            .len();
+        (file_paths, HashMap::new(), walked_dirs)
+    } else {
+        walk_changed_dirs(project_path, prior_dirs, prior_files, config, &mut warnings)
+    };
+
+    let file_paths = drop_binary_files(file_paths, prior_files, config);
+
+    // 并行计算哈希：大小、修改时间与算法均未变的文件直接复用上次的哈希
+    let total = file_paths.len();
+    let done = std::sync::Mutex::new(0usize);
+    let results: Vec<Result<FileEntry, ScanWarning>> = file_paths
         .par_iter()
         .map(|(relative, abs_path, file_size, mtime)| {
-            let hash = compute_file_hash(abs_path)?;
+            let file_hash = match prior_files.get(relative) {
+                Some(prior) if prior.reusable(*file_size, *mtime, config.hash_algo) => {
+                    Ok(prior.file_hash.clone())
+                }
+                _ => compute_file_hash_with(abs_path, config.hash_algo),
+            };
+            // 计数与回调在同一把锁内完成，保证回调看到的进度单调递增（跳过的文件同样计入）
+            if let Ok(mut done) = done.lock() {
+                *done += 1;
+                on_progress(*done, total);
+            }
             Ok(FileEntry {
                 relative_path: relative.clone(),
-                file_hash: hash,
+                file_hash: file_hash.map_err(|error| ScanWarning {
+                    relative_path: relative.clone(),
+                    error,
+                })?,
                 file_size: *file_size,
                 mtime: *mtime,
             })
         })
         .collect();
 
-    entries
+    let mut files = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok(entry) => files.push(entry),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+    log_scan_warnings(&warnings);
+
+    Ok(IncrementalScan {
+        files,
+        dir_mtimes,
+        walked_dirs,
+        warnings,
+    })
+}
+
+/// 增量扫描的目录遍历阶段：未变化目录复用上次的子项列表，变化目录重新读取
+///
+/// 无法读取的目录或文件跳过并记入 `warnings`；读取失败的目录不记录修改时间，下次扫描重新读取。
+///
+/// # 返回
+/// - `(文件列表, 各目录修改时间, 重新读取的目录数)`
+#[allow(clippy::type_complexity)]
+fn walk_changed_dirs(
+    project_path: &Path,
+    prior_dirs: &HashMap<String, u64>,
+    prior_files: &HashMap<String, PriorFile>,
+    config: &ScanConfig,
+    warnings: &mut Vec<ScanWarning>,
+) -> (Vec<(String, PathBuf, u64, u64)>, HashMap<String, u64>, usize) {
+    // 按父目录归类上次的子目录与文件，供未变化目录直接复用
+    let mut prior_children: HashMap<&str, (Vec<&str>, Vec<&str>)> = HashMap::new();
+    for dir in prior_dirs.keys().filter(|d| !d.is_empty()) {
+        prior_children.entry(parent_rel(dir)).or_default().0.push(dir);
+    }
+    for file in prior_files.keys() {
+        prior_children.entry(parent_rel(file)).or_default().1.push(file);
+    }
+
+    let mut dir_mtimes = HashMap::new();
+    let mut walked_dirs = 0usize;
+    let mut file_paths: Vec<(String, PathBuf, u64, u64)> = Vec::new();
+    let mut stack = vec![String::new()];
+
+    while let Some(rel_dir) = stack.pop() {
+        let abs_dir = project_path.join(&rel_dir);
+        // 上次记录的子目录可能已被删除
+        let Some(dir_mtime) = read_dir_mtime(&abs_dir) else {
+            continue;
+        };
+        let unchanged = prior_dirs.get(&rel_dir) == Some(&dir_mtime);
+
+        if unchanged {
+            dir_mtimes.insert(rel_dir.clone(), dir_mtime);
+            if let Some((dirs, files)) = prior_children.get(rel_dir.as_str()) {
+                stack.extend(dirs.iter().map(|d| d.to_string()));
+                for file in files {
+                    let abs_path = project_path.join(file);
+                    if let Ok((file_size, mtime)) = read_file_meta(&abs_path) {
+                        file_paths.push((file.to_string(), abs_path, file_size, mtime));
+                    }
+                }
+            }
+            continue;
+        }
+
+        walked_dirs += 1;
+        let read_dir = match std::fs::read_dir(&abs_dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                warnings.push(ScanWarning {
+                    relative_path: rel_dir.clone(),
+                    error: format!("遍历文件失败：{}: {}", abs_dir.display(), e),
+                });
+                continue;
+            }
+        };
+        dir_mtimes.insert(rel_dir.clone(), dir_mtime);
+        for entry in read_dir {
+            let (entry, file_type) = match entry.and_then(|e| e.file_type().map(|t| (e, t))) {
+                Ok(pair) => pair,
+                Err(e) => {
+                    warnings.push(ScanWarning {
+                        relative_path: rel_dir.clone(),
+                        error: format!("遍历文件失败：{}", e),
+                    });
+                    continue;
+                }
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let rel = join_rel(&rel_dir, &name);
+            if file_type.is_dir() {
+                if !config.is_ignored_dir(&name) {
+                    stack.push(rel);
+                }
+            } else if file_type.is_file() {
+                let abs_path = entry.path();
+                match read_file_meta(&abs_path) {
+                    Ok((file_size, mtime)) => file_paths.push((rel, abs_path, file_size, mtime)),
+                    Err(error) => warnings.push(ScanWarning {
+                        relative_path: rel,
+                        error,
+                    }),
+                }
+            }
+        }
+    }
+
+    (file_paths, dir_mtimes, walked_dirs)
+}
+
+/// 解析分析/构建的实际根目录：`project_path` + 可选的 `sub_path`
+///
+/// 用于 monorepo 只分析/构建某个子目录（如 `backend`）的场景。
+/// 与 `analyze_file_summary` 一致拒绝含 `..` 的路径，并校验规范化后的结果仍位于项目目录内。
+///
+/// # 返回
+/// - `sub_path` 为空时返回 `project_path` 本身
+/// - 子目录不存在、越界或不是目录时返回错误
+pub fn resolve_scan_root(project_path: &Path, sub_path: Option<&str>) -> Result<PathBuf, String> {
+    let sub = sub_path.map(str::trim).unwrap_or("");
+    if sub.is_empty() {
+        return Ok(project_path.to_path_buf());
+    }
+
+    // 路径安全校验：禁止 .. 遍历和绝对路径
+    let sub_rel = Path::new(sub);
+    if sub_rel.is_absolute()
+        || sub_rel
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("非法子目录路径（包含 .. 或为绝对路径）: {}", sub));
+    }
+
+    let root = project_path.join(sub_rel);
+    if !root.is_dir() {
+        return Err(format!("子目录不存在：{}", root.display()));
+    }
+
+    // 解析符号链接后再次确认仍在项目目录内
+    let canonical_project = project_path
+        .canonicalize()
+        .map_err(|e| format!("解析项目路径失败 {}: {}", project_path.display(), e))?;
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|e| format!("解析子目录失败 {}: {}", root.display(), e))?;
+    if !canonical_root.starts_with(&canonical_project) {
+        return Err(format!("子目录超出项目范围：{}", sub));
+    }
+
+    Ok(root)
+}
+
+/// 子目录扫描结果写入项目级索引时使用的路径前缀（如 `backend/`，未指定子目录时为空）
+///
+/// 扫描得到的路径相对于子目录，加上此前缀后才与扫描整个项目得到的路径一致。
+pub fn scan_root_prefix(sub_path: Option<&str>) -> String {
+    let sub = sub_path.map(str::trim).unwrap_or("").replace('\\', "/");
+    let parts: Vec<String> = Path::new(&sub)
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", parts.join("/"))
+    }
 }
 
 /// 计算单个文件的 SHA256 哈希值
-fn compute_file_hash(path: &Path) -> Result<String, String> {
+pub fn compute_file_hash(path: &Path) -> Result<String, String> {
+    compute_file_hash_with(path, HashAlgo::Sha256)
+}
+
+/// 二进制检测读取的文件头部字节数
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// 头部非法 UTF-8 字节占比超过该百分比即视为二进制文件
+const BINARY_INVALID_UTF8_PERCENT: usize = 10;
+
+/// 根据文件头部判断是否为二进制文件（图片、动态库、PDF 等）
+///
+/// 只读取前 `BINARY_SNIFF_BYTES` 字节：含 NUL 字节，或非法 UTF-8 字节占比超过
+/// `BINARY_INVALID_UTF8_PERCENT` 时视为二进制。截断在头部末尾的多字节字符不计为非法。
+/// 文件无法读取时返回 false，由后续读取环节报告错误。
+pub fn is_probably_binary(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    let read = std::fs::File::open(path)
+        .and_then(|f| f.take(BINARY_SNIFF_BYTES as u64).read_to_end(&mut head));
+    if read.is_err() || head.is_empty() {
+        return false;
+    }
+    if head.contains(&0) {
+        return true;
+    }
+
+    let mut invalid = 0usize;
+    let mut rest = head.as_slice();
+    while let Err(e) = std::str::from_utf8(rest) {
+        // error_len 为 None：头部末尾被截断的多字节字符
+        let Some(len) = e.error_len() else {
+            break;
+        };
+        invalid += len;
+        rest = &rest[e.valid_up_to() + len..];
+    }
+    invalid * 100 > head.len() * BINARY_INVALID_UTF8_PERCENT
+}
+
+/// 使用指定算法计算单个文件的哈希值（小写十六进制）
+pub fn compute_file_hash_with(path: &Path, algo: HashAlgo) -> Result<String, String> {
     let content = std::fs::read(path)
         .map_err(|e| format!("读取文件失败 {}: {}", path.display(), e))?;
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    let result = hasher.finalize();
-    Ok(format!("{:x}", result))
+    match algo {
+        HashAlgo::Sha256 => {
+            let mut hasher = Sha256::new();
+            hasher.update(&content);
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+        HashAlgo::Blake3 => Ok(blake3::hash(&content).to_hex().to_string()),
+    }
 }
 
 // ============================================================================
@@ -140,6 +991,36 @@ pub struct DependencyEdge {
     pub target: String,
 }
 
+/// 依赖解析中发现的源码问题（如相对导入层级超出项目根目录）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyWarning {
+    /// 源文件相对路径
+    pub file: String,
+    /// 问题所在行号（从 1 开始）
+    pub line: usize,
+    /// 问题描述
+    pub message: String,
+}
+
+// 依赖解析正则：进程内只编译一次，供所有调用复用
+
+/// JS/TS import 正则：匹配 import ... from '...' 和 require('...')
+static RE_JS_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?:import\s+.*?\s+from\s+['"]([^'"]+)['"]|require\s*\(\s*['"]([^'"]+)['"]\s*\))"#,
+    )
+    .expect("JS import 正则编译失败")
+});
+
+/// Python from import 正则：匹配 from xxx import ...（相对和绝对）
+static RE_PY_FROM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^from\s+(\.{0,3}\w[\w.]*|\.+)\s+import"#).expect("Python from 正则编译失败")
+});
+
+/// Python import 正则：匹配 import xxx（绝对导入）
+static RE_PY_IMPORT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^import\s+([\w][\w.]*)"#).expect("Python import 正则编译失败"));
+
 /// 从项目文件中提取 import 依赖关系
 ///
 /// 支持的语法：
@@ -158,30 +1039,40 @@ pub fn extract_dependencies(
     project_path: &Path,
     file_paths: &[String],
 ) -> Result<Vec<DependencyEdge>, String> {
-    // 构建已知文件集合，用于验证目标是否存在
-    let known_files: HashSet<&str> = file_paths.iter().map(|s| s.as_str()).collect();
-
-    // JS/TS import 正则：匹配 import ... from '...' 和 require('...')
-    let re_js_import = Regex::new(
-        r#"(?:import\s+.*?\s+from\s+['"]([^'"]+)['"]|require\s*\(\s*['"]([^'"]+)['"]\s*\))"#,
-    )
-    .map_err(|e| format!("正则编译失败：{}", e))?;
+    extract_dependencies_with_extensions(project_path, file_paths, &[])
+}
 
-    // Python from import 正则：匹配 from xxx import ...（相对和绝对）
-    let re_py_from = Regex::new(r#"^from\s+(\.{0,3}\w[\w.]*|\.+)\s+import"#)
-        .map_err(|e| format!("正则编译失败：{}", e))?;
+/// 提取依赖关系，`extra_extensions` 为用户追加的代码文件扩展名（见 `parse_code_extensions`）
+pub fn extract_dependencies_with_extensions(
+    project_path: &Path,
+    file_paths: &[String],
+    extra_extensions: &[String],
+) -> Result<Vec<DependencyEdge>, String> {
+    extract_dependencies_with_warnings(project_path, file_paths, extra_extensions)
+        .map(|(edges, _)| edges)
+}
 
-    // Python import 正则：匹配 import xxx（绝对导入）
-    let re_py_import = Regex::new(r#"^import\s+([\w][\w.]*)"#)
-        .map_err(|e| format!("正则编译失败：{}", e))?;
+/// 提取依赖关系，并返回解析中发现的源码问题
+///
+/// 目前检测 Python 相对导入的点数超出文件所在目录深度（如项目根下一级目录中的
+/// `from ...x import y`），这类导入在运行时会抛出 ImportError。
+pub fn extract_dependencies_with_warnings(
+    project_path: &Path,
+    file_paths: &[String],
+    extra_extensions: &[String],
+) -> Result<(Vec<DependencyEdge>, Vec<DependencyWarning>), String> {
+    // 构建已知文件集合，用于验证目标是否存在
+    let known_files: HashSet<&str> = file_paths.iter().map(|s| s.as_str()).collect();
+    let namespace_pkgs = collect_namespace_packages(file_paths);
 
     let mut edges = Vec::new();
+    let mut warnings = Vec::new();
 
     for source_path in file_paths {
         let abs_path = project_path.join(source_path);
 
         // 只处理代码文件
-        if !is_code_file(source_path) {
+        if !is_code_file(source_path, extra_extensions) {
             continue;
         }
 
@@ -194,10 +1085,17 @@ pub fn extract_dependencies(
         // 获取源文件所在目录（相对路径）
         let source_dir = Path::new(source_path)
             .parent()
-            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .map(normalize_rel_path)
             .unwrap_or_default();
 
-        for line in content.lines() {
+        // 源文件所在目录的层级数（项目根目录下的文件为 0）
+        let source_depth = if source_dir.is_empty() {
+            0
+        } else {
+            source_dir.split('/').count()
+        };
+
+        for (index, line) in content.lines().enumerate() {
             let trimmed = line.trim();
 
             // 跳过注释行
@@ -206,7 +1104,7 @@ pub fn extract_dependencies(
             }
 
             // JS/TS import 解析
-            if let Some(caps) = re_js_import.captures(trimmed) {
+            if let Some(caps) = RE_JS_IMPORT.captures(trimmed) {
                 let raw_path = caps
                     .get(1)
                     .or_else(|| caps.get(2))
@@ -227,13 +1125,31 @@ pub fn extract_dependencies(
             }
 
             // Python from import 解析（相对 + 绝对）
-            if let Some(caps) = re_py_from.captures(trimmed) {
+            if let Some(caps) = RE_PY_FROM.captures(trimmed) {
                 let module_path = &caps[1];
+                let imported = py_imported_names(trimmed);
                 if module_path.starts_with('.') {
+                    // 相对导入：每多一个点向上一级，超出项目根目录时 Python 会报错
+                    let levels_up = module_path.chars().take_while(|c| *c == '.').count() - 1;
+                    if levels_up > source_depth {
+                        warnings.push(DependencyWarning {
+                            file: source_path.clone(),
+                            line: index + 1,
+                            message: format!(
+                                "相对导入 {} 需向上 {} 级目录，超出项目根目录（文件位于第 {} 级）",
+                                module_path, levels_up, source_depth
+                            ),
+                        });
+                        continue;
+                    }
                     // 相对导入：from .xxx import / from ..xxx import
-                    if let Some(target) =
-                        resolve_py_import(&source_dir, module_path, &known_files)
-                    {
+                    if let Some(target) = resolve_py_import(
+                        &source_dir,
+                        module_path,
+                        &imported,
+                        &known_files,
+                        &namespace_pkgs,
+                    ) {
                         edges.push(DependencyEdge {
                             source: source_path.clone(),
                             target,
@@ -241,9 +1157,12 @@ pub fn extract_dependencies(
                     }
                 } else {
                     // 绝对导入：from api.v1.module_system.dict.model import
-                    if let Some(target) =
-                        resolve_py_absolute_import(module_path, &known_files)
-                    {
+                    if let Some(target) = resolve_py_absolute_import(
+                        module_path,
+                        &imported,
+                        &known_files,
+                        &namespace_pkgs,
+                    ) {
                         edges.push(DependencyEdge {
                             source: source_path.clone(),
                             target,
@@ -253,11 +1172,11 @@ pub fn extract_dependencies(
             }
 
             // Python import xxx 解析（绝对导入）
-            if let Some(caps) = re_py_import.captures(trimmed) {
+            if let Some(caps) = RE_PY_IMPORT.captures(trimmed) {
                 let module_path = &caps[1];
                 // 排除标准库和第三方包（简单启发式：只匹配项目内存在的路径）
                 if let Some(target) =
-                    resolve_py_absolute_import(module_path, &known_files)
+                    resolve_py_absolute_import(module_path, &[], &known_files, &namespace_pkgs)
                 {
                     edges.push(DependencyEdge {
                         source: source_path.clone(),
@@ -268,16 +1187,69 @@ pub fn extract_dependencies(
         }
     }
 
-    Ok(edges)
+    // 命名空间包回退可能将包内文件指向自身所在包的首个模块，去掉自环
+    edges.retain(|edge| edge.source != edge.target);
+
+    Ok((edges, warnings))
 }
 
-/// 判断是否为代码文件（根据扩展名）
-fn is_code_file(path: &str) -> bool {
-    let code_exts = [
-        ".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs",
-        ".py", ".rs", ".vue", ".svelte",
-    ];
-    code_exts.iter().any(|ext| path.ends_with(ext))
+/// 收集可作为命名空间包（PEP 420，无 `__init__.py`）解析目标的目录
+///
+/// 返回 "目录 → 目录下按名称排序的第一个 `.py` 模块"，仅包含直接含有 `.py` 文件的目录。
+fn collect_namespace_packages(file_paths: &[String]) -> HashMap<String, String> {
+    let mut packages: HashMap<String, String> = HashMap::new();
+    for path in file_paths.iter().filter(|p| p.ends_with(".py")) {
+        let Some((dir, _)) = path.rsplit_once('/') else {
+            continue;
+        };
+        let first = packages.entry(dir.to_string()).or_insert_with(|| path.clone());
+        if *path < *first {
+            *first = path.clone();
+        }
+    }
+    packages
+}
+
+/// 内置的代码文件扩展名
+const BUILTIN_CODE_EXTENSIONS: &[&str] = &[
+    ".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs",
+    ".py", ".rs", ".vue", ".svelte",
+];
+
+/// 用户追加代码文件扩展名的设置键（JSON 数组，如 `[".pyi", ".mts"]`）
+pub const CODE_EXTENSIONS_KEY: &str = "code_extensions";
+
+/// 解析 `code_extensions` 设置，统一为小写、带前导点的形式
+///
+/// 设置为空或无法解析时返回空列表（仅使用内置扩展名）。
+pub fn parse_code_extensions(raw: &str) -> Vec<String> {
+    if raw.trim().is_empty() {
+        return Vec::new();
+    }
+    match serde_json::from_str::<Vec<String>>(raw) {
+        Ok(exts) => exts
+            .iter()
+            .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+            .filter(|e| !e.is_empty())
+            .map(|e| format!(".{}", e))
+            .collect(),
+        Err(e) => {
+            log::warn!("设置项 {} 不是合法的 JSON 字符串数组，已忽略：{}", CODE_EXTENSIONS_KEY, e);
+            Vec::new()
+        }
+    }
+}
+
+/// 判断路径是否以用户追加的代码扩展名结尾（不区分大小写）
+fn has_extra_extension(path: &str, extra_extensions: &[String]) -> bool {
+    let lower = path.to_lowercase();
+    extra_extensions.iter().any(|ext| lower.ends_with(ext.as_str()))
+}
+
+/// 判断是否为代码文件（内置扩展名 + 用户追加的扩展名）
+fn is_code_file(path: &str, extra_extensions: &[String]) -> bool {
+    BUILTIN_CODE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+        || has_extra_extension(path, extra_extensions)
 }
 
 /// 解析 JS/TS 相对 import 路径，尝试匹配已知文件
@@ -326,11 +1298,14 @@ fn resolve_js_import(
 
 /// 解析 Python 相对 import 路径
 ///
-/// 例如 `from .utils import helper` → 同目录下的 utils.py 或 utils/__init__.py
+/// 例如 `from .utils import helper` → 同目录下的 utils.py 或 utils/__init__.py；
+/// 均不存在时按命名空间包解析（见 `resolve_namespace_import`）
 fn resolve_py_import(
     source_dir: &str,
     module_path: &str,
+    imported: &[&str],
     known_files: &HashSet<&str>,
+    namespace_pkgs: &HashMap<String, String>,
 ) -> Option<String> {
     // 计算前导点数（相对层级）
     let dots = module_path.chars().take_while(|c| *c == '.').count();
@@ -351,6 +1326,9 @@ fn resolve_py_import(
 
     let base = if base_dir.is_empty() {
         module_as_path
+    } else if module_as_path.is_empty() {
+        // `from .. import xxx`：目标为上级包自身
+        base_dir
     } else {
         format!("{}/{}", base_dir, module_as_path)
     };
@@ -367,7 +1345,48 @@ fn resolve_py_import(
         return Some(init_file);
     }
 
-    None
+    // 命名空间包（无 __init__.py 的目录）
+    resolve_namespace_import(&base, imported, known_files, namespace_pkgs)
+}
+
+/// 提取 `from x import a, b as c` 中导入的名称（`a`、`b`），忽略 `*` 与括号
+fn py_imported_names(line: &str) -> Vec<&str> {
+    let Some((_, names)) = line.split_once(" import ") else {
+        return Vec::new();
+    };
+    names
+        .split(',')
+        .filter_map(|item| {
+            item.trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                .split_whitespace()
+                .next()
+        })
+        .filter(|name| *name != "*" && !name.starts_with('#'))
+        .collect()
+}
+
+/// 解析命名空间包（PEP 420，无 `__init__.py` 的目录）的 import 目标
+///
+/// `from pkg import name` 中的 `name` 通常是包内子模块，依次尝试 `{pkg}/{name}.py`
+/// 与 `{pkg}/{name}/__init__.py`；均未命中（或 `import pkg` 形式）时指向包下的第一个模块。
+fn resolve_namespace_import(
+    pkg: &str,
+    imported: &[&str],
+    known_files: &HashSet<&str>,
+    namespace_pkgs: &HashMap<String, String>,
+) -> Option<String> {
+    let first_module = namespace_pkgs.get(pkg)?;
+    for name in imported {
+        let py_file = format!("{}/{}.py", pkg, name);
+        if known_files.contains(py_file.as_str()) {
+            return Some(py_file);
+        }
+        let init_file = format!("{}/{}/__init__.py", pkg, name);
+        if known_files.contains(init_file.as_str()) {
+            return Some(init_file);
+        }
+    }
+    Some(first_module.clone())
 }
 /// 解析 Python 绝对 import 路径
 ///
@@ -379,10 +1398,13 @@ fn resolve_py_import(
 /// - 前缀剥离：当项目根目录本身是包目录时（如项目路径为 `app/`），
 ///   `app.api.v1.dict.model` 会尝试去掉 `app/` 前缀匹配 `api/v1/dict/model.py`
 ///
-/// 尝试顺序：`{path}.py` → `{path}/__init__.py` → 去掉首段后重试
+/// 尝试顺序：`{path}.py` → `{path}/__init__.py` → 去掉首段后重试 →
+/// 命名空间包（PEP 420，无 `__init__.py` 的目录，见 `resolve_namespace_import`）
 fn resolve_py_absolute_import(
     module_path: &str,
+    imported: &[&str],
     known_files: &HashSet<&str>,
+    namespace_pkgs: &HashMap<String, String>,
 ) -> Option<String> {
     // 将点号替换为路径分隔符
     let as_path = module_path.replace('.', "/");
@@ -419,7 +1441,14 @@ fn resolve_py_absolute_import(
         }
     }
 
-    None
+    // 命名空间包回退（保持上述优先级：仅在文件与 __init__.py 均未命中时生效）
+    if let Some(target) = resolve_namespace_import(&as_path, imported, known_files, namespace_pkgs)
+    {
+        return Some(target);
+    }
+    let stripped_path = module_path.split_once('.').map(|(_, rest)| rest.replace('.', "/"));
+    stripped_path
+        .and_then(|path| resolve_namespace_import(&path, imported, known_files, namespace_pkgs))
 }
 
 /// 规范化路径：处理 `.` 和 `..` 段
@@ -478,6 +1507,26 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     }
 }
 
+/// 将向量缩放为 L2 范数为 1 的单位向量（零向量原样返回）
+pub fn normalize_embedding(embedding: &[f32]) -> Vec<f32> {
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        return embedding.to_vec();
+    }
+    embedding.iter().map(|x| x / norm).collect()
+}
+
+/// 计算两个单位向量的点积，即二者的余弦相似度
+///
+/// 调用方须保证两个向量都已经过 `normalize_embedding`；长度不一致或为空时返回 0.0，
+/// 与 `cosine_similarity` 保持一致。
+pub fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
 /// 将 f32 向量序列化为字节数组（用于存入 SQLite BLOB）
 pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
     let mut bytes = Vec::with_capacity(embedding.len() * 4);
@@ -487,6 +1536,11 @@ pub fn embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
     bytes
 }
 
+/// 将向量归一化后序列化为字节数组，入库后检索时可直接用 `dot_product` 计分
+pub fn normalized_embedding_to_bytes(embedding: &[f32]) -> Vec<u8> {
+    embedding_to_bytes(&normalize_embedding(embedding))
+}
+
 /// 将字节数组反序列化为 f32 向量（从 SQLite BLOB 读取）
 pub fn bytes_to_embedding(bytes: &[u8]) -> Vec<f32> {
     bytes
@@ -514,6 +1568,10 @@ pub struct LanguageStat {
     pub file_count: u32,
     /// 总行数
     pub line_count: u32,
+    /// 空行数
+    pub blank_lines: u32,
+    /// 注释行数（按行首 `#`、`//`、`/*`、`*` 判断）
+    pub comment_lines: u32,
 }
 
 /// 项目概览数据
@@ -531,18 +1589,83 @@ pub struct ProjectOverview {
     pub languages: Vec<LanguageStat>,
     /// 入口文件列表（如 main.py, app.py, index.ts）
     pub entry_files: Vec<String>,
+    /// 超过大小阈值、按体积估算行数而未读取的文件（如打包产物、lockfile）
+    pub skipped_large_files: Vec<String>,
+    /// 内容完全相同的文件组（每组至少 2 个文件，如复制粘贴的模块骨架）
+    pub duplicate_groups: Vec<Vec<String>>,
+}
+
+/// 概览统计时单文件大小阈值的设置键（字节，0 表示不限制）
+pub const MAX_OVERVIEW_FILE_BYTES_KEY: &str = "max_overview_file_bytes";
+
+/// 概览统计的默认单文件大小阈值（1 MiB）
+pub const DEFAULT_MAX_OVERVIEW_FILE_BYTES: u64 = 1024 * 1024;
+
+/// 超大文件估算行数时假定的平均每行字节数
+const ESTIMATED_BYTES_PER_LINE: u64 = 40;
+
+/// 解析 `max_overview_file_bytes` 设置，未设置或非法时使用默认阈值
+pub fn parse_max_overview_file_bytes(raw: &str) -> u64 {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return DEFAULT_MAX_OVERVIEW_FILE_BYTES;
+    }
+    raw.parse().unwrap_or_else(|_| {
+        log::warn!("设置项 {} 不是合法的非负整数，使用默认值：{}", MAX_OVERVIEW_FILE_BYTES_KEY, raw);
+        DEFAULT_MAX_OVERVIEW_FILE_BYTES
+    })
+}
+
+/// 分析项目概览信息：技术栈检测、文件统计、语言分布
+///
+/// 纯文件系统操作，不依赖数据库或 Tauri。使用默认的单文件大小阈值。
+pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview, String> {
+    analyze_project_overview_with_limit(project_path, DEFAULT_MAX_OVERVIEW_FILE_BYTES)
+}
+
+/// 分析项目概览信息，超过 `max_file_bytes` 的文件不读取内容
+///
+/// 超大文件（打包产物、lockfile 等）按体积估算行数计入总数，并列入 `skipped_large_files`。
+/// `max_file_bytes` 为 0 时不限制。
+pub fn analyze_project_overview_with_limit(
+    project_path: &Path,
+    max_file_bytes: u64,
+) -> Result<ProjectOverview, String> {
+    analyze_project_overview_with_config(project_path, max_file_bytes, &ScanConfig::default())
+}
+
+/// 同 `analyze_project_overview_with_limit`，额外跳过 `config` 中追加的忽略目录
+pub fn analyze_project_overview_with_config(
+    project_path: &Path,
+    max_file_bytes: u64,
+    config: &ScanConfig,
+) -> Result<ProjectOverview, String> {
+    analyze_project_overview_with_reader(project_path, max_file_bytes, config, &|path| {
+        std::fs::read_to_string(path).ok()
+    })
 }
 
-/// 分析项目概览信息：技术栈检测、文件统计、语言分布
-///
-/// 纯文件系统操作，不依赖数据库或 Tauri
-pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview, String> {
+/// 概览分析实现（文件读取可注入，便于测试统计读取次数）
+fn analyze_project_overview_with_reader(
+    project_path: &Path,
+    max_file_bytes: u64,
+    config: &ScanConfig,
+    read_file: &dyn Fn(&Path) -> Option<String>,
+) -> Result<ProjectOverview, String> {
     if !project_path.exists() {
         return Err(format!("项目路径不存在：{}", project_path.display()));
     }
 
-    // 收集所有文件
-    let entries = scan_project_files(project_path)?;
+    // 收集所有文件（此时只有路径与大小，哈希仅在检测重复文件时按需计算）
+    let mut entries: Vec<FileEntry> = collect_project_files_with_config(project_path, config)?
+        .into_iter()
+        .map(|(relative_path, _, file_size, mtime)| FileEntry {
+            relative_path,
+            file_hash: String::new(),
+            file_size,
+            mtime,
+        })
+        .collect();
 
     // 统计目录数
     let dir_set: HashSet<String> = entries.iter().filter_map(|e| {
@@ -552,26 +1675,41 @@ pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview,
     let total_dirs = dir_set.len() as u32;
 
     // 按扩展名分组统计语言
-    let mut lang_files: HashMap<String, Vec<String>> = HashMap::new();
+    let mut lang_files: HashMap<String, Vec<&FileEntry>> = HashMap::new();
     for entry in &entries {
         let lang = detect_language(&entry.relative_path);
-        lang_files.entry(lang).or_default().push(entry.relative_path.clone());
+        lang_files.entry(lang).or_default().push(entry);
     }
 
     // 统计每种语言的行数
     let mut languages: Vec<LanguageStat> = Vec::new();
     let mut total_lines: u32 = 0;
+    let mut skipped_large_files: Vec<String> = Vec::new();
 
     for (language, files) in &lang_files {
         let mut file_count = 0u32;
         let mut line_count = 0u32;
-        for file_path in files {
-            let abs_path = project_path.join(file_path);
-            if let Ok(content) = std::fs::read_to_string(&abs_path) {
-                line_count += content.lines().count() as u32;
-                file_count += 1;
-            } else {
-                file_count += 1; // 二进制文件也计数
+        let mut blank_lines = 0u32;
+        let mut comment_lines = 0u32;
+        for entry in files {
+            file_count += 1; // 二进制文件也计数
+            if max_file_bytes > 0 && entry.file_size > max_file_bytes {
+                // 超大文件按体积估算行数，不读取内容（不区分空行与注释）
+                line_count += entry.file_size.div_ceil(ESTIMATED_BYTES_PER_LINE) as u32;
+                skipped_large_files.push(entry.relative_path.clone());
+                continue;
+            }
+            let abs_path = project_path.join(&entry.relative_path);
+            if let Some(content) = read_file(&abs_path) {
+                for line in content.lines() {
+                    let trimmed = line.trim();
+                    line_count += 1;
+                    if trimmed.is_empty() {
+                        blank_lines += 1;
+                    } else if is_comment_line(trimmed) {
+                        comment_lines += 1;
+                    }
+                }
             }
         }
         total_lines += line_count;
@@ -579,11 +1717,14 @@ pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview,
             language: language.clone(),
             file_count,
             line_count,
+            blank_lines,
+            comment_lines,
         });
     }
 
     // 按行数降序排序
     languages.sort_by(|a, b| b.line_count.cmp(&a.line_count));
+    skipped_large_files.sort();
 
     // 检测技术栈
     let tech_stack = detect_tech_stack(project_path, &entries);
@@ -591,6 +1732,9 @@ pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview,
     // 检测入口文件
     let entry_files = detect_entry_files(&entries);
 
+    // 检测内容重复的文件
+    let duplicate_groups = find_duplicate_groups(project_path, &mut entries, max_file_bytes);
+
     Ok(ProjectOverview {
         total_files: entries.len() as u32,
         total_lines,
@@ -598,11 +1742,58 @@ pub fn analyze_project_overview(project_path: &Path) -> Result<ProjectOverview,
         tech_stack,
         languages,
         entry_files,
+        skipped_large_files,
+        duplicate_groups,
     })
 }
 
+/// 按 `file_hash` 找出内容完全相同的文件组（组内路径与各组均按路径排序）
+///
+/// 只为大小与其他文件相同的文件计算哈希；超过 `max_file_bytes` 的文件不参与，避免完整读取超大文件。
+/// 空文件（如 Python 包的 `__init__.py`）不视为重复。
+fn find_duplicate_groups(
+    project_path: &Path,
+    entries: &mut [FileEntry],
+    max_file_bytes: u64,
+) -> Vec<Vec<String>> {
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+    for entry in entries.iter() {
+        *size_counts.entry(entry.file_size).or_default() += 1;
+    }
+    entries
+        .par_iter_mut()
+        .filter(|e| {
+            e.file_size > 0
+                && (max_file_bytes == 0 || e.file_size <= max_file_bytes)
+                && size_counts[&e.file_size] > 1
+        })
+        .for_each(|e| {
+            if let Ok(hash) = compute_file_hash(&project_path.join(&e.relative_path)) {
+                e.file_hash = hash;
+            }
+        });
+
+    let mut by_hash: HashMap<&str, Vec<String>> = HashMap::new();
+    for entry in entries.iter().filter(|e| !e.file_hash.is_empty()) {
+        by_hash
+            .entry(&entry.file_hash)
+            .or_default()
+            .push(entry.relative_path.clone());
+    }
+    let mut groups: Vec<Vec<String>> = by_hash
+        .into_values()
+        .filter(|group| group.len() >= 2)
+        .map(|mut group| {
+            group.sort();
+            group
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
 /// 根据文件扩展名检测语言
-fn detect_language(path: &str) -> String {
+pub fn detect_language(path: &str) -> String {
     let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
     match ext.as_str() {
         "py" => "Python".to_string(),
@@ -639,6 +1830,25 @@ fn detect_language(path: &str) -> String {
     }
 }
 
+/// 检测语言，内置映射无法识别时回退到用户追加的代码扩展名
+///
+/// 常见变体（`.pyi`、`.mts` 等）按对应语言处理，其余追加扩展名归为 "Code"，
+/// 使用通用签名提取规则。
+fn detect_language_with(path: &str, extra_extensions: &[String]) -> String {
+    let lang = detect_language(path);
+    if lang != "Other" || !has_extra_extension(path, extra_extensions) {
+        return lang;
+    }
+    let ext = path.rsplit('.').next().unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "pyi" | "pyw" => "Python",
+        "mts" | "cts" => "TypeScript",
+        "mjs" | "cjs" => "JavaScript",
+        _ => "Code",
+    }
+    .to_string()
+}
+
 /// 检测项目技术栈（通过特征文件和依赖配置）
 fn detect_tech_stack(project_path: &Path, entries: &[FileEntry]) -> Vec<String> {
     let mut stack = Vec::new();
@@ -650,21 +1860,17 @@ fn detect_tech_stack(project_path: &Path, entries: &[FileEntry]) -> Vec<String>
         stack.push("Python".to_string());
     }
 
-    // 检测 requirements.txt / pyproject.toml / setup.py 中的框架
+    // 检测 requirements.txt / pyproject.toml / setup.py / Pipfile 中声明的依赖
+    let mut py_deps: HashSet<String> = HashSet::new();
     for config_file in &["requirements.txt", "pyproject.toml", "setup.py", "Pipfile"] {
         let path = project_path.join(config_file);
         if let Ok(content) = std::fs::read_to_string(&path) {
-            let lower = content.to_lowercase();
-            if lower.contains("fastapi") { push_unique(&mut stack, "FastAPI"); }
-            if lower.contains("django") { push_unique(&mut stack, "Django"); }
-            if lower.contains("flask") { push_unique(&mut stack, "Flask"); }
-            if lower.contains("sqlalchemy") { push_unique(&mut stack, "SQLAlchemy"); }
-            if lower.contains("pydantic") { push_unique(&mut stack, "Pydantic"); }
-            if lower.contains("celery") { push_unique(&mut stack, "Celery"); }
-            if lower.contains("redis") { push_unique(&mut stack, "Redis"); }
-            if lower.contains("pytest") { push_unique(&mut stack, "Pytest"); }
-            if lower.contains("alembic") { push_unique(&mut stack, "Alembic"); }
-            if lower.contains("uvicorn") { push_unique(&mut stack, "Uvicorn"); }
+            py_deps.extend(parse_python_dependencies(config_file, &content));
+        }
+    }
+    for (package, label) in NOTABLE_PYTHON_PACKAGES {
+        if py_deps.contains(*package) {
+            push_unique(&mut stack, label);
         }
     }
 
@@ -674,30 +1880,31 @@ fn detect_tech_stack(project_path: &Path, entries: &[FileEntry]) -> Vec<String>
             || e.relative_path.ends_with(".tsx") || e.relative_path.ends_with(".jsx")
     });
     if has_js_ts {
-        // 检测 package.json
+        // 检测 package.json 的 dependencies / devDependencies
         let pkg_path = project_path.join("package.json");
         if let Ok(content) = std::fs::read_to_string(&pkg_path) {
-            let lower = content.to_lowercase();
-            if lower.contains("\"react\"") { push_unique(&mut stack, "React"); }
-            if lower.contains("\"vue\"") { push_unique(&mut stack, "Vue"); }
-            if lower.contains("\"next\"") { push_unique(&mut stack, "Next.js"); }
-            if lower.contains("\"nuxt\"") { push_unique(&mut stack, "Nuxt"); }
-            if lower.contains("\"typescript\"") { push_unique(&mut stack, "TypeScript"); }
-            if lower.contains("\"vite\"") { push_unique(&mut stack, "Vite"); }
-            if lower.contains("\"tailwindcss\"") { push_unique(&mut stack, "Tailwind CSS"); }
-            if lower.contains("\"express\"") { push_unique(&mut stack, "Express"); }
-            if lower.contains("\"nestjs\"") || lower.contains("\"@nestjs") { push_unique(&mut stack, "NestJS"); }
+            let deps = parse_package_json_dependencies(&content);
+            for (package, label) in NOTABLE_JS_PACKAGES {
+                if deps.iter().any(|d| d == package) {
+                    push_unique(&mut stack, label);
+                }
+            }
+            if deps.iter().any(|d| d.starts_with("@nestjs/")) {
+                push_unique(&mut stack, "NestJS");
+            }
         }
     }
 
-    // Rust 生态
+    // Rust 生态（解析 Cargo.toml 的依赖表，忽略注释与 dev/build 依赖）
     if file_set.contains("Cargo.toml") || project_path.join("Cargo.toml").exists() {
         push_unique(&mut stack, "Rust");
         if let Ok(content) = std::fs::read_to_string(project_path.join("Cargo.toml")) {
-            let lower = content.to_lowercase();
-            if lower.contains("tauri") { push_unique(&mut stack, "Tauri"); }
-            if lower.contains("actix") { push_unique(&mut stack, "Actix"); }
-            if lower.contains("tokio") { push_unique(&mut stack, "Tokio"); }
+            let deps = parse_cargo_dependencies(&content);
+            for (krate, label) in NOTABLE_RUST_CRATES {
+                if deps.iter().any(|d| d == krate) {
+                    push_unique(&mut stack, label);
+                }
+            }
         }
     }
 
@@ -727,6 +1934,208 @@ fn detect_tech_stack(project_path: &Path, entries: &[FileEntry]) -> Vec<String>
     stack
 }
 
+/// Python 依赖中值得在技术栈中展示的包（规范化包名 → 显示名）
+const NOTABLE_PYTHON_PACKAGES: &[(&str, &str)] = &[
+    ("fastapi", "FastAPI"),
+    ("django", "Django"),
+    ("flask", "Flask"),
+    ("sqlalchemy", "SQLAlchemy"),
+    ("pydantic", "Pydantic"),
+    ("celery", "Celery"),
+    ("redis", "Redis"),
+    ("pytest", "Pytest"),
+    ("alembic", "Alembic"),
+    ("uvicorn", "Uvicorn"),
+];
+
+/// package.json 依赖中值得在技术栈中展示的包（包名 → 显示名）
+///
+/// NestJS 以 `@nestjs/` 作用域识别，不在此表中。
+const NOTABLE_JS_PACKAGES: &[(&str, &str)] = &[
+    ("react", "React"),
+    ("vue", "Vue"),
+    ("next", "Next.js"),
+    ("nuxt", "Nuxt"),
+    ("typescript", "TypeScript"),
+    ("vite", "Vite"),
+    ("tailwindcss", "Tailwind CSS"),
+    ("express", "Express"),
+];
+
+/// 从 PEP 508 依赖声明中提取规范化包名
+///
+/// 取版本约束、extras、环境标记之前的名称，转小写并将 `_`、`.` 统一为 `-`。
+/// 例如 `Flask_SQLAlchemy[async]>=3.0; python_version>"3.8"` → `flask-sqlalchemy`。
+fn requirement_name(spec: &str) -> Option<String> {
+    let name: String = spec
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.to_lowercase().replace(['_', '.'], "-"))
+}
+
+/// 解析 Python 依赖配置文件中声明的包名（规范化）
+///
+/// - `requirements.txt`：逐行解析，忽略注释与 `-r`/`-e` 等选项行
+/// - `pyproject.toml`：`[project]` 的 dependencies / optional-dependencies，
+///   以及 Poetry 的 dependencies / dev-dependencies / group 依赖表
+/// - `Pipfile`：`[packages]` 与 `[dev-packages]`
+/// - `setup.py`：字符串字面量中形如依赖声明的部分（如 `install_requires` 列表项）
+fn parse_python_dependencies(file_name: &str, content: &str) -> Vec<String> {
+    match file_name {
+        "requirements.txt" => content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.starts_with('-'))
+            .filter_map(requirement_name)
+            .collect(),
+        "pyproject.toml" => {
+            let Ok(manifest) = content.parse::<toml::Table>() else {
+                return Vec::new();
+            };
+            let mut deps = Vec::new();
+            if let Some(project) = manifest.get("project") {
+                let specs = project
+                    .get("dependencies")
+                    .and_then(|v| v.as_array())
+                    .into_iter()
+                    .flatten()
+                    .chain(
+                        project
+                            .get("optional-dependencies")
+                            .and_then(|v| v.as_table())
+                            .into_iter()
+                            .flat_map(|t| t.values())
+                            .filter_map(|v| v.as_array())
+                            .flatten(),
+                    );
+                deps.extend(specs.filter_map(|v| v.as_str()).filter_map(requirement_name));
+            }
+            if let Some(poetry) = manifest
+                .get("tool")
+                .and_then(|t| t.get("poetry"))
+                .and_then(|v| v.as_table())
+            {
+                let groups = poetry
+                    .get("group")
+                    .and_then(|v| v.as_table())
+                    .into_iter()
+                    .flat_map(|t| t.values())
+                    .filter_map(|g| g.get("dependencies"));
+                let tables = [poetry.get("dependencies"), poetry.get("dev-dependencies")]
+                    .into_iter()
+                    .flatten()
+                    .chain(groups)
+                    .filter_map(|v| v.as_table());
+                deps.extend(tables.flat_map(|t| t.keys()).filter_map(|k| requirement_name(k)));
+            }
+            deps
+        }
+        "Pipfile" => {
+            let Ok(manifest) = content.parse::<toml::Table>() else {
+                return Vec::new();
+            };
+            ["packages", "dev-packages"]
+                .iter()
+                .filter_map(|section| manifest.get(*section).and_then(|v| v.as_table()))
+                .flat_map(|t| t.keys())
+                .filter_map(|k| requirement_name(k))
+                .collect()
+        }
+        "setup.py" => {
+            static STRING_LITERAL: Lazy<Regex> =
+                Lazy::new(|| Regex::new(r#"["']([^"'\n]+)["']"#).unwrap());
+            content
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or(""))
+                .flat_map(|line| STRING_LITERAL.captures_iter(line))
+                .filter_map(|caps| {
+                    let literal = caps[1].trim();
+                    // 只接受整体是一条依赖声明的字符串（名称后只能跟版本约束/extras/标记）
+                    let name = requirement_name(literal)?;
+                    let rest = literal[name.len()..].trim_start();
+                    let is_spec = rest.is_empty()
+                        || rest.starts_with(['=', '>', '<', '~', '!', '[', ';']);
+                    is_spec.then_some(name)
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 解析 package.json 中 `dependencies` 与 `devDependencies` 声明的包名
+///
+/// 内容无法解析时返回空列表。
+fn parse_package_json_dependencies(content: &str) -> Vec<String> {
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    ["dependencies", "devDependencies"]
+        .iter()
+        .filter_map(|section| manifest.get(*section).and_then(|v| v.as_object()))
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Cargo.toml 依赖中值得在技术栈中展示的 crate（crate 名 → 显示名）
+const NOTABLE_RUST_CRATES: &[(&str, &str)] = &[
+    ("tauri", "Tauri"),
+    ("actix-web", "Actix"),
+    ("axum", "Axum"),
+    ("rocket", "Rocket"),
+    ("tokio", "Tokio"),
+    ("diesel", "Diesel"),
+    ("sqlx", "SQLx"),
+    ("serde", "Serde"),
+    ("clap", "Clap"),
+];
+
+/// 解析 Cargo.toml 中声明的运行时依赖 crate 名
+///
+/// 包含 `[dependencies]`、`[workspace.dependencies]` 与 `[target.'cfg(..)'.dependencies]`，
+/// 不含 dev/build 依赖；`package = "..."` 重命名的依赖按真实 crate 名返回。
+/// 内容无法解析时返回空列表。
+fn parse_cargo_dependencies(content: &str) -> Vec<String> {
+    let Ok(manifest) = content.parse::<toml::Table>() else {
+        return Vec::new();
+    };
+
+    let mut tables: Vec<&toml::Table> = Vec::new();
+    if let Some(deps) = manifest.get("dependencies").and_then(|v| v.as_table()) {
+        tables.push(deps);
+    }
+    if let Some(deps) = manifest
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|v| v.as_table())
+    {
+        tables.push(deps);
+    }
+    if let Some(targets) = manifest.get("target").and_then(|v| v.as_table()) {
+        tables.extend(
+            targets
+                .values()
+                .filter_map(|t| t.get("dependencies").and_then(|v| v.as_table())),
+        );
+    }
+
+    tables
+        .into_iter()
+        .flat_map(|deps| deps.iter())
+        .map(|(name, spec)| {
+            spec.get("package")
+                .and_then(|p| p.as_str())
+                .unwrap_or(name)
+                .to_string()
+        })
+        .collect()
+}
+
 /// 辅助：去重添加
 fn push_unique(vec: &mut Vec<String>, val: &str) {
     if !vec.iter().any(|v| v == val) {
@@ -754,12 +2163,7 @@ pub fn extract_signatures_from_content(content: &str, language: &str) -> Vec<Str
     let mut sigs = Vec::new();
     for line in content.lines() {
         let trimmed = line.trim();
-        if trimmed.is_empty()
-            || trimmed.starts_with("//")
-            || trimmed.starts_with('#')
-            || trimmed.starts_with("/*")
-            || trimmed.starts_with('*')
-        {
+        if trimmed.is_empty() || is_comment_line(trimmed) {
             continue;
         }
         match language {
@@ -773,6 +2177,16 @@ pub fn extract_signatures_from_content(content: &str, language: &str) -> Vec<Str
     sigs
 }
 
+/// 按行首前缀判断是否为注释行（`trimmed` 须已去除首尾空白）
+///
+/// 简单启发式：`#`、`//`、`/*`、`*` 开头即视为注释，不识别行尾注释与多行字符串。
+fn is_comment_line(trimmed: &str) -> bool {
+    trimmed.starts_with("//")
+        || trimmed.starts_with('#')
+        || trimmed.starts_with("/*")
+        || trimmed.starts_with('*')
+}
+
 /// Python 签名提取
 fn extract_python_sig(trimmed: &str, sigs: &mut Vec<String>) {
     if trimmed.starts_with("class ") {
@@ -901,16 +2315,45 @@ fn extract_generic_sig(trimmed: &str, sigs: &mut Vec<String>) {
     }
 }
 
-/// 批量提取项目所有文件的签名
+/// 批量提取项目所有文件的签名（二进制文件跳过，不出现在结果中）
 pub fn extract_project_signatures(project_path: &Path) -> Result<Vec<FileSignature>, String> {
-    let entries = scan_project_files(project_path)?;
+    extract_project_signatures_with_extensions(project_path, &[])
+}
+
+/// 批量提取签名，`extra_extensions` 为用户追加的代码文件扩展名
+pub fn extract_project_signatures_with_extensions(
+    project_path: &Path,
+    extra_extensions: &[String],
+) -> Result<Vec<FileSignature>, String> {
+    extract_project_signatures_with_config(
+        project_path,
+        extra_extensions,
+        &ScanConfig::default(),
+        &HashMap::new(),
+    )
+}
+
+/// 同 `extract_project_signatures_with_extensions`，额外跳过 `config` 中追加的忽略目录，
+/// 文件列表扫描复用 `prior` 中的哈希（见 `scan_project_files_with_config`）
+pub fn extract_project_signatures_with_config(
+    project_path: &Path,
+    extra_extensions: &[String],
+    config: &ScanConfig,
+    prior: &HashMap<String, PriorFile>,
+) -> Result<Vec<FileSignature>, String> {
+    let entries = scan_project_files_with_config(project_path, config, prior)?;
     let mut results = Vec::new();
     for entry in &entries {
-        let lang = detect_language(&entry.relative_path);
+        let lang = detect_language_with(&entry.relative_path, extra_extensions);
         if lang == "Other" {
             continue;
         }
         let full_path = project_path.join(&entry.relative_path);
+        // 代码目录下误用代码扩展名的二进制文件只读头部即跳过
+        if is_probably_binary(&full_path) {
+            log::debug!("签名提取跳过二进制文件：{}", entry.relative_path);
+            continue;
+        }
         let content = match std::fs::read_to_string(&full_path) {
             Ok(c) => c,
             Err(_) => continue,
@@ -943,21 +2386,52 @@ pub fn format_signatures_for_llm(signatures: &[FileSignature]) -> String {
 
 
 fn detect_entry_files(entries: &[FileEntry]) -> Vec<String> {
-    let entry_patterns = [
-        "main.py", "app.py", "manage.py", "wsgi.py", "asgi.py",
-        "index.ts", "index.js", "main.ts", "main.js", "app.ts", "app.js",
-        "main.rs", "lib.rs",
-        "main.go",
-        "Main.java", "Application.java",
-    ];
-    let mut found = Vec::new();
-    for entry in entries {
-        let filename = entry.relative_path.rsplit('/').next().unwrap_or(&entry.relative_path);
-        if entry_patterns.contains(&filename) {
-            found.push(entry.relative_path.clone());
-        }
-    }
-    found
+    entries
+        .iter()
+        .filter(|entry| is_entry_file(&entry.relative_path))
+        .map(|entry| entry.relative_path.clone())
+        .collect()
+}
+
+/// 入口文件名（按文件名匹配，不限目录）
+const ENTRY_FILE_NAMES: &[&str] = &[
+    "main.py", "app.py", "manage.py", "wsgi.py", "asgi.py",
+    "index.ts", "index.js", "main.ts", "main.js", "app.ts", "app.js",
+    "main.rs", "lib.rs",
+    "main.go",
+    "Main.java", "Application.java",
+];
+
+/// 判断相对路径是否为入口文件
+pub fn is_entry_file(relative_path: &str) -> bool {
+    let filename = relative_path.rsplit('/').next().unwrap_or(relative_path);
+    ENTRY_FILE_NAMES.contains(&filename)
+}
+
+/// 依赖图节点：文件路径及供可视化使用的元信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DepNode {
+    /// 文件相对路径
+    pub path: String,
+    /// 编程语言（见 `detect_language`）
+    pub language: String,
+    /// 是否为入口文件
+    pub is_entry: bool,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 将文件索引条目转换为依赖图节点（保持输入顺序）
+pub fn dependency_nodes(entries: &[FileEntry]) -> Vec<DepNode> {
+    entries
+        .iter()
+        .map(|entry| DepNode {
+            path: entry.relative_path.clone(),
+            language: detect_language(&entry.relative_path),
+            is_entry: is_entry_file(&entry.relative_path),
+            size: entry.file_size,
+        })
+        .collect()
 }
 // ============================================================================
 // 模块依赖解析（构建时传递依赖分析）
@@ -983,9 +2457,45 @@ pub fn resolve_module_dependencies(
     modules_dir: &str,
     selected_modules: &[String],
     all_module_names: &[String],
+) -> Result<(Vec<String>, Vec<String>), String> {
+    resolve_module_dependencies_with_scope(
+        project_path,
+        modules_dir,
+        selected_modules,
+        all_module_names,
+        BfsScope::ModulesOnly,
+    )
+}
+
+/// 按指定遍历范围分析模块传递依赖（见 `BfsScope`）
+///
+/// `AllImports` 额外构建项目级文件依赖图，沿模块目录之外的共享文件继续遍历，
+/// 共享文件引用到的模块同样视为依赖；`ModulesOnly` 只跟随模块之间的直接 import。
+pub fn resolve_module_dependencies_with_scope(
+    project_path: &Path,
+    modules_dir: &str,
+    selected_modules: &[String],
+    all_module_names: &[String],
+    scope: BfsScope,
 ) -> Result<(Vec<String>, Vec<String>), String> {
     let all_modules_set: HashSet<&str> = all_module_names.iter().map(|s| s.as_str()).collect();
 
+    // 项目级文件依赖图（仅 AllImports 需要）：源文件 → 目标文件列表
+    let file_graph: HashMap<String, Vec<String>> = if scope == BfsScope::AllImports {
+        let files: Vec<String> = collect_project_files(project_path)?
+            .into_iter()
+            .map(|(relative, _, _, _)| relative)
+            .collect();
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for edge in extract_dependencies(project_path, &files)? {
+            graph.entry(edge.source).or_default().push(edge.target);
+        }
+        graph
+    } else {
+        HashMap::new()
+    };
+    let modules_prefix = format!("{}/", modules_dir.trim_end_matches('/'));
+
     // BFS 队列：从选中模块开始
     let mut queue: std::collections::VecDeque<String> = selected_modules.iter().cloned().collect();
     let mut visited: HashSet<String> = selected_modules.iter().cloned().collect();
@@ -1000,7 +2510,15 @@ pub fn resolve_module_dependencies(
         }
 
         // 扫描该模块目录下的所有代码文件
-        let deps = scan_module_imports(&module_path, modules_dir, &all_modules_set)?;
+        let mut deps = scan_module_imports(&module_path, modules_dir, &all_modules_set)?;
+        if scope == BfsScope::AllImports {
+            deps.extend(modules_reached_via_shared_files(
+                &file_graph,
+                &modules_prefix,
+                &module_name,
+                &all_modules_set,
+            ));
+        }
 
         for dep in deps {
             if !visited.contains(&dep) {
@@ -1021,6 +2539,137 @@ pub fn resolve_module_dependencies(
     Ok((full_list, auto_added))
 }
 
+/// 预览依赖自动补充：在 `resolve_module_dependencies_with_scope` 结果之上，
+/// 为每个自动补充的模块给出导致补充的文件级依赖
+///
+/// 用 `extract_dependencies` 构建文件依赖图，按模块目录折叠为模块级依赖；
+/// 从选中模块出发按 BFS 顺序查找第一条指向待补充模块的边
+/// （`AllImports` 时可经由模块目录之外的共享文件）。
+pub fn preview_module_expansion(
+    project_path: &Path,
+    modules_dir: &str,
+    selected_modules: &[String],
+    all_module_names: &[String],
+    scope: BfsScope,
+) -> Result<ExpansionResult, String> {
+    let (_, added) = resolve_module_dependencies_with_scope(
+        project_path,
+        modules_dir,
+        selected_modules,
+        all_module_names,
+        scope,
+    )?;
+
+    let files: Vec<String> = collect_project_files(project_path)?
+        .into_iter()
+        .map(|(relative, _, _, _)| relative)
+        .collect();
+    let mut file_graph: HashMap<String, Vec<String>> = HashMap::new();
+    for edge in extract_dependencies(project_path, &files)? {
+        file_graph.entry(edge.source).or_default().push(edge.target);
+    }
+    // 排序保证同一项目每次给出相同的原因
+    for targets in file_graph.values_mut() {
+        targets.sort();
+    }
+    let modules_prefix = format!("{}/", modules_dir.trim_end_matches('/'));
+    let all_modules: HashSet<&str> = all_module_names.iter().map(String::as_str).collect();
+    let module_of = |file: &str| -> Option<String> {
+        file.strip_prefix(&modules_prefix)
+            .filter(|rest| rest.contains('/'))
+            .and_then(|rest| module_rewriter::module_key(rest, &all_modules))
+    };
+
+    let pending: HashSet<&str> = added.iter().map(String::as_str).collect();
+    let mut included: HashSet<String> = selected_modules.iter().cloned().collect();
+    let mut queue: std::collections::VecDeque<String> = selected_modules.iter().cloned().collect();
+    let mut reasons: Vec<ModuleAddition> = Vec::new();
+
+    while let Some(module_name) = queue.pop_front() {
+        let own_prefix = format!("{}{}/", modules_prefix, module_name);
+        let mut sources: Vec<&str> = file_graph
+            .keys()
+            .filter(|source| source.starts_with(&own_prefix))
+            .map(String::as_str)
+            .collect();
+        sources.sort();
+        let mut frontier: std::collections::VecDeque<&str> = sources.into_iter().collect();
+        let mut visited: HashSet<&str> = HashSet::new();
+
+        while let Some(file) = frontier.pop_front() {
+            if !visited.insert(file) {
+                continue;
+            }
+            for target in file_graph.get(file).into_iter().flatten() {
+                match module_of(target) {
+                    Some(dep) if dep == module_name => {}
+                    Some(dep) if pending.contains(dep.as_str()) && !included.contains(&dep) => {
+                        included.insert(dep.clone());
+                        reasons.push(ModuleAddition {
+                            module: dep.clone(),
+                            required_by: module_name.clone(),
+                            source_file: file.to_string(),
+                            target_file: target.clone(),
+                        });
+                        queue.push_back(dep);
+                    }
+                    Some(_) => {}
+                    // 模块目录之外的共享文件：仅 AllImports 继续沿其依赖查找
+                    None if scope == BfsScope::AllImports => frontier.push_back(target.as_str()),
+                    None => {}
+                }
+            }
+        }
+    }
+
+    Ok(ExpansionResult {
+        selected: selected_modules.to_vec(),
+        added,
+        reasons,
+    })
+}
+
+/// 收集模块经由模块目录之外的共享文件（core/、utils/ 等）间接引用的其他模块
+///
+/// 从模块自身文件出发，沿文件依赖图只穿过模块目录之外的文件继续遍历，
+/// 遇到落在 `{modules_dir}/{模块名}/` 下的目标即记为依赖（不再深入该模块，由外层 BFS 处理）。
+fn modules_reached_via_shared_files(
+    file_graph: &HashMap<String, Vec<String>>,
+    modules_prefix: &str,
+    module_name: &str,
+    all_modules: &HashSet<&str>,
+) -> HashSet<String> {
+    let own_prefix = format!("{}{}/", modules_prefix, module_name);
+    let mut reached: HashSet<String> = HashSet::new();
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut queue: std::collections::VecDeque<&str> = file_graph
+        .iter()
+        .filter(|(source, _)| source.starts_with(&own_prefix))
+        .flat_map(|(_, targets)| targets.iter())
+        .filter(|target| !target.starts_with(modules_prefix))
+        .map(String::as_str)
+        .collect();
+
+    while let Some(file) = queue.pop_front() {
+        if !visited.insert(file) {
+            continue;
+        }
+        for target in file_graph.get(file).into_iter().flatten() {
+            match target.strip_prefix(modules_prefix) {
+                Some(rest) => {
+                    if let Some(dep) = module_rewriter::module_key(rest, all_modules) {
+                        if dep != module_name && all_modules.contains(dep.as_str()) {
+                            reached.insert(dep);
+                        }
+                    }
+                }
+                None => queue.push_back(target.as_str()),
+            }
+        }
+    }
+    reached
+}
+
 /// 扫描单个模块目录内的所有代码文件，提取对其他模块的引用
 ///
 /// 支持的 import 模式：
@@ -1337,25 +2986,329 @@ mod tests {
     use tempfile::TempDir;
 
     #[test]
-    fn test_scan_empty_dir() {
+    fn test_scan_empty_dir() {
+        let tmp = TempDir::new().unwrap();
+        let entries = scan_project_files(tmp.path()).unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_scan_with_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
+        fs::create_dir(tmp.path().join("utils")).unwrap();
+        fs::write(tmp.path().join("utils/helper.py"), "def help(): pass").unwrap();
+
+        let entries = scan_project_files(tmp.path()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let paths: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
+        assert!(paths.contains(&"main.py"));
+        assert!(paths.contains(&"utils/helper.py"));
+    }
+
+    #[test]
+    fn test_dependency_nodes_carry_language_and_entry_flag() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "from modules.auth import routes\n").unwrap();
+        fs::create_dir_all(tmp.path().join("modules/auth")).unwrap();
+        fs::write(tmp.path().join("modules/auth/routes.py"), "def login(): pass\n").unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/index.ts"), "import './util';\n").unwrap();
+        fs::write(tmp.path().join("src/util.ts"), "export const x = 1;\n").unwrap();
+
+        let entries = scan_project_files(tmp.path()).unwrap();
+        let nodes = dependency_nodes(&entries);
+        assert_eq!(nodes.len(), 4);
+
+        let node = |path: &str| nodes.iter().find(|n| n.path == path).unwrap();
+        let expected = [
+            ("main.py", "Python", true),
+            ("modules/auth/routes.py", "Python", false),
+            ("src/index.ts", "TypeScript", true),
+            ("src/util.ts", "TypeScript", false),
+        ];
+        for (path, language, is_entry) in expected {
+            let n = node(path);
+            assert_eq!(n.language, language, "{}", path);
+            assert_eq!(n.is_entry, is_entry, "{}", path);
+        }
+        assert_eq!(node("src/util.ts").size, "export const x = 1;\n".len() as u64);
+    }
+
+    #[test]
+    fn test_overview_skips_large_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "import os\nprint(os.getcwd())\n").unwrap();
+        fs::create_dir(tmp.path().join("static")).unwrap();
+        fs::write(tmp.path().join("static/bundle.min.js"), vec![b'x'; 4000]).unwrap();
+
+        let reads = std::cell::RefCell::new(Vec::new());
+        let overview = analyze_project_overview_with_reader(
+            tmp.path(),
+            1000,
+            &ScanConfig::default(),
+            &|path| {
+                reads.borrow_mut().push(path.to_path_buf());
+                std::fs::read_to_string(path).ok()
+            },
+        )
+        .unwrap();
+
+        // 超大文件不读取，只按体积估算行数（4000 / 40 = 100）
+        assert_eq!(*reads.borrow(), vec![tmp.path().join("main.py")]);
+        assert_eq!(overview.skipped_large_files, vec!["static/bundle.min.js".to_string()]);
+        assert_eq!(overview.total_files, 2);
+        assert_eq!(overview.total_lines, 102);
+        let js = overview.languages.iter().find(|l| l.language == "JavaScript").unwrap();
+        assert_eq!((js.file_count, js.line_count), (1, 100));
+
+        // 阈值为 0 时不限制
+        let overview = analyze_project_overview_with_limit(tmp.path(), 0).unwrap();
+        assert!(overview.skipped_large_files.is_empty());
+        assert_eq!(overview.total_lines, 3);
+
+        assert_eq!(parse_max_overview_file_bytes(""), DEFAULT_MAX_OVERVIEW_FILE_BYTES);
+        assert_eq!(parse_max_overview_file_bytes("abc"), DEFAULT_MAX_OVERVIEW_FILE_BYTES);
+        assert_eq!(parse_max_overview_file_bytes("2048"), 2048);
+    }
+
+    #[test]
+    fn test_overview_counts_blank_and_comment_lines() {
+        let tmp = TempDir::new().unwrap();
+        let source = [
+            "#!/usr/bin/env python",
+            "# 入口",
+            "import os",
+            "",
+            "def main():",
+            "    # 打印当前目录",
+            "    print(os.getcwd())  # 行尾注释不计入",
+            "    ",
+            "",
+            "main()",
+        ];
+        fs::write(tmp.path().join("main.py"), source.join("\n")).unwrap();
+
+        let overview = analyze_project_overview(tmp.path()).unwrap();
+        let python = overview
+            .languages
+            .iter()
+            .find(|l| l.language == "Python")
+            .unwrap();
+        // 共 10 行：3 行空行（含只有空白的行）、3 行注释、4 行代码
+        assert_eq!(python.line_count, 10);
+        assert_eq!(python.blank_lines, 3);
+        assert_eq!(python.comment_lines, 3);
+        assert_eq!(overview.total_lines, 10);
+    }
+
+    #[test]
+    fn test_overview_groups_identical_files() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("modules/auth")).unwrap();
+        fs::create_dir_all(tmp.path().join("modules/billing")).unwrap();
+        fs::write(
+            tmp.path().join("modules/auth/service.py"),
+            "def run():\n    pass\n",
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("modules/billing/service.py"),
+            "def run():\n    pass\n",
+        )
+        .unwrap();
+        // 大小相同但内容不同，不算重复
+        fs::write(tmp.path().join("main.py"), "def xyz():\n    pass\n").unwrap();
+        fs::write(tmp.path().join("modules/__init__.py"), "").unwrap();
+        fs::write(tmp.path().join("modules/auth/__init__.py"), "").unwrap();
+
+        let overview = analyze_project_overview(tmp.path()).unwrap();
+        assert_eq!(overview.total_files, 5);
+        assert_eq!(
+            overview.duplicate_groups,
+            vec![vec![
+                "modules/auth/service.py".to_string(),
+                "modules/billing/service.py".to_string(),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_extract_module_metadata() {
+        let tmp = TempDir::new().unwrap();
+        let users = tmp.path().join("modules/users");
+        fs::create_dir_all(users.join("tests")).unwrap();
+        fs::write(
+            users.join("routes.py"),
+            "app.include_router(list_router)\napp.include_router(detail_router)\n",
+        )
+        .unwrap();
+        fs::write(users.join("tests/test_routes.py"), "def test_ok(): pass\n").unwrap();
+        // 非代码文件中的标记不计入路由数
+        fs::write(users.join("README.md"), "include_router").unwrap();
+        fs::create_dir_all(tmp.path().join("modules/billing")).unwrap();
+        fs::write(tmp.path().join("modules/billing/service.py"), "x = 1\n").unwrap();
+
+        let metadata = extract_module_metadata(tmp.path(), "modules").unwrap();
+        assert_eq!(metadata.len(), 2);
+        let billing = &metadata[0];
+        assert_eq!(
+            (billing.name.as_str(), billing.file_count, billing.route_count),
+            ("billing", 1, 0)
+        );
+        assert!(!billing.has_tests);
+        let users_meta = &metadata[1];
+        assert_eq!(users_meta.name, "users");
+        assert_eq!(users_meta.file_count, 3);
+        assert_eq!(users_meta.route_count, 2);
+        assert!(users_meta.has_tests);
+        assert_eq!(
+            users_meta.total_bytes,
+            ["routes.py", "tests/test_routes.py", "README.md"]
+                .iter()
+                .map(|f| fs::metadata(users.join(f)).unwrap().len())
+                .sum::<u64>()
+        );
+
+        assert!(extract_module_metadata(tmp.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_project_size_breakdown() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("modules/users")).unwrap();
+        fs::write(tmp.path().join("modules/users/api.py"), vec![b'a'; 300]).unwrap();
+        fs::write(tmp.path().join("modules/common.py"), vec![b'b'; 200]).unwrap();
+        fs::create_dir(tmp.path().join("docs")).unwrap();
+        fs::write(tmp.path().join("docs/guide.md"), vec![b'c'; 800]).unwrap();
+        fs::create_dir(tmp.path().join("core")).unwrap();
+        fs::write(tmp.path().join("core/app.py"), vec![b'd'; 50]).unwrap();
+        fs::write(tmp.path().join("main.py"), vec![b'e'; 10]).unwrap();
+        // 忽略目录不计入统计
+        fs::create_dir(tmp.path().join("node_modules")).unwrap();
+        fs::write(tmp.path().join("node_modules/big.js"), vec![b'f'; 5000]).unwrap();
+
+        let breakdown = project_size_breakdown(tmp.path()).unwrap();
+        let summary: Vec<(&str, u64, usize)> = breakdown
+            .iter()
+            .map(|d| (d.name.as_str(), d.total_bytes, d.file_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("docs", 800, 1),
+                ("modules", 500, 2),
+                ("core", 50, 1),
+                (ROOT_FILES_GROUP, 10, 1),
+            ]
+        );
+
+        assert!(project_size_breakdown(&tmp.path().join("missing")).is_err());
+    }
+
+    /// 根据扫描结果构造下一次增量扫描所需的文件状态
+    fn prior_files_of(files: &[FileEntry]) -> HashMap<String, PriorFile> {
+        files
+            .iter()
+            .map(|f| {
+                let prior = PriorFile {
+                    file_hash: f.file_hash.clone(),
+                    file_size: f.file_size,
+                    mtime: f.mtime,
+                    hash_algo: HashAlgo::Sha256,
+                    binary_checked: false,
+                };
+                (f.relative_path.clone(), prior)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_scan_rewalks_only_changed_dir() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
+        fs::create_dir_all(tmp.path().join("a/nested")).unwrap();
+        fs::create_dir(tmp.path().join("b")).unwrap();
+        fs::create_dir(tmp.path().join("node_modules")).unwrap();
+        fs::write(tmp.path().join("a/nested/x.py"), "x = 1").unwrap();
+        fs::write(tmp.path().join("b/y.py"), "y = 2").unwrap();
+        fs::write(tmp.path().join("node_modules/z.js"), "z").unwrap();
+
+        // 无目录状态：全量遍历所有（非忽略）目录
+        let first =
+            scan_project_files_incremental(tmp.path(), &HashMap::new(), &HashMap::new()).unwrap();
+        assert_eq!(first.walked_dirs, 4);
+        assert_eq!(first.files.len(), 3);
+
+        // 无任何变化：不重新遍历任何目录，文件列表保持一致
+        let prior = prior_files_of(&first.files);
+        let second = scan_project_files_incremental(tmp.path(), &first.dir_mtimes, &prior).unwrap();
+        assert_eq!(second.walked_dirs, 0);
+        assert_eq!(second.files.len(), 3);
+
+        // 在 a/nested 中新增文件：只重新遍历该目录
+        fs::write(tmp.path().join("a/nested/new.py"), "n = 3").unwrap();
+        let prior = prior_files_of(&second.files);
+        let third = scan_project_files_incremental(tmp.path(), &second.dir_mtimes, &prior).unwrap();
+        assert_eq!(third.walked_dirs, 1);
+        let paths: HashSet<&str> = third.files.iter().map(|e| e.relative_path.as_str()).collect();
+        assert!(paths.contains("a/nested/new.py"));
+        assert!(paths.contains("b/y.py"));
+        assert_eq!(third.files.len(), 4);
+    }
+
+    #[test]
+    fn test_incremental_scan_detects_in_place_edit() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("main.py"), "v1").unwrap();
+        let first =
+            scan_project_files_incremental(tmp.path(), &HashMap::new(), &HashMap::new()).unwrap();
+
+        // 原地修改不改变目录 mtime，但文件大小变化会触发重新哈希
+        fs::write(tmp.path().join("main.py"), "version 2").unwrap();
+        let prior = prior_files_of(&first.files);
+        let second = scan_project_files_incremental(tmp.path(), &first.dir_mtimes, &prior).unwrap();
+        assert_eq!(second.walked_dirs, 0);
+        assert_ne!(second.files[0].file_hash, first.files[0].file_hash);
+    }
+
+    #[test]
+    fn test_sub_path_scopes_scan_to_subtree() {
         let tmp = TempDir::new().unwrap();
-        let entries = scan_project_files(tmp.path()).unwrap();
-        assert!(entries.is_empty());
+        fs::write(tmp.path().join("README.md"), "# monorepo").unwrap();
+        fs::create_dir_all(tmp.path().join("backend/app")).unwrap();
+        fs::write(tmp.path().join("backend/main.py"), "import app").unwrap();
+        fs::write(tmp.path().join("backend/app/routes.py"), "def r(): pass").unwrap();
+        fs::create_dir(tmp.path().join("frontend")).unwrap();
+        fs::write(tmp.path().join("frontend/index.ts"), "export {}").unwrap();
+
+        let root = resolve_scan_root(tmp.path(), Some("backend")).unwrap();
+        let entries = scan_project_files(&root).unwrap();
+        let mut paths: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["app/routes.py", "main.py"]);
+
+        // 未指定子目录时保持原项目根目录
+        assert_eq!(resolve_scan_root(tmp.path(), None).unwrap(), tmp.path());
+        assert_eq!(resolve_scan_root(tmp.path(), Some("")).unwrap(), tmp.path());
+
+        assert_eq!(scan_root_prefix(Some("backend")), "backend/");
+        assert_eq!(scan_root_prefix(Some("./backend/app/")), "backend/app/");
+        assert_eq!(scan_root_prefix(Some("backend\\app")), "backend/app/");
+        assert_eq!(scan_root_prefix(Some(" ")), "");
+        assert_eq!(scan_root_prefix(None), "");
     }
 
     #[test]
-    fn test_scan_with_files() {
+    fn test_sub_path_rejects_traversal() {
         let tmp = TempDir::new().unwrap();
-        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
-        fs::create_dir(tmp.path().join("utils")).unwrap();
-        fs::write(tmp.path().join("utils/helper.py"), "def help(): pass").unwrap();
-
-        let entries = scan_project_files(tmp.path()).unwrap();
-        assert_eq!(entries.len(), 2);
+        fs::create_dir(tmp.path().join("backend")).unwrap();
 
-        let paths: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
-        assert!(paths.contains(&"main.py"));
-        assert!(paths.contains(&"utils/helper.py"));
+        let err = resolve_scan_root(tmp.path(), Some("../escape")).unwrap_err();
+        assert!(err.contains(".."));
+        assert!(resolve_scan_root(tmp.path(), Some("backend/../../escape")).is_err());
+        assert!(resolve_scan_root(tmp.path(), Some("missing")).is_err());
     }
 
     #[test]
@@ -1373,6 +3326,106 @@ mod tests {
         assert_eq!(entries[0].relative_path, "app.py");
     }
 
+    #[test]
+    fn test_custom_ignored_dirs_skip_populated_directory() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("app.py"), "def main():\n    pass\n").unwrap();
+        fs::create_dir_all(tmp.path().join("coverage/lcov")).unwrap();
+        fs::write(tmp.path().join("coverage/lcov/report.py"), "def cov():\n    pass\n").unwrap();
+        fs::write(tmp.path().join("coverage/index.js"), "function cov() {}").unwrap();
+
+        // 默认配置不跳过 coverage
+        assert_eq!(scan_project_files(tmp.path()).unwrap().len(), 3);
+
+        let config = ScanConfig::with_ignored_dirs(&[" coverage ".to_string(), String::new()]);
+        assert_eq!(config.extra_ignored_dirs, vec!["coverage".to_string()]);
+        assert!(config.is_ignored_dir("node_modules"));
+
+        let entries = scan_project_files_with_config(tmp.path(), &config, &HashMap::new()).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["app.py"]);
+
+        let scan = scan_project_files_incremental_with_progress(
+            tmp.path(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config,
+            &|_, _| {},
+        )
+        .unwrap();
+        assert_eq!(scan.files.len(), 1);
+        assert!(!scan.dir_mtimes.contains_key("coverage"));
+
+        let overview = analyze_project_overview_with_config(tmp.path(), 0, &config).unwrap();
+        assert_eq!(overview.total_files, 1);
+
+        let signatures =
+            extract_project_signatures_with_config(tmp.path(), &[], &config, &HashMap::new())
+                .unwrap();
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(signatures[0].relative_path, "app.py");
+    }
+
+    #[test]
+    fn test_scan_respects_gitignore_when_enabled() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join(".gitignore"),
+            "generated/\n*.log\n!keep.log\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("app.py"), "pass").unwrap();
+        fs::write(tmp.path().join("debug.log"), "log").unwrap();
+        fs::write(tmp.path().join("keep.log"), "log").unwrap();
+        fs::create_dir_all(tmp.path().join("generated/sub")).unwrap();
+        fs::write(tmp.path().join("generated/models.py"), "pass").unwrap();
+        fs::write(tmp.path().join("generated/sub/schema.py"), "pass").unwrap();
+        // 嵌套 .gitignore 只作用于所在子树，并可反选上层规则
+        fs::create_dir(tmp.path().join("migrations")).unwrap();
+        fs::write(
+            tmp.path().join("migrations/.gitignore"),
+            "*.py\n!0001_init.py\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("migrations/0001_init.py"), "pass").unwrap();
+        fs::write(tmp.path().join("migrations/0002_auto.py"), "pass").unwrap();
+        fs::write(tmp.path().join("migrations/trace.log"), "log").unwrap();
+
+        let paths_of = |entries: Vec<FileEntry>| {
+            let mut paths: Vec<String> = entries.into_iter().map(|e| e.relative_path).collect();
+            paths.sort();
+            paths
+        };
+
+        // 默认不读取 .gitignore
+        assert_eq!(scan_project_files(tmp.path()).unwrap().len(), 10);
+
+        let config = ScanConfig {
+            respect_gitignore: true,
+            ..ScanConfig::default()
+        };
+        let expected = vec![
+            ".gitignore",
+            "app.py",
+            "keep.log",
+            "migrations/.gitignore",
+            "migrations/0001_init.py",
+        ];
+        let entries = scan_project_files_with_config(tmp.path(), &config, &HashMap::new()).unwrap();
+        assert_eq!(paths_of(entries), expected);
+
+        let scan = scan_project_files_incremental_with_progress(
+            tmp.path(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config,
+            &|_, _| {},
+        )
+        .unwrap();
+        assert_eq!(paths_of(scan.files), expected);
+        assert!(scan.dir_mtimes.is_empty());
+    }
+
     #[test]
     fn test_hash_consistency() {
         let tmp = TempDir::new().unwrap();
@@ -1388,12 +3441,294 @@ mod tests {
         assert_eq!(hash1, &entries2[0].file_hash);
     }
 
+    #[test]
+    fn test_blake3_hashes_are_stable_and_distinct() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("empty.txt"), "").unwrap();
+        fs::write(tmp.path().join("a.txt"), "hello").unwrap();
+        fs::write(tmp.path().join("b.txt"), "world").unwrap();
+
+        let empty = tmp.path().join("empty.txt");
+        assert_eq!(
+            compute_file_hash_with(&empty, HashAlgo::Blake3).unwrap(),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            compute_file_hash_with(&empty, HashAlgo::Sha256).unwrap(),
+            compute_file_hash(&empty).unwrap()
+        );
+
+        let config = ScanConfig {
+            hash_algo: HashAlgo::Blake3,
+            ..ScanConfig::default()
+        };
+        let first = scan_project_files_with_config(tmp.path(), &config, &HashMap::new()).unwrap();
+        let second = scan_project_files_with_config(tmp.path(), &config, &HashMap::new()).unwrap();
+        let sha = scan_project_files(tmp.path()).unwrap();
+        for ((b1, b2), s) in first.iter().zip(&second).zip(&sha) {
+            assert_eq!(b1.file_hash, b2.file_hash);
+            assert_ne!(b1.file_hash, s.file_hash);
+            assert_eq!(b1.file_hash.len(), 64);
+        }
+        assert_ne!(first[0].file_hash, first[1].file_hash);
+
+        assert_eq!(parse_hash_algo(""), HashAlgo::Sha256);
+        assert_eq!(parse_hash_algo(" BLAKE3 "), HashAlgo::Blake3);
+        assert_eq!(parse_hash_algo("md5"), HashAlgo::Sha256);
+    }
+
+    #[test]
+    fn test_switching_hash_algo_rehashes_every_file() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("main.py"), "print('hello')").unwrap();
+        fs::write(tmp.path().join("src/a.py"), "a = 1").unwrap();
+
+        let first =
+            scan_project_files_incremental(tmp.path(), &HashMap::new(), &HashMap::new()).unwrap();
+        let prior = prior_files_of(&first.files);
+
+        // 目录与文件均未变化，但记录的哈希为 SHA256：切换到 BLAKE3 后必须全部重算
+        let config = ScanConfig {
+            hash_algo: HashAlgo::Blake3,
+            ..ScanConfig::default()
+        };
+        let second = scan_project_files_incremental_with_progress(
+            tmp.path(),
+            &first.dir_mtimes,
+            &prior,
+            &config,
+            &|_, _| {},
+        )
+        .unwrap();
+        assert_eq!(second.walked_dirs, 0);
+        assert_eq!(second.files.len(), 2);
+        for entry in &second.files {
+            let expected =
+                compute_file_hash_with(&tmp.path().join(&entry.relative_path), HashAlgo::Blake3)
+                    .unwrap();
+            assert_eq!(entry.file_hash, expected);
+            assert_ne!(entry.file_hash, prior[&entry.relative_path].file_hash);
+        }
+    }
+
+    #[test]
+    fn test_is_probably_binary() {
+        let tmp = TempDir::new().unwrap();
+        let write = |name: &str, content: &[u8]| {
+            let path = tmp.path().join(name);
+            fs::write(&path, content).unwrap();
+            path
+        };
+
+        assert!(is_probably_binary(&write("nul.bin", b"abc\0def")));
+        assert!(is_probably_binary(&write(
+            "noise.bin",
+            &[0xff, 0xfe, 0x80, b'a', 0x90]
+        )));
+        assert!(!is_probably_binary(&write("empty.txt", b"")));
+        assert!(!is_probably_binary(&write(
+            "zh.py",
+            "# 中文注释\nx = 1\n".as_bytes()
+        )));
+        // 8KB 边界截断的多字节字符不计为非法
+        let mut long = vec![b'a'; BINARY_SNIFF_BYTES - 1];
+        long.extend_from_slice("中".as_bytes());
+        assert!(!is_probably_binary(&write("long.py", &long)));
+        assert!(!is_probably_binary(&tmp.path().join("missing.py")));
+    }
+
+    #[test]
+    fn test_binary_files_skipped_in_signatures_and_scan() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/app.py"), "def main():\n    pass\n").unwrap();
+        let mut blob = b"def fake():\n".to_vec();
+        blob.extend_from_slice(&[0u8, 1, 2, 3, 0xff, 0xd8]);
+        fs::write(tmp.path().join("src/blob.py"), &blob).unwrap();
+
+        let signatures = extract_project_signatures(tmp.path()).unwrap();
+        let paths: Vec<&str> = signatures
+            .iter()
+            .map(|s| s.relative_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["src/app.py"]);
+
+        // 默认扫描保留二进制文件，skip_binary 时剔除
+        assert_eq!(scan_project_files(tmp.path()).unwrap().len(), 2);
+        let config = ScanConfig {
+            skip_binary: true,
+            ..ScanConfig::default()
+        };
+        let entries = scan_project_files_with_config(tmp.path(), &config, &HashMap::new()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].relative_path, "src/app.py");
+
+        let scan = scan_project_files_incremental_with_progress(
+            tmp.path(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &config,
+            &|_, _| {},
+        )
+        .unwrap();
+        let paths: Vec<&str> = scan
+            .files
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["src/app.py"]);
+
+        // 未做二进制检测的旧记录即使可复用也重新检测；已检测的记录直接信任
+        let prior_with = |binary_checked: bool| -> HashMap<String, PriorFile> {
+            scan_project_files(tmp.path())
+                .unwrap()
+                .into_iter()
+                .map(|f| {
+                    let prior = PriorFile {
+                        file_hash: f.file_hash,
+                        file_size: f.file_size,
+                        mtime: f.mtime,
+                        hash_algo: HashAlgo::Sha256,
+                        binary_checked,
+                    };
+                    (f.relative_path, prior)
+                })
+                .collect()
+        };
+        let scanned = |prior: &HashMap<String, PriorFile>| -> usize {
+            scan_project_files_incremental_with_progress(
+                tmp.path(),
+                &HashMap::new(),
+                prior,
+                &config,
+                &|_, _| {},
+            )
+            .unwrap()
+            .files
+            .len()
+        };
+        assert_eq!(scanned(&prior_with(false)), 1);
+        assert_eq!(scanned(&prior_with(true)), 2);
+    }
+
+    #[test]
+    fn test_parallel_hashing_matches_sequential() {
+        let tmp = TempDir::new().unwrap();
+        for dir in ["a", "b/c", "d"] {
+            fs::create_dir_all(tmp.path().join(dir)).unwrap();
+            for i in 0..40 {
+                let content = format!("{} {}", dir, i).repeat(i + 1);
+                fs::write(tmp.path().join(format!("{}/f{}.txt", dir, i)), content).unwrap();
+            }
+        }
+
+        let parallel = scan_project_files(tmp.path()).unwrap();
+        let sequential: Vec<(String, String)> = collect_project_files(tmp.path())
+            .unwrap()
+            .into_iter()
+            .map(|(relative, abs_path, _, _)| (relative, compute_file_hash(&abs_path).unwrap()))
+            .collect();
+
+        assert_eq!(parallel.len(), 120);
+        let parallel: Vec<(String, String)> = parallel
+            .into_iter()
+            .map(|e| (e.relative_path, e.file_hash))
+            .collect();
+        assert_eq!(parallel, sequential);
+        // 遍历顺序确定：按文件名排序
+        assert_eq!(parallel[0].0, "a/f0.txt");
+        assert_eq!(parallel[1].0, "a/f1.txt");
+        assert_eq!(parallel[2].0, "a/f10.txt");
+    }
+
     #[test]
     fn test_nonexistent_path() {
         let result = scan_project_files(Path::new("/nonexistent/path/xyz"));
         assert!(result.is_err());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_file_skipped_with_warning() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("ok.py"), "x = 1\n").unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        fs::write(tmp.path().join("src/app.py"), "y = 2\n").unwrap();
+        let secret = tmp.path().join("secret.py");
+        fs::write(&secret, "token = 1\n").unwrap();
+        fs::set_permissions(&secret, fs::Permissions::from_mode(0o000)).unwrap();
+        // root 不受文件权限限制，此时无法模拟不可读文件
+        if fs::read(&secret).is_ok() {
+            return;
+        }
+
+        let (entries, warnings) = scan_project_files_with_prior(tmp.path(), &HashMap::new()).unwrap();
+        let mut paths: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["ok.py", "src/app.py"]);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].relative_path, "secret.py");
+
+        // 宽松扫描只跳过该文件，严格扫描则整体失败
+        assert_eq!(scan_project_files(tmp.path()).unwrap().len(), 2);
+        assert!(scan_project_files_strict(tmp.path()).unwrap_err().contains("secret.py"));
+
+        // 增量扫描同样跳过并返回警告；无法读取的目录不记录修改时间，下次重新读取
+        let locked = tmp.path().join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let scan =
+            scan_project_files_incremental(tmp.path(), &HashMap::new(), &HashMap::new()).unwrap();
+        assert_eq!(scan.files.len(), 2);
+        let mut skipped: Vec<&str> = scan
+            .warnings
+            .iter()
+            .map(|w| w.relative_path.as_str())
+            .collect();
+        skipped.sort();
+        assert_eq!(skipped, vec!["locked", "secret.py"]);
+        assert!(!scan.dir_mtimes.contains_key("locked"));
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(&secret, fs::Permissions::from_mode(0o644)).unwrap();
+    }
+
+    #[test]
+    fn test_scan_with_prior_reuses_unchanged_hashes() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("same.py"), "x = 1\n").unwrap();
+        fs::write(tmp.path().join("changed.py"), "y = 2\n").unwrap();
+        let first = scan_project_files(tmp.path()).unwrap();
+
+        // 记录的哈希为占位值：若被原样返回，说明复用时未读取文件内容
+        let mut prior: HashMap<String, PriorFile> = first
+            .iter()
+            .map(|e| {
+                let prior = PriorFile {
+                    file_hash: format!("cached:{}", e.relative_path),
+                    file_size: e.file_size,
+                    mtime: e.mtime,
+                    hash_algo: HashAlgo::Sha256,
+                    binary_checked: false,
+                };
+                (e.relative_path.clone(), prior)
+            })
+            .collect();
+        // 大小变化的文件必须重新计算哈希
+        prior.get_mut("changed.py").unwrap().file_size += 1;
+
+        let (entries, warnings) = scan_project_files_with_prior(tmp.path(), &prior).unwrap();
+        assert!(warnings.is_empty());
+        let hash_of = |path: &str| {
+            entries.iter().find(|e| e.relative_path == path).unwrap().file_hash.clone()
+        };
+        assert_eq!(hash_of("same.py"), "cached:same.py");
+        let expected = first.iter().find(|e| e.relative_path == "changed.py").unwrap();
+        assert_eq!(hash_of("changed.py"), expected.file_hash);
+    }
+
     // ====================================================================
     // 依赖推断测试
     // ====================================================================
@@ -1421,6 +3756,34 @@ mod tests {
         assert_eq!(edges[0].target, "src/components/Button.tsx");
     }
 
+    #[test]
+    fn test_code_extensions_setting_treats_pyi_as_code() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("stubs")).unwrap();
+        fs::write(
+            tmp.path().join("stubs/api.pyi"),
+            "from .models import User\ndef login(user: User) -> bool: ...\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("stubs/models.py"), "class User: ...\n").unwrap();
+        let file_paths = vec!["stubs/api.pyi".to_string(), "stubs/models.py".to_string()];
+
+        // 默认不识别 .pyi
+        assert!(extract_dependencies(tmp.path(), &file_paths).unwrap().is_empty());
+
+        let extra = parse_code_extensions(r#"["pyi", ".MTS"]"#);
+        assert_eq!(extra, vec![".pyi".to_string(), ".mts".to_string()]);
+        let edges = extract_dependencies_with_extensions(tmp.path(), &file_paths, &extra).unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].source, "stubs/api.pyi");
+        assert_eq!(edges[0].target, "stubs/models.py");
+
+        // 签名提取按 Python 处理 .pyi
+        let sigs = extract_project_signatures_with_extensions(tmp.path(), &extra).unwrap();
+        let api = sigs.iter().find(|s| s.relative_path == "stubs/api.pyi").unwrap();
+        assert_eq!(api.language, "Python");
+    }
+
     #[test]
     fn test_extract_js_import_ignores_packages() {
         let tmp = TempDir::new().unwrap();
@@ -1500,6 +3863,28 @@ mod tests {
         assert_eq!(edges[0].target, "app/utils.py");
     }
 
+    #[test]
+    fn test_extract_python_relative_import_beyond_root_warns() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("app")).unwrap();
+        fs::write(
+            tmp.path().join("app/main.py"),
+            "from .utils import helper\nfrom ...deep import x\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("app/utils.py"), "def helper(): pass").unwrap();
+
+        let file_paths = vec!["app/main.py".to_string(), "app/utils.py".to_string()];
+
+        let (edges, warnings) =
+            extract_dependencies_with_warnings(tmp.path(), &file_paths, &[]).unwrap();
+        assert_eq!(edges.len(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].file, "app/main.py");
+        assert_eq!(warnings[0].line, 2);
+        assert!(warnings[0].message.contains("...deep"), "{}", warnings[0].message);
+    }
+
     #[test]
     fn test_extract_fastapi_project_full() {
         // 模拟完整的 FastAPI 项目结构，验证绝对导入依赖提取
@@ -1648,6 +4033,42 @@ mod tests {
         assert_eq!(edges[0].target, "api/v1/dict/__init__.py");
     }
 
+    #[test]
+    fn test_extract_python_namespace_package_import() {
+        // PEP 420 命名空间包：plugins/ 下没有 __init__.py
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("plugins/exporters")).unwrap();
+        fs::write(tmp.path().join("app.py"), "from plugins import csv_export\n").unwrap();
+        fs::write(tmp.path().join("plugins/csv_export.py"), "def run(): pass").unwrap();
+        fs::write(tmp.path().join("plugins/json_export.py"), "def run(): pass").unwrap();
+        fs::write(
+            tmp.path().join("plugins/exporters/runner.py"),
+            "from .. import json_export\nimport plugins.exporters\n",
+        )
+        .unwrap();
+
+        let file_paths = vec![
+            "app.py".to_string(),
+            "plugins/csv_export.py".to_string(),
+            "plugins/json_export.py".to_string(),
+            "plugins/exporters/runner.py".to_string(),
+        ];
+
+        let edges = extract_dependencies(tmp.path(), &file_paths).unwrap();
+        let pairs: Vec<(&str, &str)> = edges
+            .iter()
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        // 目录无 __init__.py 时优先指向导入的同名子模块，其次为第一个模块；包内自引用不产生自环
+        assert_eq!(
+            pairs,
+            vec![
+                ("app.py", "plugins/csv_export.py"),
+                ("plugins/exporters/runner.py", "plugins/json_export.py"),
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_python_absolute_import_with_prefix() {
         // 测试项目根目录本身是包目录的场景
@@ -1729,6 +4150,36 @@ mod tests {
         assert_eq!(edges[0].target, "app/utils.py");
     }
 
+    #[test]
+    fn test_extract_repeated_calls_produce_identical_edges() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("src/utils")).unwrap();
+        fs::write(
+            tmp.path().join("src/main.ts"),
+            "import { a } from './utils/a';\nconst b = require('./utils/b');\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join("src/utils/a.ts"), "export const a = 1;").unwrap();
+        fs::write(tmp.path().join("src/utils/b.ts"), "module.exports = 2;").unwrap();
+        fs::write(tmp.path().join("app.py"), "from .helpers import run\n").unwrap();
+        fs::write(tmp.path().join("helpers.py"), "def run(): pass").unwrap();
+
+        let file_paths = vec![
+            "src/main.ts".to_string(),
+            "src/utils/a.ts".to_string(),
+            "src/utils/b.ts".to_string(),
+            "app.py".to_string(),
+            "helpers.py".to_string(),
+        ];
+
+        // 正则为进程级静态变量，多次调用结果必须完全一致
+        let first = extract_dependencies(tmp.path(), &file_paths).unwrap();
+        assert_eq!(first.len(), 3);
+        for _ in 0..5 {
+            assert_eq!(extract_dependencies(tmp.path(), &file_paths).unwrap(), first);
+        }
+    }
+
     #[test]
     fn test_extract_skips_non_code_files() {
         let tmp = TempDir::new().unwrap();
@@ -1810,4 +4261,297 @@ mod tests {
         let bytes = embedding_to_bytes(&emb);
         assert_eq!(bytes.len(), 768 * 4); // 每个 f32 占 4 字节
     }
+
+    #[test]
+    fn test_dot_product_of_normalized_equals_cosine() {
+        let pairs: [(Vec<f32>, Vec<f32>); 4] = [
+            (vec![1.0, 2.0, 3.0], vec![4.0, -5.0, 6.0]),
+            (vec![0.3, -0.7, 12.5, 0.01], vec![-2.0, 0.5, 3.25, 8.0]),
+            (vec![1.0, 2.0], vec![-1.0, -2.0]),
+            (vec![0.0, 0.0], vec![1.0, 1.0]),
+        ];
+        for (a, b) in &pairs {
+            let expected = cosine_similarity(a, b);
+            let actual = dot_product(&normalize_embedding(a), &normalize_embedding(b));
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "{} != {}",
+                actual,
+                expected
+            );
+        }
+        assert_eq!(dot_product(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(dot_product(&[], &[]), 0.0);
+    }
+
+    #[test]
+    fn test_normalized_embedding_to_bytes_stores_unit_vector() {
+        let original = vec![3.0, -4.0];
+        let stored = bytes_to_embedding(&normalized_embedding_to_bytes(&original));
+        assert!((stored[0] - 0.6).abs() < 1e-6 && (stored[1] + 0.8).abs() < 1e-6);
+        assert!((dot_product(&stored, &stored) - 1.0).abs() < 1e-6);
+
+        // 零向量无法归一化，原样保存
+        assert_eq!(normalize_embedding(&[0.0, 0.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_bfs_scope_modules_only_ignores_shared_helpers() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("utils")).unwrap();
+        fs::create_dir_all(root.join("modules/auth")).unwrap();
+        fs::create_dir_all(root.join("modules/billing")).unwrap();
+        // 两个模块都引用同一个共享工具，共享工具又引用了 billing
+        fs::write(root.join("utils/shared.py"), "from modules.billing.models import Invoice\n").unwrap();
+        fs::write(root.join("modules/auth/routes.py"), "from utils.shared import helper\n").unwrap();
+        fs::write(root.join("modules/billing/routes.py"), "from utils.shared import helper\n").unwrap();
+        fs::write(root.join("modules/billing/models.py"), "class Invoice: pass\n").unwrap();
+
+        let selected = vec!["auth".to_string()];
+        let all = vec!["auth".to_string(), "billing".to_string()];
+
+        let (full, added) = resolve_module_dependencies_with_scope(
+            root, "modules", &selected, &all, BfsScope::AllImports,
+        )
+        .unwrap();
+        assert_eq!(full, vec!["auth", "billing"]);
+        assert_eq!(added, vec!["billing"]);
+
+        let (full, added) = resolve_module_dependencies_with_scope(
+            root, "modules", &selected, &all, BfsScope::ModulesOnly,
+        )
+        .unwrap();
+        assert_eq!(full, vec!["auth"]);
+        assert!(added.is_empty());
+    }
+
+    #[test]
+    fn test_bfs_scope_defaults_to_modules_only_and_matches_nested_modules() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("utils")).unwrap();
+        fs::create_dir_all(root.join("modules/auth")).unwrap();
+        fs::create_dir_all(root.join("modules/admin/users")).unwrap();
+        fs::write(
+            root.join("utils/shared.py"),
+            "from modules.admin.users.models import User\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("modules/auth/routes.py"),
+            "from utils.shared import helper\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("modules/admin/users/models.py"),
+            "class User: pass\n",
+        )
+        .unwrap();
+
+        let selected = vec!["auth".to_string()];
+        let all = vec!["auth".to_string(), "admin/users".to_string()];
+
+        // 默认与构建选项默认值一致：不经由共享文件补充模块
+        let (_, added) = resolve_module_dependencies(root, "modules", &selected, &all).unwrap();
+        assert!(added.is_empty());
+        assert_eq!(
+            crate::models::dtos::BuildOptions::default().bfs_scope,
+            BfsScope::ModulesOnly
+        );
+
+        // AllImports 经由共享文件补充嵌套模块（按限定名匹配）
+        let (_, added) = resolve_module_dependencies_with_scope(
+            root,
+            "modules",
+            &selected,
+            &all,
+            BfsScope::AllImports,
+        )
+        .unwrap();
+        assert_eq!(added, vec!["admin/users"]);
+        let preview =
+            preview_module_expansion(root, "modules", &selected, &all, BfsScope::AllImports)
+                .unwrap();
+        assert_eq!(preview.reasons.len(), 1);
+        assert_eq!(preview.reasons[0].module, "admin/users");
+    }
+
+    #[test]
+    fn test_preview_module_expansion_explains_added_modules() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for dir in ["utils", "modules/payments", "modules/orders", "modules/billing", "modules/auth"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("modules/payments/api.py"), "from modules.orders.service import place\n").unwrap();
+        fs::write(root.join("modules/orders/service.py"), "from utils.shared import fmt\n").unwrap();
+        fs::write(root.join("utils/shared.py"), "from modules.billing.models import Invoice\n").unwrap();
+        fs::write(root.join("modules/billing/models.py"), "class Invoice: pass\n").unwrap();
+        fs::write(root.join("modules/auth/routes.py"), "def login(): pass\n").unwrap();
+
+        let selected = vec!["payments".to_string()];
+        let all: Vec<String> =
+            ["auth", "billing", "orders", "payments"].iter().map(|s| s.to_string()).collect();
+
+        let preview =
+            preview_module_expansion(root, "modules", &selected, &all, BfsScope::AllImports).unwrap();
+        assert_eq!(preview.selected, vec!["payments"]);
+        assert_eq!(preview.added, vec!["billing", "orders"]);
+        assert_eq!(
+            preview.reasons,
+            vec![
+                ModuleAddition {
+                    module: "orders".to_string(),
+                    required_by: "payments".to_string(),
+                    source_file: "modules/payments/api.py".to_string(),
+                    target_file: "modules/orders/service.py".to_string(),
+                },
+                ModuleAddition {
+                    module: "billing".to_string(),
+                    required_by: "orders".to_string(),
+                    source_file: "utils/shared.py".to_string(),
+                    target_file: "modules/billing/models.py".to_string(),
+                },
+            ]
+        );
+
+        // ModulesOnly 不经由共享文件，billing 不会被补充
+        let preview =
+            preview_module_expansion(root, "modules", &selected, &all, BfsScope::ModulesOnly).unwrap();
+        assert_eq!(preview.added, vec!["orders"]);
+        assert_eq!(preview.reasons.len(), 1);
+    }
+
+    #[test]
+    fn test_incremental_scan_reports_monotonic_progress() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("src")).unwrap();
+        for i in 0..20 {
+            fs::write(tmp.path().join(format!("src/f{}.py", i)), format!("x = {}", i)).unwrap();
+        }
+
+        let reports = std::sync::Mutex::new(Vec::new());
+        let scan = scan_project_files_incremental_with_progress(
+            tmp.path(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &ScanConfig::default(),
+            &|done, total| reports.lock().unwrap().push((done, total)),
+        )
+        .unwrap();
+
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(scan.files.len(), 20);
+        assert_eq!(reports.len(), 20);
+        assert!(reports.iter().all(|&(_, total)| total == 20));
+        assert!(reports.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+        assert_eq!(reports.last(), Some(&(20, 20)));
+    }
+
+    #[test]
+    fn test_detect_rust_crates_from_dependencies_table() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("Cargo.toml"),
+            r#"[package]
+name = "demo"
+# 计划迁移到 rocket，暂未引入
+
+[dependencies]
+axum = "0.7"
+db = { package = "sqlx", version = "0.7" }
+
+[dev-dependencies]
+clap = "4"
+
+[target.'cfg(unix)'.dependencies]
+tokio = { version = "1", features = ["full"] }
+"#,
+        )
+        .unwrap();
+
+        let stack = detect_tech_stack(tmp.path(), &[]);
+        assert!(stack.contains(&"Rust".to_string()));
+        assert!(stack.contains(&"Axum".to_string()));
+        assert!(stack.contains(&"SQLx".to_string()));
+        assert!(stack.contains(&"Tokio".to_string()));
+        // 注释与 dev-dependencies 中的提及不计入
+        assert!(!stack.contains(&"Rocket".to_string()));
+        assert!(!stack.contains(&"Clap".to_string()));
+    }
+
+    #[test]
+    fn test_detect_python_packages_by_exact_name() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("requirements.txt"),
+            "# redis 暂未使用\nfastapi-utils==0.2.1\nredisearch-fake>=1.0\nSQLAlchemy[asyncio]>=2.0  # ORM\n-r base.txt\n",
+        )
+        .unwrap();
+
+        let stack = detect_tech_stack(tmp.path(), &[]);
+        assert!(stack.contains(&"SQLAlchemy".to_string()));
+        // 前缀相同的其他包与注释中的提及不计入
+        assert!(!stack.contains(&"FastAPI".to_string()));
+        assert!(!stack.contains(&"Redis".to_string()));
+    }
+
+    #[test]
+    fn test_detect_python_packages_from_pyproject_and_setup() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("pyproject.toml"),
+            r#"[project]
+name = "demo"
+description = "celery worker"
+dependencies = ["fastapi>=0.110", "uvicorn[standard]"]
+
+[project.optional-dependencies]
+test = ["pytest"]
+"#,
+        )
+        .unwrap();
+        fs::write(
+            tmp.path().join("setup.py"),
+            "setup(\n    name='demo',\n    description='A Flask app',\n    install_requires=['redis>=5', 'alembic'],\n)\n",
+        )
+        .unwrap();
+
+        let stack = detect_tech_stack(tmp.path(), &[]);
+        for label in ["FastAPI", "Uvicorn", "Pytest", "Redis", "Alembic"] {
+            assert!(stack.contains(&label.to_string()), "缺少 {}", label);
+        }
+        assert!(!stack.contains(&"Celery".to_string()));
+        assert!(!stack.contains(&"Flask".to_string()));
+    }
+
+    #[test]
+    fn test_detect_js_packages_from_package_json() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("package.json"),
+            r#"{
+  "name": "demo",
+  "description": "not a react app",
+  "scripts": { "build": "vite build" },
+  "dependencies": { "vue": "^3.4.0", "@nestjs/core": "^10.0.0" },
+  "devDependencies": { "typescript": "^5.0.0", "react-icons": "^5.0.0" }
+}"#,
+        )
+        .unwrap();
+        let entries = vec![FileEntry {
+            relative_path: "src/main.ts".to_string(),
+            file_hash: String::new(),
+            file_size: 0,
+            mtime: 0,
+        }];
+
+        let stack = detect_tech_stack(tmp.path(), &entries);
+        assert!(stack.contains(&"Vue".to_string()));
+        assert!(stack.contains(&"NestJS".to_string()));
+        assert!(stack.contains(&"TypeScript".to_string()));
+        assert!(!stack.contains(&"React".to_string()));
+        assert!(!stack.contains(&"Vite".to_string()));
+    }
 }
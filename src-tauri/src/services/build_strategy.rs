@@ -6,16 +6,18 @@
 // 每种技术栈实现 BuildStrategy trait，通过 get_builder 工厂函数获取对应策略。
 // 新增技术栈只需添加新的 struct + impl，无需修改现有代码（OCP 原则）。
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use time::OffsetDateTime;
+use time::{format_description, OffsetDateTime, UtcOffset};
 
-use crate::models::dtos::BuildResult;
+use crate::models::dtos::{BuildOptions, BuildPlan, BuildResult, TechStackInfo, TimestampStyle};
 use crate::services::analyzer;
-use crate::services::packer::{copy_dir_excluding, create_zip_from_dir, validate_build_params};
+use crate::services::packer::{self, create_zip_from_dir_with, validate_build_params};
 use crate::services::module_rewriter;
-use crate::services::DEFAULT_EXCLUDES;
+use crate::services::{CORE_FILES, DEFAULT_EXCLUDES, TEST_EXCLUDES};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::normalize_rel_path;
+use crate::utils::pattern::wildcard_match;
 
 // ============================================================================
 // 构建策略 Trait 定义
@@ -26,6 +28,9 @@ pub trait BuildStrategy {
     /// 该策略对应的技术栈标识（如 "fastapi"、"vue3"）
     fn tech_stack(&self) -> &str;
 
+    /// 技术栈显示名称（如 "FastAPI"、"Vue 3"）
+    fn display_name(&self) -> &str;
+
     /// 获取该技术栈额外需要排除的目录（在 DEFAULT_EXCLUDES 基础上追加）
     fn extra_excludes(&self) -> Vec<String>;
 
@@ -68,6 +73,10 @@ impl BuildStrategy for FastApiBuildStrategy {
         "fastapi"
     }
 
+    fn display_name(&self) -> &str {
+        "FastAPI"
+    }
+
     fn extra_excludes(&self) -> Vec<String> {
         // FastAPI 项目无额外排除项（DEFAULT_EXCLUDES 已覆盖）
         vec![]
@@ -114,6 +123,10 @@ impl BuildStrategy for Vue3BuildStrategy {
         "vue3"
     }
 
+    fn display_name(&self) -> &str {
+        "Vue 3"
+    }
+
     fn extra_excludes(&self) -> Vec<String> {
         // Vue3 项目无额外排除项（DEFAULT_EXCLUDES 已覆盖）
         vec![]
@@ -147,15 +160,213 @@ impl BuildStrategy for Vue3BuildStrategy {
     }
 }
 
+// ============================================================================
+// Angular 构建策略
+// ============================================================================
+
+/// Angular 构建策略：排除式骨架复制 + 依赖分析 + 模块提取
+///
+/// 路由表由 `AngularImportRewriter` 按选中模块过滤。
+pub struct AngularBuildStrategy;
+
+impl BuildStrategy for AngularBuildStrategy {
+    fn tech_stack(&self) -> &str {
+        "angular"
+    }
+
+    fn display_name(&self) -> &str {
+        "Angular"
+    }
+
+    fn extra_excludes(&self) -> Vec<String> {
+        // Angular 项目无额外排除项（DEFAULT_EXCLUDES 已覆盖）
+        vec![]
+    }
+
+    fn default_modules_dir(&self) -> &str {
+        "src/app/modules"
+    }
+
+    fn build(
+        &self,
+        project_path: &Path,
+        selected_modules: &[String],
+        client_name: &str,
+        modules_dir: &str,
+        all_module_names: &[String],
+    ) -> AppResult<BuildResult> {
+        build_common(self, project_path, selected_modules, client_name, modules_dir, all_module_names)
+    }
+
+    fn build_with_log(
+        &self,
+        project_path: &Path,
+        selected_modules: &[String],
+        client_name: &str,
+        modules_dir: &str,
+        all_module_names: &[String],
+        log_fn: &dyn Fn(&str),
+    ) -> AppResult<BuildResult> {
+        build_common_with_log(self, project_path, selected_modules, client_name, modules_dir, all_module_names, log_fn)
+    }
+}
+
+// ============================================================================
+// Nuxt 构建策略
+// ============================================================================
+
+/// Nuxt 构建策略：排除式骨架复制 + 依赖分析 + 模块提取
+///
+/// 路由由 `pages/` 目录自动生成，只需交付选中模块的页面目录。
+pub struct NuxtBuildStrategy;
+
+impl BuildStrategy for NuxtBuildStrategy {
+    fn tech_stack(&self) -> &str {
+        "nuxt"
+    }
+
+    fn display_name(&self) -> &str {
+        "Nuxt"
+    }
+
+    fn extra_excludes(&self) -> Vec<String> {
+        // Nuxt 项目无额外排除项（DEFAULT_EXCLUDES 已覆盖）
+        vec![]
+    }
+
+    fn default_modules_dir(&self) -> &str {
+        "pages"
+    }
+
+    fn build(
+        &self,
+        project_path: &Path,
+        selected_modules: &[String],
+        client_name: &str,
+        modules_dir: &str,
+        all_module_names: &[String],
+    ) -> AppResult<BuildResult> {
+        build_common(self, project_path, selected_modules, client_name, modules_dir, all_module_names)
+    }
+
+    fn build_with_log(
+        &self,
+        project_path: &Path,
+        selected_modules: &[String],
+        client_name: &str,
+        modules_dir: &str,
+        all_module_names: &[String],
+        log_fn: &dyn Fn(&str),
+    ) -> AppResult<BuildResult> {
+        build_common_with_log(self, project_path, selected_modules, client_name, modules_dir, all_module_names, log_fn)
+    }
+}
+
 // ============================================================================
 // 通用构建流程（DRY 原则：提取公共逻辑）
 // ============================================================================
 
-/// 生成时间戳后缀（格式：yyyyMMdd_HHmmss）
+/// 构建时间戳时区设置键（`utc` / `local`，默认 utc）
+pub const BUILD_TIMESTAMP_TZ_KEY: &str = "build_timestamp_tz";
+
+/// `build_timestamp_tz` 的可选值
+pub const BUILD_TIMESTAMP_TZ_VALUES: &[&str] = &["utc", "local"];
+
+/// 构建时间戳格式设置键（`time` 格式描述）
+pub const BUILD_TIMESTAMP_FORMAT_KEY: &str = "build_timestamp_format";
+
+/// 默认时间戳格式（yyyyMMdd_HHmmss）
+pub const DEFAULT_BUILD_TIMESTAMP_FORMAT: &str = "[year][month][day]_[hour][minute][second]";
+
+/// 由设置项取值解析时间戳风格，未设置或为空时使用默认值
+pub fn timestamp_style_from_settings(tz: Option<&str>, format: Option<&str>) -> TimestampStyle {
+    TimestampStyle {
+        local: tz.map(str::trim) == Some("local"),
+        format: format
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string),
+    }
+}
+
+/// 校验时间戳格式：须为合法的 `time` 格式描述，且输出可用作文件名
+pub fn validate_timestamp_format(format: &str) -> Result<(), String> {
+    let description = format_description::parse(format)
+        .map_err(|e| format!("时间戳格式无效：{}", e))?;
+    let sample = OffsetDateTime::UNIX_EPOCH
+        .format(&description)
+        .map_err(|e| format!("时间戳格式无效：{}", e))?;
+    if sample.is_empty() {
+        return Err("时间戳格式无效：输出为空".to_string());
+    }
+    if let Some(c) = sample.chars().find(|c| r#"/\:*?"<>|"#.contains(*c)) {
+        return Err(format!("时间戳格式无效：输出包含文件名不允许的字符 {:?}", c));
+    }
+    Ok(())
+}
+
+/// 进程启动时记录的本地时区偏移（见 `capture_local_offset`）
+static LOCAL_OFFSET: once_cell::sync::OnceCell<UtcOffset> = once_cell::sync::OnceCell::new();
+
+/// 记录本地时区偏移，须在进程仍为单线程时（应用入口、启动异步运行时之前）调用
+///
+/// `time` 在多线程进程中出于安全考虑拒绝读取本地时区（返回 `IndeterminateOffset`），
+/// 构建时再获取会静默回退到 UTC。获取失败时记录警告，`local` 风格的时间戳仍回退到 UTC。
+pub fn capture_local_offset() {
+    match UtcOffset::current_local_offset() {
+        Ok(offset) => {
+            let _ = LOCAL_OFFSET.set(offset);
+        }
+        Err(e) => log::warn!("无法获取本地时区偏移（{}），本地时区的构建时间戳将改用 UTC", e),
+    }
+}
+
+/// 按时间戳风格生成当前时刻的后缀（默认风格为 UTC + yyyyMMdd_HHmmss）
+fn timestamp_suffix_with(style: &TimestampStyle) -> String {
+    format_build_timestamp(OffsetDateTime::now_utc(), style, LOCAL_OFFSET.get().copied())
+}
+
+/// 按时间戳风格格式化指定时刻，`local_offset` 为启动时记录的本地时区偏移
+///
+/// 本地时区偏移不可用、或格式描述无效（如保存校验之前写入的旧值）时，
+/// 记录警告并回退到 UTC / 默认格式。
+fn format_build_timestamp(
+    instant: OffsetDateTime,
+    style: &TimestampStyle,
+    local_offset: Option<UtcOffset>,
+) -> String {
+    let instant = if style.local {
+        match local_offset {
+            Some(offset) => instant.to_offset(offset),
+            None => {
+                log::warn!("本地时区偏移不可用，构建时间戳改用 UTC");
+                instant.to_offset(UtcOffset::UTC)
+            }
+        }
+    } else {
+        instant.to_offset(UtcOffset::UTC)
+    };
+
+    if let Some(format) = style.format.as_deref() {
+        match validate_timestamp_format(format) {
+            Ok(()) => {
+                if let Some(formatted) = format_description::parse(format)
+                    .ok()
+                    .and_then(|description| instant.format(&description).ok())
+                {
+                    return formatted;
+                }
+            }
+            Err(e) => log::warn!("{}，改用默认格式", e),
+        }
+    }
+    default_timestamp(instant)
+}
+
+/// 默认格式的时间戳（yyyyMMdd_HHmmss）
 ///
 /// 使用 `time` crate 替代手写日历算法，更可靠且可维护（KISS 原则）
-fn timestamp_suffix() -> String {
-    let now = OffsetDateTime::now_utc();
+fn default_timestamp(now: OffsetDateTime) -> String {
     format!(
         "{:04}{:02}{:02}_{:02}{:02}{:02}",
         now.year(),
@@ -199,25 +410,324 @@ fn fs_available_space(_path: &Path) -> u64 {
 
 /// 带日志回调的通用构建流程（V2：排除式骨架 + 依赖分析）
 ///
+/// 使用默认构建选项，等价于 `build_common_with_options(.., &BuildOptions::default(), ..)`
+pub fn build_common_with_log(
+    strategy: &dyn BuildStrategy,
+    project_path: &Path,
+    selected_modules: &[String],
+    client_name: &str,
+    modules_dir_override: &str,
+    all_module_names: &[String],
+    log_fn: &dyn Fn(&str),
+) -> AppResult<BuildResult> {
+    build_common_with_options(
+        strategy,
+        project_path,
+        selected_modules,
+        client_name,
+        modules_dir_override,
+        all_module_names,
+        &BuildOptions::default(),
+        log_fn,
+    )
+}
+
+/// 带构建选项的通用构建流程
+///
 /// 构建流程：
+/// 0. 展开 `selected_modules` 中的通配符（如 `admin_*`、`*`）
 /// 1. 复制项目骨架（排除模块目录 + DEFAULT_EXCLUDES + 技术栈额外排除项）
 /// 2. 依赖分析：BFS 遍历选中模块的 import，自动补充被依赖的模块
 /// 3. 复制扩展后的完整模块列表到骨架中
-/// 4. 重写入口文件（仅保留选中+依赖模块的 import）
-/// 5. 打包为 ZIP
-pub fn build_common_with_log(
+/// 4. 重写入口文件（仅保留选中+依赖模块的 import；`options.rewrite = false` 时跳过）
+/// 5. 打包为 ZIP（`options.dry_run = true` 时改为返回构建计划，不生成 ZIP）
+#[allow(clippy::too_many_arguments)]
+pub fn build_common_with_options(
     strategy: &dyn BuildStrategy,
     project_path: &Path,
     selected_modules: &[String],
     client_name: &str,
     modules_dir_override: &str,
     all_module_names: &[String],
+    options: &BuildOptions,
     log_fn: &dyn Fn(&str),
 ) -> AppResult<BuildResult> {
+    // 0. 展开模块通配符（字面名称原样保留），需在参数校验和依赖分析之前完成
+    let selected_modules = &expand_module_patterns(selected_modules, all_module_names)?;
+
     // 1. 验证构建参数
     validate_build_params(client_name, selected_modules)?;
     log_fn("✓ 参数验证通过");
 
+    // 时间戳后缀避免临时目录和 ZIP 文件名冲突
+    let dist_name = format!(
+        "dist_{}_{}",
+        client_name.trim(),
+        timestamp_suffix_with(&options.timestamp)
+    );
+    let zip_path = project_path.join(format!("{}.zip", dist_name));
+
+    let staged = stage_build(
+        strategy,
+        project_path,
+        selected_modules,
+        &dist_name,
+        modules_dir_override,
+        all_module_names,
+        options,
+        log_fn,
+    )?;
+    let temp_dir = staged.dir.as_path();
+
+    // 写入交付包清单（记录每个文件的哈希，供交付后校验）
+    let manifest = packer::write_package_manifest(temp_dir, client_name, &staged.actual_modules)?;
+    log_fn(&format!("✓ 已生成交付清单（{} 个文件）", manifest.files.len()));
+
+    // 7. 打包为 ZIP 文件（dry-run 时改为收集构建计划，沙箱由 scopeguard 清理）
+    let plan = if options.dry_run {
+        log_fn("→ 试运行：生成构建计划（不写入 ZIP）...");
+        let plan = collect_build_plan(
+            temp_dir,
+            staged.auto_added.clone(),
+            staged.entry_file_diff.clone(),
+            staged.warnings.clone(),
+        )?;
+        log_fn(&format!(
+            "✓ 构建计划生成完成：{} 个文件，约 {} KB",
+            plan.files.len(),
+            plan.estimated_size / 1024
+        ));
+        Some(plan)
+    } else {
+        log_fn(&format!("→ 打包 ZIP ({} 个文件)...", staged.file_count));
+        create_zip_from_dir_with(temp_dir, &zip_path, options.deterministic_zip)?;
+        log_fn("✓ ZIP 打包完成");
+        None
+    };
+
+    // 8. 返回构建结果（实际打包的模块数 = 扩展后总数 - 跳过数）
+    Ok(BuildResult {
+        zip_path: if plan.is_some() {
+            String::new()
+        } else {
+            zip_path.to_string_lossy().to_string()
+        },
+        client_name: client_name.trim().to_string(),
+        module_count: staged.actual_modules.len(),
+        expanded_modules: staged.actual_modules.clone(),
+        integrity_errors: staged.integrity_errors.clone(),
+        plan,
+        warnings: staged.warnings.clone(),
+    })
+}
+
+/// 一次构建、为多个客户分别打包
+///
+/// 骨架复制、依赖分析、模块复制和入口重写只执行一次，随后为每个客户写入
+/// 各自的交付清单并打包为 `dist_{客户名}_{时间戳}.zip`。
+/// 客户名去除首尾空白后（不区分大小写）重复时，后出现的 ZIP 名追加 `_2`、`_3` 等序号，避免互相覆盖。
+/// `options.dry_run` 时不写入 ZIP，改为在每个客户的结果中返回构建计划。
+/// 单个客户打包失败不影响其他客户，结果按 `client_names` 顺序返回。
+///
+/// # 返回
+/// - `Ok(Vec<Result<BuildResult, String>>)`: 每个客户的构建结果或失败原因
+/// - `Err(AppError)`: 参数校验或公共构建步骤失败（所有客户均未打包）
+pub fn build_for_clients(
+    strategy: &dyn BuildStrategy,
+    project_path: &Path,
+    selected_modules: &[String],
+    client_names: &[String],
+    modules_dir_override: &str,
+    all_module_names: &[String],
+    log_fn: &dyn Fn(&str),
+) -> AppResult<Vec<Result<BuildResult, String>>> {
+    build_for_clients_with_options(
+        strategy,
+        project_path,
+        selected_modules,
+        client_names,
+        modules_dir_override,
+        all_module_names,
+        &BuildOptions::default(),
+        log_fn,
+    )
+}
+
+/// 带构建选项的多客户打包（见 `build_for_clients`）
+#[allow(clippy::too_many_arguments)]
+pub fn build_for_clients_with_options(
+    strategy: &dyn BuildStrategy,
+    project_path: &Path,
+    selected_modules: &[String],
+    client_names: &[String],
+    modules_dir_override: &str,
+    all_module_names: &[String],
+    options: &BuildOptions,
+    log_fn: &dyn Fn(&str),
+) -> AppResult<Vec<Result<BuildResult, String>>> {
+    let selected_modules = &expand_module_patterns(selected_modules, all_module_names)?;
+
+    if client_names.is_empty() {
+        return Err(AppError::ValidationError("至少需要选择一个客户".to_string()));
+    }
+    for client_name in client_names {
+        validate_build_params(client_name, selected_modules)?;
+    }
+    log_fn("✓ 参数验证通过");
+
+    let ts = timestamp_suffix_with(&options.timestamp);
+    let staged = stage_build(
+        strategy,
+        project_path,
+        selected_modules,
+        &format!("dist_multi_{}", ts),
+        modules_dir_override,
+        all_module_names,
+        options,
+        log_fn,
+    )?;
+    let temp_dir = staged.dir.as_path();
+
+    let mut results = Vec::with_capacity(client_names.len());
+    for (client_name, stem) in client_names.iter().zip(unique_client_stems(client_names)) {
+        let client_name = client_name.trim();
+        log_fn(&format!("→ 为客户 {} 打包...", client_name));
+        let zip_path = project_path.join(format!("dist_{}_{}.zip", stem, ts));
+        let packed = packer::write_package_manifest(temp_dir, client_name, &staged.actual_modules)
+            .and_then(|_| {
+                if options.dry_run {
+                    collect_build_plan(
+                        temp_dir,
+                        staged.auto_added.clone(),
+                        staged.entry_file_diff.clone(),
+                        staged.warnings.clone(),
+                    )
+                    .map(Some)
+                } else {
+                    create_zip_from_dir_with(temp_dir, &zip_path, options.deterministic_zip)
+                        .map(|_| None)
+                }
+            });
+        match packed {
+            Ok(plan) => {
+                if plan.is_some() {
+                    log_fn("  ✓ 试运行：已生成构建计划");
+                } else {
+                    log_fn(&format!("  ✓ {}", zip_path.display()));
+                }
+                results.push(Ok(BuildResult {
+                    zip_path: if plan.is_some() {
+                        String::new()
+                    } else {
+                        zip_path.to_string_lossy().to_string()
+                    },
+                    client_name: client_name.to_string(),
+                    module_count: staged.actual_modules.len(),
+                    expanded_modules: staged.actual_modules.clone(),
+                    integrity_errors: staged.integrity_errors.clone(),
+                    plan,
+                    warnings: staged.warnings.clone(),
+                }));
+            }
+            Err(e) => {
+                log_fn(&format!("  ✗ 客户 {} 打包失败：{}", client_name, e));
+                results.push(Err(e.to_string()));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// 多客户打包时每个客户的 ZIP 文件名部分
+///
+/// 取去除首尾空白后的客户名；与之前的客户名重复（不区分大小写，兼顾 Windows 文件系统）时
+/// 追加 `_2`、`_3` 等序号。
+fn unique_client_stems(client_names: &[String]) -> Vec<String> {
+    let mut used: std::collections::HashSet<String> = std::collections::HashSet::new();
+    client_names
+        .iter()
+        .map(|name| {
+            let base = name.trim();
+            let mut stem = base.to_string();
+            let mut n = 2;
+            while !used.insert(stem.to_lowercase()) {
+                stem = format!("{}_{}", base, n);
+                n += 1;
+            }
+            stem
+        })
+        .collect()
+}
+
+/// 检查排除规则是否会排除核心文件
+///
+/// 匹配规则与 `copy_dir_excluding` 一致：精确名称、`xxx_` 前缀、`*.ext` 后缀；
+/// 比较时忽略末尾的 `/`，因此 `config/` 与 `config` 视为同一规则。
+///
+/// # 返回
+/// - `Err(AppError::BuildError)`: 首个冲突的规则与核心文件
+pub fn check_core_file_conflicts(exclude_patterns: &[&str], core_files: &[&str]) -> AppResult<()> {
+    for pattern in exclude_patterns {
+        let normalized = pattern.trim_end_matches('/');
+        if normalized.is_empty() {
+            continue;
+        }
+        for core_file in core_files {
+            let name = core_file.trim_end_matches('/');
+            let hit = if let Some(suffix) = normalized.strip_prefix('*') {
+                suffix.starts_with('.') && name.ends_with(suffix)
+            } else if normalized.ends_with('_') {
+                name.starts_with(normalized)
+            } else {
+                name == normalized
+            };
+            if hit {
+                return Err(AppError::BuildError(format!(
+                    "排除规则与核心文件冲突：{} 会排除 {}",
+                    pattern, core_file
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 构建临时目录守卫：离开作用域时删除目录（无论构建成功与否）
+type StagingDir = scopeguard::ScopeGuard<PathBuf, fn(PathBuf)>;
+
+/// 已完成骨架复制、模块复制与入口重写、等待打包的构建目录
+struct StagedBuild {
+    /// 构建临时目录（Drop 时清理）
+    dir: StagingDir,
+    /// 实际打包的完整模块列表（选中 + 自动补充，已去除不存在的模块）
+    actual_modules: Vec<String>,
+    /// 依赖分析自动补充的模块
+    auto_added: Vec<String>,
+    /// 入口文件重写差异（仅 dry-run 时收集）
+    entry_file_diff: Vec<String>,
+    /// 构建过程中产生的警告
+    warnings: Vec<String>,
+    /// 完整性校验发现的不一致文件（仅试运行时可能非空）
+    integrity_errors: Vec<String>,
+    /// 构建目录中的条目数
+    file_count: usize,
+}
+
+/// 公共构建步骤：复制骨架、依赖分析、复制模块、完整性校验、重写入口文件、生成模块说明
+///
+/// `dist_name` 为临时目录名；dry-run 时目录建在系统临时目录的沙箱中，不触碰项目目录。
+#[allow(clippy::too_many_arguments)]
+fn stage_build(
+    strategy: &dyn BuildStrategy,
+    project_path: &Path,
+    selected_modules: &[String],
+    dist_name: &str,
+    modules_dir_override: &str,
+    all_module_names: &[String],
+    options: &BuildOptions,
+    log_fn: &dyn Fn(&str),
+) -> AppResult<StagedBuild> {
     // 用户自定义目录优先，为空则使用策略默认值
     let modules_dir_name = if modules_dir_override.is_empty() {
         strategy.default_modules_dir()
@@ -225,6 +735,9 @@ pub fn build_common_with_log(
         modules_dir_override
     };
 
+    // 构建过程中的警告（dry-run 时随构建计划返回）
+    let mut warnings: Vec<String> = Vec::new();
+
     // 路径含空格/特殊字符时记录警告
     let path_str = project_path.to_string_lossy();
     if path_str.contains(' ') || path_str.chars().any(|c| c > '\x7F') {
@@ -232,13 +745,66 @@ pub fn build_common_with_log(
             "项目路径包含空格或非 ASCII 字符，可能影响部分外部工具兼容性: {}",
             path_str
         );
+        warnings.push("项目路径包含空格或非 ASCII 字符，可能影响部分外部工具兼容性".to_string());
     }
 
-    // 时间戳后缀避免临时目录和 ZIP 文件名冲突
-    let ts = timestamp_suffix();
-    let dist_name = format!("dist_{}_{}", client_name.trim(), ts);
-    let temp_dir = project_path.join(&dist_name);
-    let zip_path = project_path.join(format!("{}.zip", dist_name));
+    // 排除式骨架复制：复制整个项目，排除默认排除项 + 技术栈额外排除项
+    //    这样 main.py、config/、utils/、package.json、src/router/ 等全部自动包含
+    let mut exclude_list: Vec<&str> = DEFAULT_EXCLUDES.to_vec();
+    // 排除 dist_ 开头的临时目录和 ZIP 文件
+    exclude_list.push("dist_");
+    exclude_list.push("*.zip");
+    // 追加技术栈额外排除项（先存储 owned 值，再借用引用）
+    let extra = strategy.extra_excludes();
+    for ex in &extra {
+        exclude_list.push(ex.as_str());
+    }
+    if options.exclude_tests {
+        exclude_list.extend_from_slice(TEST_EXCLUDES);
+    }
+
+    // 预检：排除规则不得命中项目中存在的核心文件（避免复制后才在校验阶段失败）
+    let present_core_files: Vec<&str> = CORE_FILES
+        .iter()
+        .copied()
+        .filter(|f| project_path.join(f.trim_end_matches('/')).exists())
+        .collect();
+    check_core_file_conflicts(&exclude_list, &present_core_files)?;
+
+    // 预检：入口文件不在约定位置时重写会被跳过，提前提示（严格模式下直接失败）
+    if options.rewrite {
+        if let Some(rewriter) = module_rewriter::get_rewriter(strategy.tech_stack()) {
+            if let Some(warning) =
+                module_rewriter::check_entry_file_location(rewriter.as_ref(), project_path)
+            {
+                if options.require_entry_file {
+                    return Err(AppError::BuildError(warning));
+                }
+                log_fn(&format!("  ⚠ {}", warning));
+                warnings.push(warning);
+            }
+        }
+    }
+
+    // 扫描源项目文件哈希（在创建临时目录之前），用于构建后的完整性校验
+    let mut source_hashes: std::collections::HashMap<String, String> =
+        analyzer::scan_project_files_strict(project_path)
+            .map_err(|e| AppError::BuildError(format!("扫描源文件哈希失败: {}", e)))?
+            .into_iter()
+            .map(|entry| (entry.relative_path, entry.file_hash))
+            .collect();
+
+    // dry-run 在系统临时目录中构建沙箱，不触碰项目目录
+    let temp_dir = if options.dry_run {
+        std::env::temp_dir().join(format!("prism_dry_run_{}", dist_name))
+    } else {
+        project_path.join(dist_name)
+    };
+
+    // 可写预检：临时目录和 ZIP 都写入项目目录，只读或配额耗尽时尽早失败
+    if !options.dry_run {
+        packer::check_output_writable(project_path).map_err(AppError::BuildError)?;
+    }
 
     // 磁盘空间预检：确保可用空间 > 项目目录大小的 2 倍（骨架复制 + ZIP 打包）
     if let Ok(entries) = std::fs::read_dir(project_path) {
@@ -265,25 +831,57 @@ pub fn build_common_with_log(
     log_fn(&format!("→ 创建临时目录: {}", dist_name));
 
     // scopeguard 确保临时目录在任何情况下都会被清理
-    let temp_dir_path = temp_dir.clone();
-    let _guard = scopeguard::guard((), |_| {
-        let _ = std::fs::remove_dir_all(&temp_dir_path);
+    let guard: StagingDir = scopeguard::guard(temp_dir.clone(), |dir| {
+        let _ = std::fs::remove_dir_all(dir);
     });
 
-    // 3. 排除式骨架复制：复制整个项目，排除默认排除项 + 技术栈额外排除项
-    //    这样 main.py、config/、utils/、package.json、src/router/ 等全部自动包含
-    let mut exclude_list: Vec<&str> = DEFAULT_EXCLUDES.to_vec();
-    // 排除 dist_ 开头的临时目录和 ZIP 文件
-    exclude_list.push("dist_");
-    exclude_list.push("*.zip");
-    // 追加技术栈额外排除项（先存储 owned 值，再借用引用）
-    let extra = strategy.extra_excludes();
-    for ex in &extra {
-        exclude_list.push(ex.as_str());
-    }
-
+    // 3. 复制项目骨架
     log_fn(&format!("→ 复制项目骨架（排除 {} 项噪音目录）...", exclude_list.len()));
-    copy_dir_excluding(project_path, &temp_dir, &exclude_list)?;
+    // 复制时执行内容替换，被改写文件的期望哈希以改写后内容为准
+    // 复制进度按 10% 一档输出，避免大项目复制阶段长时间无日志
+    let last_decile = std::cell::Cell::new(0u64);
+    let report_copy = |copied: u64, total: u64| {
+        if total == 0 {
+            return;
+        }
+        let decile = copied * 10 / total;
+        if decile > last_decile.get() {
+            last_decile.set(decile);
+            log_fn(&format!(
+                "  … 已复制 {}%（{} / {} KB）",
+                decile * 10,
+                copied / 1024,
+                total / 1024
+            ));
+        }
+    };
+    let transformed = packer::copy_dir_excluding_with_transforms_and_progress(
+        project_path,
+        &temp_dir,
+        &exclude_list,
+        &options.content_transforms,
+        &report_copy,
+    )?;
+    if !transformed.is_empty() {
+        log_fn(&format!("  ✓ 已按替换规则改写 {} 个文件", transformed.len()));
+    }
+    source_hashes.extend(transformed);
+
+    // 许可证文件必须随包交付：被排除规则丢弃的补回，缺失时警告
+    let license_files = packer::detect_license_files(project_path);
+    if license_files.is_empty() {
+        let warning = "项目根目录未找到许可证文件（LICENSE / LICENSE.md / COPYING）".to_string();
+        log_fn(&format!("  ⚠ {}", warning));
+        warnings.push(warning);
+    }
+    for name in &license_files {
+        let dest = temp_dir.join(name);
+        if !dest.exists() {
+            std::fs::copy(project_path.join(name), &dest)
+                .map_err(|e| AppError::BuildError(format!("复制许可证文件失败 {}: {}", name, e)))?;
+            log_fn(&format!("  ✓ 已补回被排除的许可证文件 {}", name));
+        }
+    }
 
     // 删除骨架中的模块目录内容（后续单独复制选中的模块）
     let skeleton_modules_dir = temp_dir.join(modules_dir_name);
@@ -300,18 +898,28 @@ pub fn build_common_with_log(
     }
     log_fn("✓ 项目骨架复制完成");
 
-    // 4. 依赖分析：BFS 遍历选中模块的 import，自动补充被依赖的模块
+    // 嵌套模块目录中的同名模块：提示使用限定名（如 admin/users）选择
+    for warning in
+        crate::services::scanner::find_ambiguous_module_names(&project_path.join(modules_dir_name))
+    {
+        log_fn(&format!("  ⚠ {}", warning));
+        warnings.push(warning);
+    }
+
+    // 4. 依赖分析：BFS 遍历选中模块的 import，自动补充被依赖的模块（范围见 options.bfs_scope）
     log_fn(&format!("→ 依赖分析：选中模块 [{}]", selected_modules.join(", ")));
     let (expanded_modules, auto_added) = if all_module_names.is_empty() {
         // 没有提供全部模块名时跳过依赖分析（向后兼容）
         log_fn("  ⚠ 未提供模块列表，跳过依赖分析");
+        warnings.push("未提供模块列表，跳过依赖分析".to_string());
         (selected_modules.to_vec(), Vec::new())
     } else {
-        match analyzer::resolve_module_dependencies(
+        match analyzer::resolve_module_dependencies_with_scope(
             project_path,
             modules_dir_name,
             selected_modules,
             all_module_names,
+            options.bfs_scope,
         ) {
             Ok((full_list, added)) => {
                 if !added.is_empty() {
@@ -322,6 +930,7 @@ pub fn build_common_with_log(
             Err(e) => {
                 // 依赖分析失败不阻断构建，降级为仅复制选中模块
                 log_fn(&format!("  ⚠ 依赖分析失败（{}），仅复制选中模块", e));
+                warnings.push(format!("依赖分析失败（{}），仅复制选中模块", e));
                 (selected_modules.to_vec(), Vec::new())
             }
         }
@@ -352,13 +961,23 @@ pub fn build_common_with_log(
         let module_dst = modules_dest.join(module_name);
 
         if module_src.is_dir() {
-            crate::services::packer::copy_dir_recursive(&module_src, &module_dst)?;
+            // 模块同样执行内容替换；骨架中模块文件的改写结果已随模块目录删除，以此处为准
+            let excludes: &[&str] = if options.exclude_tests { TEST_EXCLUDES } else { &[] };
+            let rel_base = format!("{}/{}", modules_dir_name, module_name).replace('\\', "/");
+            source_hashes.extend(packer::copy_subdir_with_transforms(
+                &module_src,
+                &module_dst,
+                &rel_base,
+                excludes,
+                &options.content_transforms,
+            )?);
             let tag = if auto_added.contains(module_name) { " (依赖)" } else { "" };
             log_fn(&format!("  ✓ {}{}", module_name, tag));
         } else {
             log::warn!("选中的模块目录不存在，已跳过: {}", module_src.display());
             skipped_modules.push(module_name.clone());
             log_fn(&format!("  ⚠ 跳过不存在的模块: {}", module_name));
+            warnings.push(format!("模块目录不存在，已跳过: {}", module_name));
         }
     }
 
@@ -376,18 +995,53 @@ pub fn build_common_with_log(
             "构建包文件数量较多 ({} 个)，打包可能需要较长时间",
             file_count
         );
+        warnings.push(format!(
+            "构建包文件数量较多 ({} 个)，打包可能需要较长时间",
+            file_count
+        ));
     }
 
-    // 6. 重写入口文件中的模块导入（仅保留扩展后模块列表的 import 和 router 注册）
-    if let Some(rewriter) = module_rewriter::get_rewriter(strategy.tech_stack()) {
-        log_fn("→ 重写入口文件 import...");
-        module_rewriter::process_entry_file(
-            rewriter.as_ref(),
-            &temp_dir,
-            &expanded_modules,
+    // 完整性校验：重新哈希构建目录中的每个文件，与源文件哈希比对
+    //    （在入口文件重写之前执行，重写属于预期内的修改）
+    //    正常构建出现不一致直接失败；试运行继续生成计划，不一致文件随结果返回
+    log_fn("→ 校验文件完整性...");
+    let integrity_errors =
+        packer::verify_staging_integrity(&temp_dir, &source_hashes)?;
+    if integrity_errors.is_empty() {
+        log_fn("✓ 完整性校验通过");
+    } else {
+        for err in &integrity_errors {
+            log_fn(&format!("  ✗ {}", err));
+        }
+        if !options.dry_run {
+            return Err(AppError::BuildError(format!(
+                "完整性校验失败：{} 个文件与源文件不一致（{}）",
+                integrity_errors.len(),
+                integrity_errors.join("；")
+            )));
+        }
+    }
+
+    // 6. 重写入口文件中的模块导入（仅保留扩展后模块列表的 import 和 router 注册）
+    //    仅复制模式下入口文件保持与源项目一致
+    let mut entry_file_diff: Vec<String> = Vec::new();
+    if !options.rewrite {
+        log_fn("→ 仅复制模式：跳过入口文件重写与导入校验");
+    } else if let Some(rewriter) = module_rewriter::get_rewriter(strategy.tech_stack()) {
+        log_fn("→ 重写入口文件 import...");
+        let entry_path = temp_dir.join(rewriter.entry_file());
+        let entry_before = std::fs::read_to_string(&entry_path).unwrap_or_default();
+        module_rewriter::process_entry_file(
+            rewriter.as_ref(),
+            &temp_dir,
+            &expanded_modules,
             modules_dir_name,
         )?;
         log_fn("✓ import 重写完成");
+        if options.dry_run {
+            let entry_after = std::fs::read_to_string(&entry_path).unwrap_or_default();
+            entry_file_diff = diff_lines(&entry_before, &entry_after);
+        }
 
         // 校验重写后的入口文件导入完整性
         log_fn("→ 校验导入完整性...");
@@ -399,27 +1053,143 @@ pub fn build_common_with_log(
         log_fn("✓ 导入校验通过");
     }
 
-    // 7. 打包为 ZIP 文件
-    log_fn(&format!("→ 打包 ZIP ({} 个文件)...", file_count));
-    create_zip_from_dir(&temp_dir, &zip_path)?;
-    log_fn("✓ ZIP 打包完成");
-
-    // 8. 返回构建结果（实际打包的模块数 = 扩展后总数 - 跳过数）
-    let module_count = expanded_modules.len() - skipped_modules.len();
-    // 过滤掉跳过的模块，返回实际打包的完整模块列表
+    // 过滤掉跳过的模块，得到实际打包的完整模块列表
     let actual_modules: Vec<String> = expanded_modules
         .into_iter()
         .filter(|m| !skipped_modules.contains(m))
         .collect();
 
-    Ok(BuildResult {
-        zip_path: zip_path.to_string_lossy().to_string(),
-        client_name: client_name.trim().to_string(),
-        module_count,
-        expanded_modules: actual_modules,
+    // 汇总各模块说明（README.md 或主文件文档注释）到包根目录的 MODULES.md
+    packer::write_modules_doc(&temp_dir, modules_dir_name, &actual_modules)?;
+    log_fn("✓ 已生成模块说明 MODULES.md");
+
+    Ok(StagedBuild {
+        dir: guard,
+        actual_modules,
+        auto_added,
+        entry_file_diff,
+        warnings,
+        integrity_errors,
+        file_count,
+    })
+}
+
+/// 判断模块选择项是否为通配符模式（包含 `*` 或 `?`）
+fn is_module_pattern(selection: &str) -> bool {
+    selection.contains('*') || selection.contains('?')
+}
+
+/// 将选中模块中的通配符模式展开为实际模块名
+///
+/// - 字面名称原样保留（不要求存在于 `available` 中，缺失模块在复制阶段跳过）
+/// - 通配符按 `available`（扫描得到的模块列表）顺序展开，结果去重并保持首次出现顺序
+/// - 未匹配任何模块的通配符返回错误，避免静默选中空集合
+pub fn expand_module_patterns(selected: &[String], available: &[String]) -> AppResult<Vec<String>> {
+    let mut expanded: Vec<String> = Vec::new();
+
+    for selection in selected {
+        if !is_module_pattern(selection) {
+            if !expanded.contains(selection) {
+                expanded.push(selection.clone());
+            }
+            continue;
+        }
+
+        let matches: Vec<&String> = available
+            .iter()
+            .filter(|name| wildcard_match(selection, name))
+            .collect();
+        if matches.is_empty() {
+            return Err(AppError::ValidationError(format!(
+                "模块通配符未匹配任何模块：{}",
+                selection
+            )));
+        }
+        for name in matches {
+            if !expanded.contains(name) {
+                expanded.push(name.clone());
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// 从构建沙箱收集 dry-run 构建计划（文件列表 + 预估大小）
+fn collect_build_plan(
+    staging_dir: &Path,
+    auto_added_modules: Vec<String>,
+    entry_file_diff: Vec<String>,
+    warnings: Vec<String>,
+) -> AppResult<BuildPlan> {
+    let mut files: Vec<String> = Vec::new();
+    let mut estimated_size: u64 = 0;
+
+    for entry in walkdir::WalkDir::new(staging_dir) {
+        let entry = entry.map_err(|e| {
+            AppError::BuildError(format!("生成构建计划失败 - 遍历目录失败: {}", e))
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(staging_dir)
+            .map_err(|e| {
+                AppError::BuildError(format!("生成构建计划失败 - 路径处理失败: {}", e))
+            })?;
+        files.push(normalize_rel_path(relative));
+        estimated_size += entry.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    files.sort();
+
+    Ok(BuildPlan {
+        files,
+        auto_added_modules,
+        entry_file_diff,
+        estimated_size,
+        warnings,
     })
 }
 
+/// 逐行比较两段文本，返回差异行（`- ` 前缀为删除，`+ ` 前缀为新增）
+///
+/// 基于最长公共子序列（LCS），入口文件通常只有几百行，O(n·m) 足够
+fn diff_lines(before: &str, after: &str) -> Vec<String> {
+    let a: Vec<&str> = before.lines().collect();
+    let b: Vec<&str> = after.lines().collect();
+
+    // lcs[i][j] = a[i..] 与 b[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(format!("- {}", a[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", b[j]));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().map(|l| format!("- {}", l)));
+    diff.extend(b[j..].iter().map(|l| format!("+ {}", l)));
+    diff
+}
+
 /// 无日志版本的通用构建流程（向后兼容，供单元测试和不需要日志的场景使用）
 fn build_common(
     strategy: &dyn BuildStrategy,
@@ -438,15 +1208,36 @@ fn build_common(
 
 /// 根据技术栈类型获取对应的构建策略
 ///
-/// 优先匹配内置策略（fastapi/vue3），未匹配时尝试从数据库加载自定义模板
+/// 优先匹配内置策略（fastapi/vue3/angular/nuxt），未匹配时尝试从数据库加载自定义模板
 pub fn get_builder(tech_stack: &str) -> AppResult<Box<dyn BuildStrategy>> {
     match tech_stack {
         "fastapi" => Ok(Box::new(FastApiBuildStrategy)),
         "vue3" => Ok(Box::new(Vue3BuildStrategy)),
+        "angular" => Ok(Box::new(AngularBuildStrategy)),
+        "nuxt" => Ok(Box::new(NuxtBuildStrategy)),
         _ => Err(AppError::UnsupportedTechStack(tech_stack.to_string())),
     }
 }
 
+/// 内置构建策略的技术栈标识（与 `get_builder` 的匹配分支保持一致）
+pub const BUILTIN_TECH_STACKS: &[&str] = &["fastapi", "vue3", "angular", "nuxt"];
+
+/// 列出构建流水线支持的内置技术栈
+///
+/// 由已注册的构建策略推导，并标记是否有对应的导入重写器。
+pub fn list_supported_tech_stacks() -> Vec<TechStackInfo> {
+    BUILTIN_TECH_STACKS
+        .iter()
+        .filter_map(|id| get_builder(id).ok())
+        .map(|builder| TechStackInfo {
+            id: builder.tech_stack().to_string(),
+            display_name: builder.display_name().to_string(),
+            default_modules_dir: builder.default_modules_dir().to_string(),
+            supports_rewrite: module_rewriter::get_rewriter(builder.tech_stack()).is_some(),
+        })
+        .collect()
+}
+
 /// 根据数据库模板配置获取通用构建策略
 ///
 /// 供 commands 层在查到自定义模板后调用，避免 services 层直接依赖 Database
@@ -483,6 +1274,10 @@ impl BuildStrategy for GenericBuildStrategy {
         &self.name
     }
 
+    fn display_name(&self) -> &str {
+        &self.name
+    }
+
     fn extra_excludes(&self) -> Vec<String> {
         // 返回用户自定义的额外排除目录
         self.extra_excludes.clone()
@@ -524,6 +1319,7 @@ impl BuildStrategy for GenericBuildStrategy {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::dtos::CheckStatus;
     use std::fs;
     use tempfile::TempDir;
 
@@ -601,6 +1397,123 @@ mod tests {
         let _ = fs::remove_file(zip_path);
     }
 
+    #[test]
+    fn test_core_file_exclude_conflict_fails_before_copy() {
+        let err = check_core_file_conflicts(&[".git", "config/"], CORE_FILES).unwrap_err();
+        assert!(err.to_string().contains("排除规则与核心文件冲突：config/ 会排除 config/"));
+        assert!(check_core_file_conflicts(DEFAULT_EXCLUDES, CORE_FILES).is_ok());
+
+        // 构建时在创建临时目录之前报错
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let strategy = GenericBuildStrategy {
+            name: "custom".to_string(),
+            modules_dir: "modules".to_string(),
+            extra_excludes: vec!["config/".to_string()],
+        };
+        let modules = vec!["auth".to_string()];
+        let err = build_common(&strategy, dir.path(), &modules, "客户A", "", &modules).unwrap_err();
+        assert!(err.to_string().contains("config/ 会排除 config/"));
+        assert!(!fs::read_dir(dir.path())
+            .unwrap()
+            .any(|e| e.unwrap().file_name().to_string_lossy().starts_with("dist_")));
+    }
+
+    #[test]
+    fn test_build_for_clients_packs_each_client_from_one_staging_pass() {
+        use std::io::Read;
+
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let skeleton_copies = std::cell::Cell::new(0);
+        let log_fn = |msg: &str| {
+            if msg.starts_with("→ 复制项目骨架") {
+                skeleton_copies.set(skeleton_copies.get() + 1);
+            }
+        };
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let clients = vec!["客户A".to_string(), "客户B".to_string()];
+        let results = build_for_clients(
+            &FastApiBuildStrategy,
+            dir.path(),
+            &modules,
+            &clients,
+            "",
+            &all_modules,
+            &log_fn,
+        )
+        .unwrap();
+
+        // 骨架只复制一次
+        assert_eq!(skeleton_copies.get(), 1);
+        assert_eq!(results.len(), 2);
+        for (result, client) in results.iter().zip(&clients) {
+            let result = result.as_ref().unwrap();
+            assert_eq!(&result.client_name, client);
+            assert!(result.zip_path.contains(client.as_str()));
+
+            let zip_path = Path::new(&result.zip_path);
+            let entries = read_zip_entries(zip_path);
+            assert!(entries.iter().any(|n| n == "modules/auth/routes.py"));
+            assert!(!entries.iter().any(|n| n.starts_with("modules/billing")));
+
+            // 每个包的清单记录各自的客户名
+            let mut archive = zip::ZipArchive::new(fs::File::open(zip_path).unwrap()).unwrap();
+            let mut manifest = String::new();
+            archive
+                .by_name(packer::MANIFEST_FILE_NAME)
+                .unwrap()
+                .read_to_string(&mut manifest)
+                .unwrap();
+            let manifest: crate::models::dtos::PackageManifest =
+                serde_json::from_str(&manifest).unwrap();
+            assert_eq!(&manifest.client_name, client);
+        }
+
+        // 公共构建目录已清理
+        let leftovers = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .any(|e| e.file_name().to_string_lossy().starts_with("dist_multi_"));
+        assert!(!leftovers);
+    }
+
+    #[test]
+    fn test_build_for_clients_dedupes_zip_names_and_honors_dry_run() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let modules = vec!["auth".to_string()];
+        let clients = vec!["Acme".to_string(), " Acme ".to_string(), "acme".to_string()];
+        assert_eq!(unique_client_stems(&clients), vec!["Acme", "Acme_2", "acme_3"]);
+
+        let results = build_for_clients(
+            &FastApiBuildStrategy, dir.path(), &modules, &clients, "", &[], &|_| {},
+        )
+        .unwrap();
+        let zip_paths: std::collections::HashSet<&str> =
+            results.iter().map(|r| r.as_ref().unwrap().zip_path.as_str()).collect();
+        assert_eq!(zip_paths.len(), 3);
+        assert!(zip_paths.iter().all(|p| Path::new(p).exists()));
+        for path in zip_paths {
+            let _ = fs::remove_file(path);
+        }
+
+        // 试运行：每个客户返回构建计划，不生成 ZIP
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let results = build_for_clients_with_options(
+            &FastApiBuildStrategy, dir.path(), &modules, &clients[..1], "", &[], &options, &|_| {},
+        )
+        .unwrap();
+        let result = results[0].as_ref().unwrap();
+        assert!(result.zip_path.is_empty());
+        assert!(result.plan.as_ref().unwrap().files.contains(&"main.py".to_string()));
+        assert!(!fs::read_dir(dir.path())
+            .unwrap()
+            .any(|e| e.unwrap().file_name().to_string_lossy().ends_with(".zip")));
+    }
+
     #[test]
     fn test_vue3_build_produces_correct_zip() {
         let dir = TempDir::new().unwrap();
@@ -625,6 +1538,50 @@ mod tests {
         let _ = fs::remove_file(zip_path);
     }
 
+    #[test]
+    fn test_angular_build_drops_routes_of_unselected_modules() {
+        use std::io::Read;
+
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::write(root.join("package.json"), r#"{"name":"test"}"#).unwrap();
+        for name in ["orders", "billing"] {
+            let module = root.join("src/app/modules").join(name);
+            fs::create_dir_all(&module).unwrap();
+            fs::write(module.join(format!("{}.module.ts", name)), "export class M {}").unwrap();
+        }
+        let routing = [
+            "const routes: Routes = [",
+            "  { path: 'orders', loadChildren: () => import('./modules/orders/orders.module') },",
+            "  { path: 'billing', loadChildren: () => import('./modules/billing/billing.module') },",
+            "];",
+        ];
+        fs::write(root.join("src/app/app-routing.module.ts"), routing.join("\n")).unwrap();
+
+        let builder = get_builder("angular").unwrap();
+        let all_modules = vec!["billing".to_string(), "orders".to_string()];
+        let result = builder
+            .build(root, &["orders".to_string()], "客户C", "", &all_modules)
+            .unwrap();
+
+        let zip_path = Path::new(&result.zip_path);
+        let entries = read_zip_entries(zip_path);
+        assert!(entries.iter().any(|n| n.starts_with("src/app/modules/orders")));
+        assert!(!entries.iter().any(|n| n.starts_with("src/app/modules/billing")));
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(zip_path).unwrap()).unwrap();
+        let mut routing = String::new();
+        archive
+            .by_name("src/app/app-routing.module.ts")
+            .unwrap()
+            .read_to_string(&mut routing)
+            .unwrap();
+        assert!(routing.contains("orders.module"));
+        assert!(!routing.contains("billing"));
+
+        let _ = fs::remove_file(zip_path);
+    }
+
     #[test]
     fn test_get_builder_fastapi() {
         let builder = get_builder("fastapi");
@@ -643,6 +1600,31 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_list_supported_tech_stacks_matches_registered_builders() {
+        let stacks = list_supported_tech_stacks();
+        let summary: Vec<(&str, &str, &str, bool)> = stacks
+            .iter()
+            .map(|s| {
+                (
+                    s.id.as_str(),
+                    s.display_name.as_str(),
+                    s.default_modules_dir.as_str(),
+                    s.supports_rewrite,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fastapi", "FastAPI", "modules", true),
+                ("vue3", "Vue 3", "src/views", true),
+                ("angular", "Angular", "src/app/modules", true),
+                ("nuxt", "Nuxt", "pages", true),
+            ]
+        );
+    }
+
     #[test]
     fn test_build_with_empty_client_name_fails() {
         let dir = TempDir::new().unwrap();
@@ -698,9 +1680,559 @@ mod tests {
         let _ = fs::remove_file(zip_path);
     }
 
+    #[test]
+    fn test_no_rewrite_build_keeps_entry_file_identical() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let main_py = "from modules.auth import router as auth_router\n\
+                       from modules.billing import router as billing_router\n\
+                       app.include_router(auth_router)\n\
+                       app.include_router(billing_router)\n";
+        fs::write(dir.path().join("main.py"), main_py).unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let options = BuildOptions { rewrite: false, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &all_modules, &options, &|_| {},
+        )
+        .unwrap();
+
+        // 入口文件应与源文件逐字节一致（未选中的 billing 导入仍保留）
+        let file = fs::File::open(&result.zip_path).unwrap();
+        let mut archive = zip::ZipArchive::new(file).unwrap();
+        let mut entry = archive.by_name("main.py").unwrap();
+        let mut packed = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut packed).unwrap();
+        assert_eq!(packed, main_py.as_bytes());
+
+        let _ = fs::remove_file(&result.zip_path);
+    }
+
+    #[test]
+    fn test_content_transforms_apply_to_module_files() {
+        use std::io::Read;
+
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        fs::write(dir.path().join("modules/auth/routes.py"), "API = 'http://dev.local'\n").unwrap();
+        fs::write(dir.path().join("config/settings.py"), "API = 'http://dev.local'\n").unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let options = BuildOptions {
+            content_transforms: vec![crate::models::dtos::ContentTransform {
+                glob: "*.py".to_string(),
+                find: "http://dev.local".to_string(),
+                replace: "https://client-a.example.com".to_string(),
+            }],
+            ..BuildOptions::default()
+        };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &[], &options, &|_| {},
+        )
+        .unwrap();
+
+        // 骨架文件和选中模块中的文件都被改写，且通过完整性校验
+        let file = fs::File::open(&result.zip_path).unwrap();
+        let mut archive = zip::ZipArchive::new(file).unwrap();
+        for name in ["modules/auth/routes.py", "config/settings.py"] {
+            let mut packed = String::new();
+            archive.by_name(name).unwrap().read_to_string(&mut packed).unwrap();
+            assert_eq!(packed, "API = 'https://client-a.example.com'\n");
+        }
+
+        let _ = fs::remove_file(&result.zip_path);
+    }
+
+    #[test]
+    fn test_exclude_tests_option_drops_test_files() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let root = dir.path();
+        fs::write(root.join("test_auth.py"), "def test_login(): pass").unwrap();
+        fs::create_dir_all(root.join("tests")).unwrap();
+        fs::write(root.join("tests").join("conftest.py"), "# fixtures").unwrap();
+        fs::write(root.join("modules").join("auth").join("routes_test.py"), "# 测试").unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let packed_names = |options: &BuildOptions| -> Vec<String> {
+            let result = build_common_with_options(
+                &builder, root, &modules, "客户A", "", &all_modules, options, &|_| {},
+            )
+            .unwrap();
+            let file = fs::File::open(&result.zip_path).unwrap();
+            let names = zip::ZipArchive::new(file)
+                .unwrap()
+                .file_names()
+                .map(str::to_string)
+                .collect();
+            let _ = fs::remove_file(&result.zip_path);
+            names
+        };
+
+        // 默认不排除测试
+        let names = packed_names(&BuildOptions::default());
+        assert!(names.iter().any(|n| n == "test_auth.py"));
+        assert!(names.iter().any(|n| n == "tests/conftest.py"));
+
+        let names = packed_names(&BuildOptions { exclude_tests: true, ..BuildOptions::default() });
+        assert!(!names.iter().any(|n| n == "test_auth.py"));
+        assert!(!names.iter().any(|n| n.starts_with("tests/")));
+        assert!(!names.iter().any(|n| n == "modules/auth/routes_test.py"));
+        // 生产代码保留
+        assert!(names.iter().any(|n| n == "main.py"));
+        assert!(names.iter().any(|n| n == "modules/auth/routes.py"));
+        assert!(names.iter().any(|n| n == "utils/helpers.py"));
+    }
+
+    #[test]
+    fn test_entry_file_mismatch_reported_in_warnings() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let root = dir.path();
+        fs::rename(root.join("main.py"), root.join("app.py")).unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = build_common_with_options(
+            &builder, root, &modules, "客户A", "", &all_modules, &BuildOptions::default(), &|_| {},
+        )
+        .unwrap();
+        let _ = fs::remove_file(&result.zip_path);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.contains("未找到入口文件 main.py") && w.contains("app.py")));
+
+        // 严格模式下入口文件缺失直接失败
+        let strict = BuildOptions { require_entry_file: true, ..BuildOptions::default() };
+        let err = build_common_with_options(
+            &builder, root, &modules, "客户A", "", &all_modules, &strict, &|_| {},
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("未找到入口文件 main.py"));
+    }
+
+    #[test]
+    fn test_license_file_always_packed_and_missing_warned() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let root = dir.path();
+        fs::write(root.join("LICENSE"), "MIT License").unwrap();
+
+        // 模板排除规则命中 LICENSE 时仍随包交付
+        let builder = get_generic_builder(
+            "fastapi-lite".to_string(),
+            "modules".to_string(),
+            r#"["LICENSE*"]"#.to_string(),
+        )
+        .unwrap();
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = build_common_with_options(
+            builder.as_ref(), root, &modules, "客户A", "", &all_modules,
+            &BuildOptions::default(), &|_| {},
+        )
+        .unwrap();
+        let entries = read_zip_entries(Path::new(&result.zip_path));
+        let _ = fs::remove_file(&result.zip_path);
+        assert!(entries.iter().any(|e| e == "LICENSE"));
+        assert!(!result.warnings.iter().any(|w| w.contains("许可证")));
+
+        // 无许可证文件时警告
+        fs::remove_file(root.join("LICENSE")).unwrap();
+        let result = build_common_with_options(
+            &FastApiBuildStrategy, root, &modules, "客户A", "", &all_modules,
+            &BuildOptions::default(), &|_| {},
+        )
+        .unwrap();
+        let _ = fs::remove_file(&result.zip_path);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.contains("未找到许可证文件")));
+    }
+
+    #[test]
+    fn test_normal_build_passes_integrity_check() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = builder.build(dir.path(), &modules, "客户A", "", &all_modules).unwrap();
+        assert!(std::path::Path::new(&result.zip_path).exists());
+        assert!(result.integrity_errors.is_empty());
+
+        let _ = fs::remove_file(&result.zip_path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_follows_symlinks() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let shared = TempDir::new().unwrap();
+        fs::write(shared.path().join("common.py"), "# 共享代码").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("main.py"), dir.path().join("main_link.py"))
+            .unwrap();
+        std::os::unix::fs::symlink(shared.path(), dir.path().join("shared")).unwrap();
+
+        // 链接指向的文件与目录按目标内容打包，源文件哈希同样跟随链接，完整性校验不应误报
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &[], &options, &|_| {},
+        )
+        .unwrap();
+
+        assert!(result.integrity_errors.is_empty());
+        let plan = result.plan.unwrap();
+        assert!(plan.files.contains(&"main_link.py".to_string()));
+        assert!(plan.files.contains(&"shared/common.py".to_string()));
+    }
+
+    #[test]
+    fn test_build_detects_mid_copy_edit() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let helpers = dir.path().join("utils").join("helpers.py");
+
+        // 在骨架复制开始时修改源文件，模拟构建过程中的并发编辑
+        let log_fn = |msg: &str| {
+            if msg.starts_with("→ 复制项目骨架") {
+                fs::write(&helpers, "# 并发修改").unwrap();
+            }
+        };
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = builder.build_with_log(dir.path(), &modules, "客户A", "", &all_modules, &log_fn);
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("完整性校验失败"));
+        assert!(err.contains("utils/helpers.py"));
+        // 失败的构建不应留下 ZIP 文件
+        let zips = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "zip"))
+            .count();
+        assert_eq!(zips, 0);
+    }
+
+    #[test]
+    fn test_dry_run_lists_integrity_errors() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        let helpers = dir.path().join("utils").join("helpers.py");
+
+        // 试运行不因不一致失败，而是在结果中列出不一致的文件
+        let log_fn = |msg: &str| {
+            if msg.starts_with("→ 复制项目骨架") {
+                fs::write(&helpers, "# 并发修改").unwrap();
+            }
+        };
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &[], &options, &log_fn,
+        )
+        .unwrap();
+
+        assert_eq!(result.integrity_errors.len(), 1);
+        assert!(result.integrity_errors[0].starts_with("utils/helpers.py"));
+        assert!(result.plan.is_some());
+    }
+
+    #[test]
+    fn test_dry_run_returns_plan_without_artifacts() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        fs::write(
+            dir.path().join("main.py"),
+            "from modules.auth import router as auth_router\n\
+             from modules.billing import router as billing_router\n\
+             app.include_router(auth_router)\n\
+             app.include_router(billing_router)\n",
+        )
+        .unwrap();
+        let list_dir = |p: &Path| {
+            let mut names: Vec<String> = fs::read_dir(p)
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        let before = list_dir(dir.path());
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &all_modules, &options, &|_| {},
+        )
+        .unwrap();
+
+        // 项目目录未产生任何新文件（无 ZIP、无 dist_ 临时目录）
+        assert_eq!(list_dir(dir.path()), before);
+        assert!(result.zip_path.is_empty());
+
+        // 构建计划包含文件列表、预估大小和入口文件差异
+        let plan = result.plan.expect("dry-run 应返回构建计划");
+        assert!(plan.files.contains(&"main.py".to_string()));
+        assert!(plan.files.contains(&"modules/auth/routes.py".to_string()));
+        assert!(!plan.files.iter().any(|f| f.starts_with("modules/billing")));
+        assert!(plan.estimated_size > 0);
+        assert!(plan
+            .entry_file_diff
+            .contains(&"- from modules.billing import router as billing_router".to_string()));
+    }
+
+    #[test]
+    fn test_build_result_relative_paths_use_forward_slashes() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        fs::create_dir_all(dir.path().join("modules/auth/api/v1")).unwrap();
+        fs::write(dir.path().join("modules/auth/api/v1/login.py"), "# login").unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &modules, "客户A", "", &[], &options, &|_| {},
+        )
+        .unwrap();
+
+        let plan = result.plan.unwrap();
+        assert!(plan.files.contains(&"modules/auth/api/v1/login.py".to_string()));
+        assert!(plan.files.iter().all(|f| !f.contains('\\')));
+        assert!(result.integrity_errors.iter().all(|e| !e.contains('\\')));
+    }
+
+    #[test]
+    fn test_expand_module_patterns() {
+        let available: Vec<String> = ["admin_users", "admin_roles", "billing", "auth"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let select = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        // 通配符展开为匹配的模块
+        let expanded = expand_module_patterns(&select(&["admin_*"]), &available).unwrap();
+        assert_eq!(expanded, vec!["admin_users".to_string(), "admin_roles".to_string()]);
+
+        // 字面名称保持不变，与通配符结果去重
+        let expanded =
+            expand_module_patterns(&select(&["auth", "admin_user?", "admin_users"]), &available)
+                .unwrap();
+        assert_eq!(expanded, vec!["auth".to_string(), "admin_users".to_string()]);
+
+        // `*` 选中全部模块
+        assert_eq!(expand_module_patterns(&select(&["*"]), &available).unwrap(), available);
+
+        // 未匹配的通配符报错并指明模式
+        let err = expand_module_patterns(&select(&["admin_*typo"]), &available).unwrap_err();
+        assert!(err.to_string().contains("admin_*typo"));
+    }
+
+    #[test]
+    fn test_build_expands_module_glob() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let builder = FastApiBuildStrategy;
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let options = BuildOptions { dry_run: true, ..BuildOptions::default() };
+        let result = build_common_with_options(
+            &builder, dir.path(), &["a*".to_string()], "客户A", "", &all_modules, &options, &|_| {},
+        )
+        .unwrap();
+
+        assert_eq!(result.expanded_modules, vec!["auth".to_string()]);
+    }
+
+    #[test]
+    fn test_built_package_passes_verification() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = builder.build(dir.path(), &modules, "客户A", "", &all_modules).unwrap();
+        let zip_path = Path::new(&result.zip_path);
+
+        assert!(read_zip_entries(zip_path).contains(&packer::MANIFEST_FILE_NAME.to_string()));
+        let verification = packer::verify_package(zip_path).unwrap();
+        assert!(verification.passed, "{:?}", verification);
+        assert_eq!(verification.archive_hash_ok, None);
+
+        // 存在 .sha256 校验文件时同时校验整包哈希
+        let sidecar = format!("{}.sha256", result.zip_path);
+        let hash = analyzer::compute_file_hash(zip_path).unwrap();
+        fs::write(&sidecar, format!("{}  dist.zip\n", hash)).unwrap();
+        assert_eq!(packer::verify_package(zip_path).unwrap().archive_hash_ok, Some(true));
+        fs::write(&sidecar, "0".repeat(64)).unwrap();
+        let verification = packer::verify_package(zip_path).unwrap();
+        assert_eq!(verification.archive_hash_ok, Some(false));
+        assert!(!verification.passed);
+
+        let _ = fs::remove_file(&sidecar);
+        let _ = fs::remove_file(zip_path);
+    }
+
+    #[test]
+    fn test_delivery_checklist_passes_complete_package() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        fs::write(dir.path().join("README.md"), "# 交付说明").unwrap();
+        fs::write(dir.path().join("LICENSE"), "MIT").unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec![
+            "auth".to_string(),
+            "billing".to_string(),
+            "users".to_string(),
+        ];
+        let result = builder
+            .build(dir.path(), &modules, "客户A", "", &all_modules)
+            .unwrap();
+        let zip_path = Path::new(&result.zip_path);
+        let sidecar = format!("{}.sha256", result.zip_path);
+        let hash = analyzer::compute_file_hash(zip_path).unwrap();
+        fs::write(&sidecar, format!("{}  dist.zip\n", hash)).unwrap();
+
+        let items = packer::delivery_checklist(zip_path).unwrap();
+        let keys: Vec<&str> = items.iter().map(|i| i.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "manifest",
+                "checksum",
+                "sensitive",
+                "core_files",
+                "entry_file",
+                "docs"
+            ]
+        );
+        for item in &items {
+            assert_eq!(item.status, CheckStatus::Pass, "{:?}", item);
+        }
+
+        let _ = fs::remove_file(&sidecar);
+        let _ = fs::remove_file(zip_path);
+    }
+
+    #[test]
+    fn test_delivery_checklist_warns_on_missing_license() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+        fs::write(dir.path().join("README.md"), "# 交付说明").unwrap();
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec![
+            "auth".to_string(),
+            "billing".to_string(),
+            "users".to_string(),
+        ];
+        let result = builder
+            .build(dir.path(), &modules, "客户A", "", &all_modules)
+            .unwrap();
+        let zip_path = Path::new(&result.zip_path);
+
+        let items = packer::delivery_checklist(zip_path).unwrap();
+        let status = |key: &str| items.iter().find(|i| i.key == key).unwrap().clone();
+        let docs = status("docs");
+        assert_eq!(docs.status, CheckStatus::Warn);
+        assert!(docs.detail.contains("LICENSE"));
+        assert!(!docs.detail.contains("README"));
+        // 未写入 .sha256 时仅警告，不影响其余检查
+        assert_eq!(status("checksum").status, CheckStatus::Warn);
+        assert_eq!(status("manifest").status, CheckStatus::Pass);
+        assert_eq!(status("entry_file").status, CheckStatus::Pass);
+
+        let _ = fs::remove_file(zip_path);
+    }
+
+    #[test]
+    fn test_describe_archive_matches_packed_files() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = builder.build(dir.path(), &modules, "客户A", "", &all_modules).unwrap();
+        let zip_path = Path::new(&result.zip_path);
+
+        let info = packer::describe_archive(zip_path).unwrap();
+        let packed_files = read_zip_entries(zip_path)
+            .iter()
+            .filter(|name| !name.ends_with('/'))
+            .count();
+        assert_eq!(info.file_count, packed_files);
+        assert_eq!(info.archive_size, fs::metadata(zip_path).unwrap().len());
+        assert!(info.compressed_bytes <= info.archive_size);
+        assert!(info.uncompressed_bytes > 0);
+        assert!(info.top_level.contains(&"main.py".to_string()));
+        assert!(info.top_level.contains(&"modules/".to_string()));
+
+        let _ = fs::remove_file(zip_path);
+    }
+
+    #[test]
+    fn test_tampered_package_reports_mismatch() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let builder = FastApiBuildStrategy;
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let result = builder.build(dir.path(), &modules, "客户A", "", &all_modules).unwrap();
+        let zip_path = Path::new(&result.zip_path);
+
+        // 解压 → 篡改一个文件、删除一个文件、新增一个文件 → 重新打包
+        let extracted = TempDir::new().unwrap();
+        let file = fs::File::open(zip_path).unwrap();
+        zip::ZipArchive::new(file).unwrap().extract(extracted.path()).unwrap();
+        fs::write(extracted.path().join("main.py"), "print('tampered')").unwrap();
+        fs::remove_file(extracted.path().join("requirements.txt")).unwrap();
+        fs::write(extracted.path().join("backdoor.py"), "import os").unwrap();
+        let out_dir = TempDir::new().unwrap();
+        let tampered_zip = out_dir.path().join("tampered.zip");
+        packer::create_zip_from_dir(extracted.path(), &tampered_zip).unwrap();
+
+        let verification = packer::verify_package(&tampered_zip).unwrap();
+        assert!(!verification.passed);
+        assert_eq!(verification.mismatched, vec!["main.py".to_string()]);
+        assert_eq!(verification.missing, vec!["requirements.txt".to_string()]);
+        assert_eq!(verification.extra, vec!["backdoor.py".to_string()]);
+
+        let _ = fs::remove_file(zip_path);
+    }
+
+    #[test]
+    fn test_diff_lines_reports_removed_and_added() {
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(diff, vec!["- b".to_string(), "+ d".to_string()]);
+    }
+
     #[test]
     fn test_timestamp_suffix_format() {
-        let ts = timestamp_suffix();
+        let ts = timestamp_suffix_with(&TimestampStyle::default());
         // 格式应为 yyyyMMdd_HHmmss（15 个字符）
         assert_eq!(ts.len(), 15);
         assert_eq!(&ts[8..9], "_");
@@ -708,6 +2240,41 @@ mod tests {
         assert!(ts.chars().filter(|&c| c != '_').all(|c| c.is_ascii_digit()));
     }
 
+    #[test]
+    fn test_format_build_timestamp_local_and_custom_format() {
+        // 2026-01-02 03:04:05 UTC
+        let instant = OffsetDateTime::from_unix_timestamp(1_767_323_045).unwrap();
+        let utc8 = UtcOffset::from_hms(8, 0, 0).ok();
+        assert_eq!(
+            format_build_timestamp(instant, &TimestampStyle::default(), utc8),
+            "20260102_030405"
+        );
+
+        let style = timestamp_style_from_settings(Some("local"), None);
+        assert!(style.local);
+        assert_eq!(format_build_timestamp(instant, &style, utc8), "20260102_110405");
+        // 启动时未能记录本地偏移 → 回退到 UTC
+        assert_eq!(format_build_timestamp(instant, &style, None), "20260102_030405");
+
+        let style = timestamp_style_from_settings(Some("utc"), Some("[year]-[month]-[day]"));
+        assert_eq!(format_build_timestamp(instant, &style, utc8), "2026-01-02");
+    }
+
+    #[test]
+    fn test_invalid_timestamp_format_rejected() {
+        assert!(validate_timestamp_format(DEFAULT_BUILD_TIMESTAMP_FORMAT).is_ok());
+        assert!(validate_timestamp_format("[year][nonsense]").is_err());
+        assert!(validate_timestamp_format("[year").is_err());
+        // 输出含文件名非法字符
+        assert!(validate_timestamp_format("[hour]:[minute]").is_err());
+        assert!(validate_timestamp_format("[year]/[month]").is_err());
+
+        // 运行时遇到无效格式回退到默认格式
+        let instant = OffsetDateTime::from_unix_timestamp(1_767_323_045).unwrap();
+        let style = timestamp_style_from_settings(None, Some("[hour]:[minute]"));
+        assert_eq!(format_build_timestamp(instant, &style, None), "20260102_030405");
+    }
+
     #[test]
     fn test_zip_filename_contains_timestamp() {
         let dir = TempDir::new().unwrap();
@@ -730,4 +2297,37 @@ mod tests {
 
         let _ = fs::remove_file(&result.zip_path);
     }
+
+    #[test]
+    fn test_deterministic_build_produces_identical_archives() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir);
+
+        let modules = vec!["auth".to_string()];
+        let all_modules = vec!["auth".to_string(), "billing".to_string(), "users".to_string()];
+        let options = BuildOptions {
+            deterministic_zip: true,
+            ..BuildOptions::default()
+        };
+        let build = || {
+            let result = build_common_with_options(
+                &FastApiBuildStrategy,
+                dir.path(),
+                &modules,
+                "客户A",
+                "",
+                &all_modules,
+                &options,
+                &|_| {},
+            )
+            .unwrap();
+            let bytes = fs::read(&result.zip_path).unwrap();
+            fs::remove_file(&result.zip_path).unwrap();
+            bytes
+        };
+
+        let first = build();
+        let second = build();
+        assert_eq!(first, second);
+    }
 }
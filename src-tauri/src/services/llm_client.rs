@@ -4,7 +4,10 @@
 // ⛔ 禁止：依赖 tauri::*，直接操作数据库
 // ============================================================================
 
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 /// OpenAI /v1/models 响应结构
 #[derive(Deserialize)]
@@ -24,6 +27,9 @@ struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
     temperature: f32,
+    /// 最大生成 token 数（仅探测请求设置，常规调用交给服务端默认值）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
 }
 
 /// Chat 消息
@@ -51,26 +57,256 @@ struct ChatResponseMessage {
     content: String,
 }
 
+/// 允许使用 `${ENV:变量名}` 引用环境变量的设置键（仅 LLM 凭据，避免影响其他设置）
+pub const ENV_INTERPOLATED_KEYS: &[&str] = &["llm_api_key", "llm_base_url"];
+
+/// 环境变量引用的解析结果
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnvResolution {
+    /// 解析后的值（变量未设置时为空字符串）
+    pub value: String,
+    /// 引用了但未设置的环境变量名
+    pub missing_var: Option<String>,
+}
+
+/// 解析 `${ENV:变量名}` 形式的设置值，`lookup` 用于查询环境变量（便于测试注入）
+///
+/// 非该形式的值原样返回。
+pub fn resolve_env_reference(value: &str, lookup: impl Fn(&str) -> Option<String>) -> EnvResolution {
+    let var_name = value
+        .trim()
+        .strip_prefix("${ENV:")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+
+    match var_name {
+        Some(name) => match lookup(name) {
+            Some(resolved) => EnvResolution { value: resolved, missing_var: None },
+            None => EnvResolution {
+                value: String::new(),
+                missing_var: Some(name.to_string()),
+            },
+        },
+        None => EnvResolution { value: value.to_string(), missing_var: None },
+    }
+}
+
+/// 在读取 LLM 配置时解析设置值中的环境变量引用
+///
+/// 仅 `ENV_INTERPOLATED_KEYS` 中的键会被解析；引用的环境变量未设置时返回空字符串并记录警告。
+pub fn resolve_setting_value(key: &str, value: String) -> String {
+    if !ENV_INTERPOLATED_KEYS.contains(&key) {
+        return value;
+    }
+    let resolution = resolve_env_reference(&value, |name| std::env::var(name).ok());
+    if let Some(var) = &resolution.missing_var {
+        log::warn!("设置项 {} 引用的环境变量 {} 未设置，按空值处理", key, var);
+    }
+    resolution.value
+}
+
+/// 解析调用方（前端）传入的设置值
+///
+/// 只有已保存的设置值才解析环境变量引用：传入值与已保存的原始值 `stored` 相同时按其解析，
+/// 否则原样返回，避免借由任意 `${ENV:变量名}` 读取进程环境变量。
+pub fn resolve_supplied_value(key: &str, supplied: String, stored: String) -> String {
+    if supplied == stored {
+        resolve_setting_value(key, stored)
+    } else {
+        supplied
+    }
+}
+
+/// 附加请求头设置键：JSON 对象（请求头名 → 值），应用于所有 LLM 请求
+pub const EXTRA_HEADERS_KEY: &str = "llm_extra_headers";
+
+/// 解析 `llm_extra_headers` 设置为请求头列表
+///
+/// 部分 API 网关要求额外的请求头（如 `X-Org-Id`、`Helicone-Auth`）。
+/// 请求头名须为 ASCII 且是合法的 HTTP 请求头名，值不得包含控制字符；
+/// 不合法的条目跳过并记录警告，设置为空或无法解析时返回空列表。
+pub fn parse_extra_headers(raw: &str) -> Vec<(String, String)> {
+    if raw.trim().is_empty() {
+        return Vec::new();
+    }
+    let map = match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(raw) {
+        Ok(map) => map,
+        Err(e) => {
+            log::warn!("设置项 {} 不是合法的 JSON 对象，已忽略：{}", EXTRA_HEADERS_KEY, e);
+            return Vec::new();
+        }
+    };
+
+    let mut headers = Vec::with_capacity(map.len());
+    for (name, value) in map {
+        let Some(value) = value.as_str() else {
+            log::warn!("附加请求头 {} 的值不是字符串，已跳过", name);
+            continue;
+        };
+        let name_ok = name.is_ascii()
+            && reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_ok();
+        if !name_ok {
+            log::warn!("附加请求头名 {:?} 不合法，已跳过", name);
+            continue;
+        }
+        if value.chars().any(char::is_control) {
+            log::warn!("附加请求头 {} 的值包含控制字符，已跳过", name);
+            continue;
+        }
+        headers.push((name, value.to_string()));
+    }
+    headers
+}
+
+/// 为请求附加认证头（API Key 非空时）和用户配置的附加请求头
+fn apply_headers(
+    mut req: reqwest::RequestBuilder,
+    api_key: &str,
+    extra_headers: &[(String, String)],
+) -> reqwest::RequestBuilder {
+    if !api_key.is_empty() {
+        req = req.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in extra_headers {
+        req = req.header(name.as_str(), value.as_str());
+    }
+    req
+}
+
+/// 全局 LLM 并发上限的设置键（跨批量操作的在途请求总数）
+pub const GLOBAL_CONCURRENCY_KEY: &str = "llm_global_concurrency";
+
+/// 全局 LLM 并发上限的默认值
+pub const DEFAULT_GLOBAL_CONCURRENCY: usize = 8;
+
+/// 进程级 LLM 请求信号量及其许可总数：所有请求函数发起调用前获取许可
+///
+/// 调整上限时整体替换信号量，已在途的请求继续持有旧信号量的许可直至结束。
+static GLOBAL_LIMITER: Lazy<RwLock<(usize, Arc<Semaphore>)>> = Lazy::new(|| {
+    RwLock::new((
+        DEFAULT_GLOBAL_CONCURRENCY,
+        Arc::new(Semaphore::new(DEFAULT_GLOBAL_CONCURRENCY)),
+    ))
+});
+
+/// 解析 `llm_global_concurrency` 设置，未设置、非法或为 0 时使用默认值
+pub fn parse_global_concurrency(raw: &str) -> usize {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return DEFAULT_GLOBAL_CONCURRENCY;
+    }
+    match raw.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            log::warn!("设置项 {} 不是合法的正整数，使用默认值：{}", GLOBAL_CONCURRENCY_KEY, raw);
+            DEFAULT_GLOBAL_CONCURRENCY
+        }
+    }
+}
+
+/// 设置全局 LLM 并发上限（与当前上限相同时不做处理）
+pub fn set_global_concurrency(permits: usize) {
+    let mut limiter = GLOBAL_LIMITER.write().unwrap_or_else(|e| e.into_inner());
+    if limiter.0 != permits {
+        *limiter = (permits, Arc::new(Semaphore::new(permits)));
+        log::info!("全局 LLM 并发上限已设置为 {}", permits);
+    }
+}
+
+/// 获取许可；需要排队时记录等待耗时，便于定位限流造成的延迟
+async fn acquire_permit(
+    semaphore: Arc<Semaphore>,
+    label: &str,
+) -> Result<OwnedSemaphorePermit, String> {
+    if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+        return Ok(permit);
+    }
+    let started = std::time::Instant::now();
+    log::info!("LLM 请求排队等待并发许可：{}", label);
+    let permit = semaphore
+        .acquire_owned()
+        .await
+        .map_err(|e| format!("获取 LLM 并发许可失败：{}", e))?;
+    log::info!("LLM 请求 {} 排队 {} ms 后开始", label, started.elapsed().as_millis());
+    Ok(permit)
+}
+
+/// 获取全局 LLM 并发许可，许可在返回值被丢弃时归还
+async fn acquire_global_permit(label: &str) -> Result<OwnedSemaphorePermit, String> {
+    let semaphore = GLOBAL_LIMITER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .1
+        .clone();
+    acquire_permit(semaphore, label).await
+}
+
+/// 错误响应体的最大保留字符数，避免整页 HTML 塞进错误信息
+const MAX_ERROR_BODY_CHARS: usize = 300;
+
+/// 将 API 的非成功响应格式化为本地化错误信息，并附带服务商返回的原始错误
+///
+/// 优先提取 OpenAI 兼容格式的 `error.message`（或 Ollama 的 `error` 字符串），
+/// 无法解析时退回截断后的原始响应体。
+/// 示例：`模型调用失败 (404): model 'gpt-foo' not found`
+fn format_api_error(label: &str, status: u16, body: &str) -> String {
+    let detail = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| {
+            let error = v.get("error")?;
+            error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .or_else(|| error.as_str())
+                .map(|m| m.to_string())
+        })
+        .unwrap_or_else(|| body.trim().to_string());
+
+    // 按字符截断，避免切断多字节字符
+    let detail: String = if detail.chars().count() > MAX_ERROR_BODY_CHARS {
+        let truncated: String = detail.chars().take(MAX_ERROR_BODY_CHARS).collect();
+        format!("{}…", truncated)
+    } else {
+        detail
+    };
+
+    if detail.is_empty() {
+        format!("{} ({})", label, status)
+    } else {
+        format!("{} ({}): {}", label, status, detail)
+    }
+}
+
+/// 读取非成功响应的状态码和响应体，生成带服务商详情的错误信息
+async fn api_error_from_response(label: &str, resp: reqwest::Response) -> String {
+    let status = resp.status().as_u16();
+    let body = resp.text().await.unwrap_or_default();
+    format_api_error(label, status, &body)
+}
+
 /// 从 OpenAI 兼容 API 获取可用模型列表
 ///
 /// # 参数
 /// - `base_url`: API 基础地址（如 http://localhost:11434/v1）
 /// - `api_key`: API Key（可为空字符串）
+/// - `extra_headers`: 附加请求头（见 `parse_extra_headers`）
 ///
 /// # 返回
 /// - `Ok(Vec<String>)`: 模型 ID 列表
 /// - `Err(String)`: 请求失败的错误描述
-pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>, String> {
+pub async fn fetch_models(
+    base_url: &str,
+    api_key: &str,
+    extra_headers: &[(String, String)],
+) -> Result<Vec<String>, String> {
     // 拼接 /models 端点，兼容末尾有无斜杠
     let url = format!("{}/models", base_url.trim_end_matches('/'));
 
+    let _permit = acquire_global_permit("获取模型列表").await?;
     let client = reqwest::Client::new();
-    let mut req = client.get(&url);
-
-    // 如果提供了 API Key，添加 Authorization 头
-    if !api_key.is_empty() {
-        req = req.header("Authorization", format!("Bearer {}", api_key));
-    }
+    // 添加 Authorization 头（如有 API Key）与附加请求头
+    let req = apply_headers(client.get(&url), api_key, extra_headers);
 
     let resp = req
         .timeout(std::time::Duration::from_secs(10))
@@ -79,10 +315,7 @@ pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>,
         .map_err(|e| format!("请求模型列表失败：{}", e))?;
 
     if !resp.status().is_success() {
-        return Err(format!(
-            "获取模型列表失败：HTTP {}",
-            resp.status()
-        ));
+        return Err(api_error_from_response("获取模型列表失败", resp).await);
     }
 
     let body = resp
@@ -99,6 +332,7 @@ pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>,
 /// # 参数
 /// - `base_url`: API 基础地址
 /// - `api_key`: API Key（可为空）
+/// - `extra_headers`: 附加请求头
 /// - `model`: 模型名称
 /// - `file_path`: 文件相对路径（用于 prompt 上下文）
 /// - `file_content`: 文件内容
@@ -109,6 +343,7 @@ pub async fn fetch_models(base_url: &str, api_key: &str) -> Result<Vec<String>,
 pub async fn generate_summary(
     base_url: &str,
     api_key: &str,
+    extra_headers: &[(String, String)],
     model: &str,
     file_path: &str,
     file_content: &str,
@@ -136,14 +371,12 @@ pub async fn generate_summary(
             },
         ],
         temperature: 0.3,
+        max_tokens: None,
     };
 
+    let _permit = acquire_global_permit(&format!("摘要 {}", file_path)).await?;
     let client = reqwest::Client::new();
-    let mut req = client.post(&url).json(&request_body);
-
-    if !api_key.is_empty() {
-        req = req.header("Authorization", format!("Bearer {}", api_key));
-    }
+    let req = apply_headers(client.post(&url).json(&request_body), api_key, extra_headers);
 
     let resp = req
         .timeout(std::time::Duration::from_secs(60))
@@ -152,9 +385,7 @@ pub async fn generate_summary(
         .map_err(|e| format!("调用 LLM API 失败：{}", e))?;
 
     if !resp.status().is_success() {
-        let status = resp.status();
-        let body_text = resp.text().await.unwrap_or_default();
-        return Err(format!("LLM API 返回错误：HTTP {} - {}", status, body_text));
+        return Err(api_error_from_response("模型调用失败", resp).await);
     }
 
     let chat_resp = resp
@@ -198,6 +429,7 @@ struct EmbeddingData {
 /// # 参数
 /// - `base_url`: API 基础地址
 /// - `api_key`: API Key（可为空）
+/// - `extra_headers`: 附加请求头
 /// - `model`: Embedding 模型名称（如 nomic-embed-text）
 /// - `text`: 要生成向量的文本
 ///
@@ -207,6 +439,7 @@ struct EmbeddingData {
 pub async fn generate_embedding(
     base_url: &str,
     api_key: &str,
+    extra_headers: &[(String, String)],
     model: &str,
     text: &str,
 ) -> Result<Vec<f32>, String> {
@@ -217,12 +450,9 @@ pub async fn generate_embedding(
         input: text.to_string(),
     };
 
+    let _permit = acquire_global_permit("Embedding").await?;
     let client = reqwest::Client::new();
-    let mut req = client.post(&url).json(&request_body);
-
-    if !api_key.is_empty() {
-        req = req.header("Authorization", format!("Bearer {}", api_key));
-    }
+    let req = apply_headers(client.post(&url).json(&request_body), api_key, extra_headers);
 
     let resp = req
         .timeout(std::time::Duration::from_secs(30))
@@ -231,9 +461,7 @@ pub async fn generate_embedding(
         .map_err(|e| format!("调用 Embedding API 失败：{}", e))?;
 
     if !resp.status().is_success() {
-        let status = resp.status();
-        let body_text = resp.text().await.unwrap_or_default();
-        return Err(format!("Embedding API 返回错误：HTTP {} - {}", status, body_text));
+        return Err(api_error_from_response("Embedding 模型调用失败", resp).await);
     }
 
     let emb_resp = resp
@@ -254,6 +482,7 @@ pub async fn generate_embedding(
 /// # 参数
 /// - `base_url`: API 基础地址
 /// - `api_key`: API Key
+/// - `extra_headers`: 附加请求头
 /// - `model`: 模型名称
 /// - `system_prompt`: 系统提示词
 /// - `user_prompt`: 用户提示词（包含项目数据）
@@ -263,6 +492,7 @@ pub async fn generate_embedding(
 pub async fn generate_report(
     base_url: &str,
     api_key: &str,
+    extra_headers: &[(String, String)],
     model: &str,
     system_prompt: &str,
     user_prompt: &str,
@@ -282,14 +512,12 @@ pub async fn generate_report(
             },
         ],
         temperature: 0.3,
+        max_tokens: None,
     };
 
+    let _permit = acquire_global_permit("生成报告").await?;
     let client = reqwest::Client::new();
-    let mut req = client.post(&url).json(&request_body);
-
-    if !api_key.is_empty() {
-        req = req.header("Authorization", format!("Bearer {}", api_key));
-    }
+    let req = apply_headers(client.post(&url).json(&request_body), api_key, extra_headers);
 
     let resp = req
         .timeout(std::time::Duration::from_secs(120))
@@ -298,9 +526,7 @@ pub async fn generate_report(
         .map_err(|e| format!("调用 LLM API 失败：{}", e))?;
 
     if !resp.status().is_success() {
-        let status = resp.status();
-        let body_text = resp.text().await.unwrap_or_default();
-        return Err(format!("LLM API 返回错误：HTTP {} - {}", status, body_text));
+        return Err(api_error_from_response("模型调用失败", resp).await);
     }
 
     let chat_resp = resp
@@ -316,8 +542,343 @@ pub async fn generate_report(
         .ok_or_else(|| "LLM 返回了空的 choices".to_string())
 }
 
+// ============================================================================
+// 模型角色校验
+// ============================================================================
+
+/// 单个模型的探测结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelProbe {
+    /// 模型是否可胜任该角色
+    pub ok: bool,
+    /// 结果说明（失败时为面向用户的中文提示）
+    pub detail: String,
+}
+
+/// 对话模型与 Embedding 模型的角色校验结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelRoleCheck {
+    /// 对话模型（`llm_model_name`）
+    pub chat: ModelProbe,
+    /// Embedding 模型（`llm_embedding_model`）
+    pub embedding: ModelProbe,
+}
+
+/// 服务商错误信息中表示"该模型不支持 Embedding"的片段（小写匹配）
+const NOT_EMBEDDING_MARKERS: &[&str] = &[
+    "not an embedding model",
+    "does not support embedding",
+    "not support embeddings",
+    "embeddings are not supported",
+    "embedding is not supported",
+];
+
+/// 服务商错误信息中表示"该模型不支持对话补全"的片段（小写匹配）
+const NOT_CHAT_MARKERS: &[&str] = &[
+    "not a chat model",
+    "does not support chat",
+    "is an embedding model",
+    "only supports embedding",
+    "not supported for chat",
+];
+
+/// 服务商错误信息中表示模型不存在的片段（小写匹配）
+const MODEL_NOT_FOUND_MARKERS: &[&str] = &["not found", "does not exist", "no such model"];
+
+fn contains_any(error: &str, markers: &[&str]) -> bool {
+    let lower = error.to_lowercase();
+    markers.iter().any(|m| lower.contains(m))
+}
+
+/// 将对话模型探测结果转换为面向用户的校验结论
+fn chat_probe_result(model: &str, result: Result<String, String>) -> ModelProbe {
+    match result {
+        Ok(_) => ModelProbe {
+            ok: true,
+            detail: format!("对话模型「{}」可用", model),
+        },
+        Err(e) if contains_any(&e, NOT_CHAT_MARKERS) => ModelProbe {
+            ok: false,
+            detail: format!(
+                "「{}」不支持对话补全，可能误填了 Embedding 模型，请检查对话模型设置：{}",
+                model, e
+            ),
+        },
+        Err(e) if contains_any(&e, MODEL_NOT_FOUND_MARKERS) => ModelProbe {
+            ok: false,
+            detail: format!("对话模型「{}」不存在，请确认模型名称：{}", model, e),
+        },
+        Err(e) => ModelProbe {
+            ok: false,
+            detail: format!("对话模型「{}」探测失败：{}", model, e),
+        },
+    }
+}
+
+/// 将 Embedding 模型探测结果转换为面向用户的校验结论
+fn embedding_probe_result(model: &str, result: Result<Vec<f32>, String>) -> ModelProbe {
+    match result {
+        Ok(vector) if vector.is_empty() => ModelProbe {
+            ok: false,
+            detail: format!(
+                "「{}」返回了空向量，可能不是 Embedding 模型，请检查 Embedding 模型设置",
+                model
+            ),
+        },
+        Ok(vector) => ModelProbe {
+            ok: true,
+            detail: format!("Embedding 模型「{}」可用（向量维度 {}）", model, vector.len()),
+        },
+        Err(e) if contains_any(&e, NOT_EMBEDDING_MARKERS) => ModelProbe {
+            ok: false,
+            detail: format!(
+                "「{}」不是 Embedding 模型，可能误填了对话模型，请检查 Embedding 模型设置：{}",
+                model, e
+            ),
+        },
+        Err(e) if contains_any(&e, MODEL_NOT_FOUND_MARKERS) => ModelProbe {
+            ok: false,
+            detail: format!("Embedding 模型「{}」不存在，请确认模型名称：{}", model, e),
+        },
+        Err(e) => ModelProbe {
+            ok: false,
+            detail: format!("Embedding 模型「{}」探测失败：{}", model, e),
+        },
+    }
+}
+
+/// 发送 1 token 的对话补全请求，确认模型支持 Chat Completion
+async fn probe_chat_model(
+    base_url: &str,
+    api_key: &str,
+    extra_headers: &[(String, String)],
+    model: &str,
+) -> Result<String, String> {
+    let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
+
+    let request_body = ChatRequest {
+        model: model.to_string(),
+        messages: vec![ChatMessage {
+            role: "user".to_string(),
+            content: "ping".to_string(),
+        }],
+        temperature: 0.0,
+        max_tokens: Some(1),
+    };
+
+    let _permit = acquire_global_permit("探测对话模型").await?;
+    let client = reqwest::Client::new();
+    let req = apply_headers(client.post(&url).json(&request_body), api_key, extra_headers);
+
+    let resp = req
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .map_err(|e| format!("调用 LLM API 失败：{}", e))?;
+
+    if !resp.status().is_success() {
+        return Err(api_error_from_response("模型调用失败", resp).await);
+    }
+
+    let chat_resp = resp
+        .json::<ChatResponse>()
+        .await
+        .map_err(|e| format!("解析 LLM 响应失败：{}", e))?;
+
+    chat_resp
+        .choices
+        .into_iter()
+        .next()
+        .map(|c| c.message.content)
+        .ok_or_else(|| "LLM 返回了空的 choices".to_string())
+}
+
+/// 校验对话模型与 Embedding 模型是否填在了正确的位置
+///
+/// 分别发送一次 1 token 的对话补全和单条输入的 Embedding 请求，
+/// 将"不是 Embedding 模型"等服务商错误转换为针对性的中文提示，
+/// 避免误配的模型在 `embed_all_files` 深处才报出难以理解的错误。
+///
+/// # 参数
+/// - `base_url`: API 基础地址
+/// - `api_key`: API Key（可为空）
+/// - `extra_headers`: 附加请求头
+/// - `chat_model`: 对话模型名称
+/// - `embedding_model`: Embedding 模型名称
+pub async fn validate_model_roles(
+    base_url: &str,
+    api_key: &str,
+    extra_headers: &[(String, String)],
+    chat_model: &str,
+    embedding_model: &str,
+) -> ModelRoleCheck {
+    let chat = if chat_model.trim().is_empty() {
+        ModelProbe {
+            ok: false,
+            detail: "未配置对话模型".to_string(),
+        }
+    } else {
+        let result = probe_chat_model(base_url, api_key, extra_headers, chat_model).await;
+        chat_probe_result(chat_model, result)
+    };
+
+    let embedding = if embedding_model.trim().is_empty() {
+        ModelProbe {
+            ok: false,
+            detail: "未配置 Embedding 模型".to_string(),
+        }
+    } else {
+        let result =
+            generate_embedding(base_url, api_key, extra_headers, embedding_model, "ping").await;
+        embedding_probe_result(embedding_model, result)
+    };
+
+    ModelRoleCheck { chat, embedding }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_reference_resolves_when_set() {
+        std::env::set_var("PRISM_TEST_ENV_FOO", "sk-from-env");
+        assert_eq!(
+            resolve_setting_value("llm_api_key", "${ENV:PRISM_TEST_ENV_FOO}".to_string()),
+            "sk-from-env"
+        );
+        // 非 LLM 凭据键不做解析
+        assert_eq!(
+            resolve_setting_value("default_output_dir", "${ENV:PRISM_TEST_ENV_FOO}".to_string()),
+            "${ENV:PRISM_TEST_ENV_FOO}"
+        );
+        // 普通值原样返回
+        assert_eq!(resolve_setting_value("llm_api_key", "sk-plain".to_string()), "sk-plain");
+    }
+
+    #[test]
+    fn test_supplied_value_resolves_only_when_stored() {
+        std::env::set_var("PRISM_TEST_ENV_SUPPLIED", "sk-from-env");
+        let reference = "${ENV:PRISM_TEST_ENV_SUPPLIED}".to_string();
+        // 与已保存值一致：按已保存的引用解析
+        assert_eq!(
+            resolve_supplied_value("llm_api_key", reference.clone(), reference.clone()),
+            "sk-from-env"
+        );
+        // 前端自行构造的引用不解析
+        assert_eq!(
+            resolve_supplied_value("llm_api_key", reference.clone(), "sk-saved".to_string()),
+            reference
+        );
+    }
+
+    #[test]
+    fn test_env_reference_unset_reports_missing_var() {
+        let resolution = resolve_env_reference("${ENV:FOO}", |_| None);
+        assert_eq!(resolution.value, "");
+        assert_eq!(resolution.missing_var.as_deref(), Some("FOO"));
+
+        let resolution = resolve_env_reference("${ENV:FOO}", |name| {
+            (name == "FOO").then(|| "bar".to_string())
+        });
+        assert_eq!(resolution, EnvResolution { value: "bar".to_string(), missing_var: None });
+    }
+
+    #[test]
+    fn test_parse_extra_headers_skips_invalid_entries() {
+        let raw = r#"{"X-Org-Id": "org-42", "Bad Name": "x", "X-Newline": "a\nb",
+                      "Ünicode": "x", "X-Number": 1}"#;
+        assert_eq!(
+            parse_extra_headers(raw),
+            vec![("X-Org-Id".to_string(), "org-42".to_string())]
+        );
+        assert!(parse_extra_headers("").is_empty());
+        assert!(parse_extra_headers("not json").is_empty());
+    }
+
+    #[test]
+    fn test_extra_headers_sent_with_request() {
+        use std::io::{Read, Write};
+
+        // 桩服务器：记录收到的请求头并返回一个模型列表
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&chunk[..n]);
+            }
+            let body = r#"{"data":[{"id":"m1"}]}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request).to_ascii_lowercase()
+        });
+
+        let headers = parse_extra_headers(r#"{"X-Org-Id": "org-42", "Helicone-Auth": "Bearer h"}"#);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let base_url = format!("http://{}/v1", addr);
+        let models = runtime
+            .block_on(fetch_models(&base_url, "sk-test", &headers))
+            .unwrap();
+        assert_eq!(models, vec!["m1".to_string()]);
+
+        let request = server.join().unwrap();
+        assert!(request.contains("x-org-id: org-42"));
+        assert!(request.contains("helicone-auth: bearer h"));
+        assert!(request.contains("authorization: bearer sk-test"));
+    }
+
+    #[test]
+    fn test_permit_serializes_requests_when_limit_is_one() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let run = |permits: usize| -> Vec<String> {
+            let semaphore = Arc::new(Semaphore::new(permits));
+            let events = std::sync::Mutex::new(Vec::new());
+            let request = |id: u32| {
+                let (semaphore, events) = (semaphore.clone(), &events);
+                async move {
+                    let _permit = acquire_permit(semaphore, "test").await.unwrap();
+                    events.lock().unwrap().push(format!("start {}", id));
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    events.lock().unwrap().push(format!("end {}", id));
+                }
+            };
+            runtime.block_on(async { futures::join!(request(1), request(2)) });
+            events.into_inner().unwrap()
+        };
+
+        // 上限为 1：第二个请求等第一个结束后才开始
+        assert_eq!(run(1), ["start 1", "end 1", "start 2", "end 2"]);
+        // 上限为 2：两个请求同时在途
+        assert_eq!(run(2)[..2], ["start 1", "start 2"]);
+    }
+
+    #[test]
+    fn test_parse_global_concurrency() {
+        assert_eq!(parse_global_concurrency(""), DEFAULT_GLOBAL_CONCURRENCY);
+        assert_eq!(parse_global_concurrency(" 3 "), 3);
+        assert_eq!(parse_global_concurrency("0"), DEFAULT_GLOBAL_CONCURRENCY);
+        assert_eq!(parse_global_concurrency("abc"), DEFAULT_GLOBAL_CONCURRENCY);
+    }
+
     #[test]
     fn test_url_trailing_slash_handling() {
         // 验证 URL 拼接逻辑（不发起实际请求）
@@ -329,4 +890,167 @@ mod tests {
         let url2 = format!("{}/models", base2.trim_end_matches('/'));
         assert_eq!(url2, "http://localhost:11434/v1/models");
     }
+
+    #[test]
+    fn test_format_api_error_openai_json_body() {
+        let body = r#"{"error":{"message":"model 'gpt-foo' not found","type":"invalid_request_error"}}"#;
+        let msg = format_api_error("模型调用失败", 400, body);
+        assert_eq!(msg, "模型调用失败 (400): model 'gpt-foo' not found");
+    }
+
+    #[test]
+    fn test_format_api_error_ollama_string_error() {
+        let body = r#"{"error":"model \"nomic\" not found, try pulling it first"}"#;
+        let msg = format_api_error("Embedding 模型调用失败", 404, body);
+        assert!(msg.starts_with("Embedding 模型调用失败 (404): "));
+        assert!(msg.contains("try pulling it first"));
+    }
+
+    #[test]
+    fn test_format_api_error_truncates_plain_body() {
+        let body = "<html>".repeat(200);
+        let msg = format_api_error("模型调用失败", 502, &body);
+        assert!(msg.starts_with("模型调用失败 (502): <html>"));
+        assert!(msg.chars().count() < body.chars().count());
+        assert!(msg.ends_with('…'));
+    }
+
+    #[test]
+    fn test_format_api_error_empty_body() {
+        assert_eq!(format_api_error("模型调用失败", 500, ""), "模型调用失败 (500)");
+    }
+
+    #[test]
+    fn test_fetch_models_surfaces_status_and_provider_message() {
+        use std::io::{Read, Write};
+
+        // 桩服务器：返回 400 与 OpenAI 风格的错误体
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&chunk[..n]);
+            }
+            let body = r#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error"}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let base_url = format!("http://{}/v1", addr);
+        let err = runtime
+            .block_on(fetch_models(&base_url, "sk-bad", &[]))
+            .unwrap_err();
+        server.join().unwrap();
+
+        assert_eq!(err, "获取模型列表失败 (400): Incorrect API key provided");
+    }
+
+    #[test]
+    fn test_validate_model_roles_flags_chat_model_in_embedding_slot() {
+        use std::io::{Read, Write};
+
+        // 桩服务器：对话补全正常返回，Embedding 端点报"不是 Embedding 模型"
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = Vec::new();
+                let mut chunk = [0u8; 1024];
+                let header_end = loop {
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                    let n = stream.read(&mut chunk).unwrap();
+                    assert!(n > 0, "请求头未完整到达");
+                    request.extend_from_slice(&chunk[..n]);
+                };
+                let head = String::from_utf8_lossy(&request[..header_end]).to_ascii_lowercase();
+                let content_length: usize = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .map(|v| v.trim().parse().unwrap())
+                    .unwrap_or(0);
+                while request.len() < header_end + content_length {
+                    let n = stream.read(&mut chunk).unwrap();
+                    request.extend_from_slice(&chunk[..n]);
+                }
+
+                let (status, body) = if head.starts_with("post /v1/embeddings") {
+                    (
+                        "400 Bad Request",
+                        r#"{"error":{"message":"this is not an embedding model"}}"#,
+                    )
+                } else {
+                    ("200 OK", r#"{"choices":[{"message":{"content":"p"}}]}"#)
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let base_url = format!("http://{}/v1", addr);
+        let check = runtime.block_on(validate_model_roles(
+            &base_url,
+            "",
+            &[],
+            "qwen2.5",
+            "qwen2.5",
+        ));
+        server.join().unwrap();
+
+        assert!(check.chat.ok, "{}", check.chat.detail);
+        assert!(!check.embedding.ok);
+        assert!(check.embedding.detail.contains("不是 Embedding 模型"), "{}", check.embedding.detail);
+        assert!(check.embedding.detail.contains("this is not an embedding model"));
+    }
+
+    #[test]
+    fn test_probe_results_map_role_errors_to_targeted_messages() {
+        let chat = chat_probe_result(
+            "nomic-embed-text",
+            Err("模型调用失败 (400): \"nomic-embed-text\" does not support chat".to_string()),
+        );
+        assert!(!chat.ok);
+        assert!(chat.detail.contains("可能误填了 Embedding 模型"));
+
+        let missing = embedding_probe_result(
+            "nomic",
+            Err("Embedding 模型调用失败 (404): model 'nomic' not found".to_string()),
+        );
+        assert!(!missing.ok);
+        assert!(missing.detail.contains("不存在"));
+
+        let ok = embedding_probe_result("nomic", Ok(vec![0.1, 0.2, 0.3]));
+        assert!(ok.ok);
+        assert!(ok.detail.contains("向量维度 3"));
+    }
 }
@@ -0,0 +1,141 @@
+// ============================================================================
+// 命令耗时统计：内存中记录各命令的平均/最近/最大耗时
+// ✅ 只能做：记录与汇总耗时数据（作为 Tauri managed state 使用，应用重启即清空）
+// ⛔ 禁止：依赖 tauri::*，持久化到数据库
+// ============================================================================
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::dtos::CommandMetric;
+
+/// 指数移动平均的平滑系数：新样本权重，越大越偏向最近的调用
+const EMA_ALPHA: f64 = 0.2;
+
+/// 单个命令的累计统计
+#[derive(Debug, Clone, Copy, Default)]
+struct CommandStats {
+    count: u64,
+    avg_ms: f64,
+    last_ms: f64,
+    max_ms: f64,
+}
+
+/// 命令耗时统计表（线程安全，注册为 Tauri managed state）
+#[derive(Debug, Default)]
+pub struct CommandMetrics {
+    stats: Mutex<HashMap<String, CommandStats>>,
+}
+
+impl CommandMetrics {
+    /// 记录一次命令耗时
+    ///
+    /// 首次调用以该耗时作为平均值初始值，之后按指数移动平均更新。
+    pub fn record(&self, command: &str, elapsed: Duration) {
+        let ms = elapsed.as_secs_f64() * 1000.0;
+        // 锁中毒时仍继续记录：统计数据不影响业务正确性
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = stats.entry(command.to_string()).or_default();
+        entry.avg_ms = if entry.count == 0 {
+            ms
+        } else {
+            EMA_ALPHA * ms + (1.0 - EMA_ALPHA) * entry.avg_ms
+        };
+        entry.count += 1;
+        entry.last_ms = ms;
+        entry.max_ms = entry.max_ms.max(ms);
+    }
+
+    /// 开始计时，返回的计时器在离开作用域时自动记录耗时
+    ///
+    /// 命令中提前 `?` 返回的错误路径同样会被记录。
+    pub fn start<'a>(&'a self, command: &'static str) -> CommandTimer<'a> {
+        CommandTimer {
+            metrics: self,
+            command,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// 获取所有命令的统计快照（按命令名排序）
+    pub fn snapshot(&self) -> Vec<CommandMetric> {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let mut result: Vec<CommandMetric> = stats
+            .iter()
+            .map(|(command, s)| CommandMetric {
+                command: command.clone(),
+                count: s.count,
+                avg_ms: s.avg_ms,
+                last_ms: s.last_ms,
+                max_ms: s.max_ms,
+            })
+            .collect();
+        result.sort_by(|a, b| a.command.cmp(&b.command));
+        result
+    }
+}
+
+/// 命令计时器：Drop 时将耗时写入 `CommandMetrics`
+pub struct CommandTimer<'a> {
+    metrics: &'a CommandMetrics,
+    command: &'static str,
+    started_at: Instant,
+}
+
+impl Drop for CommandTimer<'_> {
+    fn drop(&mut self) {
+        self.metrics.record(self.command, self.started_at.elapsed());
+    }
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟一个被计时包装的命令
+    fn wrapped_command(metrics: &CommandMetrics, fail: bool) -> Result<(), String> {
+        let _timer = metrics.start("wrapped_command");
+        std::thread::sleep(Duration::from_millis(20));
+        if fail {
+            return Err("失败".to_string());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrapped_command_updates_metrics() {
+        let metrics = CommandMetrics::default();
+        assert!(metrics.snapshot().is_empty());
+
+        wrapped_command(&metrics, false).unwrap();
+        let _ = wrapped_command(&metrics, true);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        let m = &snapshot[0];
+        assert_eq!(m.command, "wrapped_command");
+        assert_eq!(m.count, 2);
+        // 耗时应不少于 sleep 时长，且在合理范围内
+        assert!(m.last_ms >= 20.0 && m.last_ms < 5000.0);
+        assert!(m.max_ms >= m.last_ms);
+        assert!(m.avg_ms >= 20.0 && m.avg_ms <= m.max_ms);
+    }
+
+    #[test]
+    fn test_record_uses_exponential_moving_average() {
+        let metrics = CommandMetrics::default();
+        metrics.record("cmd", Duration::from_millis(100));
+        metrics.record("cmd", Duration::from_millis(200));
+
+        let m = &metrics.snapshot()[0];
+        // 100 * 0.8 + 200 * 0.2 = 120
+        assert!((m.avg_ms - 120.0).abs() < 1e-6);
+        assert!((m.last_ms - 200.0).abs() < 1e-6);
+        assert!((m.max_ms - 200.0).abs() < 1e-6);
+    }
+}
@@ -4,13 +4,19 @@
 // ⛔ 禁止：直接返回前端专用的错误格式
 // ============================================================================
 
+pub mod analysis_pipeline;
 pub mod analyzer;
 pub mod build_strategy;
 pub mod llm_client;
+pub mod metrics;
 pub mod module_rewriter;
 pub mod packer;
+pub mod report;
 pub mod scan_strategy;
 pub mod scanner;
+pub mod secret_store;
+pub mod settings_schema;
+pub mod vector_index;
 
 // ============================================================================
 // 常量定义
@@ -56,3 +62,30 @@ pub const DEFAULT_EXCLUDES: &[&str] = &[
     ".env.production",
     ".env.development",
 ];
+
+/// 不应出现在交付包中的敏感文件（按文件名匹配，支持 `*` 通配）
+pub const SENSITIVE_FILE_PATTERNS: &[&str] = &[
+    ".env",
+    ".env.local",
+    ".env.production",
+    ".env.development",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "id_rsa",
+    "id_ed25519",
+    ".npmrc",
+    ".pypirc",
+];
+
+/// 启用 `exclude_tests` 构建选项时额外排除的测试目录与测试文件
+/// 目录按名称精确匹配，文件名支持 `*` 通配
+pub const TEST_EXCLUDES: &[&str] = &[
+    "tests",
+    "__tests__",
+    "test_*.py",
+    "*_test.py",
+    "*.test.ts",
+    "*.spec.ts",
+];
@@ -8,6 +8,8 @@
 // 使用 ImportRewriter trait 实现可扩展的多技术栈支持：
 // - FastApiImportRewriter: 处理 main.py 中的 from modules.xxx import / app.include_router
 // - Vue3ImportRewriter: 处理 router/index.ts 中的 import / route 定义（预留）
+// - AngularImportRewriter: 处理 app-routing.module.ts 中的路由表与 loadChildren 懒加载
+// - NuxtImportRewriter: pages/ 自动路由，无需重写，仅校验模块目录
 //
 // 新增技术栈只需实现 ImportRewriter trait，无需修改现有代码（OCP 原则）。
 // ============================================================================
@@ -15,6 +17,8 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+use serde::Serialize;
+
 use crate::utils::error::{AppError, AppResult};
 
 // ============================================================================
@@ -26,6 +30,13 @@ pub trait ImportRewriter {
     /// 入口文件的相对路径（如 "main.py"、"src/router/index.ts"）
     fn entry_file(&self) -> &str;
 
+    /// 入口文件不在约定位置时，项目中常见的其他入口文件位置（用于提示用户）
+    ///
+    /// 默认不提供候选。
+    fn entry_file_alternatives(&self) -> &[&str] {
+        &[]
+    }
+
     /// 重写入口文件内容，只保留选中模块的导入和注册
     ///
     /// # 参数
@@ -49,6 +60,60 @@ pub trait ImportRewriter {
         build_dir: &Path,
         modules_dir: &str,
     ) -> Vec<String>;
+
+    /// 同 `validate`，但为每个缺失模块附带模块名与入口文件中的引用行号
+    ///
+    /// 默认实现由 `validate` 的结果转换而来，不含行号。
+    fn validate_detailed(
+        &self,
+        content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<MissingImport> {
+        let prefix = format!("{}/", modules_dir);
+        self.validate(content, build_dir, modules_dir)
+            .into_iter()
+            .map(|expected_path| MissingImport {
+                module: expected_path
+                    .strip_prefix(&prefix)
+                    .unwrap_or(&expected_path)
+                    .to_string(),
+                expected_path,
+                line: None,
+            })
+            .collect()
+    }
+}
+
+/// 构建前检查入口文件是否位于技术栈约定的位置
+///
+/// 入口文件缺失时 `process_entry_file` 会跳过重写，交付包中保留全部模块导入。
+/// 此检查在构建前给出明确提示，并列出项目中实际存在的常见入口文件。
+///
+/// # 返回
+/// - `None`: 入口文件存在
+/// - `Some(String)`: 入口文件缺失的中文说明（含期望位置与检测到的候选文件）
+pub fn check_entry_file_location(rewriter: &dyn ImportRewriter, project_dir: &Path) -> Option<String> {
+    let expected = rewriter.entry_file();
+    if project_dir.join(expected).is_file() {
+        return None;
+    }
+
+    let detected: Vec<&str> = rewriter
+        .entry_file_alternatives()
+        .iter()
+        .copied()
+        .filter(|candidate| project_dir.join(candidate).is_file())
+        .collect();
+    Some(if detected.is_empty() {
+        format!("未找到入口文件 {}，将跳过模块导入重写", expected)
+    } else {
+        format!(
+            "未找到入口文件 {}，检测到可能的入口文件：{}；将跳过模块导入重写",
+            expected,
+            detected.join("、")
+        )
+    })
 }
 
 /// 在构建目录中执行入口文件重写
@@ -91,7 +156,7 @@ pub fn process_entry_file(
 
 /// 校验构建目录中入口文件的导入完整性
 ///
-/// 读取重写后的入口文件，调用 rewriter.validate() 检查所有模块导入
+/// 读取重写后的入口文件，调用 `validate_entry_file_detailed` 检查所有模块导入
 /// 引用的路径是否在构建目录中实际存在。
 /// 如果存在缺失导入，返回 BuildError。
 pub fn validate_entry_file(
@@ -109,15 +174,54 @@ pub fn validate_entry_file(
         AppError::BuildError(format!("校验时读取 {} 失败：{}", rewriter.entry_file(), e))
     })?;
 
-    let missing = rewriter.validate(&content, build_dir, modules_dir);
-    if !missing.is_empty() {
-        return Err(AppError::BuildError(format!(
+    validate_entry_file_detailed(rewriter, &content, build_dir, modules_dir).map_err(|missing| {
+        AppError::BuildError(format!(
             "导入完整性校验失败：以下模块在构建目录中不存在 → {}",
-            missing.join(", ")
+            expected_paths(missing).join(", ")
+        ))
+    })
+}
+
+/// 校验入口文件内容的导入完整性，返回结构化的缺失导入列表
+///
+/// 每项包含模块名、期望路径和入口文件中的引用行号，便于前端逐项定位修复。
+pub fn validate_entry_file_detailed(
+    rewriter: &dyn ImportRewriter,
+    content: &str,
+    build_dir: &Path,
+    modules_dir: &str,
+) -> Result<(), Vec<MissingImport>> {
+    let missing = rewriter.validate_detailed(content, build_dir, modules_dir);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(missing)
+    }
+}
+
+/// 校验源项目（非构建目录）入口文件的导入完整性
+///
+/// 直接读取 `project_dir` 中未经重写的入口文件，对照项目自身的模块目录检查，
+/// 用于交付前发现源码中本就引用了不存在模块的导入。
+/// 与构建阶段的校验不同，入口文件不存在时返回错误而非跳过。
+pub fn validate_project_imports(
+    rewriter: &dyn ImportRewriter,
+    project_dir: &Path,
+    modules_dir: &str,
+) -> AppResult<Vec<MissingImport>> {
+    let entry_path = project_dir.join(rewriter.entry_file());
+    if !entry_path.is_file() {
+        return Err(AppError::ValidationError(format!(
+            "未找到入口文件 {}",
+            rewriter.entry_file()
         )));
     }
 
-    Ok(())
+    let content = std::fs::read_to_string(&entry_path).map_err(|e| {
+        AppError::ValidationError(format!("读取 {} 失败：{}", rewriter.entry_file(), e))
+    })?;
+
+    Ok(rewriter.validate_detailed(&content, project_dir, modules_dir))
 }
 
 // ============================================================================
@@ -137,6 +241,10 @@ impl ImportRewriter for FastApiImportRewriter {
         "main.py"
     }
 
+    fn entry_file_alternatives(&self) -> &[&str] {
+        &["app.py", "server.py", "run.py", "asgi.py", "app/main.py", "src/main.py"]
+    }
+
     fn rewrite(
         &self,
         content: &str,
@@ -154,6 +262,15 @@ impl ImportRewriter for FastApiImportRewriter {
     ) -> Vec<String> {
         validate_python_imports(content, build_dir, modules_dir)
     }
+
+    fn validate_detailed(
+        &self,
+        content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<MissingImport> {
+        validate_python_imports_detailed(content, build_dir, modules_dir)
+    }
 }
 
 // ============================================================================
@@ -186,6 +303,10 @@ impl ImportRewriter for Vue3ImportRewriter {
         "src/router/index.ts"
     }
 
+    fn entry_file_alternatives(&self) -> &[&str] {
+        &["src/router/index.js", "src/router.ts", "src/router.js"]
+    }
+
     fn rewrite(
         &self,
         content: &str,
@@ -203,6 +324,116 @@ impl ImportRewriter for Vue3ImportRewriter {
     ) -> Vec<String> {
         validate_vue3_imports(content, build_dir, modules_dir)
     }
+
+    fn validate_detailed(
+        &self,
+        content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<MissingImport> {
+        validate_vue3_imports_detailed(content, build_dir, modules_dir)
+    }
+}
+
+// ============================================================================
+// Angular 导入重写器
+// ============================================================================
+
+/// Angular 导入重写器
+///
+/// 处理 `src/app/app-routing.module.ts` 中 `RouterModule.forRoot([...])` 的路由表：
+///
+/// **静态导入**
+/// ```ts
+/// import { OrdersComponent } from './modules/orders/orders.component';
+/// ```
+/// → 移除未选中模块的 import 行、引用其组件的路由对象，以及 `imports: [...]` 中独占一行的标识符
+///
+/// **懒加载路由**
+/// ```ts
+/// { path: 'orders', loadChildren: () => import('./modules/orders/orders.module').then(m => m.OrdersModule) }
+/// ```
+/// → 移除 `loadChildren` / `loadComponent` 指向未选中模块的路由对象
+pub struct AngularImportRewriter;
+
+impl ImportRewriter for AngularImportRewriter {
+    fn entry_file(&self) -> &str {
+        ANGULAR_ENTRY_FILE
+    }
+
+    fn entry_file_alternatives(&self) -> &[&str] {
+        &["src/app/app.routes.ts", "src/app/app.module.ts"]
+    }
+
+    fn rewrite(
+        &self,
+        content: &str,
+        selected_modules: &[String],
+        modules_dir: &str,
+    ) -> String {
+        rewrite_angular_routing(content, selected_modules, modules_dir)
+    }
+
+    fn validate(
+        &self,
+        content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<String> {
+        validate_angular_imports(content, build_dir, modules_dir)
+    }
+
+    fn validate_detailed(
+        &self,
+        content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<MissingImport> {
+        validate_angular_imports_detailed(content, build_dir, modules_dir)
+    }
+}
+
+// ============================================================================
+// Nuxt 导入重写器
+// ============================================================================
+
+/// Nuxt 导入重写器
+///
+/// Nuxt 基于 `pages/` 目录自动生成路由（同 Vue3 模式 3），入口配置中没有需要过滤的模块导入，
+/// 构建时只需确保 modules_dir 中仅包含选中模块的目录，因此 `rewrite` 原样返回内容。
+/// `validate` 检查模块目录在构建目录中存在，避免交付包缺少全部页面。
+pub struct NuxtImportRewriter;
+
+impl ImportRewriter for NuxtImportRewriter {
+    fn entry_file(&self) -> &str {
+        "nuxt.config.ts"
+    }
+
+    fn entry_file_alternatives(&self) -> &[&str] {
+        &["nuxt.config.js", "nuxt.config.mjs"]
+    }
+
+    fn rewrite(
+        &self,
+        content: &str,
+        _selected_modules: &[String],
+        _modules_dir: &str,
+    ) -> String {
+        content.to_string()
+    }
+
+    fn validate(
+        &self,
+        _content: &str,
+        build_dir: &Path,
+        modules_dir: &str,
+    ) -> Vec<String> {
+        if build_dir.join(modules_dir).is_dir() {
+            Vec::new()
+        } else {
+            vec![modules_dir.to_string()]
+        }
+    }
 }
 
 // ============================================================================
@@ -216,6 +447,8 @@ pub fn get_rewriter(tech_stack: &str) -> Option<Box<dyn ImportRewriter>> {
     match tech_stack {
         "fastapi" => Some(Box::new(FastApiImportRewriter)),
         "vue3" => Some(Box::new(Vue3ImportRewriter)),
+        "angular" => Some(Box::new(AngularImportRewriter)),
+        "nuxt" => Some(Box::new(NuxtImportRewriter)),
         _ => None,
     }
 }
@@ -315,34 +548,66 @@ fn to_vue3_import_prefix(modules_dir: &str) -> String {
     format!("@/{}", stripped)
 }
 
+// ============================================================================
+// 限定模块名（嵌套模块目录）
+// ============================================================================
+
+/// 将模块目录下的相对路径（`/` 分隔）映射为模块名
+///
+/// 默认取第一段；若选中列表中有以该段为分组的限定名（如 `admin/users`），
+/// 则取前两段，以区分嵌套模块 `admin/users` 与顶层模块 `users`。
+pub(crate) fn module_key(rest: &str, selected: &HashSet<&str>) -> Option<String> {
+    let mut segments = rest.split('/');
+    let first = segments.next().unwrap_or(rest);
+    if first.is_empty() {
+        return None;
+    }
+    if is_module_group(first, selected) {
+        if let Some(second) = segments.next().filter(|s| !s.is_empty()) {
+            return Some(format!("{}/{}", first, second));
+        }
+    }
+    Some(first.to_string())
+}
+
+/// 判断名称是否为某个选中限定名的分组目录（如选中 `admin/users` 时的 `admin`）
+fn is_module_group(name: &str, selected: &HashSet<&str>) -> bool {
+    selected
+        .iter()
+        .any(|s| s.strip_prefix(name).is_some_and(|rest| rest.starts_with('/')))
+}
+
+/// 判断模块名是否被选中（分组目录在其下任一子模块被选中时同样视为选中）
+fn is_module_selected(module_name: &str, selected: &HashSet<&str>) -> bool {
+    selected.contains(module_name) || is_module_group(module_name, selected)
+}
+
 /// 从 Vue3 import 路径中提取模块名（views 目录下的第一级子目录）
 ///
+/// 选中列表包含限定名（如 `system/user`）时，对应分组下取两级目录作为模块名；
+/// 校验等不涉及选中列表的场景传入空集合即可。
+///
 /// 例如：
 /// - `@/views/dashboard/index.vue` → Some("dashboard")
-/// - `@/views/system/user/index.vue` → Some("system")
+/// - `@/views/system/user/index.vue` → Some("system")（选中 `system/user` 时为 Some("system/user")）
 /// - `@/components/Button.vue` → None（不在 views 目录下）
 /// - `../views/login/index.vue` → Some("login")（相对路径）
-fn extract_vue3_module_name(import_path: &str, import_prefix: &str) -> Option<String> {
+fn extract_vue3_module_name(
+    import_path: &str,
+    import_prefix: &str,
+    selected: &HashSet<&str>,
+) -> Option<String> {
     // 尝试匹配 @/views/xxx 或自定义前缀
-    let after_prefix = if let Some(rest) = import_path.strip_prefix(import_prefix) {
-        rest.strip_prefix('/')
-    } else {
-        None
-    };
-
-    let after_prefix = after_prefix?;
+    let after_prefix = import_path.strip_prefix(import_prefix)?.strip_prefix('/')?;
 
-    // 取第一个 "/" 之前的部分作为模块名
-    let module_name = match after_prefix.find('/') {
-        Some(pos) => &after_prefix[..pos],
-        None => after_prefix.trim_end_matches(".vue").trim_end_matches(".ts"),
+    // 单文件视图（如 login.vue）去掉扩展名
+    let rest = if after_prefix.contains('/') {
+        after_prefix
+    } else {
+        after_prefix.trim_end_matches(".vue").trim_end_matches(".ts")
     };
 
-    if module_name.is_empty() {
-        return None;
-    }
-
-    Some(module_name.to_string())
+    module_key(rest, selected)
 }
 
 /// 重写 Vue3 router/index.ts 文件，只保留选中模块的路由
@@ -376,9 +641,9 @@ fn rewrite_vue3_router(
 
         // 处理静态 import 语句：import XxxView from '@/views/xxx/...'
         if let Some((identifier, module_name)) =
-            parse_static_import(trimmed, &import_prefix)
+            parse_static_import(trimmed, &import_prefix, &selected)
         {
-            if selected.contains(module_name.as_str()) {
+            if is_module_selected(&module_name, &selected) {
                 output.push(line.to_string());
             } else {
                 // 未选中 → 移除此行，记录标识符
@@ -390,9 +655,9 @@ fn rewrite_vue3_router(
 
         // 处理 const Xxx = () => import('...') 形式的顶层懒加载声明
         if let Some((identifier, module_name)) =
-            parse_lazy_const_import(trimmed, &import_prefix)
+            parse_lazy_const_import(trimmed, &import_prefix, &selected)
         {
-            if selected.contains(module_name.as_str()) {
+            if is_module_selected(&module_name, &selected) {
                 output.push(line.to_string());
             } else {
                 removed_identifiers.insert(identifier);
@@ -405,28 +670,21 @@ fn rewrite_vue3_router(
         // 检测是否是路由对象的开始（以 { 开头，在数组上下文中）
         if is_route_object_start(trimmed) {
             // 收集整个路由对象块
-            let (block_lines, end_idx) = collect_brace_block(&lines, i);
-            let block_text = block_lines.join("\n");
-
-            // 判断此路由对象是否应被移除
-            if should_remove_route_block(
-                &block_text,
+            let (_, end_idx) = collect_brace_block(&lines, i);
+            let block_end = end_idx.min(lines.len() - 1);
+
+            // 过滤路由对象（递归处理 children），返回 None 表示整个块应被移除
+            let module_of =
+                |path: &str| extract_vue3_module_name(path, &import_prefix, &selected);
+            if let Some(kept) = filter_route_block(
+                &lines[i..=block_end],
                 &selected,
                 &removed_identifiers,
-                &import_prefix,
+                &module_of,
             ) {
-                // 跳过整个块
-                i = end_idx + 1;
-                continue;
+                output.extend(kept);
             }
-
-            // 保留整个块
-            for li in i..=end_idx {
-                if li < lines.len() {
-                    output.push(lines[li].to_string());
-                }
-            }
-            i = end_idx + 1;
+            i = block_end + 1;
             continue;
         }
 
@@ -441,7 +699,11 @@ fn rewrite_vue3_router(
 /// 解析静态 import 语句，返回 (标识符, 模块名)
 ///
 /// 匹配模式：`import XxxView from '@/views/xxx/...'`
-fn parse_static_import(line: &str, import_prefix: &str) -> Option<(String, String)> {
+fn parse_static_import(
+    line: &str,
+    import_prefix: &str,
+    selected: &HashSet<&str>,
+) -> Option<(String, String)> {
     // 必须以 "import " 开头（排除 "import {" 和 "import type"）
     if !line.starts_with("import ") {
         return None;
@@ -463,7 +725,7 @@ fn parse_static_import(line: &str, import_prefix: &str) -> Option<(String, Strin
     let import_path = extract_quoted_string(path_part)?;
 
     // 从路径中提取模块名
-    let module_name = extract_vue3_module_name(&import_path, import_prefix)?;
+    let module_name = extract_vue3_module_name(&import_path, import_prefix, selected)?;
 
     Some((identifier, module_name))
 }
@@ -471,7 +733,11 @@ fn parse_static_import(line: &str, import_prefix: &str) -> Option<(String, Strin
 /// 解析顶层懒加载常量声明，返回 (标识符, 模块名)
 ///
 /// 匹配模式：`const XxxView = () => import('@/views/xxx/...')`
-fn parse_lazy_const_import(line: &str, import_prefix: &str) -> Option<(String, String)> {
+fn parse_lazy_const_import(
+    line: &str,
+    import_prefix: &str,
+    selected: &HashSet<&str>,
+) -> Option<(String, String)> {
     if !line.starts_with("const ") {
         return None;
     }
@@ -487,7 +753,7 @@ fn parse_lazy_const_import(line: &str, import_prefix: &str) -> Option<(String, S
 
     // 提取 import('...') 中的路径
     let import_path = extract_import_call_path(line)?;
-    let module_name = extract_vue3_module_name(&import_path, import_prefix)?;
+    let module_name = extract_vue3_module_name(&import_path, import_prefix, selected)?;
 
     Some((identifier, module_name))
 }
@@ -569,31 +835,137 @@ fn collect_brace_block(lines: &[&str], start: usize) -> (Vec<String>, usize) {
     (block, end)
 }
 
+/// 判断一行是否是 children 数组的开始（如 `children: [`）
+fn is_children_array_start(trimmed: &str) -> bool {
+    (trimmed.starts_with("children:") || trimmed.starts_with("children :"))
+        && trimmed.ends_with('[')
+}
+
+/// 计算一行中花括号的深度变化
+fn brace_delta(line: &str) -> i32 {
+    line.chars().fold(0, |acc, ch| match ch {
+        '{' => acc + 1,
+        '}' => acc - 1,
+        _ => acc,
+    })
+}
+
+/// 过滤单个路由对象块，递归处理嵌套的 children 路由
+///
+/// 布局路由（如 `{ component: Layout, children: [...] }`）本身不属于任何模块，
+/// 只需逐个过滤其子路由：
+/// - 父路由自身属性（children 之外的行）引用了未选中模块 → 移除整个块
+/// - 子路由按同样规则递归过滤，仅移除未选中模块的子路由
+/// - 原本有子路由但全部被移除 → 父路由也一并移除（避免留下空布局）
+///
+/// # 返回
+/// - `Some(lines)`: 保留的块内容（已移除未选中的子路由）
+/// - `None`: 整个块应被移除
+fn filter_route_block(
+    block: &[&str],
+    selected: &HashSet<&str>,
+    removed_identifiers: &HashSet<String>,
+    module_of: &dyn Fn(&str) -> Option<String>,
+) -> Option<Vec<String>> {
+    let mut own_lines: Vec<&str> = Vec::new();
+    let mut output: Vec<String> = Vec::new();
+    let mut child_count = 0usize;
+    let mut kept_child_count = 0usize;
+    let mut depth = 0i32;
+
+    let mut i = 0;
+    while i < block.len() {
+        let line = block[i];
+
+        // 父路由直属的 children 数组：逐个过滤子路由对象
+        if depth == 1 && is_children_array_start(line.trim()) {
+            own_lines.push(line);
+            output.push(line.to_string());
+            i += 1;
+            while i < block.len() {
+                let child_trimmed = block[i].trim();
+                if child_trimmed.starts_with(']') {
+                    break; // 数组结束行交回外层作为父路由自身的行处理
+                }
+                if is_route_object_start(child_trimmed) {
+                    let (_, end_idx) = collect_brace_block(block, i);
+                    let child_end = end_idx.min(block.len() - 1);
+                    child_count += 1;
+                    if let Some(kept) = filter_route_block(
+                        &block[i..=child_end],
+                        selected,
+                        removed_identifiers,
+                        module_of,
+                    ) {
+                        kept_child_count += 1;
+                        output.extend(kept);
+                    }
+                    i = child_end + 1;
+                    continue;
+                }
+                output.push(block[i].to_string());
+                i += 1;
+            }
+            continue;
+        }
+
+        own_lines.push(line);
+        output.push(line.to_string());
+        depth += brace_delta(line);
+        i += 1;
+    }
+
+    if should_remove_route_block(
+        &own_lines.join("\n"),
+        selected,
+        removed_identifiers,
+        module_of,
+    ) {
+        return None;
+    }
+    if child_count > 0 && kept_child_count == 0 {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// 提取一行中 `component:` 属性引用的标识符
+///
+/// 属性可位于行首（多行路由对象）或行内（单行路由对象），标识符取到第一个非标识符字符为止。
+fn extract_component_ref(line: &str) -> Option<&str> {
+    let start = line.match_indices("component").find_map(|(pos, key)| {
+        let at_boundary = !matches!(
+            line[..pos].chars().next_back(),
+            Some(c) if c != '{' && c != ',' && !c.is_whitespace()
+        );
+        let rest = line[pos + key.len()..].trim_start().strip_prefix(':')?;
+        at_boundary.then_some(rest.trim_start())
+    })?;
+    let end = start
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(start.len());
+    (end > 0).then(|| &start[..end])
+}
+
 /// 判断路由对象块是否应被移除
 ///
 /// 移除条件（满足任一）：
 /// 1. component 属性引用了已被移除的静态 import 标识符
-/// 2. 包含指向未选中模块的动态 import() 调用
+/// 2. 包含指向未选中模块的动态 import() 调用（`module_of` 将导入路径映射为模块名）
 fn should_remove_route_block(
     block_text: &str,
     selected: &HashSet<&str>,
     removed_identifiers: &HashSet<String>,
-    import_prefix: &str,
+    module_of: &dyn Fn(&str) -> Option<String>,
 ) -> bool {
     for line in block_text.lines() {
         let trimmed = line.trim();
 
-        // 检查 component: XxxView（静态引用）
-        if trimmed.starts_with("component:") || trimmed.starts_with("component :") {
-            let after_component = trimmed
-                .strip_prefix("component:")
-                .or_else(|| trimmed.strip_prefix("component :"))
-                .unwrap_or("")
-                .trim()
-                .trim_end_matches(',');
-
+        // 检查 component: XxxView（静态引用，含单行路由对象 `{ path: 'x', component: XxxView }`）
+        if let Some(identifier) = extract_component_ref(trimmed) {
             // 如果引用了被移除的标识符 → 移除此路由
-            if removed_identifiers.contains(after_component) {
+            if removed_identifiers.contains(identifier) {
                 return true;
             }
         }
@@ -601,10 +973,8 @@ fn should_remove_route_block(
         // 检查动态 import()：component: () => import('@/views/xxx/...')
         if trimmed.contains("import(") {
             if let Some(import_path) = extract_import_call_path(trimmed) {
-                if let Some(module_name) =
-                    extract_vue3_module_name(&import_path, import_prefix)
-                {
-                    if !selected.contains(module_name.as_str()) {
+                if let Some(module_name) = module_of(&import_path) {
+                    if !is_module_selected(&module_name, selected) {
                         return true;
                     }
                 }
@@ -615,6 +985,142 @@ fn should_remove_route_block(
     false
 }
 
+// ============================================================================
+// Angular 路由重写核心逻辑（供 AngularImportRewriter 使用）
+// ============================================================================
+
+/// Angular 路由入口文件（相对路径导入以其所在目录为基准解析）
+const ANGULAR_ENTRY_FILE: &str = "src/app/app-routing.module.ts";
+
+/// 从 Angular import 路径中提取模块名（modules_dir 下的第一级子目录）
+///
+/// 相对路径以入口文件所在目录（`src/app`）为基准解析；非相对路径按 tsconfig 的
+/// `baseUrl: "src"` 约定解析，也接受直接以 modules_dir 开头的路径。
+///
+/// 例如（modules_dir = "src/app/modules"）：
+/// - `./modules/orders/orders.module` → Some("orders")
+/// - `app/modules/orders/orders.module` → Some("orders")
+/// - `./shared/shared.module` → None（不在模块目录下）
+fn extract_angular_module_name(
+    import_path: &str,
+    modules_dir: &str,
+    selected: &HashSet<&str>,
+) -> Option<String> {
+    let entry_dir = ANGULAR_ENTRY_FILE.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let resolved = if import_path.starts_with('.') {
+        normalize_rel_segments(&format!("{}/{}", entry_dir, import_path))
+    } else if import_path.starts_with(modules_dir) {
+        import_path.to_string()
+    } else {
+        format!("src/{}", import_path)
+    };
+
+    let rest = resolved
+        .strip_prefix(modules_dir.trim_end_matches('/'))?
+        .strip_prefix('/')?;
+    module_key(rest, selected)
+}
+
+/// 规范化以 `/` 分隔的相对路径：处理 `.` 与 `..` 段
+fn normalize_rel_segments(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+/// 解析 Angular 具名导入，返回 (导入的标识符列表, 导入路径)
+///
+/// 匹配模式：`import { A, B as C } from '...'`（别名取 `as` 之后的名称）
+fn parse_named_import(line: &str) -> Option<(Vec<String>, String)> {
+    let after_import = line.strip_prefix("import ")?.trim_start();
+    let inner = after_import.strip_prefix('{')?;
+    let close = inner.find('}')?;
+    let path_part = inner[close + 1..].trim_start().strip_prefix("from ")?;
+    let import_path = extract_quoted_string(path_part)?;
+
+    let identifiers = inner[..close]
+        .split(',')
+        .filter_map(|item| {
+            let name = item.split(" as ").last()?.trim();
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect();
+    Some((identifiers, import_path))
+}
+
+/// 重写 Angular 路由模块，只保留选中模块的路由
+///
+/// 策略：
+/// - 过滤指向模块目录的具名 import，收集被移除的标识符
+/// - 路由对象复用 Vue3 的花括号块过滤（含 children 递归），
+///   `loadChildren` / `loadComponent` 中的 `import()` 按模块名判断
+/// - `imports: [...]` 等数组中独占一行的被移除标识符一并删除
+fn rewrite_angular_routing(
+    content: &str,
+    selected_modules: &[String],
+    modules_dir: &str,
+) -> String {
+    let selected: HashSet<&str> = selected_modules.iter().map(|s| s.as_str()).collect();
+    let module_of = |path: &str| extract_angular_module_name(path, modules_dir, &selected);
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut removed_identifiers: HashSet<String> = HashSet::new();
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        // 具名 import：import { OrdersComponent } from './modules/orders/...'
+        if let Some((identifiers, import_path)) = parse_named_import(trimmed) {
+            match module_of(&import_path) {
+                Some(module_name) if !is_module_selected(&module_name, &selected) => {
+                    removed_identifiers.extend(identifiers);
+                }
+                _ => output.push(line.to_string()),
+            }
+            i += 1;
+            continue;
+        }
+
+        // 路由对象块 { ... }（可能跨多行）
+        if is_route_object_start(trimmed) {
+            let (_, end_idx) = collect_brace_block(&lines, i);
+            let block_end = end_idx.min(lines.len() - 1);
+            if let Some(kept) = filter_route_block(
+                &lines[i..=block_end],
+                &selected,
+                &removed_identifiers,
+                &module_of,
+            ) {
+                output.extend(kept);
+            }
+            i = block_end + 1;
+            continue;
+        }
+
+        // 数组中独占一行的被移除标识符（如 NgModule 的 imports: [ OrdersModule, ]）
+        if removed_identifiers.contains(trimmed.trim_end_matches(',')) {
+            i += 1;
+            continue;
+        }
+
+        output.push(line.to_string());
+        i += 1;
+    }
+
+    output.join("\n")
+}
+
 // ============================================================================
 // Python 导入重写核心逻辑（供 FastApiImportRewriter 使用）
 // ============================================================================
@@ -634,7 +1140,7 @@ fn rewrite_python_imports(
     // 第一遍：扫描所有 import 行，建立 "别名 → 模块名" 映射
     let mut alias_map: HashMap<String, String> = HashMap::new();
     for line in content.lines() {
-        collect_aliases(line.trim(), &import_prefix, &mut alias_map);
+        collect_aliases(line.trim(), &import_prefix, &selected, &mut alias_map);
     }
 
     // 第二遍：逐行过滤
@@ -643,8 +1149,10 @@ fn rewrite_python_imports(
         let trimmed = line.trim();
 
         // 情况 1: from {prefix}.xxx... import ...
-        if let Some(module_name) = extract_module_from_from_import(trimmed, &import_prefix) {
-            if selected.contains(module_name.as_str()) {
+        if let Some(module_name) =
+            extract_module_from_from_import(trimmed, &import_prefix, &selected)
+        {
+            if is_module_selected(&module_name, &selected) {
                 output.push(line.to_string());
             }
             continue;
@@ -654,7 +1162,7 @@ fn rewrite_python_imports(
         if let Some(names) = extract_names_from_bulk_import(trimmed, &import_prefix) {
             let kept: Vec<&str> = names
                 .iter()
-                .filter(|n| selected.contains(n.as_str()))
+                .filter(|n| is_module_selected(n, &selected))
                 .map(|s| s.as_str())
                 .collect();
             if kept.is_empty() {
@@ -689,11 +1197,19 @@ fn rewrite_python_imports(
 
 /// 从 `from {prefix}.xxx...` 格式的 import 行中提取顶层模块名
 ///
+/// 选中列表包含限定名（如 `admin/users`）时，对应分组下取两级包名作为模块名。
+///
 /// 例如：
 /// - `from modules.auth.routes import router` → Some("auth")
 /// - `from modules.users import models` → Some("users")
+/// - `from modules.users import *` → Some("users")（通配再导出同样按模块过滤）
+/// - `from modules.admin.users import router` → Some("admin/users")（选中 `admin/users` 时）
 /// - `from fastapi import FastAPI` → None
-fn extract_module_from_from_import(line: &str, prefix: &str) -> Option<String> {
+fn extract_module_from_from_import(
+    line: &str,
+    prefix: &str,
+    selected: &HashSet<&str>,
+) -> Option<String> {
     if !line.starts_with("from ") {
         return None;
     }
@@ -705,20 +1221,12 @@ fn extract_module_from_from_import(line: &str, prefix: &str) -> Option<String> {
     // 检查是否以 prefix. 开头
     let after_prefix = module_path.strip_prefix(prefix)?.strip_prefix('.')?;
 
-    // 取第一个 "." 之前的部分作为模块名
-    let module_name = match after_prefix.find('.') {
-        Some(pos) => &after_prefix[..pos],
-        None => after_prefix,
-    };
-
-    if module_name.is_empty() {
-        return None;
-    }
-
-    Some(module_name.to_string())
+    module_key(&after_prefix.replace('.', "/"), selected)
 }
 
 /// 从 `from {prefix} import xxx, yyy` 格式中提取模块名列表
+///
+/// 整包通配导入 `from {prefix} import *` 不指向具体模块，返回 None（按普通行保留）。
 fn extract_names_from_bulk_import(line: &str, prefix: &str) -> Option<Vec<String>> {
     let expected_start = format!("from {} import ", prefix);
     if !line.starts_with(&expected_start) {
@@ -736,7 +1244,7 @@ fn extract_names_from_bulk_import(line: &str, prefix: &str) -> Option<Vec<String
                 None => s.to_string(),
             }
         })
-        .filter(|s| !s.is_empty())
+        .filter(|s| !s.is_empty() && s != "*")
         .collect();
 
     if names.is_empty() {
@@ -747,9 +1255,14 @@ fn extract_names_from_bulk_import(line: &str, prefix: &str) -> Option<Vec<String
 }
 
 /// 收集 import 行中的别名映射（"别名 → 模块名"）
-fn collect_aliases(line: &str, prefix: &str, alias_map: &mut HashMap<String, String>) {
+fn collect_aliases(
+    line: &str,
+    prefix: &str,
+    selected: &HashSet<&str>,
+    alias_map: &mut HashMap<String, String>,
+) {
     // 情况 1: from {prefix}.xxx... import yyy as zzz
-    if let Some(module_name) = extract_module_from_from_import(line, prefix) {
+    if let Some(module_name) = extract_module_from_from_import(line, prefix, selected) {
         if let Some(import_pos) = line.find(" import ") {
             let imports_part = &line[import_pos + 8..];
             for item in imports_part.split(',') {
@@ -798,7 +1311,7 @@ fn should_remove_router_line(
 
     // 策略 1：直接在别名映射中查找
     if let Some(module_name) = alias_map.get(&ref_name) {
-        return !selected.contains(module_name.as_str());
+        return !is_module_selected(module_name, selected);
     }
 
     // 策略 2：xxx_router / xxx_routes 命名约定
@@ -807,7 +1320,7 @@ fn should_remove_router_line(
         .trim_end_matches("_routes");
     if base != ref_name {
         if let Some(module_name) = alias_map.get(base) {
-            return !selected.contains(module_name.as_str());
+            return !is_module_selected(module_name, selected);
         }
     }
 
@@ -816,12 +1329,10 @@ fn should_remove_router_line(
         // 尝试 prefix.xxx.router 模式
         let dotted_prefix = format!("{}.", prefix);
         if let Some(rest) = ref_name.strip_prefix(&dotted_prefix) {
-            let module_name = match rest.find('.') {
-                Some(pos) => &rest[..pos],
-                None => rest,
-            };
-            if alias_map.contains_key(module_name) {
-                return !selected.contains(module_name);
+            if let Some(module_name) = module_key(&rest.replace('.', "/"), selected) {
+                if alias_map.contains_key(&module_name) {
+                    return !is_module_selected(&module_name, selected);
+                }
             }
         }
 
@@ -829,7 +1340,7 @@ fn should_remove_router_line(
         if let Some(dot_pos) = ref_name.find('.') {
             let module_ref = &ref_name[..dot_pos];
             if let Some(module_name) = alias_map.get(module_ref) {
-                return !selected.contains(module_name.as_str());
+                return !is_module_selected(module_name, selected);
             }
         }
     }
@@ -858,24 +1369,60 @@ fn extract_router_ref(line: &str) -> Option<String> {
 // 导入完整性校验函数
 // ============================================================================
 
+/// 入口文件中引用了构建目录内不存在模块的一处导入
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingImport {
+    /// 模块名
+    pub module: String,
+    /// 期望存在的模块路径（相对构建目录，如 `modules/ghost`）
+    pub expected_path: String,
+    /// 入口文件中首次引用该模块的行号（从 1 开始）；无法定位到具体行时为 None
+    pub line: Option<usize>,
+}
+
+/// 构造缺失导入记录（`index` 为从 0 开始的行索引）
+fn missing_import(modules_dir: &str, module_name: &str, index: usize) -> MissingImport {
+    MissingImport {
+        module: module_name.to_string(),
+        expected_path: format!("{}/{}", modules_dir, module_name),
+        line: Some(index + 1),
+    }
+}
+
+/// 缺失导入记录 → 期望路径列表（`ImportRewriter::validate` 的返回形式）
+fn expected_paths(missing: Vec<MissingImport>) -> Vec<String> {
+    missing.into_iter().map(|m| m.expected_path).collect()
+}
+
+/// 校验 Python 入口文件导入完整性，返回缺失模块路径列表（见 `validate_python_imports_detailed`）
+fn validate_python_imports(content: &str, build_dir: &Path, modules_dir: &str) -> Vec<String> {
+    expected_paths(validate_python_imports_detailed(content, build_dir, modules_dir))
+}
+
 /// 校验 Python 入口文件中所有 `from {modules_dir}.xxx` 导入引用的模块目录是否存在
 ///
 /// 扫描重写后的 main.py，提取所有 `from modules.xxx...` 行中的模块名，
 /// 检查 `build_dir/{modules_dir}/{module_name}/` 是否存在。
-fn validate_python_imports(content: &str, build_dir: &Path, modules_dir: &str) -> Vec<String> {
+fn validate_python_imports_detailed(
+    content: &str,
+    build_dir: &Path,
+    modules_dir: &str,
+) -> Vec<MissingImport> {
     let import_prefix = modules_dir.replace('/', ".");
-    let mut missing: Vec<String> = Vec::new();
+    let mut missing: Vec<MissingImport> = Vec::new();
     let mut checked: HashSet<String> = HashSet::new();
 
-    for line in content.lines() {
+    for (index, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
         // 情况 1: from {prefix}.xxx... import ...
-        if let Some(module_name) = extract_module_from_from_import(trimmed, &import_prefix) {
+        if let Some(module_name) =
+            extract_module_from_from_import(trimmed, &import_prefix, &HashSet::new())
+        {
             if checked.insert(module_name.clone()) {
                 let module_path = build_dir.join(modules_dir).join(&module_name);
                 if !module_path.exists() {
-                    missing.push(format!("{}/{}", modules_dir, module_name));
+                    missing.push(missing_import(modules_dir, &module_name, index));
                 }
             }
             continue;
@@ -887,7 +1434,7 @@ fn validate_python_imports(content: &str, build_dir: &Path, modules_dir: &str) -
                 if checked.insert(name.clone()) {
                     let module_path = build_dir.join(modules_dir).join(&name);
                     if !module_path.exists() {
-                        missing.push(format!("{}/{}", modules_dir, name));
+                        missing.push(missing_import(modules_dir, &name, index));
                     }
                 }
             }
@@ -897,36 +1444,45 @@ fn validate_python_imports(content: &str, build_dir: &Path, modules_dir: &str) -
     missing
 }
 
+/// 校验 Vue3 入口文件导入完整性，返回缺失模块路径列表（见 `validate_vue3_imports_detailed`）
+fn validate_vue3_imports(content: &str, build_dir: &Path, modules_dir: &str) -> Vec<String> {
+    expected_paths(validate_vue3_imports_detailed(content, build_dir, modules_dir))
+}
+
 /// 校验 Vue3 router 入口文件中所有模块导入引用的目录是否存在
 ///
 /// 扫描重写后的 router/index.ts，提取所有 `import ... from '@/views/xxx/...'`
 /// 和 `import('@/views/xxx/...')` 中的模块名，
 /// 检查 `build_dir/{modules_dir}/{module_name}/` 是否存在。
-fn validate_vue3_imports(content: &str, build_dir: &Path, modules_dir: &str) -> Vec<String> {
+fn validate_vue3_imports_detailed(
+    content: &str,
+    build_dir: &Path,
+    modules_dir: &str,
+) -> Vec<MissingImport> {
     let import_prefix = to_vue3_import_prefix(modules_dir);
-    let mut missing: Vec<String> = Vec::new();
+    let mut missing: Vec<MissingImport> = Vec::new();
     let mut checked: HashSet<String> = HashSet::new();
 
-    for line in content.lines() {
+    for (index, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
         // 静态 import: import XxxView from '@/views/xxx/...'
-        if let Some((_ident, module_name)) = parse_static_import(trimmed, &import_prefix) {
+        if let Some((_ident, module_name)) = parse_static_import(trimmed, &import_prefix, &HashSet::new()) {
             if checked.insert(module_name.clone()) {
                 let module_path = build_dir.join(modules_dir).join(&module_name);
                 if !module_path.exists() {
-                    missing.push(format!("{}/{}", modules_dir, module_name));
+                    missing.push(missing_import(modules_dir, &module_name, index));
                 }
             }
             continue;
         }
 
         // 顶层懒加载: const XxxView = () => import('@/views/xxx/...')
-        if let Some((_ident, module_name)) = parse_lazy_const_import(trimmed, &import_prefix) {
+        if let Some((_ident, module_name)) = parse_lazy_const_import(trimmed, &import_prefix, &HashSet::new()) {
             if checked.insert(module_name.clone()) {
                 let module_path = build_dir.join(modules_dir).join(&module_name);
                 if !module_path.exists() {
-                    missing.push(format!("{}/{}", modules_dir, module_name));
+                    missing.push(missing_import(modules_dir, &module_name, index));
                 }
             }
             continue;
@@ -934,11 +1490,13 @@ fn validate_vue3_imports(content: &str, build_dir: &Path, modules_dir: &str) ->
 
         // 内联动态 import: component: () => import('@/views/xxx/...')
         if let Some(import_path) = extract_import_call_path(trimmed) {
-            if let Some(module_name) = extract_vue3_module_name(&import_path, &import_prefix) {
+            if let Some(module_name) =
+                extract_vue3_module_name(&import_path, &import_prefix, &HashSet::new())
+            {
                 if checked.insert(module_name.clone()) {
                     let module_path = build_dir.join(modules_dir).join(&module_name);
                     if !module_path.exists() {
-                        missing.push(format!("{}/{}", modules_dir, module_name));
+                        missing.push(missing_import(modules_dir, &module_name, index));
                     }
                 }
             }
@@ -948,6 +1506,44 @@ fn validate_vue3_imports(content: &str, build_dir: &Path, modules_dir: &str) ->
     missing
 }
 
+/// 校验 Angular 入口文件导入完整性，返回缺失模块路径列表（见 `validate_angular_imports_detailed`）
+fn validate_angular_imports(content: &str, build_dir: &Path, modules_dir: &str) -> Vec<String> {
+    expected_paths(validate_angular_imports_detailed(content, build_dir, modules_dir))
+}
+
+/// 校验 Angular 路由模块中所有模块导入引用的目录是否存在
+///
+/// 扫描重写后的路由模块，提取具名 import 与 `import()` 懒加载路径中的模块名，
+/// 检查 `build_dir/{modules_dir}/{module_name}/` 是否存在。
+fn validate_angular_imports_detailed(
+    content: &str,
+    build_dir: &Path,
+    modules_dir: &str,
+) -> Vec<MissingImport> {
+    let mut missing: Vec<MissingImport> = Vec::new();
+    let mut checked: HashSet<String> = HashSet::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let import_path = match parse_named_import(trimmed) {
+            Some((_, path)) => Some(path),
+            None => extract_import_call_path(trimmed),
+        };
+        let Some(module_name) = import_path
+            .and_then(|path| extract_angular_module_name(&path, modules_dir, &HashSet::new()))
+        else {
+            continue;
+        };
+        if checked.insert(module_name.clone()) {
+            let module_path = build_dir.join(modules_dir).join(&module_name);
+            if !module_path.exists() {
+                missing.push(missing_import(modules_dir, &module_name, index));
+            }
+        }
+    }
+
+    missing
+}
 
 // ============================================================================
 // 单元测试
@@ -1027,6 +1623,29 @@ app.include_router(orders.router)";
     // 边界情况
     // -----------------------------------------------------------------------
 
+    #[test]
+    fn test_star_import_filtering() {
+        // 聚合文件中的通配导入：按模块过滤，整包通配导入原样保留
+        let content = "\
+from modules import *
+from modules.auth import *
+from modules.users import *
+from modules.orders.routes import *";
+
+        let selected = vec!["auth".to_string(), "orders".to_string()];
+        let result = rewrite_python_imports(content, &selected, "modules");
+
+        assert!(result.contains("from modules import *"));
+        assert!(result.contains("from modules.auth import *"));
+        assert!(!result.contains("users"));
+        assert!(result.contains("from modules.orders.routes import *"));
+
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("modules/auth")).unwrap();
+        let missing = validate_python_imports("from modules import *", tmp.path(), "modules");
+        assert!(missing.is_empty(), "{:?}", missing);
+    }
+
     #[test]
     fn test_non_module_lines_preserved() {
         // 非模块相关的行应原样保留
@@ -1085,6 +1704,20 @@ app.include_router(modules.users.router)";
     // process_entry_file 集成测试
     // -----------------------------------------------------------------------
 
+    #[test]
+    fn test_entry_file_location_mismatch_reported() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("app.py"), "from fastapi import FastAPI\n").unwrap();
+
+        let rewriter = FastApiImportRewriter;
+        let warning = check_entry_file_location(&rewriter, tmp.path()).unwrap();
+        assert!(warning.contains("未找到入口文件 main.py"));
+        assert!(warning.contains("检测到可能的入口文件：app.py"));
+
+        std::fs::write(tmp.path().join("main.py"), "app = FastAPI()\n").unwrap();
+        assert!(check_entry_file_location(&rewriter, tmp.path()).is_none());
+    }
+
     #[test]
     fn test_process_entry_file_missing_file() {
         // 入口文件不存在时应跳过，不报错
@@ -1362,6 +1995,104 @@ const routes = [
         assert!(!result.contains("DashboardView"));
     }
 
+    #[test]
+    fn test_vue3_qualified_module_selection() {
+        // 限定名 system/user 只保留该嵌套模块，不影响同组其他模块与顶层同名模块
+        let content = "\
+import UserView from '@/views/system/user/index.vue'
+import RoleView from '@/views/system/role/index.vue'
+import TopUserView from '@/views/user/index.vue'
+
+const routes = [
+  { path: '/system/user', component: UserView },
+  { path: '/system/role', component: RoleView },
+  { path: '/user', component: TopUserView },
+]";
+
+        let selected = vec!["system/user".to_string()];
+        let result = rewrite_vue3_router(content, &selected, "src/views");
+
+        assert!(result.contains("import UserView"));
+        assert!(!result.contains("RoleView"));
+        assert!(!result.contains("TopUserView"));
+    }
+
+    #[test]
+    fn test_python_qualified_module_selection() {
+        let content = "\
+from fastapi import FastAPI
+from modules.admin.users.routes import router as admin_users_router
+from modules.admin.roles.routes import router as admin_roles_router
+from modules.users.routes import router as users_router
+
+app = FastAPI()
+app.include_router(admin_users_router)
+app.include_router(admin_roles_router)
+app.include_router(users_router)";
+
+        let selected = vec!["admin/users".to_string()];
+        let result = rewrite_python_imports(content, &selected, "modules");
+
+        assert!(result.contains("from modules.admin.users.routes import"));
+        assert!(result.contains("app.include_router(admin_users_router)"));
+        assert!(!result.contains("admin_roles_router"));
+        assert!(!result.contains("from modules.users"));
+        assert!(!result.contains("app.include_router(users_router)"));
+    }
+
+    #[test]
+    fn test_vue3_layout_children_filtering() {
+        // 布局路由：父路由引用 Layout，子路由分属不同模块
+        let content = "\
+import { createRouter, createWebHistory } from 'vue-router'
+import Layout from '@/layout/index.vue'
+
+const routes = [
+  {
+    path: '/',
+    component: Layout,
+    children: [
+      {
+        path: 'dashboard',
+        component: () => import('@/views/dashboard/index.vue'),
+      },
+      {
+        path: 'report',
+        component: () => import('@/views/report/index.vue'),
+      },
+    ],
+  },
+  {
+    path: '/admin',
+    component: Layout,
+    children: [
+      {
+        path: 'settings',
+        component: () => import('@/views/settings/index.vue'),
+      },
+    ],
+  },
+]";
+
+        let selected = vec!["dashboard".to_string()];
+        let result = rewrite_vue3_router(content, &selected, "src/views");
+
+        // 布局父路由和选中的子路由保留
+        assert!(result.contains("import Layout from '@/layout/index.vue'"));
+        assert!(result.contains("path: '/',"));
+        assert!(result.contains("@/views/dashboard/index.vue"));
+        assert!(result.contains("children: ["));
+        // 仅未选中的子路由被移除
+        assert!(!result.contains("'report'"));
+        assert!(!result.contains("@/views/report/index.vue"));
+        // 子路由全部未选中的布局父路由一并移除
+        assert!(!result.contains("'/admin'"));
+        assert!(!result.contains("@/views/settings/index.vue"));
+        // 括号保持平衡
+        assert_eq!(result.matches('{').count(), result.matches('}').count());
+        assert_eq!(result.matches('[').count(), result.matches(']').count());
+    }
+
     #[test]
     fn test_vue3_get_rewriter_returns_some() {
         // get_rewriter("vue3") 应返回 Some
@@ -1540,4 +2271,144 @@ const routes = [
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("modules/ghost"), "错误信息应包含缺失模块: {}", err_msg);
     }
+
+    #[test]
+    fn test_validate_project_imports_reports_missing_source_module() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("modules/auth")).unwrap();
+        std::fs::write(
+            tmp.path().join("main.py"),
+            "from modules.auth.routes import router
+from modules.ghost import api
+",
+        )
+        .unwrap();
+
+        let missing =
+            validate_project_imports(&FastApiImportRewriter, tmp.path(), "modules").unwrap();
+        assert_eq!(
+            missing,
+            vec![MissingImport {
+                module: "ghost".to_string(),
+                expected_path: "modules/ghost".to_string(),
+                line: Some(2),
+            }]
+        );
+
+        // 源入口文件不存在时报错，而不是像构建校验那样跳过
+        std::fs::remove_file(tmp.path().join("main.py")).unwrap();
+        assert!(validate_project_imports(&FastApiImportRewriter, tmp.path(), "modules").is_err());
+    }
+
+    #[test]
+    fn test_validate_entry_file_detailed_reports_line_numbers() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("modules/auth")).unwrap();
+        let content = "from fastapi import FastAPI\n\
+                       from modules.auth.routes import router\n\
+                       from modules.ghost import api\n\
+                       from modules.ghost.models import Ghost\n\
+                       from modules import auth, phantom\n";
+
+        let missing =
+            validate_entry_file_detailed(&FastApiImportRewriter, content, tmp.path(), "modules")
+                .unwrap_err();
+        assert_eq!(
+            missing,
+            vec![
+                MissingImport {
+                    module: "ghost".to_string(),
+                    expected_path: "modules/ghost".to_string(),
+                    line: Some(3),
+                },
+                MissingImport {
+                    module: "phantom".to_string(),
+                    expected_path: "modules/phantom".to_string(),
+                    line: Some(5),
+                },
+            ]
+        );
+
+        // 无行号信息的重写器使用默认实现
+        let missing = validate_entry_file_detailed(&NuxtImportRewriter, "", tmp.path(), "pages")
+            .unwrap_err();
+        assert_eq!(missing[0].module, "pages");
+        assert_eq!(missing[0].line, None);
+
+        assert!(validate_entry_file_detailed(
+            &FastApiImportRewriter,
+            "from modules.auth.routes import router\n",
+            tmp.path(),
+            "modules",
+        )
+        .is_ok());
+    }
+
+    // -----------------------------------------------------------------------
+    // Angular / Nuxt 重写器测试
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn test_angular_lazy_route_dropped_for_unselected_module() {
+        let content = "\
+import { NgModule } from '@angular/core';
+import { RouterModule, Routes } from '@angular/router';
+import { HomeComponent } from './home/home.component';
+import { ReportsComponent } from './modules/reports/reports.component';
+
+const routes: Routes = [
+  { path: '', component: HomeComponent },
+  { path: 'reports', component: ReportsComponent },
+  {
+    path: 'orders',
+    loadChildren: () => import('./modules/orders/orders.module').then(m => m.OrdersModule)
+  },
+  {
+    path: 'billing',
+    loadChildren: () => import('./modules/billing/billing.module').then(m => m.BillingModule)
+  },
+];
+
+@NgModule({
+  imports: [RouterModule.forRoot(routes)],
+  exports: [RouterModule]
+})
+export class AppRoutingModule {}";
+
+        let selected = vec!["orders".to_string()];
+        let result = rewrite_angular_routing(content, &selected, "src/app/modules");
+
+        assert!(result.contains("import('./modules/orders/orders.module')"));
+        assert!(!result.contains("billing"));
+        assert!(!result.contains("ReportsComponent"));
+        // 非模块路由与框架导入保留
+        assert!(result.contains("{ path: '', component: HomeComponent },"));
+        assert!(result.contains("import { RouterModule, Routes } from '@angular/router';"));
+        assert!(result.contains("imports: [RouterModule.forRoot(routes)],"));
+    }
+
+    #[test]
+    fn test_angular_validate_reports_missing_module() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("src/app/modules/orders")).unwrap();
+        let content = "\
+{ path: 'orders', loadChildren: () => import('./modules/orders/orders.module').then(m => m.OrdersModule) },
+{ path: 'ghost', loadChildren: () => import('./modules/ghost/ghost.module').then(m => m.GhostModule) },";
+
+        let missing = validate_angular_imports(content, tmp.path(), "src/app/modules");
+        assert_eq!(missing, vec!["src/app/modules/ghost"]);
+    }
+
+    #[test]
+    fn test_nuxt_rewriter_keeps_config_and_checks_pages_dir() {
+        let rewriter = get_rewriter("nuxt").unwrap();
+        let content = "export default defineNuxtConfig({})";
+        assert_eq!(rewriter.rewrite(content, &["home".to_string()], "pages"), content);
+
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(rewriter.validate(content, tmp.path(), "pages"), vec!["pages"]);
+        std::fs::create_dir_all(tmp.path().join("pages/home")).unwrap();
+        assert!(rewriter.validate(content, tmp.path(), "pages").is_empty());
+        assert_eq!(get_rewriter("angular").unwrap().entry_file(), ANGULAR_ENTRY_FILE);
+    }
 }
@@ -3,10 +3,29 @@
 // 纯 Rust 函数，不依赖 tauri::*，方便单元测试
 // ============================================================================
 
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
 use std::io::{Read, Write};
 use std::path::Path;
 
+use crate::models::dtos::{
+    ArchiveInfo, CheckItem, CheckStatus, ContentTransform, ManifestFile, PackageManifest,
+    PackageVerification,
+};
+use crate::services::analyzer::{self, compute_file_hash};
+use crate::services::{CORE_FILES, SENSITIVE_FILE_PATTERNS};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::normalize_rel_path;
+use crate::utils::pattern::wildcard_match;
+
+/// 交付包清单文件名（位于 ZIP 根目录）
+pub const MANIFEST_FILE_NAME: &str = "prism-manifest.json";
+
+/// 模块说明汇总文件名（位于 ZIP 根目录）
+pub const MODULES_DOC_FILE_NAME: &str = "MODULES.md";
+
+/// 模块无 README 时依次查找文档注释的主文件
+const MODULE_MAIN_FILES: &[&str] = &["__init__.py", "main.py", "index.ts", "index.js"];
 
 /// 验证构建参数：客户名称非空且至少选中一个模块
 pub fn validate_build_params(client_name: &str, selected_modules: &[String]) -> AppResult<()> {
@@ -28,6 +47,8 @@ pub fn validate_build_params(client_name: &str, selected_modules: &[String]) ->
 }
 
 /// 递归复制目录及其所有内容到目标路径
+///
+/// 跟随符号链接：指向文件的链接按目标内容复制，指向目录的链接展开复制。
 pub fn copy_dir_recursive(src: &Path, dst: &Path) -> AppResult<()> {
     // 创建目标目录
     std::fs::create_dir_all(dst).map_err(|e| {
@@ -39,7 +60,7 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> AppResult<()> {
     })?;
 
     // 使用 walkdir 遍历源目录
-    for entry in walkdir::WalkDir::new(src) {
+    for entry in walkdir::WalkDir::new(src).follow_links(true) {
         let entry = entry.map_err(|e| {
             AppError::BuildError(format!("复制文件时出错 - 遍历目录失败: {}", e))
         })?;
@@ -74,17 +95,81 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> AppResult<()> {
     Ok(())
 }
 
+/// 写入检查使用的临时文件名前缀
+const WRITE_CHECK_FILE_PREFIX: &str = ".prism_write_check_";
+
+/// 检查输出目录可写：在目录中创建并删除一个小临时文件
+///
+/// 用于构建前预检，将只读挂载、权限不足、空间或配额耗尽等问题
+/// 提前暴露为明确的错误，而不是在耗时的复制/打包之后才失败。
+///
+/// # 返回
+/// - `Ok(())`: 目录存在且可写
+/// - `Err(String)`: 目录不存在、不是目录、无写权限或空间不足，返回中文错误描述
+pub fn check_output_writable(dir: &Path) -> Result<(), String> {
+    use std::io::ErrorKind;
+
+    let metadata = std::fs::metadata(dir).map_err(|e| match e.kind() {
+        ErrorKind::NotFound => format!("输出目录不存在：{}", dir.display()),
+        _ => format!("无法访问输出目录 {}: {}", dir.display(), e),
+    })?;
+    if !metadata.is_dir() {
+        return Err(format!("输出路径不是目录：{}", dir.display()));
+    }
+
+    let probe = dir.join(format!("{}{}", WRITE_CHECK_FILE_PREFIX, std::process::id()));
+    let result = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .and_then(|mut file| {
+            file.write_all(b"ok")?;
+            file.sync_all()
+        });
+    // 写入失败时文件也可能已创建，统一尝试删除
+    let _ = std::fs::remove_file(&probe);
+
+    result.map_err(|e| match e.kind() {
+        ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+            format!("输出目录不可写（权限不足或为只读）：{}", dir.display())
+        }
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => {
+            format!("输出目录所在磁盘空间不足或已超出配额：{}", dir.display())
+        }
+        _ => format!("输出目录写入检查失败 {}: {}", dir.display(), e),
+    })
+}
+
 /// 将目录内容打包为 ZIP 文件
 pub fn create_zip_from_dir(src_dir: &Path, zip_path: &Path) -> AppResult<()> {
+    create_zip_from_dir_with(src_dir, zip_path, false)
+}
+
+/// 将目录内容打包为 ZIP 文件，`deterministic` 为 true 时生成可复现的归档
+///
+/// 可复现模式下条目按路径排序，且所有条目使用固定时间戳（环境变量 `SOURCE_DATE_EPOCH`，
+/// 未设置或无效时为 1980-01-01 00:00:00），相同输入得到逐字节相同的 ZIP。
+pub fn create_zip_from_dir_with(
+    src_dir: &Path,
+    zip_path: &Path,
+    deterministic: bool,
+) -> AppResult<()> {
     let file = std::fs::File::create(zip_path)
         .map_err(|e| AppError::BuildError(format!("打包 ZIP 时出错 - 无法创建 ZIP 文件: {}", e)))?;
     let mut zip_writer = zip::ZipWriter::new(file);
 
     // 设置 ZIP 压缩选项（使用 Deflated 压缩）
-    let options = zip::write::SimpleFileOptions::default()
+    let mut options = zip::write::SimpleFileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated);
+    let mut walker = walkdir::WalkDir::new(src_dir);
+    if deterministic {
+        options = options.last_modified_time(reproducible_zip_time(
+            std::env::var("SOURCE_DATE_EPOCH").ok().as_deref(),
+        ));
+        walker = walker.sort_by_file_name();
+    }
 
-    for entry in walkdir::WalkDir::new(src_dir) {
+    for entry in walker {
         let entry = entry
             .map_err(|e| AppError::BuildError(format!("打包 ZIP 时出错 - 遍历目录失败: {}", e)))?;
 
@@ -99,7 +184,7 @@ pub fn create_zip_from_dir(src_dir: &Path, zip_path: &Path) -> AppResult<()> {
         }
 
         // 统一使用正斜杠作为 ZIP 内路径分隔符
-        let zip_entry_name = relative_path.to_string_lossy().replace('\\', "/");
+        let zip_entry_name = normalize_rel_path(relative_path);
 
         if path.is_dir() {
             zip_writer
@@ -132,6 +217,16 @@ pub fn create_zip_from_dir(src_dir: &Path, zip_path: &Path) -> AppResult<()> {
 
     Ok(())
 }
+/// 可复现 ZIP 的条目时间戳：`SOURCE_DATE_EPOCH`（Unix 秒）可解析且在 ZIP 时间范围内时使用该时刻，
+/// 否则为 ZIP 格式的最早时间 1980-01-01 00:00:00
+fn reproducible_zip_time(source_date_epoch: Option<&str>) -> zip::DateTime {
+    source_date_epoch
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .and_then(|secs| time::OffsetDateTime::from_unix_timestamp(secs).ok())
+        .and_then(|instant| zip::DateTime::try_from(instant).ok())
+        .unwrap_or_default()
+}
+
 /// 复制项目目录到目标路径，排除指定的目录名
 ///
 /// 用于构建时复制项目骨架：复制除 modules_dir 和忽略目录以外的所有文件。
@@ -142,50 +237,144 @@ pub fn create_zip_from_dir(src_dir: &Path, zip_path: &Path) -> AppResult<()> {
 /// - `dst`: 目标构建目录
 /// - `exclude_dirs`: 需要排除的目录名列表（如 `[".git", "node_modules", "modules"]`）
 pub fn copy_dir_excluding(src: &Path, dst: &Path, exclude_dirs: &[&str]) -> AppResult<()> {
-    std::fs::create_dir_all(dst).map_err(|e| {
-        AppError::BuildError(format!("无法创建目标目录 {}: {}", dst.display(), e))
-    })?;
+    copy_dir_impl(src, dst, "", exclude_dirs, &[], None).map(|_| ())
+}
 
-    for entry in walkdir::WalkDir::new(src)
-        .into_iter()
-        .filter_entry(|e| {
-            // 只对目录做排除判断，文件始终保留
-            if e.file_type().is_dir() {
-                if let Some(name) = e.file_name().to_str() {
-                    // 精确匹配或前缀匹配（如 "dist_" 匹配 "dist_客户A_20260209"）
-                    for pattern in exclude_dirs {
-                        if pattern.ends_with('_') {
-                            // 前缀匹配模式
-                            if name.starts_with(pattern) {
-                                return false;
-                            }
-                        } else if pattern.starts_with("*.") {
-                            // 通配符模式（如 "*.egg-info"）跳过，仅用于文件
-                            continue;
-                        } else if name == *pattern {
-                            return false;
-                        }
+/// 复制项目目录（同 `copy_dir_excluding`），并通过 `progress(已复制字节, 总字节)` 报告进度
+///
+/// 复制前先遍历一遍统计待复制文件的总字节数；每复制完一个文件回调一次。
+pub fn copy_dir_excluding_with_progress(
+    src: &Path,
+    dst: &Path,
+    exclude_dirs: &[&str],
+    progress: &dyn Fn(u64, u64),
+) -> AppResult<()> {
+    copy_dir_impl(src, dst, "", exclude_dirs, &[], Some(progress)).map(|_| ())
+}
+
+/// 复制项目目录（同 `copy_dir_excluding`），并在复制时对匹配的文本文件执行内容替换
+///
+/// 替换与复制一起完成，构建目录中不会出现未替换的中间状态。
+/// 二进制文件（含 NUL 字节或非 UTF-8）原样复制。
+///
+/// # 返回
+/// - `Ok(HashMap)`: 被改写文件的相对路径 → 改写后内容的 SHA256（供完整性校验替换源文件哈希）
+pub fn copy_dir_excluding_with_transforms(
+    src: &Path,
+    dst: &Path,
+    exclude_dirs: &[&str],
+    transforms: &[ContentTransform],
+) -> AppResult<HashMap<String, String>> {
+    copy_dir_impl(src, dst, "", exclude_dirs, transforms, None)
+}
+
+/// 复制项目中的子目录（如单个模块），内容替换规则按项目根目录的相对路径匹配
+///
+/// `rel_base` 为 `src` 相对项目根目录的路径（`/` 分隔，如 `modules/auth`），
+/// 返回的改写文件路径同样相对项目根目录，可直接并入源文件哈希。
+pub fn copy_subdir_with_transforms(
+    src: &Path,
+    dst: &Path,
+    rel_base: &str,
+    exclude_dirs: &[&str],
+    transforms: &[ContentTransform],
+) -> AppResult<HashMap<String, String>> {
+    copy_dir_impl(src, dst, rel_base, exclude_dirs, transforms, None)
+}
+
+/// 同 `copy_dir_excluding_with_transforms`，并报告复制进度（见 `copy_dir_excluding_with_progress`）
+pub fn copy_dir_excluding_with_transforms_and_progress(
+    src: &Path,
+    dst: &Path,
+    exclude_dirs: &[&str],
+    transforms: &[ContentTransform],
+    progress: &dyn Fn(u64, u64),
+) -> AppResult<HashMap<String, String>> {
+    copy_dir_impl(src, dst, "", exclude_dirs, transforms, Some(progress))
+}
+
+/// 判断遍历到的条目是否应被排除（规则见 `copy_dir_excluding`）
+fn is_copy_excluded(e: &walkdir::DirEntry, exclude_dirs: &[&str]) -> bool {
+    // 只对目录做排除判断，文件始终保留
+    if e.file_type().is_dir() {
+        if let Some(name) = e.file_name().to_str() {
+            // 精确匹配或前缀匹配（如 "dist_" 匹配 "dist_客户A_20260209"）
+            for pattern in exclude_dirs {
+                if pattern.ends_with('_') {
+                    // 前缀匹配模式
+                    if name.starts_with(pattern) {
+                        return true;
                     }
+                } else if pattern.starts_with("*.") {
+                    // 通配符模式（如 "*.egg-info"）跳过，仅用于文件
+                    continue;
+                } else if name == *pattern {
+                    return true;
                 }
-            } else {
-                // 文件级排除：处理通配符模式和精确文件名匹配
-                if let Some(name) = e.file_name().to_str() {
-                    for pattern in exclude_dirs {
-                        if pattern.starts_with("*.") {
-                            // 通配符后缀匹配（如 "*.egg-info"、"*.zip"）
-                            let suffix = &pattern[1..]; // ".egg-info"
-                            if name.ends_with(suffix) {
-                                return false;
-                            }
-                        } else if pattern.starts_with('.') && name == *pattern {
-                            // 精确匹配隐藏文件（如 ".env"、".env.local"）
-                            return false;
-                        }
+            }
+        }
+    } else {
+        // 文件级排除：处理通配符模式和精确文件名匹配
+        if let Some(name) = e.file_name().to_str() {
+            for pattern in exclude_dirs {
+                if pattern.starts_with("*.") {
+                    // 通配符后缀匹配（如 "*.egg-info"、"*.zip"）
+                    let suffix = &pattern[1..]; // ".egg-info"
+                    if name.ends_with(suffix) {
+                        return true;
                     }
+                } else if pattern.starts_with('.') && name == *pattern {
+                    // 精确匹配隐藏文件（如 ".env"、".env.local"）
+                    return true;
+                } else if pattern.contains('*') && wildcard_match(pattern, name) {
+                    // 文件名通配（如 "test_*.py"、"*_test.py"）
+                    return true;
                 }
             }
-            true
-        })
+        }
+    }
+    false
+}
+
+/// 目录复制的公共实现：`progress` 为 None 时不做总量统计
+///
+/// 与 `copy_dir_recursive` 一样跟随符号链接，与构建前计算源文件哈希的扫描保持一致。
+/// `rel_base` 非空时作为相对路径前缀，用于匹配替换规则和生成返回的路径。
+fn copy_dir_impl(
+    src: &Path,
+    dst: &Path,
+    rel_base: &str,
+    exclude_dirs: &[&str],
+    transforms: &[ContentTransform],
+    progress: Option<&dyn Fn(u64, u64)>,
+) -> AppResult<HashMap<String, String>> {
+    let mut transformed: HashMap<String, String> = HashMap::new();
+    std::fs::create_dir_all(dst).map_err(|e| {
+        AppError::BuildError(format!("无法创建目标目录 {}: {}", dst.display(), e))
+    })?;
+
+    // 快速统计待复制的总字节数（遍历失败的条目忽略，复制阶段再报错）
+    let total_bytes: u64 = match progress {
+        Some(_) => walkdir::WalkDir::new(src)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|e| !is_copy_excluded(e, exclude_dirs))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.metadata().ok())
+            .map(|m| m.len())
+            .sum(),
+        None => 0,
+    };
+    let mut copied_bytes: u64 = 0;
+    if let Some(report) = progress {
+        report(0, total_bytes);
+    }
+
+    for entry in walkdir::WalkDir::new(src)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| !is_copy_excluded(e, exclude_dirs))
     {
         let entry = entry.map_err(|e| {
             AppError::BuildError(format!("遍历项目目录失败: {}", e))
@@ -213,20 +402,644 @@ pub fn copy_dir_excluding(src: &Path, dst: &Path, exclude_dirs: &[&str]) -> AppR
                     AppError::BuildError(format!("无法创建目录 {}: {}", parent.display(), e))
                 })?;
             }
-            std::fs::copy(entry.path(), &target).map_err(|e| {
-                AppError::BuildError(format!(
-                    "无法复制 {} → {}: {}",
-                    entry.path().display(),
-                    target.display(),
-                    e
-                ))
-            })?;
+            let rel = if rel_base.is_empty() {
+                normalize_rel_path(relative)
+            } else {
+                format!("{}/{}", rel_base.trim_end_matches('/'), normalize_rel_path(relative))
+            };
+            let matching: Vec<&ContentTransform> = transforms
+                .iter()
+                .filter(|t| transform_applies(&t.glob, &rel))
+                .collect();
+            let mut rewritten = false;
+            if !matching.is_empty() {
+                if let Some(hash) = copy_with_transforms(entry.path(), &target, &matching)? {
+                    transformed.insert(rel, hash);
+                    rewritten = true;
+                }
+            }
+            if !rewritten {
+                std::fs::copy(entry.path(), &target).map_err(|e| {
+                    AppError::BuildError(format!(
+                        "无法复制 {} → {}: {}",
+                        entry.path().display(),
+                        target.display(),
+                        e
+                    ))
+                })?;
+            }
+            if let Some(report) = progress {
+                // 按源文件大小计入进度（与统计阶段口径一致，改写后的大小不影响进度）
+                copied_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                report(copied_bytes.min(total_bytes), total_bytes);
+            }
         }
     }
 
-    Ok(())
+    Ok(transformed)
+}
+
+/// 判断内容替换规则是否作用于该文件
+///
+/// glob 含 `/` 时匹配完整相对路径，否则只匹配文件名（如 `*.env.js`）。
+fn transform_applies(glob: &str, relative_path: &str) -> bool {
+    if glob.contains('/') {
+        wildcard_match(glob, relative_path)
+    } else {
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+        wildcard_match(glob, file_name)
+    }
+}
+
+/// 读取源文件、依次执行替换后写入目标文件
+///
+/// # 返回
+/// - `Ok(Some(hash))`: 内容已改写，返回写入内容的 SHA256
+/// - `Ok(None)`: 二进制文件或无任何替换命中，由调用方原样复制
+fn copy_with_transforms(
+    src: &Path,
+    dst: &Path,
+    transforms: &[&ContentTransform],
+) -> AppResult<Option<String>> {
+    let bytes = std::fs::read(src).map_err(|e| {
+        AppError::BuildError(format!("无法读取 {}: {}", src.display(), e))
+    })?;
+    if bytes.contains(&0) {
+        return Ok(None);
+    }
+    let Ok(original) = String::from_utf8(bytes) else {
+        return Ok(None);
+    };
+
+    let mut content = original.clone();
+    for transform in transforms.iter().filter(|t| !t.find.is_empty()) {
+        content = content.replace(&transform.find, &transform.replace);
+    }
+    if content == original {
+        return Ok(None);
+    }
+
+    std::fs::write(dst, &content).map_err(|e| {
+        AppError::BuildError(format!("无法写入 {}: {}", dst.display(), e))
+    })?;
+    // 保留源文件权限（如可执行位），与 fs::copy 行为一致
+    if let Ok(metadata) = std::fs::metadata(src) {
+        let _ = std::fs::set_permissions(dst, metadata.permissions());
+    }
+    Ok(Some(format!("{:x}", Sha256::digest(content.as_bytes()))))
 }
 
+/// 校验构建目录中的文件与源项目是否一致（逐文件重新哈希比对）
+///
+/// 用于构建后完整性校验：复制过程中的 bug 或源文件被并发修改，
+/// 都会导致构建目录中的文件哈希与构建前扫描得到的源文件哈希不一致。
+///
+/// # 参数
+/// - `staging_dir`: 构建临时目录
+/// - `source_hashes`: 源项目文件哈希表（相对路径 → SHA256），来自构建前扫描
+///
+/// # 返回
+/// - `Ok(Vec<String>)`: 不一致文件的描述列表，空列表 = 校验通过
+/// - `Err(AppError)`: 遍历或读取构建目录失败
+pub fn verify_staging_integrity(
+    staging_dir: &Path,
+    source_hashes: &HashMap<String, String>,
+) -> AppResult<Vec<String>> {
+    let mut errors = Vec::new();
+
+    for entry in walkdir::WalkDir::new(staging_dir) {
+        let entry = entry
+            .map_err(|e| AppError::BuildError(format!("完整性校验时出错 - 遍历目录失败: {}", e)))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        // 统一使用正斜杠，与扫描结果的相对路径格式一致
+        let relative = entry
+            .path()
+            .strip_prefix(staging_dir)
+            .map(normalize_rel_path)
+            .map_err(|e| AppError::BuildError(format!("完整性校验时出错 - 路径处理失败: {}", e)))?;
+
+        let staged_hash = compute_file_hash(entry.path()).map_err(AppError::BuildError)?;
+        match source_hashes.get(&relative) {
+            Some(source_hash) if *source_hash == staged_hash => {}
+            Some(_) => errors.push(format!("{}：内容与源文件不一致", relative)),
+            None => errors.push(format!("{}：源项目中不存在该文件", relative)),
+        }
+    }
+
+    errors.sort();
+    Ok(errors)
+}
+
+/// 为构建目录生成交付包清单并写入 `prism-manifest.json`
+///
+/// 清单记录包内每个文件的相对路径、SHA256 和大小，供 `verify_package` 校验传输完整性。
+pub fn write_package_manifest(
+    staging_dir: &Path,
+    client_name: &str,
+    modules: &[String],
+) -> AppResult<PackageManifest> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(staging_dir) {
+        let entry = entry
+            .map_err(|e| AppError::BuildError(format!("生成清单时出错 - 遍历目录失败: {}", e)))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .strip_prefix(staging_dir)
+            .map(normalize_rel_path)
+            .map_err(|e| AppError::BuildError(format!("生成清单时出错 - 路径处理失败: {}", e)))?;
+        if path == MANIFEST_FILE_NAME {
+            continue;
+        }
+        let size = entry
+            .metadata()
+            .map_err(|e| AppError::BuildError(format!("生成清单时出错 - 读取元数据失败: {}", e)))?
+            .len();
+        let sha256 = compute_file_hash(entry.path()).map_err(AppError::BuildError)?;
+        files.push(ManifestFile { path, sha256, size });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let manifest = PackageManifest {
+        client_name: client_name.trim().to_string(),
+        modules: modules.to_vec(),
+        files,
+    };
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| AppError::BuildError(format!("生成清单时出错 - 序列化失败: {}", e)))?;
+    std::fs::write(staging_dir.join(MANIFEST_FILE_NAME), json)
+        .map_err(|e| AppError::BuildError(format!("生成清单时出错 - 写入失败: {}", e)))?;
+
+    Ok(manifest)
+}
+
+/// 将 README 内容转为 `MODULES.md` 中的小节正文
+///
+/// 去掉开头的一级标题（小节标题已是模块名），其余标题下沉两级以嵌套在模块小节下。
+/// 围栏代码块（```` ``` ```` / `~~~`）内以 `#` 开头的行（如 shell 注释）保持原样。
+fn readme_section_body(readme: &str) -> String {
+    let mut lines = readme.trim().lines().peekable();
+    if lines.peek().is_some_and(|l| l.starts_with("# ")) {
+        lines.next();
+    }
+    let mut fence: Option<&str> = None;
+    lines
+        .map(|line| {
+            let trimmed = line.trim_start();
+            let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+            match (fence, marker) {
+                (None, Some(marker)) => fence = Some(marker),
+                (Some(open), Some(marker)) if open == marker => fence = None,
+                _ => {}
+            }
+            if fence.is_none() && line.starts_with('#') {
+                format!("##{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// 提取源文件开头的文档注释（Python `"""` / `'''` docstring 或 JS/TS `/** */` 注释）
+fn leading_docstring(source: &str) -> Option<String> {
+    let text = source.trim_start();
+    let doc = if let Some(rest) = text.strip_prefix("/**") {
+        let body = &rest[..rest.find("*/")?];
+        body.lines()
+            .map(|l| l.trim().trim_start_matches('*').trim())
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        let quote = ["\"\"\"", "'''"].into_iter().find(|q| text.starts_with(q))?;
+        let rest = &text[quote.len()..];
+        rest[..rest.find(quote)?].to_string()
+    };
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+/// 读取模块说明：优先 `README.md`，其次主文件开头的文档注释
+fn module_description(module_dir: &Path) -> Option<String> {
+    if let Ok(readme) = std::fs::read_to_string(module_dir.join("README.md")) {
+        let body = readme_section_body(&readme);
+        if !body.is_empty() {
+            return Some(body);
+        }
+    }
+    MODULE_MAIN_FILES.iter().find_map(|name| {
+        let source = std::fs::read_to_string(module_dir.join(name)).ok()?;
+        leading_docstring(&source)
+    })
+}
+
+/// 汇总各交付模块的说明并写入构建目录根部的 `MODULES.md`
+///
+/// 每个模块一个小节，无说明的模块标注"(无说明)"。
+pub fn write_modules_doc(
+    staging_dir: &Path,
+    modules_dir_name: &str,
+    modules: &[String],
+) -> AppResult<()> {
+    let mut doc = String::from("# 交付模块说明\n");
+    for module in modules {
+        let description = module_description(&staging_dir.join(modules_dir_name).join(module))
+            .unwrap_or_else(|| "(无说明)".to_string());
+        doc.push_str(&format!("\n## {}\n\n{}\n", module, description));
+    }
+    std::fs::write(staging_dir.join(MODULES_DOC_FILE_NAME), doc)
+        .map_err(|e| AppError::BuildError(format!("生成模块说明失败: {}", e)))
+}
+
+/// 许可证文件名前缀（不区分大小写）
+const LICENSE_FILE_STEMS: &[&str] = &["LICENSE", "LICENCE", "COPYING"];
+
+/// 检测项目根目录下的许可证文件（如 `LICENSE`、`LICENSE.md`、`COPYING`）
+///
+/// 多许可证仓库的 `LICENSE-MIT`、`LICENSE-APACHE` 等同样识别；返回文件名，已排序。
+pub fn detect_license_files(project_root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(project_root) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .filter(|name| is_license_file_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// 文件名是否为许可证文件（不区分大小写，兼容 `LICENSE-MIT` 等多许可证命名）
+fn is_license_file_name(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    LICENSE_FILE_STEMS.iter().any(|stem| {
+        upper
+            .strip_prefix(stem)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '-', '_']))
+    })
+}
+
+/// 计算字节内容的 SHA256 哈希值（十六进制）
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// 校验已交付的 ZIP 包与其内嵌清单是否一致
+///
+/// 逐个重新哈希包内文件并与 `prism-manifest.json` 比对，报告不一致、缺失和多余的文件；
+/// 若存在同名 `.sha256` 校验文件（如 `dist_xxx.zip.sha256`），同时校验整包哈希。
+///
+/// # 参数
+/// - `archive_path`: ZIP 交付包路径
+pub fn verify_package(archive_path: &Path) -> AppResult<PackageVerification> {
+    let file = std::fs::File::open(archive_path).map_err(|e| {
+        AppError::BuildError(format!("校验交付包失败 - 无法打开 {}: {}", archive_path.display(), e))
+    })?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::BuildError(format!("校验交付包失败 - 无效的 ZIP 文件: {}", e)))?;
+
+    // 1. 读取内嵌清单
+    let manifest: PackageManifest = {
+        let mut entry = archive.by_name(MANIFEST_FILE_NAME).map_err(|_| {
+            AppError::BuildError(format!("校验交付包失败 - 包内缺少 {}", MANIFEST_FILE_NAME))
+        })?;
+        let mut content = String::new();
+        entry
+            .read_to_string(&mut content)
+            .map_err(|e| AppError::BuildError(format!("校验交付包失败 - 读取清单失败: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::BuildError(format!("校验交付包失败 - 清单格式无效: {}", e)))?
+    };
+    let expected: HashMap<&str, &str> = manifest
+        .files
+        .iter()
+        .map(|f| (f.path.as_str(), f.sha256.as_str()))
+        .collect();
+
+    // 2. 重新哈希包内每个文件并与清单比对
+    let mut mismatched = Vec::new();
+    let mut extra = Vec::new();
+    let mut seen: BTreeSet<String> = BTreeSet::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| AppError::BuildError(format!("校验交付包失败 - 读取条目失败: {}", e)))?;
+        if entry.is_dir() || entry.name() == MANIFEST_FILE_NAME {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| AppError::BuildError(format!("校验交付包失败 - 读取 {} 失败: {}", name, e)))?;
+
+        match expected.get(name.as_str()) {
+            Some(hash) if *hash == sha256_hex(&data) => {}
+            Some(_) => mismatched.push(name.clone()),
+            None => extra.push(name.clone()),
+        }
+        seen.insert(name);
+    }
+    let missing: Vec<String> = manifest
+        .files
+        .iter()
+        .filter(|f| !seen.contains(&f.path))
+        .map(|f| f.path.clone())
+        .collect();
+
+    // 3. 校验整包哈希（存在 .sha256 校验文件时）
+    let archive_hash_ok = check_archive_sidecar(archive_path)?;
+
+    mismatched.sort();
+    extra.sort();
+    let passed = mismatched.is_empty()
+        && missing.is_empty()
+        && extra.is_empty()
+        && archive_hash_ok != Some(false);
+
+    Ok(PackageVerification {
+        passed,
+        mismatched,
+        missing,
+        extra,
+        archive_hash_ok,
+    })
+}
+
+/// 按同名 `.sha256` 校验文件校验整包哈希；无校验文件时返回 None
+fn check_archive_sidecar(archive_path: &Path) -> AppResult<Option<bool>> {
+    let mut sidecar = archive_path.as_os_str().to_owned();
+    sidecar.push(".sha256");
+    match std::fs::read_to_string(Path::new(&sidecar)) {
+        Ok(content) => {
+            // 兼容 `sha256sum` 输出格式：`<hash>  <文件名>`
+            let expected_hash = content.split_whitespace().next().unwrap_or("").to_lowercase();
+            let actual_hash = compute_file_hash(archive_path).map_err(AppError::BuildError)?;
+            Ok(Some(expected_hash == actual_hash))
+        }
+        Err(_) => Ok(None),
+    }
+}
+
+/// 交付前检查清单：汇总清单、校验文件、敏感文件、核心文件、入口文件与 README/LICENSE 检查
+///
+/// 每项给出通过 / 警告 / 失败结论，清单缺失或内容不一致、整包哈希不符、
+/// 含敏感文件、缺少入口文件视为失败；缺少校验文件、核心文件或 README/LICENSE 仅警告。
+///
+/// # 参数
+/// - `archive_path`: ZIP 交付包路径
+pub fn delivery_checklist(archive_path: &Path) -> AppResult<Vec<CheckItem>> {
+    let files = archive_file_names(archive_path)?;
+    let item = |key: &str, label: &str, status: CheckStatus, detail: String| CheckItem {
+        key: key.to_string(),
+        label: label.to_string(),
+        status,
+        detail,
+    };
+    let mut items = Vec::new();
+
+    // 1. 交付清单存在且与包内文件一致
+    items.push(if !files.iter().any(|f| f == MANIFEST_FILE_NAME) {
+        item(
+            "manifest",
+            "交付清单",
+            CheckStatus::Fail,
+            format!("包内缺少 {}", MANIFEST_FILE_NAME),
+        )
+    } else {
+        match verify_package(archive_path) {
+            Ok(v) if v.mismatched.is_empty() && v.missing.is_empty() && v.extra.is_empty() => item(
+                "manifest",
+                "交付清单",
+                CheckStatus::Pass,
+                format!("清单与包内 {} 个文件一致", files.len() - 1),
+            ),
+            Ok(v) => item(
+                "manifest",
+                "交付清单",
+                CheckStatus::Fail,
+                format!(
+                    "清单与包内文件不一致：哈希不符 {} 个，缺失 {} 个，多余 {} 个",
+                    v.mismatched.len(),
+                    v.missing.len(),
+                    v.extra.len()
+                ),
+            ),
+            Err(e) => item("manifest", "交付清单", CheckStatus::Fail, e.to_string()),
+        }
+    });
+
+    // 2. .sha256 校验文件存在且与整包哈希一致
+    items.push(match check_archive_sidecar(archive_path)? {
+        Some(true) => item(
+            "checksum",
+            "校验文件",
+            CheckStatus::Pass,
+            "整包哈希与 .sha256 一致".into(),
+        ),
+        Some(false) => item(
+            "checksum",
+            "校验文件",
+            CheckStatus::Fail,
+            "整包哈希与 .sha256 不一致，交付包可能已损坏或被篡改".into(),
+        ),
+        None => item(
+            "checksum",
+            "校验文件",
+            CheckStatus::Warn,
+            "未找到同名 .sha256 校验文件".into(),
+        ),
+    });
+
+    // 3. 不含敏感文件
+    let sensitive: Vec<&str> = files
+        .iter()
+        .map(String::as_str)
+        .filter(|path| {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            SENSITIVE_FILE_PATTERNS
+                .iter()
+                .any(|p| wildcard_match(p, name))
+        })
+        .collect();
+    items.push(if sensitive.is_empty() {
+        item(
+            "sensitive",
+            "敏感文件",
+            CheckStatus::Pass,
+            "未发现敏感文件".into(),
+        )
+    } else {
+        item(
+            "sensitive",
+            "敏感文件",
+            CheckStatus::Fail,
+            format!("包含敏感文件：{}", sensitive.join("、")),
+        )
+    });
+
+    // 4. 核心文件齐全（目录以 `/` 结尾，包内存在其下任一文件即可）
+    let missing_core: Vec<&str> = CORE_FILES
+        .iter()
+        .copied()
+        .filter(|core| {
+            if core.ends_with('/') {
+                !files.iter().any(|f| f.starts_with(core))
+            } else {
+                !files.iter().any(|f| f == core)
+            }
+        })
+        .collect();
+    items.push(if missing_core.is_empty() {
+        item(
+            "core_files",
+            "核心文件",
+            CheckStatus::Pass,
+            "核心文件齐全".into(),
+        )
+    } else {
+        item(
+            "core_files",
+            "核心文件",
+            CheckStatus::Warn,
+            format!("缺少核心文件：{}", missing_core.join("、")),
+        )
+    });
+
+    // 5. 入口文件存在
+    let entries: Vec<&str> = files
+        .iter()
+        .map(String::as_str)
+        .filter(|f| analyzer::is_entry_file(f))
+        .collect();
+    items.push(if entries.is_empty() {
+        item(
+            "entry_file",
+            "入口文件",
+            CheckStatus::Fail,
+            "包内未找到入口文件".into(),
+        )
+    } else {
+        item(
+            "entry_file",
+            "入口文件",
+            CheckStatus::Pass,
+            entries.join("、"),
+        )
+    });
+
+    // 6. 根目录包含 README 与许可证文件
+    let root_files: Vec<&str> = files
+        .iter()
+        .map(String::as_str)
+        .filter(|f| !f.contains('/'))
+        .collect();
+    let mut missing_docs = Vec::new();
+    if !root_files
+        .iter()
+        .any(|f| f.to_uppercase().starts_with("README"))
+    {
+        missing_docs.push("README");
+    }
+    if !root_files.iter().any(|f| is_license_file_name(f)) {
+        missing_docs.push("LICENSE");
+    }
+    items.push(if missing_docs.is_empty() {
+        item(
+            "docs",
+            "README / LICENSE",
+            CheckStatus::Pass,
+            "README 与许可证文件齐全".into(),
+        )
+    } else {
+        item(
+            "docs",
+            "README / LICENSE",
+            CheckStatus::Warn,
+            format!("根目录缺少 {}", missing_docs.join("、")),
+        )
+    });
+
+    Ok(items)
+}
+
+/// 列出 ZIP 包内的文件路径（不含目录条目，不解压内容）
+fn archive_file_names(archive_path: &Path) -> AppResult<Vec<String>> {
+    let file = std::fs::File::open(archive_path).map_err(|e| {
+        AppError::BuildError(format!("读取交付包失败 - 无法打开 {}: {}", archive_path.display(), e))
+    })?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::BuildError(format!("读取交付包失败 - 无效的 ZIP 文件: {}", e)))?;
+    let mut names = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive
+            .by_index_raw(i)
+            .map_err(|e| AppError::BuildError(format!("读取交付包失败 - 读取条目失败: {}", e)))?;
+        if !entry.is_dir() {
+            names.push(entry.name().to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// 读取交付包概要：大小、文件数、压缩前后字节数与顶层条目
+///
+/// 只读取 ZIP 中央目录中的条目元数据，不解压文件内容，适合构建完成后即时展示。
+///
+/// # 参数
+/// - `archive_path`: ZIP 交付包路径
+pub fn describe_archive(archive_path: &Path) -> AppResult<ArchiveInfo> {
+    let file = std::fs::File::open(archive_path).map_err(|e| {
+        AppError::BuildError(format!("读取交付包失败 - 无法打开 {}: {}", archive_path.display(), e))
+    })?;
+    let archive_size = file
+        .metadata()
+        .map_err(|e| AppError::BuildError(format!("读取交付包失败 - 无法获取文件大小: {}", e)))?
+        .len();
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::BuildError(format!("读取交付包失败 - 无效的 ZIP 文件: {}", e)))?;
+
+    let mut file_count = 0;
+    let mut compressed_bytes = 0;
+    let mut uncompressed_bytes = 0;
+    let mut top_level: BTreeSet<String> = BTreeSet::new();
+    for i in 0..archive.len() {
+        // by_index_raw 不解压内容，仅读取条目元数据
+        let entry = archive
+            .by_index_raw(i)
+            .map_err(|e| AppError::BuildError(format!("读取交付包失败 - 读取条目失败: {}", e)))?;
+        let name = entry.name();
+        let top = match name.split_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => name.to_string(),
+        };
+        top_level.insert(top);
+        if entry.is_dir() {
+            continue;
+        }
+        file_count += 1;
+        compressed_bytes += entry.compressed_size();
+        uncompressed_bytes += entry.size();
+    }
+
+    Ok(ArchiveInfo {
+        archive_size,
+        file_count,
+        compressed_bytes,
+        uncompressed_bytes,
+        top_level: top_level.into_iter().collect(),
+    })
+}
 
 // ============================================================================
 // 单元测试
@@ -238,6 +1051,38 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_check_output_writable() {
+        let tmp = TempDir::new().unwrap();
+        assert!(check_output_writable(tmp.path()).is_ok());
+        // 检查后不留下临时文件
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+
+        let missing = tmp.path().join("missing");
+        let err = check_output_writable(&missing).unwrap_err();
+        assert!(err.starts_with("输出目录不存在"));
+
+        let file = tmp.path().join("out.zip");
+        fs::write(&file, "x").unwrap();
+        assert!(check_output_writable(&file).unwrap_err().starts_with("输出路径不是目录"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_output_writable_read_only_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        fs::set_permissions(tmp.path(), fs::Permissions::from_mode(0o555)).unwrap();
+        let result = check_output_writable(tmp.path());
+        fs::set_permissions(tmp.path(), fs::Permissions::from_mode(0o755)).unwrap();
+
+        // root 不受目录权限限制，此时无法模拟只读目录
+        if let Err(err) = result {
+            assert!(err.starts_with("输出目录不可写"), "{}", err);
+        }
+    }
+
     #[test]
     fn test_validate_build_params_valid() {
         let modules = vec!["auth".to_string()];
@@ -297,6 +1142,99 @@ mod tests {
         assert_eq!(fs::read_to_string(dest.join("file1.txt")).unwrap(), "内容1");
     }
 
+    #[test]
+    fn test_copy_dir_excluding_with_progress_reports_all_bytes() {
+        let src_dir = TempDir::new().unwrap();
+        let dst_dir = TempDir::new().unwrap();
+        let src = src_dir.path();
+        fs::write(src.join("a.txt"), "12345").unwrap();
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("sub/b.txt"), "1234567890").unwrap();
+        // 被排除的目录不计入总量
+        fs::create_dir_all(src.join("node_modules")).unwrap();
+        fs::write(src.join("node_modules/big.js"), "x".repeat(1000)).unwrap();
+
+        let calls = std::cell::RefCell::new(Vec::new());
+        copy_dir_excluding_with_progress(src, &dst_dir.path().join("out"), &["node_modules"], &|c, t| {
+            calls.borrow_mut().push((c, t))
+        })
+        .unwrap();
+
+        let calls = calls.into_inner();
+        assert_eq!(calls.first(), Some(&(0, 15)));
+        assert_eq!(calls.last(), Some(&(15, 15)));
+        assert!(calls.windows(2).all(|w| w[0].0 <= w[1].0));
+        assert!(!dst_dir.path().join("out/node_modules").exists());
+    }
+
+    #[test]
+    fn test_copy_with_transforms_rewrites_only_matching_text_files() {
+        let src_dir = TempDir::new().unwrap();
+        let dst_dir = TempDir::new().unwrap();
+        let src = src_dir.path();
+        fs::create_dir_all(src.join("config")).unwrap();
+        fs::write(src.join("config/api.js"), "export const BASE = 'http://dev.local/api';\n").unwrap();
+        fs::write(src.join("notes.txt"), "dev server: http://dev.local/api\n").unwrap();
+        let binary = [0u8, 159, 146, 150, b'h', b't', b't', b'p'];
+        fs::write(src.join("config/logo.js"), binary).unwrap();
+
+        let transforms = vec![ContentTransform {
+            glob: "*.js".to_string(),
+            find: "http://dev.local/api".to_string(),
+            replace: "https://client-a.example.com/api".to_string(),
+        }];
+        let dest = dst_dir.path().join("out");
+        let transformed =
+            copy_dir_excluding_with_transforms(src, &dest, &[], &transforms).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.join("config/api.js")).unwrap(),
+            "export const BASE = 'https://client-a.example.com/api';\n"
+        );
+        assert_eq!(
+            transformed.get("config/api.js"),
+            Some(&compute_file_hash(&dest.join("config/api.js")).unwrap())
+        );
+        // 不匹配的文件和二进制文件保持逐字节一致
+        assert_eq!(fs::read(dest.join("notes.txt")).unwrap(), fs::read(src.join("notes.txt")).unwrap());
+        assert_eq!(fs::read(dest.join("config/logo.js")).unwrap(), binary);
+        assert_eq!(transformed.len(), 1);
+    }
+
+    #[test]
+    fn test_deterministic_zip_is_byte_identical() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("source");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("b.txt"), "b").unwrap();
+        fs::write(src.join("a.txt"), "a").unwrap();
+        fs::write(src.join("sub/c.txt"), "c").unwrap();
+
+        let first = dir.path().join("first.zip");
+        create_zip_from_dir_with(&src, &first, true).unwrap();
+        // 修改时间变化不影响可复现归档
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(src.join("a.txt"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let second = dir.path().join("second.zip");
+        create_zip_from_dir_with(&src, &second, true).unwrap();
+
+        assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+    }
+
+    #[test]
+    fn test_reproducible_zip_time() {
+        assert_eq!(reproducible_zip_time(None), zip::DateTime::default());
+        assert_eq!(reproducible_zip_time(Some("not-a-number")), zip::DateTime::default());
+        // 2021-01-01 00:00:00 UTC
+        let t = reproducible_zip_time(Some("1609459200"));
+        assert_eq!((t.year(), t.month(), t.day(), t.hour()), (2021, 1, 1, 0));
+    }
+
     #[test]
     fn test_create_zip_from_dir_basic() {
         let dir = TempDir::new().unwrap();
@@ -324,4 +1262,76 @@ mod tests {
         assert!(file_names.contains(&"hello.txt".to_string()));
         assert!(file_names.contains(&"sub/nested.txt".to_string()));
     }
+
+    #[test]
+    fn test_verify_staging_integrity_detects_mismatch() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("same.txt"), "不变").unwrap();
+        fs::write(dir.path().join("changed.txt"), "已修改").unwrap();
+
+        let mut source_hashes = HashMap::new();
+        source_hashes.insert(
+            "same.txt".to_string(),
+            compute_file_hash(&dir.path().join("same.txt")).unwrap(),
+        );
+        source_hashes.insert("changed.txt".to_string(), "0".repeat(64));
+
+        let errors = verify_staging_integrity(dir.path(), &source_hashes).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("changed.txt"));
+    }
+
+    #[test]
+    fn test_detect_license_files() {
+        let dir = TempDir::new().unwrap();
+        let files = ["LICENSE-MIT", "license.md", "COPYING", "LICENSES_README", "README.md"];
+        for name in files {
+            fs::write(dir.path().join(name), "text").unwrap();
+        }
+        fs::create_dir(dir.path().join("LICENSE-APACHE")).unwrap();
+
+        assert_eq!(
+            detect_license_files(dir.path()),
+            vec!["COPYING", "LICENSE-MIT", "license.md"]
+        );
+    }
+
+    #[test]
+    fn test_write_modules_doc_aggregates_descriptions() {
+        let staging = TempDir::new().unwrap();
+        let modules_dir = staging.path().join("modules");
+        fs::create_dir_all(modules_dir.join("auth")).unwrap();
+        fs::create_dir_all(modules_dir.join("billing")).unwrap();
+        fs::create_dir_all(modules_dir.join("legacy")).unwrap();
+        fs::write(
+            modules_dir.join("auth/README.md"),
+            "# auth\n\n用户登录与权限校验。\n\n## 接口\n\n```bash\n# 启动服务\nmake run\n```\n\n- /login\n",
+        )
+        .unwrap();
+        fs::write(modules_dir.join("billing/README.md"), "账单生成与对账。").unwrap();
+        fs::write(modules_dir.join("legacy/__init__.py"), "import os\n").unwrap();
+
+        let modules = vec!["auth".to_string(), "billing".to_string(), "legacy".to_string()];
+        write_modules_doc(staging.path(), "modules", &modules).unwrap();
+
+        let doc = fs::read_to_string(staging.path().join(MODULES_DOC_FILE_NAME)).unwrap();
+        assert!(doc.contains("## auth\n\n用户登录与权限校验。"));
+        assert!(doc.contains("#### 接口"));
+        // 代码块内的注释行不下沉
+        assert!(doc.contains("```bash\n# 启动服务\nmake run\n```"));
+        assert!(!doc.contains("\n# auth"));
+        assert!(doc.contains("## billing\n\n账单生成与对账。"));
+        assert!(doc.contains("## legacy\n\n(无说明)"));
+    }
+
+    #[test]
+    fn test_module_description_falls_back_to_docstring() {
+        let py = TempDir::new().unwrap();
+        fs::write(py.path().join("__init__.py"), "\"\"\"订单模块：下单与退款。\"\"\"\n").unwrap();
+        assert_eq!(module_description(py.path()).as_deref(), Some("订单模块：下单与退款。"));
+
+        let ts = TempDir::new().unwrap();
+        fs::write(ts.path().join("index.ts"), "/**\n * 报表模块\n */\nexport {}\n").unwrap();
+        assert_eq!(module_description(ts.path()).as_deref(), Some("报表模块"));
+    }
 }
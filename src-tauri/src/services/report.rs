@@ -0,0 +1,712 @@
+// ============================================================================
+// 项目报告：离线报告拼装 + 深度报告的压缩结果缓存
+// ✅ 只能做：汇总概览/签名/依赖数据，检测循环依赖、孤立文件与高耦合文件，格式化 Markdown；
+//           编排深度报告的"压缩 → 汇总"两步（LLM 调用由调用方注入）
+// ⛔ 禁止：依赖 tauri::*，直接操作数据库，发起网络请求
+// ============================================================================
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::services::analyzer::{
+    self, DependencyEdge, FileSignature, PriorFile, ProjectOverview, ScanConfig,
+};
+
+/// 报告中每个章节最多列出的条目数，避免大型项目报告过长
+const MAX_LISTED_ITEMS: usize = 30;
+
+/// 项目中没有代码文件时的提示信息
+pub const NO_CODE_FILES_MESSAGE: &str = "项目中未发现可分析的代码文件";
+
+/// 不计入代码文件的语言分类（文档、配置、数据文件）
+const NON_CODE_LANGUAGES: &[&str] = &[
+    "Markdown", "Text", "JSON", "YAML", "TOML", "XML", "Config", "Other",
+];
+
+/// 统计概览中的代码文件数（排除文档、配置和未识别文件）
+pub fn code_file_count(overview: &ProjectOverview) -> u32 {
+    overview
+        .languages
+        .iter()
+        .filter(|l| !NON_CODE_LANGUAGES.contains(&l.language.as_str()))
+        .map(|l| l.file_count)
+        .sum()
+}
+
+/// 生成报告前的前置校验：没有代码文件时返回提示信息，避免生成空报告或浪费 LLM 调用
+pub fn ensure_has_code_files(overview: &ProjectOverview) -> Result<(), String> {
+    if code_file_count(overview) == 0 {
+        return Err(NO_CODE_FILES_MESSAGE.to_string());
+    }
+    Ok(())
+}
+
+/// 检测依赖图中的循环依赖（Tarjan 强连通分量）
+///
+/// 返回每个循环涉及的文件列表（组内按路径排序，组间按首个文件排序）；
+/// 自依赖（文件引用自身）也视为一个循环。
+pub fn find_dependency_cycles(edges: &[DependencyEdge]) -> Vec<Vec<String>> {
+    // 构建邻接表（BTreeMap 保证遍历顺序确定）
+    let mut graph: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for edge in edges {
+        graph.entry(edge.source.as_str()).or_default().insert(edge.target.as_str());
+        graph.entry(edge.target.as_str()).or_default();
+    }
+
+    struct Tarjan<'a> {
+        graph: &'a BTreeMap<&'a str, BTreeSet<&'a str>>,
+        index: HashMap<&'a str, usize>,
+        low_link: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        next_index: usize,
+        components: Vec<Vec<String>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        /// 为节点分配访问序号并压栈
+        fn enter(&mut self, node: &'a str) {
+            self.index.insert(node, self.next_index);
+            self.low_link.insert(node, self.next_index);
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack.insert(node);
+        }
+
+        /// 从 `root` 出发深度优先遍历
+        ///
+        /// 以显式栈（节点, 未处理的后继）代替递归，长依赖链不会导致栈溢出。
+        fn visit(&mut self, root: &'a str) {
+            let graph = self.graph;
+            self.enter(root);
+            let mut call_stack = vec![(root, graph[root].iter())];
+            while let Some((node, successors)) = call_stack.last_mut() {
+                let node = *node;
+                if let Some(&next) = successors.next() {
+                    if !self.index.contains_key(next) {
+                        self.enter(next);
+                        call_stack.push((next, graph[next].iter()));
+                    } else if self.on_stack.contains(next) {
+                        let low = self.low_link[node].min(self.index[next]);
+                        self.low_link.insert(node, low);
+                    }
+                    continue;
+                }
+
+                // 后继均已处理：出栈并将 low_link 回传给父节点
+                call_stack.pop();
+                if let Some((parent, _)) = call_stack.last() {
+                    let low = self.low_link[*parent].min(self.low_link[node]);
+                    self.low_link.insert(*parent, low);
+                }
+                self.close(node);
+            }
+        }
+
+        /// 节点为强连通分量的根时弹出整个分量，构成循环的分量加入结果
+        fn close(&mut self, node: &'a str) {
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                let self_loop = self.graph[node].contains(node);
+                if component.len() > 1 || self_loop {
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        graph: &graph,
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for &node in graph.keys() {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+
+    let mut cycles = tarjan.components;
+    cycles.sort();
+    cycles
+}
+
+/// 查找孤立文件：有代码签名但既不引用其他文件、也不被其他文件引用（入口文件除外）
+pub fn find_orphan_files(
+    signatures: &[FileSignature],
+    edges: &[DependencyEdge],
+    entry_files: &[String],
+) -> Vec<String> {
+    let connected: BTreeSet<&str> = edges
+        .iter()
+        .flat_map(|e| [e.source.as_str(), e.target.as_str()])
+        .collect();
+
+    let mut orphans: Vec<String> = signatures
+        .iter()
+        .map(|s| s.relative_path.as_str())
+        .filter(|path| !connected.contains(path) && !entry_files.iter().any(|e| e == path))
+        .map(str::to_string)
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+/// LLM 报告 prompt 中最多逐条列出的依赖边数
+pub const MAX_PROMPT_DEP_EDGES: usize = 200;
+
+/// 高耦合文件（热点）列出的数量
+pub const DEPENDENCY_HOTSPOT_COUNT: usize = 10;
+
+/// 高耦合文件：出边（引用其他文件）与入边（被其他文件引用）数量
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DependencyHotspot {
+    /// 文件相对路径
+    pub file: String,
+    /// 出边数
+    pub outgoing: usize,
+    /// 入边数
+    pub incoming: usize,
+}
+
+/// 统计每个文件的（出边数, 入边数）
+fn count_degrees(edges: &[DependencyEdge]) -> BTreeMap<&str, (usize, usize)> {
+    let mut degrees: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for edge in edges {
+        degrees.entry(edge.source.as_str()).or_default().0 += 1;
+        degrees.entry(edge.target.as_str()).or_default().1 += 1;
+    }
+    degrees
+}
+
+/// 计算每个文件的入度与出度，返回 (相对路径, 入度, 出度)，顺序与 `files` 一致
+///
+/// 没有任何依赖边的文件入度、出度均为 0。
+pub fn file_degrees(files: &[String], edges: &[DependencyEdge]) -> Vec<(String, usize, usize)> {
+    let degrees = count_degrees(edges);
+    files
+        .iter()
+        .map(|file| {
+            let (outgoing, incoming) = degrees.get(file.as_str()).copied().unwrap_or_default();
+            (file.clone(), incoming, outgoing)
+        })
+        .collect()
+}
+
+/// 按出入边总数降序找出耦合度最高的 `limit` 个文件（总数相同按路径排序）
+pub fn dependency_hotspots(edges: &[DependencyEdge], limit: usize) -> Vec<DependencyHotspot> {
+    let degrees = count_degrees(edges);
+    let mut hotspots: Vec<DependencyHotspot> = degrees
+        .into_iter()
+        .map(|(file, (outgoing, incoming))| DependencyHotspot {
+            file: file.to_string(),
+            outgoing,
+            incoming,
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        (b.outgoing + b.incoming)
+            .cmp(&(a.outgoing + a.incoming))
+            .then_with(|| a.file.cmp(&b.file))
+    });
+    hotspots.truncate(limit);
+    hotspots
+}
+
+/// 格式化 LLM prompt 中的依赖关系文本
+///
+/// 最多逐条列出 `max_edges` 条边；超出时注明截断数量，并附上高耦合文件列表，
+/// 避免被截掉的边中耦合最严重的文件从报告中消失。
+pub fn format_dependency_text(edges: &[DependencyEdge], max_edges: usize) -> String {
+    let mut text = edges
+        .iter()
+        .take(max_edges)
+        .map(|e| format!("  {} -> {}", e.source, e.target))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if edges.len() <= max_edges {
+        return text;
+    }
+
+    let _ = write!(
+        text,
+        "\n  ...（共 {} 条依赖，以上仅列出前 {} 条）\n\n高耦合文件（按出入边总数）：",
+        edges.len(),
+        max_edges
+    );
+    for hotspot in dependency_hotspots(edges, DEPENDENCY_HOTSPOT_COUNT) {
+        let _ = write!(
+            text,
+            "\n  - {}：引用 {} 个文件，被 {} 个文件引用",
+            hotspot.file, hotspot.outgoing, hotspot.incoming
+        );
+    }
+    text
+}
+
+/// 转义为 DOT 双引号字符串（反斜杠、双引号与换行）
+fn dot_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "");
+    format!("\"{}\"", escaped)
+}
+
+/// 将依赖图导出为 Graphviz DOT 文本
+///
+/// 节点按顶层目录分组为 `cluster_N` 子图（标签为目录名），根目录下的文件不分组；
+/// 节点 ID 为转义后的相对路径，每条依赖边输出一行。输出顺序确定，便于比对。
+pub fn to_dot(nodes: &[String], edges: &[DependencyEdge]) -> String {
+    let mut groups: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut root_files: BTreeSet<&str> = BTreeSet::new();
+    for node in nodes.iter().map(String::as_str) {
+        match node.split_once('/') {
+            Some((top, _)) => {
+                groups.entry(top).or_default().insert(node);
+            }
+            None => {
+                root_files.insert(node);
+            }
+        }
+    }
+
+    let mut dot = String::from("digraph dependencies {\n");
+    dot.push_str("  rankdir=LR;\n  node [shape=box, fontsize=10];\n");
+    for (index, (dir, files)) in groups.iter().enumerate() {
+        let _ = writeln!(dot, "  subgraph cluster_{} {{", index);
+        let _ = writeln!(dot, "    label={};", dot_quote(dir));
+        for file in files {
+            let _ = writeln!(dot, "    {};", dot_quote(file));
+        }
+        dot.push_str("  }\n");
+    }
+    for file in &root_files {
+        let _ = writeln!(dot, "  {};", dot_quote(file));
+    }
+    for edge in edges {
+        let _ = writeln!(
+            dot,
+            "  {} -> {};",
+            dot_quote(&edge.source),
+            dot_quote(&edge.target)
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// 基于静态分析结果拼装离线 Markdown 报告（结果确定，不依赖网络）
+pub fn build_offline_report(
+    overview: &ProjectOverview,
+    signatures: &[FileSignature],
+    edges: &[DependencyEdge],
+) -> String {
+    let cycles = find_dependency_cycles(edges);
+    let orphans = find_orphan_files(signatures, edges, &overview.entry_files);
+    let or_none = |items: &[String]| {
+        if items.is_empty() {
+            "无".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+
+    // 写入 String 不会失败，忽略 fmt::Result
+    let mut md = String::new();
+    let _ = writeln!(md, "# 项目分析报告（离线模式）\n");
+    let _ = writeln!(md, "> 本报告基于静态分析自动生成，未调用 LLM。\n");
+
+    let _ = writeln!(md, "## 1. 项目概述\n");
+    let _ = writeln!(md, "- 文件数：{}", overview.total_files);
+    let _ = writeln!(md, "- 代码行数：{}", overview.total_lines);
+    let _ = writeln!(md, "- 目录数：{}", overview.total_dirs);
+    let _ = writeln!(md, "- 技术栈：{}", or_none(&overview.tech_stack));
+    let _ = writeln!(md, "- 入口文件：{}\n", or_none(&overview.entry_files));
+
+    let _ = writeln!(md, "## 2. 语言分布\n");
+    if overview.languages.is_empty() {
+        let _ = writeln!(md, "无");
+    } else {
+        let _ = writeln!(md, "| 语言 | 文件数 | 行数 |");
+        let _ = writeln!(md, "| --- | --- | --- |");
+        for lang in &overview.languages {
+            let _ = writeln!(md, "| {} | {} | {} |", lang.language, lang.file_count, lang.line_count);
+        }
+    }
+    md.push('\n');
+
+    let _ = writeln!(md, "## 3. 核心文件（按签名数量）\n");
+    let mut ranked: Vec<&FileSignature> = signatures.iter().collect();
+    ranked.sort_by(|a, b| {
+        b.signatures
+            .len()
+            .cmp(&a.signatures.len())
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    if ranked.is_empty() {
+        let _ = writeln!(md, "未提取到代码签名");
+    }
+    for sig in ranked.iter().take(MAX_LISTED_ITEMS) {
+        let _ = writeln!(
+            md,
+            "- `{}`（{}，{} 个声明）：{}",
+            sig.relative_path,
+            sig.language,
+            sig.signatures.len(),
+            sig.signatures.join(", ")
+        );
+    }
+    md.push('\n');
+
+    let _ = writeln!(md, "## 4. 依赖关系\n");
+    let _ = writeln!(md, "- 依赖边数量：{}", edges.len());
+    let mut in_degree: BTreeMap<&str, usize> = BTreeMap::new();
+    for edge in edges {
+        *in_degree.entry(edge.target.as_str()).or_default() += 1;
+    }
+    let mut most_depended: Vec<(&str, usize)> = in_degree.into_iter().collect();
+    most_depended.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    for (path, count) in most_depended.iter().take(10) {
+        let _ = writeln!(md, "- `{}` 被 {} 个文件引用", path, count);
+    }
+    md.push('\n');
+
+    let _ = writeln!(md, "### 循环依赖（{} 组）\n", cycles.len());
+    if cycles.is_empty() {
+        let _ = writeln!(md, "未发现循环依赖");
+    }
+    for cycle in cycles.iter().take(MAX_LISTED_ITEMS) {
+        let _ = writeln!(md, "- {}", cycle.join(" ⇄ "));
+    }
+    md.push('\n');
+
+    let _ = writeln!(md, "### 孤立文件（{} 个）\n", orphans.len());
+    if orphans.is_empty() {
+        let _ = writeln!(md, "未发现孤立文件");
+    }
+    for orphan in orphans.iter().take(MAX_LISTED_ITEMS) {
+        let _ = writeln!(md, "- `{}`", orphan);
+    }
+
+    md
+}
+
+/// 收集项目静态分析数据并生成离线报告
+///
+/// # 参数
+/// - `project_path`: 项目根目录路径
+/// - `prior`: 文件索引中已记录的哈希（路径相对 `project_path`），未变化的文件不再重新计算
+pub fn generate_offline_report(
+    project_path: &Path,
+    prior: &HashMap<String, PriorFile>,
+) -> Result<String, String> {
+    let overview = analyzer::analyze_project_overview(project_path)?;
+    ensure_has_code_files(&overview)?;
+    let config = ScanConfig::default();
+    let signatures =
+        analyzer::extract_project_signatures_with_config(project_path, &[], &config, prior)?;
+    let entries = analyzer::scan_project_files_with_config(project_path, &config, prior)?;
+    let file_paths: Vec<String> = entries.iter().map(|e| e.relative_path.clone()).collect();
+    let edges = analyzer::extract_dependencies(project_path, &file_paths)?;
+
+    Ok(build_offline_report(&overview, &signatures, &edges))
+}
+
+// ============================================================================
+// 深度报告：压缩结果缓存
+// ============================================================================
+
+/// 深度模式下签名文本超过该长度（字节）时先压缩再汇总
+pub const DEEP_COMPRESS_THRESHOLD: usize = 30000;
+
+/// 深度报告的中间压缩结果缓存（签名文本 SHA256 → 压缩摘要）
+///
+/// 注册为 Tauri managed state，应用重启即清空。汇总失败后以 `resume` 重试时
+/// 直接复用压缩结果，避免重复执行耗时、耗 token 的压缩调用。
+#[derive(Debug, Default)]
+pub struct CompressionCache {
+    entries: Mutex<HashMap<String, String>>,
+}
+
+impl CompressionCache {
+    fn get(&self, key: &str) -> Option<String> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    fn insert(&self, key: String, compressed: String) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, compressed);
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(key);
+    }
+}
+
+/// 深度报告两步生成：压缩签名 → 基于压缩摘要汇总报告
+///
+/// 压缩成功后即写入缓存；`resume` 为 true 且缓存中有相同签名内容的压缩结果时跳过压缩。
+/// 汇总成功后清除该缓存项，失败时保留以便重试。
+///
+/// # 参数
+/// - `sig_text`: 格式化后的签名文本（缓存键为其 SHA256）
+/// - `compress`: 压缩调用，输入签名文本，返回压缩摘要
+/// - `synthesize`: 汇总调用，输入压缩摘要，返回最终报告
+pub async fn deep_report_with_cache<C, CFut, S, SFut>(
+    sig_text: &str,
+    cache: &CompressionCache,
+    resume: bool,
+    compress: C,
+    synthesize: S,
+) -> Result<String, String>
+where
+    C: FnOnce(&str) -> CFut,
+    CFut: Future<Output = Result<String, String>>,
+    S: FnOnce(String) -> SFut,
+    SFut: Future<Output = Result<String, String>>,
+{
+    let key = format!("{:x}", Sha256::digest(sig_text.as_bytes()));
+
+    let cached = if resume { cache.get(&key) } else { None };
+    let compressed = match cached {
+        Some(compressed) => {
+            log::info!("深度报告：复用已缓存的签名压缩结果");
+            compressed
+        }
+        None => {
+            let compressed = compress(sig_text).await?;
+            cache.insert(key.clone(), compressed.clone());
+            compressed
+        }
+    };
+
+    let report = synthesize(compressed).await?;
+    cache.remove(&key);
+    Ok(report)
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn edge(source: &str, target: &str) -> DependencyEdge {
+        DependencyEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hub_file_survives_edge_truncation() {
+        // 前 5 条普通边之后才是枢纽文件的 50 条出边，截断后逐条列表中看不到它
+        let mut edges: Vec<DependencyEdge> =
+            (0..5).map(|i| edge(&format!("a{}.py", i), &format!("b{}.py", i))).collect();
+        edges.extend((0..50).map(|i| edge("core/hub.py", &format!("mod{}.py", i))));
+
+        let hotspots = dependency_hotspots(&edges, DEPENDENCY_HOTSPOT_COUNT);
+        assert_eq!(
+            hotspots[0],
+            DependencyHotspot {
+                file: "core/hub.py".to_string(),
+                outgoing: 50,
+                incoming: 0,
+            }
+        );
+
+        let text = format_dependency_text(&edges, 5);
+        assert_eq!(text.lines().filter(|l| l.contains("->")).count(), 5);
+        assert!(text.contains("共 55 条依赖"));
+        assert!(text.contains("core/hub.py：引用 50 个文件"));
+
+        // 未超过上限时不附加热点
+        assert!(!format_dependency_text(&edges, 100).contains("高耦合文件"));
+    }
+
+    #[test]
+    fn test_to_dot_groups_by_top_dir_and_escapes_paths() {
+        let nodes: Vec<String> = ["main.py", "app/api.py", "app/db.py", "lib/we\"ird\\name.py"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let edges = vec![
+            edge("main.py", "app/api.py"),
+            edge("app/api.py", "app/db.py"),
+            edge("app/api.py", "lib/we\"ird\\name.py"),
+        ];
+
+        let dot = to_dot(&nodes, &edges);
+        assert!(dot.starts_with("digraph dependencies {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.lines().filter(|l| l.contains(" -> ")).count(), 3);
+        assert!(dot.contains("  subgraph cluster_0 {\n    label=\"app\";\n    \"app/api.py\";\n"));
+        assert!(dot.contains("  subgraph cluster_1 {\n    label=\"lib\";\n"));
+        assert!(dot.contains("  \"main.py\";\n"));
+        // 双引号与反斜杠均被转义，每个节点 ID 仍是一个完整的带引号字符串
+        assert!(dot.contains(r#""app/api.py" -> "lib/we\"ird\\name.py";"#));
+        assert_eq!(dot.matches("{").count(), dot.matches("}").count());
+
+        assert_eq!(to_dot(&[], &[]).lines().count(), 4);
+    }
+
+    #[test]
+    fn test_offline_report_contains_overview() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("requirements.txt"), "fastapi\nuvicorn\n").unwrap();
+        fs::write(tmp.path().join("main.py"), "from app import create\n\ndef main():\n    pass\n").unwrap();
+        fs::write(tmp.path().join("app.py"), "def create():\n    pass\n").unwrap();
+
+        let report = generate_offline_report(tmp.path(), &HashMap::new()).unwrap();
+
+        assert!(report.starts_with("# 项目分析报告"));
+        assert!(report.contains("- 文件数：3"));
+        assert!(report.contains("FastAPI"));
+        assert!(report.contains("## 4. 依赖关系"));
+    }
+
+    #[test]
+    fn test_empty_project_yields_guard_message() {
+        let tmp = TempDir::new().unwrap();
+        let overview = analyzer::analyze_project_overview(tmp.path()).unwrap();
+        assert_eq!(ensure_has_code_files(&overview).unwrap_err(), NO_CODE_FILES_MESSAGE);
+
+        // 仅包含文档/配置文件同样视为无代码
+        fs::write(tmp.path().join("README.md"), "# docs").unwrap();
+        fs::write(tmp.path().join("config.yaml"), "a: 1").unwrap();
+        let overview = analyzer::analyze_project_overview(tmp.path()).unwrap();
+        assert_eq!(code_file_count(&overview), 0);
+        assert_eq!(
+            generate_offline_report(tmp.path(), &HashMap::new()).unwrap_err(),
+            NO_CODE_FILES_MESSAGE
+        );
+    }
+
+    #[test]
+    fn test_find_dependency_cycles() {
+        let edges = vec![
+            edge("a.py", "b.py"),
+            edge("b.py", "c.py"),
+            edge("c.py", "a.py"),
+            edge("c.py", "d.py"),
+            edge("e.py", "e.py"),
+        ];
+
+        let cycles = find_dependency_cycles(&edges);
+
+        assert_eq!(
+            cycles,
+            vec![
+                vec!["a.py".to_string(), "b.py".to_string(), "c.py".to_string()],
+                vec!["e.py".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_dependency_cycles_handles_long_chains() {
+        // 20 万个文件串成一条链并首尾相连：递归实现会在默认测试线程栈上溢出
+        let n = 200_000;
+        let name = |i: usize| format!("f{:06}.py", i);
+        let mut edges: Vec<DependencyEdge> =
+            (0..n - 1).map(|i| edge(&name(i), &name(i + 1))).collect();
+        edges.push(edge(&name(n - 1), &name(0)));
+        edges.push(edge(&name(n - 1), "tail.py"));
+
+        let cycles = find_dependency_cycles(&edges);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(cycles[0].len(), n);
+    }
+
+    #[test]
+    fn test_find_orphan_files_skips_entry_and_connected() {
+        let sig = |path: &str| FileSignature {
+            relative_path: path.to_string(),
+            language: "Python".to_string(),
+            signatures: vec!["def f()".to_string()],
+        };
+        let signatures = vec![sig("main.py"), sig("used.py"), sig("lonely.py")];
+        let edges = vec![edge("other.py", "used.py")];
+
+        let orphans = find_orphan_files(&signatures, &edges, &["main.py".to_string()]);
+
+        assert_eq!(orphans, vec!["lonely.py".to_string()]);
+    }
+
+    #[test]
+    fn test_deep_report_resume_reuses_cached_compression() {
+        let cache = CompressionCache::default();
+        let compress_calls = std::cell::Cell::new(0);
+        let compress = |sig: &str| {
+            compress_calls.set(compress_calls.get() + 1);
+            let compressed = format!("摘要（{} 字节）", sig.len());
+            async move { Ok::<_, String>(compressed) }
+        };
+
+        // 第一次：压缩成功、汇总失败
+        let result = futures::executor::block_on(deep_report_with_cache(
+            "class A: ...",
+            &cache,
+            true,
+            compress,
+            |_| async { Err::<String, _>("连接超时".to_string()) },
+        ));
+        assert_eq!(result.unwrap_err(), "连接超时");
+
+        // 重试：跳过压缩，直接用缓存结果汇总
+        let report = futures::executor::block_on(deep_report_with_cache(
+            "class A: ...",
+            &cache,
+            true,
+            compress,
+            |compressed| async move { Ok(format!("# 报告\n{}", compressed)) },
+        ))
+        .unwrap();
+        assert_eq!(report, "# 报告\n摘要（12 字节）");
+        assert_eq!(compress_calls.get(), 1);
+
+        // 汇总成功后缓存清除，再次生成会重新压缩
+        futures::executor::block_on(deep_report_with_cache(
+            "class A: ...",
+            &cache,
+            true,
+            compress,
+            |compressed| async move { Ok(compressed) },
+        ))
+        .unwrap();
+        assert_eq!(compress_calls.get(), 2);
+    }
+}
@@ -24,7 +24,20 @@ pub trait ScanStrategy {
     /// 扫描项目模块，返回模块列表
     /// - `project_path`: 项目根目录
     /// - `modules_dir`: 用户自定义的模块目录（相对路径），为空则使用默认值
-    fn scan(&self, project_path: &Path, modules_dir: &str) -> AppResult<Vec<ModuleInfo>>;
+    /// - `module_depth`: 模块发现深度（见 `scanner::scan_modules_dir_with_depth`）
+    fn scan(
+        &self,
+        project_path: &Path,
+        modules_dir: &str,
+        module_depth: usize,
+    ) -> AppResult<Vec<ModuleInfo>>;
+
+    /// 按目录布局推测模块目录（相对路径），无法判断时返回 None
+    ///
+    /// 用于新建项目时预填 `modules_dir`，默认不做推测。
+    fn detect_modules_dir(&self, _project_path: &Path) -> Option<String> {
+        None
+    }
 }
 
 // ============================================================================
@@ -34,11 +47,16 @@ pub trait ScanStrategy {
 /// FastAPI 扫描策略：扫描 modules/ 子目录
 ///
 /// 复用 `services::scanner::scan_modules_dir` 逻辑，扫描项目根目录下的 modules/ 目录，
-/// 返回一级子目录作为模块列表，自动过滤 __pycache__、.git 等忽略条目。
+/// 返回一级子目录（按 `module_depth` 展开分组目录）作为模块列表，自动过滤 __pycache__、.git 等忽略条目。
 pub struct FastApiScanner;
 
 impl ScanStrategy for FastApiScanner {
-    fn scan(&self, project_path: &Path, modules_dir: &str) -> AppResult<Vec<ModuleInfo>> {
+    fn scan(
+        &self,
+        project_path: &Path,
+        modules_dir: &str,
+        module_depth: usize,
+    ) -> AppResult<Vec<ModuleInfo>> {
         // 用户自定义目录优先，为空则使用默认值 "modules"
         let dir_name = if modules_dir.is_empty() { "modules" } else { modules_dir };
         let target_dir = project_path.join(dir_name);
@@ -47,10 +65,76 @@ impl ScanStrategy for FastApiScanner {
                 format!("fastapi 项目应包含 {} 目录", dir_name),
             ));
         }
-        crate::services::scanner::scan_modules_dir(&target_dir)
+        crate::services::scanner::scan_modules_dir_with_depth(&target_dir, module_depth)
+    }
+
+    /// 优先使用非空的 `modules/`；否则在两层目录内寻找包含路由的同级包最多的目录
+    /// （子包中含 `routes.py`/`router.py` 或 `routes/`/`router/`），至少两个才视为模块目录
+    fn detect_modules_dir(&self, project_path: &Path) -> Option<String> {
+        if !feature_subdirs(&project_path.join("modules")).is_empty() {
+            return Some("modules".to_string());
+        }
+
+        let mut candidates: Vec<String> = Vec::new();
+        for top in feature_subdirs(project_path) {
+            candidates.extend(
+                feature_subdirs(&project_path.join(&top))
+                    .into_iter()
+                    .map(|child| format!("{}/{}", top, child)),
+            );
+            candidates.push(top);
+        }
+
+        candidates
+            .into_iter()
+            .map(|dir| {
+                let routed = feature_subdirs(&project_path.join(&dir))
+                    .iter()
+                    .filter(|pkg| has_router(&project_path.join(&dir).join(pkg)))
+                    .count();
+                (dir, routed)
+            })
+            .filter(|(_, routed)| *routed >= 2)
+            // 路由包最多者优先；相同时取层级更浅、名称更靠前的目录
+            .min_by(|(a, a_routed), (b, b_routed)| {
+                b_routed
+                    .cmp(a_routed)
+                    .then_with(|| a.matches('/').count().cmp(&b.matches('/').count()))
+                    .then_with(|| a.cmp(b))
+            })
+            .map(|(dir, _)| dir)
     }
 }
 
+/// 目录下的功能子目录名（排序，跳过忽略条目、隐藏目录与依赖/构建目录）；目录不存在时为空
+fn feature_subdirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| {
+            !name.starts_with('.')
+                && !crate::services::IGNORED_ENTRIES.contains(&name.as_str())
+                && !crate::services::DEFAULT_EXCLUDES.contains(&name.as_str())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Python 包是否声明了路由（`routes.py`/`router.py` 或 `routes/`/`router/` 子包）
+fn has_router(package: &Path) -> bool {
+    ["routes.py", "router.py"]
+        .iter()
+        .any(|file| package.join(file).is_file())
+        || ["routes", "router"]
+            .iter()
+            .any(|dir| package.join(dir).is_dir())
+}
+
 // ============================================================================
 // Vue3 扫描策略（复用 scan_modules_dir，消除重复代码）
 // ============================================================================
@@ -59,7 +143,12 @@ impl ScanStrategy for FastApiScanner {
 pub struct Vue3Scanner;
 
 impl ScanStrategy for Vue3Scanner {
-    fn scan(&self, project_path: &Path, modules_dir: &str) -> AppResult<Vec<ModuleInfo>> {
+    fn scan(
+        &self,
+        project_path: &Path,
+        modules_dir: &str,
+        module_depth: usize,
+    ) -> AppResult<Vec<ModuleInfo>> {
         // 用户自定义目录优先，为空则使用默认值 "src/views"
         let dir_name = if modules_dir.is_empty() { "src/views" } else { modules_dir };
         let target_dir = project_path.join(dir_name);
@@ -68,7 +157,15 @@ impl ScanStrategy for Vue3Scanner {
                 format!("vue3 项目应包含 {} 目录", dir_name),
             ));
         }
-        crate::services::scanner::scan_modules_dir(&target_dir)
+        crate::services::scanner::scan_modules_dir_with_depth(&target_dir, module_depth)
+    }
+
+    /// 依次检查 `src/views`、`src/pages`，取第一个按功能划分子目录的目录
+    fn detect_modules_dir(&self, project_path: &Path) -> Option<String> {
+        ["src/views", "src/pages"]
+            .into_iter()
+            .find(|dir| !feature_subdirs(&project_path.join(dir)).is_empty())
+            .map(str::to_string)
     }
 }
 
@@ -81,6 +178,9 @@ pub fn get_scanner(tech_stack: &str) -> AppResult<Box<dyn ScanStrategy>> {
     match tech_stack {
         "fastapi" => Ok(Box::new(FastApiScanner)),
         "vue3" => Ok(Box::new(Vue3Scanner)),
+        // 无专用扫描逻辑的内置技术栈按其默认模块目录扫描
+        "angular" => Ok(get_generic_scanner("src/app/modules".to_string())),
+        "nuxt" => Ok(get_generic_scanner("pages".to_string())),
         _ => Err(AppError::UnsupportedTechStack(tech_stack.to_string())),
     }
 }
@@ -102,7 +202,12 @@ pub struct GenericScanner {
 }
 
 impl ScanStrategy for GenericScanner {
-    fn scan(&self, project_path: &Path, modules_dir: &str) -> AppResult<Vec<ModuleInfo>> {
+    fn scan(
+        &self,
+        project_path: &Path,
+        modules_dir: &str,
+        module_depth: usize,
+    ) -> AppResult<Vec<ModuleInfo>> {
         // 用户自定义目录优先，为空则使用模板默认值
         let dir_name = if modules_dir.is_empty() {
             &self.modules_dir
@@ -115,7 +220,7 @@ impl ScanStrategy for GenericScanner {
                 format!("项目应包含 {} 目录", dir_name),
             ));
         }
-        crate::services::scanner::scan_modules_dir(&target_dir)
+        crate::services::scanner::scan_modules_dir_with_depth(&target_dir, module_depth)
     }
 }
 
@@ -152,7 +257,7 @@ mod tests {
 
         let scanner = FastApiScanner;
         // 传空字符串使用默认值 "modules"
-        let result = scanner.scan(dir.path(), "").unwrap();
+        let result = scanner.scan(dir.path(), "", 1).unwrap();
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].name, "auth");
         assert_eq!(result[1].name, "orders");
@@ -165,7 +270,7 @@ mod tests {
         create_fastapi_project(&dir, &["auth", "__pycache__", ".git", ".DS_Store"]);
 
         let scanner = FastApiScanner;
-        let result = scanner.scan(dir.path(), "").unwrap();
+        let result = scanner.scan(dir.path(), "", 1).unwrap();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].name, "auth");
     }
@@ -174,7 +279,7 @@ mod tests {
     fn test_fastapi_scanner_missing_modules_dir() {
         let dir = TempDir::new().unwrap();
         let scanner = FastApiScanner;
-        let result = scanner.scan(dir.path(), "");
+        let result = scanner.scan(dir.path(), "", 1);
         assert!(result.is_err());
     }
 
@@ -187,7 +292,7 @@ mod tests {
         std::fs::create_dir_all(api_dir.join("orders")).unwrap();
 
         let scanner = FastApiScanner;
-        let result = scanner.scan(dir.path(), "api").unwrap();
+        let result = scanner.scan(dir.path(), "api", 1).unwrap();
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].name, "orders");
         assert_eq!(result[1].name, "users");
@@ -199,7 +304,7 @@ mod tests {
         create_vue3_project(&dir, &["dashboard", "login", "settings"]);
 
         let scanner = Vue3Scanner;
-        let result = scanner.scan(dir.path(), "").unwrap();
+        let result = scanner.scan(dir.path(), "", 1).unwrap();
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].name, "dashboard");
         assert_eq!(result[1].name, "login");
@@ -210,7 +315,7 @@ mod tests {
     fn test_vue3_scanner_missing_views_dir() {
         let dir = TempDir::new().unwrap();
         let scanner = Vue3Scanner;
-        let result = scanner.scan(dir.path(), "");
+        let result = scanner.scan(dir.path(), "", 1);
         assert!(result.is_err());
     }
 
@@ -223,12 +328,55 @@ mod tests {
         std::fs::create_dir_all(pages_dir.join("about")).unwrap();
 
         let scanner = Vue3Scanner;
-        let result = scanner.scan(dir.path(), "pages").unwrap();
+        let result = scanner.scan(dir.path(), "pages", 1).unwrap();
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].name, "about");
         assert_eq!(result[1].name, "home");
     }
 
+    #[test]
+    fn test_detect_modules_dir_fastapi_standard_layout() {
+        let dir = TempDir::new().unwrap();
+        create_fastapi_project(&dir, &["auth", "users"]);
+        std::fs::create_dir_all(dir.path().join("core")).unwrap();
+
+        assert_eq!(FastApiScanner.detect_modules_dir(dir.path()), Some("modules".to_string()));
+    }
+
+    #[test]
+    fn test_detect_modules_dir_fastapi_router_packages() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        // app/api 下的同级包各自带路由；app/core 只有一个子包，不应被选中
+        for pkg in ["users", "orders"] {
+            std::fs::create_dir_all(root.join("app/api").join(pkg)).unwrap();
+        }
+        std::fs::write(root.join("app/api/users/routes.py"), "").unwrap();
+        std::fs::create_dir_all(root.join("app/api/orders/router")).unwrap();
+        std::fs::create_dir_all(root.join("app/core/config")).unwrap();
+        std::fs::write(root.join("app/core/config/router.py"), "").unwrap();
+
+        assert_eq!(FastApiScanner.detect_modules_dir(root), Some("app/api".to_string()));
+
+        let empty = TempDir::new().unwrap();
+        assert_eq!(FastApiScanner.detect_modules_dir(empty.path()), None);
+    }
+
+    #[test]
+    fn test_detect_modules_dir_vue3_views_layout() {
+        let dir = TempDir::new().unwrap();
+        create_vue3_project(&dir, &["dashboard", "login"]);
+        std::fs::create_dir_all(dir.path().join("src/components")).unwrap();
+
+        assert_eq!(Vue3Scanner.detect_modules_dir(dir.path()), Some("src/views".to_string()));
+
+        // 只有 src/pages 按功能划分时回退到 src/pages
+        let pages = TempDir::new().unwrap();
+        std::fs::create_dir_all(pages.path().join("src/views")).unwrap();
+        std::fs::create_dir_all(pages.path().join("src/pages/home")).unwrap();
+        assert_eq!(Vue3Scanner.detect_modules_dir(pages.path()), Some("src/pages".to_string()));
+    }
+
     #[test]
     fn test_get_scanner_fastapi() {
         let scanner = get_scanner("fastapi");
@@ -3,9 +3,10 @@
 // 纯 Rust 函数，不依赖 tauri::*，方便单元测试
 // ============================================================================
 
-use crate::models::dtos::ModuleInfo;
+use crate::models::dtos::{ModuleDiff, ModuleInfo, ModuleSelectionState};
 use crate::services::{CORE_FILES, IGNORED_ENTRIES};
 use crate::utils::error::{AppError, AppResult};
+use crate::utils::path::normalize_rel_path;
 
 /// 验证项目文件夹结构并扫描核心文件
 ///
@@ -47,12 +48,76 @@ pub fn validate_project(path: &std::path::Path) -> AppResult<Vec<String>> {
     Ok(core_files)
 }
 
+/// 模块发现深度设置项（`1` 或 `2`，未设置时为 1），模块列表与构建时的依赖分析共用
+pub const MODULE_DEPTH_KEY: &str = "module_depth";
+
+/// `module_depth` 的可选值
+pub const MODULE_DEPTH_VALUES: &[&str] = &["1", "2"];
+
+/// 模块发现深度上限：构建时的限定模块名最多两段（`admin/users`）
+pub const MAX_MODULE_DEPTH: usize = 2;
+
+/// 解析 `module_depth` 设置值，未设置或无法解析时为 1，超过上限时取上限
+pub fn module_depth_from_setting(raw: Option<&str>) -> usize {
+    raw.and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, MAX_MODULE_DEPTH)
+}
+
 /// 扫描 modules 目录下的一级子目录，过滤忽略条目
 pub fn scan_modules_dir(modules_path: &std::path::Path) -> AppResult<Vec<ModuleInfo>> {
+    scan_modules_dir_with_depth(modules_path, 1)
+}
+
+/// 按深度扫描模块目录，支持分组组织的嵌套模块
+///
+/// `module_depth` 为 1 时与 `scan_modules_dir` 相同，只取一级子目录；大于 1 时，
+/// 只含子目录的分组目录（如 `modules/admin/`）继续向下展开，其下模块以限定名
+/// （`admin/users`）返回，最多展开到 `module_depth` 层。含文件的目录本身即为模块，不再展开。
+/// `module_depth` 为 0 时按 1 处理，超过 `MAX_MODULE_DEPTH` 时按上限处理。
+pub fn scan_modules_dir_with_depth(
+    modules_path: &std::path::Path,
+    module_depth: usize,
+) -> AppResult<Vec<ModuleInfo>> {
+    let module_depth = module_depth.clamp(1, MAX_MODULE_DEPTH);
+    let mut modules = scan_top_level_modules(modules_path)?;
+    if module_depth > 1 {
+        modules = modules
+            .into_iter()
+            .flat_map(|module| expand_module_group(module, module_depth - 1))
+            .collect();
+    }
+
+    modules.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(modules)
+}
+
+/// 将分组目录展开为其下的限定名模块；非分组目录或已无剩余深度时原样返回
+fn expand_module_group(module: ModuleInfo, remaining_depth: usize) -> Vec<ModuleInfo> {
+    let dir = std::path::PathBuf::from(&module.path);
+    let children = match list_subdirs(&dir) {
+        Some(children) if remaining_depth > 0 => children,
+        _ => return vec![module],
+    };
+    children
+        .into_iter()
+        .flat_map(|child| {
+            let nested = ModuleInfo {
+                name: format!("{}/{}", module.name, child),
+                path: dir.join(&child).to_string_lossy().to_string(),
+            };
+            expand_module_group(nested, remaining_depth - 1)
+        })
+        .collect()
+}
+
+/// 读取 modules 目录下的一级子目录（未排序）
+fn scan_top_level_modules(modules_path: &std::path::Path) -> AppResult<Vec<ModuleInfo>> {
     let entries = std::fs::read_dir(modules_path)
         .map_err(|_| AppError::ScanError("无法读取 modules/ 目录".to_string()))?;
 
-    let mut modules: Vec<ModuleInfo> = entries
+    let modules: Vec<ModuleInfo> = entries
         .filter_map(|entry| entry.ok())
         .filter(|entry| entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
         .filter(|entry| {
@@ -66,10 +131,117 @@ pub fn scan_modules_dir(modules_path: &std::path::Path) -> AppResult<Vec<ModuleI
         })
         .collect();
 
-    modules.sort_by(|a, b| a.name.cmp(&b.name));
-
     Ok(modules)
 }
+
+/// 检测嵌套模块目录中的同名模块
+///
+/// 只包含子目录、不含文件的一级目录视为分组目录（如 `modules/admin/`），
+/// 其下的子目录按限定名（`admin/users`）计为嵌套模块。若嵌套模块与顶层模块
+/// 或其他分组下的模块同名，按第一级目录取名时会互相混淆，需使用限定名选择。
+///
+/// # 返回
+/// 每个歧义名称一条中文警告，按名称排序；目录不可读时返回空列表
+pub fn find_ambiguous_module_names(modules_path: &std::path::Path) -> Vec<String> {
+    let mut by_name: std::collections::BTreeMap<String, Vec<String>> =
+        std::collections::BTreeMap::new();
+
+    let Ok(modules) = scan_modules_dir(modules_path) else {
+        return Vec::new();
+    };
+    for module in modules {
+        let children = list_subdirs(std::path::Path::new(&module.path));
+        by_name
+            .entry(module.name.clone())
+            .or_default()
+            .push(module.name.clone());
+        if let Some(children) = children {
+            for child in children {
+                by_name
+                    .entry(child.clone())
+                    .or_default()
+                    .push(format!("{}/{}", module.name, child));
+            }
+        }
+    }
+
+    by_name
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(name, mut paths)| {
+            paths.sort();
+            format!(
+                "模块名 {} 存在歧义：{}（请使用限定名选择）",
+                name,
+                paths.join("、")
+            )
+        })
+        .collect()
+}
+
+/// 列出分组目录下的子目录名；目录中包含文件（即本身是模块）时返回 None
+fn list_subdirs(dir: &std::path::Path) -> Option<Vec<String>> {
+    let mut children = Vec::new();
+    for entry in std::fs::read_dir(dir).ok()?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if IGNORED_ENTRIES.contains(&name.as_str()) {
+            continue;
+        }
+        if !entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+            return None;
+        }
+        children.push(name);
+    }
+    (!children.is_empty()).then_some(children)
+}
+
+/// 比较两个模块目录的模块集合（如模板项目与其派生项目），用于发现模块漂移
+///
+/// # 参数
+/// - `modules_path_a` / `modules_path_b`: 两个项目模块目录的完整路径
+pub fn compare_modules_dirs(
+    modules_path_a: &std::path::Path,
+    modules_path_b: &std::path::Path,
+) -> AppResult<ModuleDiff> {
+    let names = |path: &std::path::Path| -> AppResult<std::collections::BTreeSet<String>> {
+        Ok(scan_modules_dir(path)?
+            .into_iter()
+            .map(|m| m.name)
+            .collect())
+    };
+    let a = names(modules_path_a)?;
+    let b = names(modules_path_b)?;
+    Ok(ModuleDiff {
+        only_in_a: a.difference(&b).cloned().collect(),
+        only_in_b: b.difference(&a).cloned().collect(),
+        common: a.intersection(&b).cloned().collect(),
+    })
+}
+
+/// 扫描模块目录并标记上次构建的选中状态
+///
+/// # 参数
+/// - `modules_path`: 模块目录的完整路径
+/// - `last_selected`: 上次构建选中的模块名列表（无历史构建时为空）
+///
+/// # 返回
+/// 按名称排序的模块列表，`previously_selected` 仅在模块出现于 `last_selected` 时为 true
+pub fn scan_modules_with_state(
+    modules_path: &std::path::Path,
+    last_selected: &[String],
+    module_depth: usize,
+) -> AppResult<Vec<ModuleSelectionState>> {
+    let modules = scan_modules_dir_with_depth(modules_path, module_depth)?;
+    Ok(modules
+        .into_iter()
+        .map(|m| ModuleSelectionState {
+            previously_selected: last_selected.contains(&m.name),
+            name: m.name,
+            path: m.path,
+        })
+        .collect())
+}
+
 /// 扫描项目骨架文件树（排除模块目录和默认排除项）
 ///
 /// 返回项目中除模块目录外的所有文件/目录的相对路径列表，
@@ -128,7 +300,7 @@ pub fn scan_skeleton_files(
             .strip_prefix(project_path)
             .map_err(|e| AppError::ScanError(format!("路径处理失败: {}", e)))?;
 
-        let rel_str = relative.to_string_lossy().replace('\\', "/");
+        let rel_str = normalize_rel_path(relative);
         if entry.file_type().is_dir() {
             skeleton.push(format!("{}/", rel_str));
         } else {
@@ -266,6 +438,27 @@ mod tests {
         assert_eq!(result[0].name, "auth");
     }
 
+    #[test]
+    fn test_scan_modules_with_state_flags_last_selection() {
+        let dir = TempDir::new().unwrap();
+        let modules_path = dir.path().join("modules");
+        for name in ["auth", "billing", "reports", "users"] {
+            fs::create_dir_all(modules_path.join(name)).unwrap();
+        }
+
+        // 上次构建选中了 auth 和 users；"legacy" 已从项目中删除
+        let last = vec!["auth".to_string(), "users".to_string(), "legacy".to_string()];
+        let result = scan_modules_with_state(&modules_path, &last, 1).unwrap();
+
+        assert_eq!(result.len(), 4);
+        let selected: Vec<&str> = result
+            .iter()
+            .filter(|m| m.previously_selected)
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(selected, vec!["auth", "users"]);
+    }
+
     #[test]
     fn test_scan_modules_dir_nonexistent_path() {
         let dir = TempDir::new().unwrap();
@@ -274,4 +467,91 @@ mod tests {
         let result = scan_modules_dir(&nonexistent);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_nested_duplicate_module_names_reported() {
+        let dir = TempDir::new().unwrap();
+        let modules = dir.path().join("modules");
+        fs::create_dir_all(modules.join("admin/users")).unwrap();
+        fs::create_dir_all(modules.join("admin/roles")).unwrap();
+        fs::create_dir_all(modules.join("users")).unwrap();
+        fs::write(modules.join("users/__init__.py"), "").unwrap();
+        // 普通模块内的子目录不视为嵌套模块
+        fs::create_dir_all(modules.join("orders/users")).unwrap();
+        fs::write(modules.join("orders/__init__.py"), "").unwrap();
+
+        let warnings = find_ambiguous_module_names(&modules);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("模块名 users 存在歧义"));
+        assert!(warnings[0].contains("admin/users、users"));
+    }
+
+    #[test]
+    fn test_scan_modules_dir_with_depth_qualifies_grouped_modules() {
+        let dir = TempDir::new().unwrap();
+        let modules = dir.path().join("modules");
+        fs::create_dir_all(modules.join("admin/users")).unwrap();
+        fs::write(modules.join("admin/users/__init__.py"), "").unwrap();
+        fs::create_dir_all(modules.join("admin/roles")).unwrap();
+        fs::write(modules.join("admin/roles/__init__.py"), "").unwrap();
+        fs::create_dir_all(modules.join("public/home")).unwrap();
+        fs::write(modules.join("public/home/__init__.py"), "").unwrap();
+        // 含文件的目录本身即为模块，其子目录不展开
+        fs::create_dir_all(modules.join("orders/schemas")).unwrap();
+        fs::write(modules.join("orders/__init__.py"), "").unwrap();
+        // 深度超过上限时按两层展开，与构建时的限定模块名一致
+        fs::create_dir_all(modules.join("sys/iam/users")).unwrap();
+        fs::write(modules.join("sys/iam/users/__init__.py"), "").unwrap();
+
+        let names = |depth: usize| -> Vec<String> {
+            scan_modules_dir_with_depth(&modules, depth)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.name)
+                .collect()
+        };
+        assert_eq!(names(1), vec!["admin", "orders", "public", "sys"]);
+        assert_eq!(
+            names(2),
+            vec!["admin/roles", "admin/users", "orders", "public/home", "sys/iam"]
+        );
+        assert_eq!(names(3), names(2));
+
+        assert_eq!(module_depth_from_setting(None), 1);
+        assert_eq!(module_depth_from_setting(Some("2")), 2);
+        assert_eq!(module_depth_from_setting(Some("5")), 2);
+        assert_eq!(module_depth_from_setting(Some("abc")), 1);
+
+        let nested = scan_modules_dir_with_depth(&modules, 2).unwrap();
+        assert_eq!(
+            std::path::Path::new(&nested[0].path),
+            modules.join("admin").join("roles")
+        );
+    }
+
+    #[test]
+    fn test_compare_modules_dirs_partitions_modules() {
+        let a = TempDir::new().unwrap();
+        let b = TempDir::new().unwrap();
+        for name in ["auth", "billing"] {
+            fs::create_dir_all(a.path().join("modules").join(name)).unwrap();
+        }
+        for name in ["auth", "reports", "__pycache__"] {
+            fs::create_dir_all(b.path().join("modules").join(name)).unwrap();
+        }
+
+        let diff =
+            compare_modules_dirs(&a.path().join("modules"), &b.path().join("modules")).unwrap();
+        assert_eq!(
+            diff,
+            ModuleDiff {
+                only_in_a: vec!["billing".to_string()],
+                only_in_b: vec!["reports".to_string()],
+                common: vec!["auth".to_string()],
+            }
+        );
+
+        let missing = a.path().join("missing");
+        assert!(compare_modules_dirs(&missing, &b.path().join("modules")).is_err());
+    }
 }
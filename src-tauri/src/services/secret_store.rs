@@ -0,0 +1,252 @@
+// ============================================================================
+// 密钥存储：LLM API Key 在系统钥匙串与数据库之间的来源选择
+// ✅ 只能做：按来源设置读写密钥、钥匙串不可用时回退（存储后端可注入，便于测试）
+// ⛔ 禁止：依赖 tauri::*，读写数据库（数据库读写由调用方负责）
+// ============================================================================
+
+/// API Key 来源设置键：值为 `keychain` 时从系统钥匙串读写 `llm_api_key`
+pub const API_KEY_SOURCE_KEY: &str = "llm_api_key_source";
+
+/// 使用系统钥匙串的来源取值
+pub const KEYCHAIN_SOURCE: &str = "keychain";
+
+/// 钥匙串条目的服务名
+const KEYRING_SERVICE: &str = "prism-delivery-console";
+
+/// 密钥实际读写的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretSource {
+    /// 系统钥匙串
+    Keychain,
+    /// SQLite settings 表（明文）
+    Database,
+}
+
+/// 密钥存储后端
+pub trait SecretStore {
+    /// 读取密钥，条目不存在时返回 `Ok(None)`
+    fn get_secret(&self, key: &str) -> Result<Option<String>, String>;
+
+    /// 写入密钥
+    fn set_secret(&self, key: &str, value: &str) -> Result<(), String>;
+
+    /// 写入的密钥能否在重启后保留；不能时数据库中仍需保存一份
+    fn is_persistent(&self) -> bool {
+        true
+    }
+}
+
+/// 系统钥匙串（macOS Keychain / Windows 凭据管理器 / Linux 内核密钥环）
+///
+/// Linux 内核密钥环（keyutils）只存在于内存中，重启后条目丢失。
+pub struct OsKeychain;
+
+impl SecretStore for OsKeychain {
+    fn get_secret(&self, key: &str) -> Result<Option<String>, String> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, key)
+            .map_err(|e| format!("打开钥匙串条目失败：{}", e))?;
+        match entry.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("读取钥匙串失败：{}", e)),
+        }
+    }
+
+    fn set_secret(&self, key: &str, value: &str) -> Result<(), String> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .and_then(|entry| entry.set_password(value))
+            .map_err(|e| format!("写入钥匙串失败：{}", e))
+    }
+
+    fn is_persistent(&self) -> bool {
+        !cfg!(target_os = "linux")
+    }
+}
+
+/// 来源设置是否指定使用钥匙串
+pub fn uses_keychain(source_setting: &str) -> bool {
+    source_setting.trim() == KEYCHAIN_SOURCE
+}
+
+/// 按来源设置读取密钥
+///
+/// 指定钥匙串时优先读取钥匙串条目；钥匙串不可用或无此条目时回退到数据库中的值。
+///
+/// # 返回
+/// - (密钥值, 实际来源)
+pub fn read_secret(
+    store: &dyn SecretStore,
+    source_setting: &str,
+    key: &str,
+    db_value: String,
+) -> (String, SecretSource) {
+    if uses_keychain(source_setting) {
+        match store.get_secret(key) {
+            Ok(Some(value)) => {
+                log::info!("设置项 {} 从系统钥匙串读取", key);
+                return (value, SecretSource::Keychain);
+            }
+            Ok(None) => log::warn!("钥匙串中没有 {} 条目，回退到数据库", key),
+            Err(e) => log::warn!("{}，设置项 {} 回退到数据库", e, key),
+        }
+    }
+    log::info!("设置项 {} 从数据库读取", key);
+    (db_value, SecretSource::Database)
+}
+
+/// 按来源设置写入密钥
+///
+/// 指定钥匙串时写入钥匙串；钥匙串不可用或重启后不保留（见 `SecretStore::is_persistent`）时
+/// 返回 `Database`，由调用方写入数据库。
+///
+/// # 返回
+/// - 需要保存的位置；为 `Keychain` 时调用方不应再在数据库中保存明文
+pub fn write_secret(
+    store: &dyn SecretStore,
+    source_setting: &str,
+    key: &str,
+    value: &str,
+) -> SecretSource {
+    if uses_keychain(source_setting) {
+        match store.set_secret(key, value) {
+            Ok(()) if store.is_persistent() => {
+                log::info!("设置项 {} 已写入系统钥匙串", key);
+                return SecretSource::Keychain;
+            }
+            Ok(()) => log::warn!("系统钥匙串重启后不保留，设置项 {} 同时保存到数据库", key),
+            Err(e) => log::warn!("{}，设置项 {} 回退到数据库存储", e, key),
+        }
+    }
+    log::info!("设置项 {} 保存到数据库", key);
+    SecretSource::Database
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// 内存钥匙串
+    #[derive(Default)]
+    struct MemoryStore(RefCell<HashMap<String, String>>);
+
+    impl SecretStore for MemoryStore {
+        fn get_secret(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.0.borrow().get(key).cloned())
+        }
+
+        fn set_secret(&self, key: &str, value: &str) -> Result<(), String> {
+            self.0
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    /// 重启后不保留条目的钥匙串（如 Linux 内核密钥环）
+    #[derive(Default)]
+    struct VolatileStore(MemoryStore);
+
+    impl SecretStore for VolatileStore {
+        fn get_secret(&self, key: &str) -> Result<Option<String>, String> {
+            self.0.get_secret(key)
+        }
+
+        fn set_secret(&self, key: &str, value: &str) -> Result<(), String> {
+            self.0.set_secret(key, value)
+        }
+
+        fn is_persistent(&self) -> bool {
+            false
+        }
+    }
+
+    /// 不可用的钥匙串（如无桌面会话的 Linux 环境）
+    struct UnavailableStore;
+
+    impl SecretStore for UnavailableStore {
+        fn get_secret(&self, _key: &str) -> Result<Option<String>, String> {
+            Err("读取钥匙串失败：平台不支持".to_string())
+        }
+
+        fn set_secret(&self, _key: &str, _value: &str) -> Result<(), String> {
+            Err("写入钥匙串失败：平台不支持".to_string())
+        }
+    }
+
+    #[test]
+    fn test_keychain_source_reads_and_writes_keychain() {
+        let store = MemoryStore::default();
+        assert_eq!(
+            write_secret(&store, KEYCHAIN_SOURCE, "llm_api_key", "sk-secret"),
+            SecretSource::Keychain
+        );
+        let (value, source) = read_secret(&store, KEYCHAIN_SOURCE, "llm_api_key", String::new());
+        assert_eq!(value, "sk-secret");
+        assert_eq!(source, SecretSource::Keychain);
+    }
+
+    #[test]
+    fn test_database_source_ignores_keychain() {
+        let store = MemoryStore::default();
+        store.set_secret("llm_api_key", "sk-keychain").unwrap();
+
+        assert_eq!(
+            write_secret(&store, "", "llm_api_key", "sk-db"),
+            SecretSource::Database
+        );
+        let (value, source) = read_secret(&store, "", "llm_api_key", "sk-db".to_string());
+        assert_eq!(value, "sk-db");
+        assert_eq!(source, SecretSource::Database);
+    }
+
+    #[test]
+    fn test_unavailable_keychain_falls_back_to_database() {
+        assert_eq!(
+            write_secret(&UnavailableStore, KEYCHAIN_SOURCE, "llm_api_key", "sk-db"),
+            SecretSource::Database
+        );
+        let (value, source) = read_secret(
+            &UnavailableStore,
+            KEYCHAIN_SOURCE,
+            "llm_api_key",
+            "sk-db".to_string(),
+        );
+        assert_eq!(value, "sk-db");
+        assert_eq!(source, SecretSource::Database);
+
+        // 钥匙串可用但尚无条目（如刚切换来源）→ 沿用数据库中的旧值
+        let (value, source) = read_secret(
+            &MemoryStore::default(),
+            KEYCHAIN_SOURCE,
+            "llm_api_key",
+            "sk-old".to_string(),
+        );
+        assert_eq!(value, "sk-old");
+        assert_eq!(source, SecretSource::Database);
+    }
+    #[test]
+    fn test_volatile_keychain_keeps_database_copy() {
+        let store = VolatileStore::default();
+        assert_eq!(
+            write_secret(&store, KEYCHAIN_SOURCE, "llm_api_key", "sk-secret"),
+            SecretSource::Database
+        );
+        // 钥匙串中仍写入一份，重启前优先从钥匙串读取
+        assert_eq!(
+            store.get_secret("llm_api_key").unwrap().as_deref(),
+            Some("sk-secret")
+        );
+
+        // 重启后钥匙串条目丢失 → 回退到数据库中保存的值
+        let (value, source) = read_secret(
+            &VolatileStore::default(),
+            KEYCHAIN_SOURCE,
+            "llm_api_key",
+            "sk-secret".to_string(),
+        );
+        assert_eq!(value, "sk-secret");
+        assert_eq!(source, SecretSource::Database);
+    }
+}
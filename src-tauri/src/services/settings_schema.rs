@@ -0,0 +1,185 @@
+// ============================================================================
+// 设置项 Schema：已知设置键的注册表与取值校验
+// ✅ 只能做：判断键是否已知、校验取值格式（纯函数，便于测试）
+// ⛔ 禁止：依赖 tauri::*，读写数据库
+// ============================================================================
+
+use std::path::Path;
+
+use crate::services::analysis_pipeline::{EmbeddingInputMode, EMBEDDING_INPUT_MODE_KEY};
+use crate::services::analyzer::{
+    CODE_EXTENSIONS_KEY, HASH_ALGO_KEY, HASH_ALGO_VALUES, MAX_OVERVIEW_FILE_BYTES_KEY,
+};
+use crate::services::build_strategy::{
+    validate_timestamp_format, BUILD_TIMESTAMP_FORMAT_KEY, BUILD_TIMESTAMP_TZ_KEY,
+    BUILD_TIMESTAMP_TZ_VALUES,
+};
+use crate::services::llm_client::{
+    ENV_INTERPOLATED_KEYS, EXTRA_HEADERS_KEY, GLOBAL_CONCURRENCY_KEY,
+};
+use crate::services::scanner::{MODULE_DEPTH_KEY, MODULE_DEPTH_VALUES};
+use crate::services::secret_store::API_KEY_SOURCE_KEY;
+
+/// 设置值的格式约束
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    /// 任意文本
+    Text,
+    /// http(s) URL
+    Url,
+    /// 非负整数
+    NonNegativeInt,
+    /// 绝对路径
+    Path,
+    /// `true` / `false`
+    Bool,
+    /// JSON 对象
+    JsonObject,
+    /// JSON 字符串数组
+    JsonStringArray,
+    /// 取值限定在给定列表内
+    OneOf(&'static [&'static str]),
+    /// `time` 格式描述，输出须可用作文件名
+    TimeFormat,
+}
+
+/// 已知设置键及其格式约束
+///
+/// 新增设置项时在此登记；未登记的键保存时会被拒绝（除非显式强制保存）。
+pub const KNOWN_SETTINGS: &[(&str, SettingKind)] = &[
+    ("llm_base_url", SettingKind::Url),
+    ("llm_api_key", SettingKind::Text),
+    (API_KEY_SOURCE_KEY, SettingKind::Text),
+    ("llm_model_name", SettingKind::Text),
+    ("llm_embedding_model", SettingKind::Text),
+    ("llm_timeout_secs", SettingKind::NonNegativeInt),
+    (GLOBAL_CONCURRENCY_KEY, SettingKind::NonNegativeInt),
+    (EXTRA_HEADERS_KEY, SettingKind::JsonObject),
+    ("default_output_dir", SettingKind::Path),
+    ("auto_index_signatures", SettingKind::Bool),
+    (CODE_EXTENSIONS_KEY, SettingKind::JsonStringArray),
+    (MAX_OVERVIEW_FILE_BYTES_KEY, SettingKind::NonNegativeInt),
+    (EMBEDDING_INPUT_MODE_KEY, SettingKind::OneOf(EmbeddingInputMode::ALL)),
+    (BUILD_TIMESTAMP_TZ_KEY, SettingKind::OneOf(BUILD_TIMESTAMP_TZ_VALUES)),
+    (BUILD_TIMESTAMP_FORMAT_KEY, SettingKind::TimeFormat),
+    (MODULE_DEPTH_KEY, SettingKind::OneOf(MODULE_DEPTH_VALUES)),
+    (HASH_ALGO_KEY, SettingKind::OneOf(HASH_ALGO_VALUES)),
+];
+
+/// 查询设置键的格式约束，未登记的键返回 None
+pub fn setting_kind(key: &str) -> Option<SettingKind> {
+    KNOWN_SETTINGS
+        .iter()
+        .find(|(known, _)| *known == key)
+        .map(|(_, kind)| *kind)
+}
+
+/// 校验设置键值
+///
+/// 空值始终允许（用于重置设置）；支持环境变量引用的键允许 `${ENV:变量名}` 形式。
+///
+/// # 返回
+/// - `Ok(())`: 键已登记且取值合法
+/// - `Err(String)`: 未知键或取值不合法，返回中文错误描述
+pub fn validate_setting(key: &str, value: &str) -> Result<(), String> {
+    let kind = setting_kind(key).ok_or_else(|| format!("未知的设置项：{}", key))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(());
+    }
+    if ENV_INTERPOLATED_KEYS.contains(&key) && value.starts_with("${ENV:") {
+        return Ok(());
+    }
+
+    let valid = match kind {
+        SettingKind::Text => true,
+        SettingKind::Url => reqwest::Url::parse(value)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or(false),
+        SettingKind::NonNegativeInt => value.parse::<u64>().is_ok(),
+        SettingKind::Path => Path::new(value).is_absolute(),
+        SettingKind::Bool => matches!(value, "true" | "false"),
+        SettingKind::JsonObject => {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(value).is_ok()
+        }
+        SettingKind::JsonStringArray => serde_json::from_str::<Vec<String>>(value).is_ok(),
+        SettingKind::OneOf(choices) => choices.contains(&value),
+        SettingKind::TimeFormat => validate_timestamp_format(value).is_ok(),
+    };
+    if valid {
+        return Ok(());
+    }
+
+    let expected = match kind {
+        SettingKind::Text => "文本".to_string(),
+        SettingKind::Url => "以 http:// 或 https:// 开头的 URL".to_string(),
+        SettingKind::NonNegativeInt => "非负整数".to_string(),
+        SettingKind::Path => "绝对路径".to_string(),
+        SettingKind::Bool => "true 或 false".to_string(),
+        SettingKind::JsonObject => "JSON 对象".to_string(),
+        SettingKind::JsonStringArray => "JSON 字符串数组".to_string(),
+        SettingKind::OneOf(choices) => format!("{} 之一", choices.join(" / ")),
+        SettingKind::TimeFormat => {
+            "可用作文件名的 time 格式描述（如 [year][month][day]_[hour][minute][second]）"
+                .to_string()
+        }
+    };
+    Err(format!("设置项 {} 的值无效：应为{}，实际为 {:?}", key, expected, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        let err = validate_setting("llm_model", "gpt-4o").unwrap_err();
+        assert!(err.contains("未知的设置项：llm_model"));
+        assert!(validate_setting("llm_model_name", "gpt-4o").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_timeout_rejected() {
+        let err = validate_setting("llm_timeout_secs", "-5").unwrap_err();
+        assert!(err.contains("llm_timeout_secs"));
+        assert!(err.contains("非负整数"));
+        assert!(validate_setting("llm_timeout_secs", "abc").is_err());
+        assert!(validate_setting("llm_timeout_secs", "30").is_ok());
+        // 空值用于重置，始终允许
+        assert!(validate_setting("llm_timeout_secs", "").is_ok());
+    }
+
+    #[test]
+    fn test_url_and_path_validation() {
+        assert!(validate_setting("llm_base_url", "http://localhost:11434/v1").is_ok());
+        assert!(validate_setting("llm_base_url", "${ENV:LLM_BASE_URL}").is_ok());
+        assert!(validate_setting("llm_base_url", "localhost:11434").is_err());
+        assert!(validate_setting("default_output_dir", "relative/out").is_err());
+        let abs = std::env::temp_dir();
+        assert!(validate_setting("default_output_dir", abs.to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_one_of_validation() {
+        assert!(validate_setting("embedding_input_mode", "signatures").is_ok());
+        let err = validate_setting("embedding_input_mode", "tokens").unwrap_err();
+        assert!(err.contains("path_plus_signatures"));
+    }
+
+    #[test]
+    fn test_build_timestamp_settings_validation() {
+        assert!(validate_setting("build_timestamp_tz", "local").is_ok());
+        assert!(validate_setting("build_timestamp_tz", "Asia/Shanghai").is_err());
+        assert!(validate_setting("build_timestamp_format", "[year][month][day]").is_ok());
+        let err = validate_setting("build_timestamp_format", "[yeer]").unwrap_err();
+        assert!(err.contains("build_timestamp_format"));
+    }
+
+    #[test]
+    fn test_hash_algo_setting_validation() {
+        assert!(validate_setting("hash_algo", "sha256").is_ok());
+        assert!(validate_setting("hash_algo", "blake3").is_ok());
+        let err = validate_setting("hash_algo", "md5").unwrap_err();
+        assert!(err.contains("blake3"));
+    }
+}
@@ -0,0 +1,313 @@
+// ============================================================================
+// 向量检索索引：按项目缓存归一化后的 Embedding 矩阵，重复查询无需重读 SQLite
+// ✅ 只能做：构建与查询内存中的向量索引（缓存作为 Tauri managed state 使用，应用重启即清空）
+// ⛔ 禁止：依赖 tauri::*，直接操作数据库
+// ============================================================================
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::services::analyzer::{self, SimilarFileResult};
+
+/// 单个项目的向量检索索引
+///
+/// 向量在构建时统一归一化（兼容归一化入库之前写入的旧向量），查询时一次点积即得余弦相似度。
+/// 维度与首个向量一致的向量按行存入连续矩阵；维度不一致的向量（如中途更换过 Embedding 模型）单独保存。
+#[derive(Debug, Default)]
+pub struct VectorIndex {
+    paths: Vec<String>,
+    summaries: Vec<Option<String>>,
+    /// 矩阵中每个向量的维度
+    dim: usize,
+    /// 归一化后的向量矩阵（行优先，每行 `dim` 个元素）
+    matrix: Vec<f32>,
+    /// 矩阵第 N 行对应的条目下标
+    rows: Vec<usize>,
+    /// 维度与矩阵不一致的向量：(条目下标, 归一化后的向量)
+    others: Vec<(usize, Vec<f32>)>,
+}
+
+impl VectorIndex {
+    /// 由 (相对路径, 摘要, 向量) 列表构建索引
+    pub fn build(entries: Vec<(String, Option<String>, Vec<f32>)>) -> Self {
+        let mut index = VectorIndex {
+            dim: entries
+                .first()
+                .map_or(0, |(_, _, embedding)| embedding.len()),
+            ..Default::default()
+        };
+        for (i, (path, summary, embedding)) in entries.into_iter().enumerate() {
+            index.paths.push(path);
+            index.summaries.push(summary);
+            let embedding = analyzer::normalize_embedding(&embedding);
+            if index.dim > 0 && embedding.len() == index.dim {
+                index.matrix.extend(embedding);
+                index.rows.push(i);
+            } else {
+                index.others.push((i, embedding));
+            }
+        }
+        index
+    }
+
+    /// 索引中的文件数
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// 索引是否为空
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// 查询与 `query` 最相似的 `top_k` 个文件，按相似度降序（相同分数保持构建顺序）
+    ///
+    /// `path_prefix` 非空时只在该路径本身或其下的文件中查找（`modules/auth` 不匹配 `modules/auth_v2/`），
+    /// 与 `Database::list_file_embeddings` 一致（反斜杠视为 `/`，ASCII 字母不区分大小写）。
+    /// `min_score` 非空时先剔除相似度低于该值的文件再取 Top-K，没有足够相关的文件时返回结果少于 `top_k`。
+    pub fn search(
+        &self,
+        query: &[f32],
+        top_k: usize,
+        path_prefix: Option<&str>,
+        min_score: Option<f32>,
+    ) -> Vec<SimilarFileResult> {
+        let prefix = path_prefix.unwrap_or("").replace('\\', "/");
+        let prefix = prefix.trim_end_matches('/').as_bytes();
+        let in_scope = |i: usize| {
+            let path = self.paths[i].as_bytes();
+            prefix.is_empty()
+                || (path.len() >= prefix.len()
+                    && path[..prefix.len()].eq_ignore_ascii_case(prefix)
+                    && path.get(prefix.len()).is_none_or(|&b| b == b'/'))
+        };
+
+        // 维度不一致的向量由 dot_product 计 0 分
+        let query = analyzer::normalize_embedding(query);
+        let mut scored: Vec<(usize, f32)> = Vec::new();
+        for (row, &i) in self.rows.iter().enumerate() {
+            if in_scope(i) {
+                let embedding = &self.matrix[row * self.dim..(row + 1) * self.dim];
+                scored.push((i, analyzer::dot_product(&query, embedding)));
+            }
+        }
+        for (i, embedding) in &self.others {
+            if in_scope(*i) {
+                scored.push((*i, analyzer::dot_product(&query, embedding)));
+            }
+        }
+
+        if let Some(min_score) = min_score {
+            scored.retain(|(_, score)| *score >= min_score);
+        }
+        scored.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+        scored.truncate(top_k);
+        scored
+            .into_iter()
+            .map(|(i, score)| SimilarFileResult {
+                relative_path: self.paths[i].clone(),
+                summary: self.summaries[i].clone(),
+                score,
+            })
+            .collect()
+    }
+}
+
+/// 向量检索索引缓存（项目 ID → 构建时的向量版本号与索引）
+///
+/// 注册为 Tauri managed state。版本号来自 `Database::embedding_version`，
+/// 项目的向量、摘要或路径变化后版本号递增，缓存的索引随之失效并在下次查询时重建。
+#[derive(Debug, Default)]
+pub struct VectorIndexCache {
+    entries: Mutex<HashMap<i64, (i64, Arc<VectorIndex>)>>,
+}
+
+impl VectorIndexCache {
+    /// 取出版本号为 `version` 的项目索引；没有缓存或已过期时调用 `load` 重新构建并缓存
+    ///
+    /// `load` 返回 (相对路径, 摘要, 向量) 列表，失败时不修改缓存。
+    pub fn get_or_build<F>(
+        &self,
+        project_id: i64,
+        version: i64,
+        load: F,
+    ) -> Result<Arc<VectorIndex>, String>
+    where
+        F: FnOnce() -> Result<Vec<(String, Option<String>, Vec<f32>)>, String>,
+    {
+        if let Some((cached_version, index)) = self
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&project_id)
+        {
+            if *cached_version == version {
+                return Ok(Arc::clone(index));
+            }
+        }
+
+        let index = Arc::new(VectorIndex::build(load()?));
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(project_id, (version, Arc::clone(&index)));
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 确定性的伪随机向量（线性同余），避免引入随机数依赖
+    fn pseudo_vectors(count: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        ((state >> 33) as f32 / u32::MAX as f32) - 0.25
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn entries_of(vectors: &[Vec<f32>]) -> Vec<(String, Option<String>, Vec<f32>)> {
+        vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let dir = match i % 4 {
+                    0 | 2 => "modules/auth",
+                    1 => "core",
+                    _ => "modules/auth_v2",
+                };
+                (
+                    format!("{}/f{}.py", dir, i),
+                    Some(format!("摘要 {}", i)),
+                    v.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// 逐条计算余弦相似度并排序（与索引引入前的检索方式一致）
+    fn brute_force(
+        entries: &[(String, Option<String>, Vec<f32>)],
+        query: &[f32],
+        top_k: usize,
+        prefix: &str,
+    ) -> Vec<(String, f32)> {
+        let mut scored: Vec<(String, f32)> = entries
+            .iter()
+            .filter(|(path, _, _)| {
+                prefix.is_empty() || path.starts_with(&format!("{}/", prefix.trim_end_matches('/')))
+            })
+            .map(|(path, _, v)| (path.clone(), analyzer::cosine_similarity(query, v)))
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k);
+        scored
+    }
+
+    #[test]
+    fn test_index_top_k_matches_brute_force() {
+        let vectors = pseudo_vectors(200, 16, 7);
+        let entries = entries_of(&vectors);
+        let index = VectorIndex::build(entries.clone());
+        assert_eq!(index.len(), 200);
+
+        for query in pseudo_vectors(5, 16, 99) {
+            for prefix in ["", "modules/auth", "MODULES/AUTH", "modules/auth/"] {
+                let expected = brute_force(&entries, &query, 10, &prefix.to_lowercase());
+                let actual = index.search(&query, 10, Some(prefix), None);
+                let actual_paths: Vec<&str> =
+                    actual.iter().map(|r| r.relative_path.as_str()).collect();
+                let expected_paths: Vec<&str> = expected.iter().map(|(p, _)| p.as_str()).collect();
+                assert_eq!(actual_paths, expected_paths);
+                for (r, (_, score)) in actual.iter().zip(&expected) {
+                    assert!((r.score - score).abs() < 1e-5);
+                }
+            }
+        }
+
+        let hit = &index.search(&vectors[1], 1, None, None)[0];
+        assert_eq!(hit.relative_path, "core/f1.py");
+        assert_eq!(hit.summary.as_deref(), Some("摘要 1"));
+    }
+
+    #[test]
+    fn test_mismatched_dimensions_scored_separately() {
+        let mut entries = entries_of(&pseudo_vectors(3, 4, 1));
+        entries.push(("legacy.py".to_string(), None, vec![1.0, 0.0]));
+        let index = VectorIndex::build(entries);
+
+        // 与旧模型维度一致的查询只与 legacy.py 有非零分数
+        let results = index.search(&[2.0, 0.0], 10, None, None);
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].relative_path, "legacy.py");
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert!(results[1..].iter().all(|r| r.score == 0.0));
+
+        assert!(VectorIndex::build(Vec::new())
+            .search(&[1.0], 5, None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_min_score_filters_before_top_k() {
+        let entries = vec![
+            ("a.py".to_string(), None, vec![1.0, 0.0]),
+            ("b.py".to_string(), None, vec![0.8, 0.6]),
+            ("c.py".to_string(), None, vec![0.0, 1.0]),
+            ("d.py".to_string(), None, vec![-1.0, 0.0]),
+        ];
+        let index = VectorIndex::build(entries);
+
+        // 阈值高于所有分数时不返回任何结果
+        assert!(index.search(&[1.0, 0.0], 10, None, Some(1.5)).is_empty());
+
+        // 只保留高分匹配，返回数可少于 top_k
+        let results = index.search(&[1.0, 0.0], 10, None, Some(0.5));
+        let paths: Vec<&str> = results.iter().map(|r| r.relative_path.as_str()).collect();
+        assert_eq!(paths, vec!["a.py", "b.py"]);
+
+        // 未设置阈值时保持原行为：返回 top_k 个，包括负分
+        assert_eq!(index.search(&[1.0, 0.0], 10, None, None).len(), 4);
+        assert_eq!(index.search(&[1.0, 0.0], 3, None, Some(-1.0)).len(), 3);
+    }
+
+    #[test]
+    fn test_cache_rebuilds_when_version_changes() {
+        let cache = VectorIndexCache::default();
+        let loads = std::cell::Cell::new(0);
+        let load = || {
+            loads.set(loads.get() + 1);
+            Ok(entries_of(&pseudo_vectors(2, 3, 5)))
+        };
+
+        let first = cache.get_or_build(1, 0, load).unwrap();
+        let again = cache.get_or_build(1, 0, load).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(loads.get(), 1);
+
+        cache.get_or_build(1, 1, load).unwrap();
+        cache.get_or_build(2, 1, load).unwrap();
+        assert_eq!(loads.get(), 3);
+
+        // 构建失败时保留旧索引
+        assert!(cache
+            .get_or_build(1, 2, || Err("boom".to_string()))
+            .is_err());
+        assert_eq!(cache.get_or_build(1, 1, load).unwrap().len(), 2);
+        assert_eq!(loads.get(), 3);
+    }
+}
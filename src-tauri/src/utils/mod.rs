@@ -3,3 +3,5 @@
 // ============================================================================
 
 pub mod error;
+pub mod path;
+pub mod pattern;
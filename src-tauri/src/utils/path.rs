@@ -0,0 +1,30 @@
+// ============================================================================
+// 路径工具：存储/返回给前端的路径统一使用正斜杠
+// 仅用于记录与展示，实际文件系统操作仍使用原生 Path
+// ============================================================================
+
+use std::path::Path;
+
+/// 将路径规范化为正斜杠分隔的字符串
+///
+/// Windows 上构建、Linux 上查看的交付记录需保持一致的路径格式，
+/// 所有写入数据库、清单或返回给前端的相对路径都应经过此函数。
+pub fn normalize_rel_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_rel_path_uses_forward_slashes() {
+        assert_eq!(normalize_rel_path(Path::new(r"modules\auth\routes.py")), "modules/auth/routes.py");
+        assert_eq!(normalize_rel_path(&Path::new("modules").join("auth")), "modules/auth");
+        assert_eq!(normalize_rel_path(Path::new("main.py")), "main.py");
+    }
+}
@@ -0,0 +1,50 @@
+// ============================================================================
+// 通配符工具：模块选择、打包排除规则与依赖分析共用的简单通配符匹配
+// ============================================================================
+
+/// 简单通配符匹配：`*` 匹配任意长度字符，`?` 匹配单个字符
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    // 最近一次 `*` 的位置及其已匹配到的 name 位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star_pi, star_ni)) = star {
+            pi = star_pi + 1;
+            ni = star_ni + 1;
+            star = Some((star_pi, star_ni + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+// ============================================================================
+// 单元测试
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("user*", "user_admin"));
+        assert!(wildcard_match("*.pyc", "cache.pyc"));
+        assert!(wildcard_match("v?", "v2"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("a*b*c", "axxbyyc"));
+        assert!(!wildcard_match("user*", "admin_user"));
+        assert!(!wildcard_match("v?", "v10"));
+        assert!(!wildcard_match("a*b*c", "axxbyy"));
+    }
+}
@@ -10,7 +10,7 @@
 import { useState } from "react";
 import {
   Loader2, BarChart3, Code2, FolderOpen, Layers, FileCode,
-  ScanSearch, Fingerprint, Brain, Zap, Microscope, CheckCircle2, Circle,
+  ScanSearch, Fingerprint, Brain, Zap, Microscope, CheckCircle2, Circle, Copy,
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
 import Markdown from "react-markdown";
@@ -87,6 +87,8 @@ export function OverviewPanel({
   }
 
   const maxLines = Math.max(...overview.languages.map((l) => l.line_count), 1);
+  // 每组保留一份，其余视为重复文件
+  const duplicateCount = overview.duplicate_groups.reduce((sum, g) => sum + g.length - 1, 0);
 
   return (
     <div className="flex flex-col gap-5">
@@ -136,7 +138,10 @@ export function OverviewPanel({
                   <span className="w-20 shrink-0 text-right text-xs text-muted-foreground">
                     {lang.file_count} 文件
                   </span>
-                  <span className="w-24 shrink-0 text-right text-xs text-muted-foreground">
+                  <span
+                    className="w-24 shrink-0 text-right text-xs text-muted-foreground"
+                    title={`代码 ${lang.line_count - lang.blank_lines - lang.comment_lines} / 注释 ${lang.comment_lines} / 空行 ${lang.blank_lines}`}
+                  >
                     {lang.line_count.toLocaleString()} 行
                   </span>
                 </div>
@@ -161,6 +166,27 @@ export function OverviewPanel({
             </div>
           </div>
         )}
+
+        {/* 重复文件 */}
+        {duplicateCount > 0 && (
+          <div className="glass p-4 mt-3">
+            <div className="flex items-center gap-2 mb-3 text-sm font-medium text-foreground">
+              <Copy className="h-4 w-4" />
+              检测到 {duplicateCount} 个重复文件
+            </div>
+            <div className="flex flex-col gap-2">
+              {overview.duplicate_groups.map((group) => (
+                <div key={group[0]} className="flex flex-wrap gap-2">
+                  {group.map((f) => (
+                    <span key={f} className="rounded-md border border-border bg-muted/50 px-2 py-1 text-xs text-foreground">
+                      {f}
+                    </span>
+                  ))}
+                </div>
+              ))}
+            </div>
+          </div>
+        )}
       </section>
 
       {/* ========== 第二部分：数据准备状态 ========== */}
@@ -16,7 +16,7 @@ import {
   Maximize2, Minimize2, EyeOff, Eye, Search, X,
 } from "lucide-react";
 import { Button } from "@/components/ui/button";
-import type { DependencyGraph } from "@/types";
+import type { DepEdge, DependencyGraph } from "@/types";
 
 /** 视图模式 */
 type ViewMode = "force" | "tree";
@@ -27,6 +27,12 @@ interface TopologyViewProps {
   graph: DependencyGraph;
 }
 
+/** 渲染用的图数据：节点仅保留路径（目录级视图中为目录路径） */
+interface PathGraph {
+  nodes: string[];
+  edges: DepEdge[];
+}
+
 /** D3 节点类型 */
 interface GraphNode extends d3.SimulationNodeDatum {
   id: string;
@@ -43,7 +49,7 @@ interface GraphLink extends d3.SimulationLinkDatum<GraphNode> {
 }
 
 /** 将文件级图数据聚合为目录级 */
-function aggregateToDirectory(graph: DependencyGraph): DependencyGraph {
+function aggregateToDirectory(graph: PathGraph): PathGraph {
   const getDir = (path: string) => {
     const idx = path.lastIndexOf("/");
     return idx >= 0 ? path.substring(0, idx) : "(root)";
@@ -55,7 +61,7 @@ function aggregateToDirectory(graph: DependencyGraph): DependencyGraph {
 
   // 聚合边：去重
   const edgeSet = new Set<string>();
-  const edges: DependencyGraph["edges"] = [];
+  const edges: DepEdge[] = [];
   for (const e of graph.edges) {
     const srcDir = getDir(e.source);
     const tgtDir = getDir(e.target);
@@ -71,7 +77,7 @@ function aggregateToDirectory(graph: DependencyGraph): DependencyGraph {
 }
 
 /** 过滤掉孤立节点（无任何依赖边的节点） */
-function filterIsolatedNodes(graph: DependencyGraph): DependencyGraph {
+function filterIsolatedNodes(graph: PathGraph): PathGraph {
   const connected = new Set<string>();
   graph.edges.forEach((e) => {
     connected.add(e.source);
@@ -98,7 +104,8 @@ export function TopologyView({ graph }: TopologyViewProps) {
 
   /** 获取当前粒度下的图数据（含孤立节点过滤） */
   const getGraphData = useCallback(() => {
-    const base = granularity === "directory" ? aggregateToDirectory(graph) : graph;
+    const flat: PathGraph = { nodes: graph.nodes.map((n) => n.path), edges: graph.edges };
+    const base = granularity === "directory" ? aggregateToDirectory(flat) : flat;
     return hideIsolated ? filterIsolatedNodes(base) : base;
   }, [graph, granularity, hideIsolated]);
 
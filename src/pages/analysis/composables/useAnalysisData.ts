@@ -14,9 +14,11 @@ import { toast } from "sonner";
 import type {
   Project,
   FileIndexEntry,
+  FileIndexScan,
   DependencyGraph,
   SimilarFile,
   EmbedBatchResult,
+  FileSummaryResult,
   ProjectOverview,
   IndexSignaturesResult,
 } from "@/types";
@@ -60,13 +62,19 @@ export function useAnalysisData() {
     if (!selectedProjectId || !selectedProject) return;
     setScanning(true);
     try {
-      const entries = await invoke<FileIndexEntry[]>("scan_project_file_index", {
+      const { entries, warnings } = await invoke<FileIndexScan>("scan_project_file_index", {
         projectId: selectedProjectId,
         projectPath: selectedProject.repo_path,
       });
       setFileEntries(entries);
       const changedCount = entries.filter((e) => e.changed).length;
       toast.success(`扫描完成：${entries.length} 个文件，${changedCount} 个有变更`);
+      if (warnings.length > 0) {
+        const first = warnings[0];
+        toast.warning(
+          `${warnings.length} 个文件无法读取已跳过（如 ${first.relative_path}：${first.error}）`
+        );
+      }
     } catch (err) {
       toast.error(`扫描失败：${String(err)}`);
     } finally {
@@ -80,7 +88,7 @@ export function useAnalysisData() {
       if (!selectedProjectId || !selectedProject) return;
       setAnalyzingFiles((prev) => new Set(prev).add(filePath));
       try {
-        const summary = await invoke<string>("analyze_file_summary", {
+        const { summary } = await invoke<FileSummaryResult>("analyze_file_summary", {
           projectId: selectedProjectId,
           projectPath: selectedProject.repo_path,
           filePath,
@@ -118,7 +126,7 @@ export function useAnalysisData() {
     let failCount = 0;
     for (const file of changedFiles) {
       try {
-        const summary = await invoke<string>("analyze_file_summary", {
+        const { summary } = await invoke<FileSummaryResult>("analyze_file_summary", {
           projectId: selectedProjectId,
           projectPath: selectedProject.repo_path,
           filePath: file.relative_path,
@@ -150,6 +158,7 @@ export function useAnalysisData() {
     try {
       const graph = await invoke<DependencyGraph>("analyze_dependencies", {
         projectPath: selectedProject.repo_path,
+        projectId: selectedProjectId,
       });
       setDependencyGraph(graph);
       toast.success(`依赖分析完成：${graph.nodes.length} 个节点，${graph.edges.length} 条依赖`);
@@ -158,7 +167,7 @@ export function useAnalysisData() {
     } finally {
       setAnalyzingDeps(false);
     }
-  }, [selectedProject]);
+  }, [selectedProjectId, selectedProject]);
 
   /** 切换项目时清空所有状态 */
   const handleProjectChange = useCallback((id: number | null) => {
@@ -262,6 +271,8 @@ export function useAnalysisData() {
         projectId: selectedProjectId,
         projectPath: selectedProject.repo_path,
         mode,
+        // 深度模式：上次汇总失败时复用已完成的签名压缩结果
+        resume: mode === "deep",
       });
       setReport(result);
       toast.success("项目报告生成完成");
@@ -297,7 +308,7 @@ export function useAnalysisData() {
         autoIndexTriggered.current = selectedProjectId;
 
         // 步骤 1：扫描文件索引
-        const entries = await invoke<FileIndexEntry[]>("scan_project_file_index", {
+        const { entries } = await invoke<FileIndexScan>("scan_project_file_index", {
           projectId: selectedProjectId,
           projectPath: selectedProject!.repo_path,
         });
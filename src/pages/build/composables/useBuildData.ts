@@ -13,7 +13,14 @@ import { listen } from "@tauri-apps/api/event";
 import type { UnlistenFn } from "@tauri-apps/api/event";
 import { toast } from "sonner";
 import { useAppStore } from "@/store";
-import type { Project, Client, BuildRecord, BuildResult, ModuleInfo } from "@/types";
+import type {
+  ArtifactDeletion,
+  Project,
+  Client,
+  BuildRecord,
+  BuildResult,
+  ModuleInfo,
+} from "@/types";
 
 export function useBuildData() {
   // ---- 全局 Store ----
@@ -256,6 +263,9 @@ export function useBuildData() {
     setBuildingState(true);
     setBuildResult(null);
 
+    // 本次构建的幂等键：保存构建记录失败重试时复用，避免超时重试写入重复记录
+    const idempotencyKey = crypto.randomUUID();
+
     // 初始化构建日志
     setBuildLogs([]);
     setShowBuildLog(true);
@@ -306,14 +316,23 @@ export function useBuildData() {
 
       // 持久化构建记录（使用实际打包的完整模块列表，含依赖分析自动补充的模块）
       appendLog("→ 保存构建记录...");
-      await invoke("db_create_build_record", {
+      const recordArgs = {
         projectId: selectedProject.id,
         clientId: client.id,
         modulesJson: JSON.stringify(result.expanded_modules),
         outputPath: result.zip_path,
         version,
         changelog,
-      });
+        techStack: selectedProject.tech_stack_type,
+        modulesDir: selectedProject.modules_dir,
+        idempotencyKey,
+      };
+      try {
+        await invoke("db_create_build_record", recordArgs);
+      } catch (err) {
+        appendLog(`⚠ 保存构建记录失败，重试中: ${String(err)}`);
+        await invoke("db_create_build_record", recordArgs);
+      }
 
       await loadBuildRecords(selectedProject.id);
 
@@ -358,8 +377,19 @@ export function useBuildData() {
   /** 删除单条构建记录 */
   const handleDeleteRecord = async (recordId: number, deleteFiles: boolean = false) => {
     try {
-      await invoke("db_delete_build_record", { id: recordId, deleteFiles });
-      toast.success(deleteFiles ? "已删除记录和文件" : "已删除构建记录");
+      const result = await invoke<ArtifactDeletion>("db_delete_build_record", {
+        id: recordId,
+        deleteFiles,
+      });
+      if (!deleteFiles) {
+        toast.success("已删除构建记录");
+      } else if (result.file_deleted) {
+        toast.success("已删除记录和文件");
+      } else if (result.file_found) {
+        toast.warning("已删除记录，但文件删除失败");
+      } else {
+        toast.success("已删除记录（文件已不存在）");
+      }
       if (selectedProjectId) await loadBuildRecords(selectedProjectId);
     } catch (err) {
       toast.error(String(err));
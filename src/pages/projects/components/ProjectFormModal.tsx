@@ -15,7 +15,7 @@ import { X, FolderOpen } from "lucide-react";
 import { Modal } from "@/components/ui/modal";
 import { TECH_STACK_OPTIONS } from "../types";
 import type { ProjectFormProps } from "../types";
-import type { TechStackTemplate } from "@/types";
+import type { TechStackInfo, TechStackTemplate } from "@/types";
 
 export function ProjectFormModal({
   project,
@@ -36,15 +36,20 @@ export function ProjectFormModal({
   const [saving, setSaving] = useState(false);
   const isEdit = project !== null;
 
-  // 从数据库加载技术栈模板列表，回退到硬编码选项
+  // 从数据库加载技术栈模板列表；无模板时使用构建流水线支持的内置技术栈，最后回退到硬编码选项
   const [stackOptions, setStackOptions] = useState<{ value: string; label: string }[]>(
     TECH_STACK_OPTIONS as unknown as { value: string; label: string }[]
   );
   useEffect(() => {
     invoke<TechStackTemplate[]>("db_list_templates")
-      .then((list) => {
+      .then(async (list) => {
         if (list.length > 0) {
           setStackOptions(list.map((t) => ({ value: t.name, label: t.name })));
+          return;
+        }
+        const stacks = await invoke<TechStackInfo[]>("list_supported_tech_stacks");
+        if (stacks.length > 0) {
+          setStackOptions(stacks.map((s) => ({ value: s.id, label: s.display_name })));
         }
       })
       .catch(() => {
@@ -58,6 +63,16 @@ export function ProjectFormModal({
       const selected = await open({ directory: true });
       if (selected) {
         setRepoPath(selected);
+        // 模块目录未填写时按目录布局推测并预填
+        if (!modulesDir.trim()) {
+          const detected = await invoke<string | null>("detect_modules_dir", {
+            projectPath: selected,
+            techStack,
+          }).catch(() => null);
+          if (detected) {
+            setModulesDir(detected);
+          }
+        }
       }
     } catch (err) {
       toast.error(String(err));
@@ -10,6 +10,8 @@ import type { Category, Project } from "@/types";
 export const TECH_STACK_OPTIONS = [
   { value: "fastapi", label: "FastAPI" },
   { value: "vue3", label: "Vue 3" },
+  { value: "angular", label: "Angular" },
+  { value: "nuxt", label: "Nuxt" },
 ] as const;
 
 /** 分类表单弹窗 Props */
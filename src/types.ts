@@ -29,6 +29,245 @@ export interface BuildResult {
   module_count: number;
   /** 实际打包的完整模块列表（用户选中 + 依赖分析自动补充） */
   expanded_modules: string[];
+  /** 完整性校验发现的不一致文件（仅试运行时可能非空，正常构建出现不一致会直接失败） */
+  integrity_errors: string[];
+  /** 试运行（dry-run）时返回的构建计划，正常构建时为 null */
+  plan: BuildPlan | null;
+  /** 构建过程中产生的警告（如入口文件不在约定位置） */
+  warnings: string[];
+}
+
+/** 多客户构建中单个客户的结果（对应 Rust ClientBuildResult） */
+export interface ClientBuildResult {
+  /** 客户 ID */
+  client_id: number;
+  /** 客户名称 */
+  client_name: string;
+  /** 构建结果（打包失败时为 null） */
+  result: BuildResult | null;
+  /** 写入的构建记录 ID（打包失败时为 null） */
+  build_record_id: number | null;
+  /** 打包失败原因 */
+  error: string | null;
+}
+
+/** 交付包校验结果（由 verify_package 返回） */
+export interface PackageVerification {
+  /** 是否全部校验通过 */
+  passed: boolean;
+  /** 内容与清单哈希不一致的文件 */
+  mismatched: string[];
+  /** 清单中列出但包内缺失的文件 */
+  missing: string[];
+  /** 包内存在但清单中未列出的文件 */
+  extra: string[];
+  /** 整包哈希与 .sha256 校验文件是否一致；无校验文件时为 null */
+  archive_hash_ok: boolean | null;
+}
+
+/** 交付检查项结论 */
+export type CheckStatus = 'pass' | 'warn' | 'fail';
+
+/** 交付前检查清单中的单项结果（由 delivery_checklist 返回） */
+export interface CheckItem {
+  /** 检查项标识（如 manifest、checksum） */
+  key: string;
+  /** 检查项名称 */
+  label: string;
+  /** 检查结论 */
+  status: CheckStatus;
+  /** 结论说明 */
+  detail: string;
+}
+
+/** 入口文件中引用了不存在模块的导入（由 validate_project_imports 返回） */
+export interface MissingImport {
+  /** 模块名 */
+  module: string;
+  /** 期望存在的模块路径（相对项目根目录） */
+  expected_path: string;
+  /** 入口文件中首次引用该模块的行号；无法定位时为 null */
+  line: number | null;
+}
+
+/** 删除项目的级联影响（由 db_project_delete_impact 返回） */
+export interface DeleteImpact {
+  /** 构建记录数 */
+  build_records: number;
+  /** 客户关联数（客户本身不会被删除） */
+  client_associations: number;
+  /** 客户模块配置数 */
+  client_module_configs: number;
+  /** 文件索引条目数 */
+  file_index_entries: number;
+}
+
+/** 孤立客户关联清理结果（由 db_repair_orphaned_associations 返回） */
+export interface RepairReport {
+  /** 因项目已不存在而删除的关联数 */
+  missing_project: number;
+  /** 因客户已不存在而删除的关联数 */
+  missing_client: number;
+}
+
+/** 数据库健康快照（由 db_health 返回） */
+export interface DbHealth {
+  /** 各表行数（表名 → 行数） */
+  table_counts: Record<string, number>;
+  /** 数据库主文件大小（字节） */
+  file_size_bytes: number;
+  /** WAL 文件大小（字节） */
+  wal_size_bytes: number;
+  /** SQLite 版本 */
+  sqlite_version: string;
+  /** 日志模式（如 wal、delete） */
+  journal_mode: string;
+  /** 外键约束是否启用 */
+  foreign_keys: boolean;
+  /** 快速完整性检查是否通过 */
+  integrity_ok: boolean;
+  /** 完整性检查报告的问题（通过时为空） */
+  integrity_errors: string[];
+}
+
+/** 删除构建记录时输出文件的处理结果（由 db_delete_build_record 返回） */
+export interface ArtifactDeletion {
+  /** 请求删除文件且文件仍存在 */
+  file_found: boolean;
+  /** 文件已成功删除 */
+  file_deleted: boolean;
+}
+
+/** 迁移构建产物的结果（由 db_relocate_build_artifacts 返回） */
+export interface RelocateReport {
+  /** 已移动文件并更新输出路径的记录数 */
+  moved: number;
+  /** 输出文件已不存在的记录数（记录保持不变） */
+  missing: number;
+}
+
+/** 模块被自动补充的原因：一条跨模块的文件级依赖 */
+export interface ModuleAddition {
+  /** 被自动补充的模块 */
+  module: string;
+  /** 引入该模块的已包含模块 */
+  required_by: string;
+  /** 发起 import 的文件（可能是经由的共享文件） */
+  source_file: string;
+  /** 被 import 的文件（位于被补充模块内） */
+  target_file: string;
+}
+
+/** 依赖自动补充预览（由 preview_module_expansion 返回） */
+export interface ExpansionResult {
+  /** 用户选中的模块 */
+  selected: string[];
+  /** 自动补充的模块 */
+  added: string[];
+  /** 每个自动补充模块的原因（按补充顺序） */
+  reasons: ModuleAddition[];
+}
+
+/** 模块概况（由 module_metadata 返回） */
+export interface ModuleMetadata {
+  /** 模块名称 */
+  name: string;
+  /** 模块内文件数 */
+  file_count: number;
+  /** 模块内文件累计字节数 */
+  total_bytes: number;
+  /** 路由数（include_router 与 path: 的出现次数） */
+  route_count: number;
+  /** 是否包含测试 */
+  has_tests: boolean;
+}
+
+/** 两个项目模块集合的差异（compare_project_modules 返回，各列表已排序） */
+export interface ModuleDiff {
+  /** 仅项目 A 拥有的模块 */
+  only_in_a: string[];
+  /** 仅项目 B 拥有的模块 */
+  only_in_b: string[];
+  /** 两个项目共有的模块 */
+  common: string[];
+}
+
+/** 批量导入时被跳过的目录 */
+export interface SkippedRepo {
+  /** 目录路径 */
+  path: string;
+  /** 跳过原因 */
+  reason: string;
+}
+
+/** 批量导入项目的结果（scan_and_import_projects 返回） */
+export interface ProjectImportSummary {
+  /** 新创建的项目 */
+  created: Project[];
+  /** 被跳过的目录及原因 */
+  skipped: SkippedRepo[];
+}
+
+/** 导入项目包的结果（db_import_project_bundle 返回） */
+export interface BundleImportResult {
+  /** 新建的项目 */
+  project: Project;
+  /** 新建的客户数（同名客户已存在时直接复用，不计入） */
+  clients_created: number;
+  /** 写入的文件摘要数 */
+  summaries_imported: number;
+  /** 导入时的提示（如仓库路径在本机不存在） */
+  warnings: string[];
+}
+
+/** 构建流水线支持的技术栈（由 list_supported_tech_stacks 返回） */
+export interface TechStackInfo {
+  /** 技术栈标识（如 fastapi） */
+  id: string;
+  /** 显示名称（如 FastAPI） */
+  display_name: string;
+  /** 默认模块目录 */
+  default_modules_dir: string;
+  /** 是否支持入口文件导入重写 */
+  supports_rewrite: boolean;
+}
+
+/** 项目顶层目录体积统计（由 project_size_breakdown 返回） */
+export interface DirSize {
+  /** 顶层目录名（根目录下直接存放的文件为 "."） */
+  name: string;
+  /** 目录内文件累计字节数 */
+  total_bytes: number;
+  /** 目录内文件数 */
+  file_count: number;
+}
+
+/** 交付包概要（由 describe_archive 返回） */
+export interface ArchiveInfo {
+  /** ZIP 文件自身大小（字节） */
+  archive_size: number;
+  /** 包内文件数（不含目录条目） */
+  file_count: number;
+  /** 压缩后总字节数 */
+  compressed_bytes: number;
+  /** 解压后总字节数 */
+  uncompressed_bytes: number;
+  /** 顶层条目（目录以 / 结尾，按名称排序） */
+  top_level: string[];
+}
+
+/** 试运行构建计划，对应 Rust BuildPlan */
+export interface BuildPlan {
+  /** 交付包中将包含的文件（相对路径） */
+  files: string[];
+  /** 依赖分析自动补充的模块 */
+  auto_added_modules: string[];
+  /** 入口文件重写差异（"- " 删除行，"+ " 新增行） */
+  entry_file_diff: string[];
+  /** 预估交付包大小（未压缩字节数） */
+  estimated_size: number;
+  /** 构建过程中产生的警告 */
+  warnings: string[];
 }
 
 // ============================================================
@@ -95,6 +334,38 @@ export interface BuildRecord {
   changelog: string | null;
   /** 创建时间 */
   created_at: string;
+  /** 构建时使用的技术栈（旧记录为空字符串） */
+  tech_stack: string;
+  /** 构建时使用的模块目录（为空表示技术栈默认目录） */
+  modules_dir: string;
+}
+
+/** 清空项目分析数据的结果（由 clear_project_analysis 返回，各表删除的行数） */
+export interface ClearReport {
+  /** 文件索引条目（含哈希、摘要、签名与向量） */
+  file_index_entries: number;
+  /** 目录索引条目 */
+  dir_index_entries: number;
+  /** 已保存的分析报告 */
+  reports: number;
+}
+
+/** 已保存的项目分析报告，对应数据库 project_reports 表 */
+export interface ProjectReport {
+  /** 报告唯一标识 */
+  id: number;
+  /** 关联项目 ID */
+  project_id: number;
+  /** 生成模式（fast / deep / offline） */
+  mode: string;
+  /** 分析的子目录（为 null 表示整个项目） */
+  sub_path: string | null;
+  /** 报告正文（Markdown） */
+  content: string;
+  /** 创建时间 */
+  created_at: string;
+  /** 被本报告取代的旧版本报告 ID（首个版本为 null） */
+  supersedes: number | null;
 }
 
 /** 应用全局设置 */
@@ -123,6 +394,20 @@ export interface LlmModel {
   id: string;
 }
 
+/** 单个模型的角色探测结果 */
+export interface ModelProbe {
+  /** 模型是否可胜任该角色 */
+  ok: boolean;
+  /** 结果说明（失败时为中文提示） */
+  detail: string;
+}
+
+/** 对话模型与 Embedding 模型的角色校验结果 */
+export interface ModelRoleCheck {
+  chat: ModelProbe;
+  embedding: ModelProbe;
+}
+
 /** 页面导航标识 */
 export type PageId = 'projects' | 'build' | 'analysis' | 'templates' | 'settings' | 'about';
 
@@ -138,6 +423,58 @@ export interface FileIndexEntry {
   summary: string | null;
 }
 
+/** 文件索引扫描结果（由 scan_project_file_index 返回） */
+export interface FileIndexScan {
+  /** 扫描到的文件及变更信息 */
+  entries: FileIndexEntry[];
+  /** 被跳过的文件或目录及原因（无读权限、扫描期间被删除等） */
+  warnings: ScanWarning[];
+}
+
+/** 扫描中被跳过的文件 */
+export interface ScanWarning {
+  /** 相对于项目根目录的路径 */
+  relative_path: string;
+  /** 跳过原因 */
+  error: string;
+}
+
+/** 文件索引进度（scan_project_file_index 期间通过 index-progress 事件推送） */
+export interface IndexProgress {
+  /** 已完成哈希的文件数 */
+  done: number;
+  /** 待处理的文件总数 */
+  total: number;
+}
+
+/** 已存储的文件索引记录（由 db_list_file_index 返回，只读） */
+export interface FileIndexRow {
+  /** 相对路径 */
+  file_path: string;
+  /** 文件哈希（十六进制） */
+  file_hash: string;
+  /** 计算 file_hash 所用的算法 */
+  hash_algo: "sha256" | "blake3";
+  /** 文件大小（字节） */
+  file_size: number;
+  /** 文件修改时间（Unix 时间戳秒数） */
+  mtime: number;
+  /** LLM 生成的文件摘要（可为空） */
+  summary: string | null;
+  /** 是否已生成摘要 */
+  has_summary: boolean;
+  /** 是否已生成 Embedding 向量 */
+  has_embedding: boolean;
+  /** 生成向量时的输入模式（无向量时为 null） */
+  embedding_mode: string | null;
+  /** 被其他文件引用的次数（尚未做过依赖分析时为 null） */
+  in_degree: number | null;
+  /** 引用其他文件的次数（尚未做过依赖分析时为 null） */
+  out_degree: number | null;
+  /** 最后分析时间 */
+  last_analyzed_at: string;
+}
+
 /** 依赖边 */
 export interface DepEdge {
   /** 源文件相对路径 */
@@ -146,12 +483,48 @@ export interface DepEdge {
   target: string;
 }
 
+/** 高耦合文件：出边与入边数量 */
+export interface DependencyHotspot {
+  /** 文件相对路径 */
+  file: string;
+  /** 出边数（引用其他文件） */
+  outgoing: number;
+  /** 入边数（被其他文件引用） */
+  incoming: number;
+}
+
+/** 依赖图节点（文件及其元信息） */
+export interface DepNode {
+  /** 文件相对路径 */
+  path: string;
+  /** 编程语言（如 Python、TypeScript，无法识别时为 Other） */
+  language: string;
+  /** 是否为入口文件（如 main.py、index.ts） */
+  is_entry: boolean;
+  /** 文件大小（字节） */
+  size: number;
+}
+
 /** 依赖图数据（由 analyze_dependencies 返回） */
 export interface DependencyGraph {
-  /** 所有文件节点（相对路径） */
-  nodes: string[];
+  /** 所有文件节点 */
+  nodes: DepNode[];
   /** 依赖边列表 */
   edges: DepEdge[];
+  /** 出入边总数最多的文件（高耦合热点） */
+  hotspots: DependencyHotspot[];
+  /** 依赖解析中发现的源码问题（如相对导入超出项目根目录） */
+  warnings: DependencyWarning[];
+}
+
+/** 依赖解析发现的源码问题 */
+export interface DependencyWarning {
+  /** 源文件相对路径 */
+  file: string;
+  /** 问题所在行号（从 1 开始） */
+  line: number;
+  /** 问题描述 */
+  message: string;
 }
 
 /** 语义搜索结果条目（由 search_similar_files 返回） */
@@ -164,6 +537,14 @@ export interface SimilarFile {
   score: number;
 }
 
+/** 单文件摘要结果（由 analyze_file_summary 返回） */
+export interface FileSummaryResult {
+  /** 新生成的摘要 */
+  summary: string;
+  /** 旧 Embedding 已因摘要变化被清空，需重新生成 */
+  needs_reembed: boolean;
+}
+
 /** 批量 Embedding 结果（由 embed_all_files 返回） */
 export interface EmbedBatchResult {
   /** 总文件数 */
@@ -174,6 +555,14 @@ export interface EmbedBatchResult {
   failed: number;
 }
 
+/** Embedding 模型信息（由 get_embedding_model_info 返回） */
+export interface EmbeddingModelInfo {
+  /** 当前配置的 Embedding 模型 */
+  model: string;
+  /** 已记录的向量维度；尚未生成过向量时为 null */
+  dimension: number | null;
+}
+
 /** 语言统计条目（由 get_project_overview 返回） */
 export interface LanguageStat {
   /** 语言名称 */
@@ -182,6 +571,10 @@ export interface LanguageStat {
   file_count: number;
   /** 总行数 */
   line_count: number;
+  /** 空行数 */
+  blank_lines: number;
+  /** 注释行数 */
+  comment_lines: number;
 }
 
 /** 项目概览数据（由 get_project_overview 返回） */
@@ -198,6 +591,10 @@ export interface ProjectOverview {
   languages: LanguageStat[];
   /** 入口文件列表 */
   entry_files: string[];
+  /** 超过大小阈值、按体积估算行数而未读取的文件 */
+  skipped_large_files: string[];
+  /** 内容完全相同的文件组（每组至少 2 个文件） */
+  duplicate_groups: string[][];
 }
 
 /** 签名索引结果（由 index_project_signatures 返回） */
@@ -229,3 +626,17 @@ export interface TechStackTemplate {
   /** 创建时间 */
   created_at: string;
 }
+
+/** 命令耗时统计（由 get_command_metrics 返回） */
+export interface CommandMetric {
+  /** 命令名称 */
+  command: string;
+  /** 调用次数 */
+  count: number;
+  /** 耗时指数移动平均（毫秒） */
+  avg_ms: number;
+  /** 最近一次耗时（毫秒） */
+  last_ms: number;
+  /** 最大耗时（毫秒） */
+  max_ms: number;
+}